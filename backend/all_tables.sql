@@ -24,7 +24,7 @@ CREATE TABLE IF NOT EXISTS groups (
 CREATE TABLE IF NOT EXISTS group_members (
     group_id TEXT NOT NULL,
     user_id TEXT NOT NULL,
-    role TEXT NOT NULL DEFAULT 'member', -- 'admin', 'member'
+    role TEXT NOT NULL DEFAULT 'member', -- 'owner', 'admin', 'member'
     joined_at TEXT NOT NULL,
     PRIMARY KEY (group_id, user_id)
 );
@@ -0,0 +1,5 @@
+-- users.created_at is INTEGER epoch seconds (see schema.sql); rows written by the
+-- old repository stored RFC 3339 text. Convert them in place.
+UPDATE users
+SET created_at = CAST(strftime('%s', created_at) AS INTEGER)
+WHERE typeof(created_at) = 'text';
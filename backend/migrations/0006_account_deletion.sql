@@ -0,0 +1,2 @@
+-- Deleted accounts keep an anonymised users row so shared expenses and payments still resolve
+ALTER TABLE users ADD COLUMN deleted_at INTEGER;
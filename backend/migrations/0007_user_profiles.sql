@@ -0,0 +1,11 @@
+-- Display preferences; a missing row means every default applies
+CREATE TABLE IF NOT EXISTS user_profiles (
+    user_id TEXT PRIMARY KEY,
+    display_name TEXT,
+    avatar_key TEXT,
+    locale TEXT NOT NULL DEFAULT 'en-US',
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    default_currency TEXT NOT NULL DEFAULT 'USD',
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,19 @@
+-- Group invitations; only accepting one creates the membership
+CREATE TABLE IF NOT EXISTS group_invitations (
+    id TEXT PRIMARY KEY,
+    group_id TEXT NOT NULL,
+    invited_user_id TEXT NOT NULL,
+    invited_by TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'accepted', 'declined', 'expired', 'revoked')),
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    responded_at INTEGER,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (invited_user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (invited_by) REFERENCES users(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_group_invitations_invitee ON group_invitations(invited_user_id, status);
+CREATE INDEX IF NOT EXISTS idx_group_invitations_group ON group_invitations(group_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_group_invitations_expiry ON group_invitations(status, expires_at);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_group_invitations_one_pending ON group_invitations(group_id, invited_user_id) WHERE status = 'pending';
@@ -0,0 +1,20 @@
+-- Invite links and join codes; only the hash of a join code is stored
+CREATE TABLE IF NOT EXISTS group_invite_links (
+    id TEXT PRIMARY KEY,
+    group_id TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('link', 'code')),
+    code_hash TEXT UNIQUE,
+    role TEXT NOT NULL DEFAULT 'member' CHECK (role IN ('admin', 'member')),
+    single_use INTEGER NOT NULL DEFAULT 0,
+    use_count INTEGER NOT NULL DEFAULT 0,
+    created_by TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    revoked_at INTEGER,
+    last_used_at INTEGER,
+    last_used_by TEXT,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_group_invite_links_group ON group_invite_links(group_id, created_at DESC);
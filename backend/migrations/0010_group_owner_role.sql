@@ -0,0 +1,5 @@
+-- Groups get a single 'owner' role above 'admin'; the creator's admin row becomes the owner
+UPDATE group_members
+SET role = 'owner'
+WHERE role = 'admin'
+  AND user_id = (SELECT created_by FROM groups WHERE groups.id = group_members.group_id);
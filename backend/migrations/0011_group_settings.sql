@@ -0,0 +1,13 @@
+-- Per-group currency, timezone, week start, locale and module toggles; a missing row means every default applies
+CREATE TABLE IF NOT EXISTS group_settings (
+    group_id TEXT PRIMARY KEY,
+    default_currency TEXT NOT NULL DEFAULT 'USD',
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    week_start TEXT NOT NULL DEFAULT 'monday' CHECK (week_start IN ('monday', 'saturday', 'sunday')),
+    locale TEXT NOT NULL DEFAULT 'en-US',
+    expenses_enabled INTEGER NOT NULL DEFAULT 1,
+    chores_enabled INTEGER NOT NULL DEFAULT 1,
+    calendar_enabled INTEGER NOT NULL DEFAULT 1,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+-- Deleted groups stay restorable for 30 days before the scheduled sweep purges them
+ALTER TABLE groups ADD COLUMN deleted_at INTEGER;
+ALTER TABLE groups ADD COLUMN deleted_by TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_groups_deleted ON groups(deleted_at) WHERE deleted_at IS NOT NULL;
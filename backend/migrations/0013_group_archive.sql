@@ -0,0 +1,3 @@
+-- Archived groups keep their history but take no new expenses, chores or events
+ALTER TABLE groups ADD COLUMN archived_at INTEGER;
+ALTER TABLE groups ADD COLUMN archived_by TEXT;
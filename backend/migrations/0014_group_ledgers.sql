@@ -0,0 +1,20 @@
+-- Trip ledgers live inside a parent group; closing one carries its balances into the parent
+ALTER TABLE groups ADD COLUMN parent_group_id TEXT REFERENCES groups(id);
+ALTER TABLE groups ADD COLUMN closed_at INTEGER;
+
+CREATE TABLE IF NOT EXISTS ledger_transfers (
+    id TEXT PRIMARY KEY,
+    group_id TEXT NOT NULL,
+    child_group_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    amount INTEGER NOT NULL, -- minor units of the currency, e.g. cents
+    currency TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (child_group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_groups_parent ON groups(parent_group_id) WHERE parent_group_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_ledger_transfers_group ON ledger_transfers(group_id);
+CREATE INDEX IF NOT EXISTS idx_ledger_transfers_child ON ledger_transfers(child_group_id);
@@ -0,0 +1,24 @@
+-- Append-only record of what happened in each group, plus each member's read marker
+CREATE TABLE IF NOT EXISTS activity_log (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT, -- orders the feed and serves as its cursor
+    group_id TEXT NOT NULL,
+    actor_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    subject_id TEXT,
+    summary TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (actor_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS activity_reads (
+    user_id TEXT NOT NULL,
+    group_id TEXT NOT NULL,
+    last_read_seq INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (user_id, group_id),
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_log_group ON activity_log(group_id, seq DESC);
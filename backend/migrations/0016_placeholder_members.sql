@@ -0,0 +1,5 @@
+-- Placeholder members: users rows with no password that nobody can sign in as
+ALTER TABLE users ADD COLUMN is_placeholder INTEGER NOT NULL DEFAULT 0;
+
+-- Invite links that hand a placeholder over to whoever redeems them
+ALTER TABLE group_invite_links ADD COLUMN placeholder_id TEXT;
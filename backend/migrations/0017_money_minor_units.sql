@@ -0,0 +1,119 @@
+-- Amounts become whole numbers of the currency's minor unit (cents for USD, yen for JPY,
+-- fils for BHD), stored in INTEGER columns. SQLite cannot change a column's type, so the
+-- tables that still declare REAL amounts (expenses, expense_shares, payments) are copied
+-- aside, recreated under their own names and filled with the rescaled values. Nothing is
+-- renamed, so foreign keys and the expense_balances view keep resolving, and the shares are
+-- copied out before dropping expenses can cascade into them. ledger_transfers already has an
+-- INTEGER column and is rescaled in place. Exponents follow ISO 4217.
+PRAGMA defer_foreign_keys = on;
+
+CREATE TABLE expenses_old AS SELECT * FROM expenses;
+CREATE TABLE expense_shares_old AS SELECT * FROM expense_shares;
+CREATE TABLE payments_old AS SELECT * FROM payments;
+
+DROP TABLE expense_shares;
+DROP TABLE expenses;
+DROP TABLE payments;
+
+CREATE TABLE expenses (
+    id TEXT PRIMARY KEY,
+    group_id TEXT NOT NULL,
+    description TEXT NOT NULL,
+    amount INTEGER NOT NULL, -- minor units of the currency, e.g. cents
+    currency TEXT NOT NULL DEFAULT 'USD',
+    paid_by TEXT NOT NULL,
+    created_by TEXT NOT NULL,
+    category TEXT,
+    date TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups(id),
+    FOREIGN KEY (paid_by) REFERENCES users(id),
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+INSERT INTO expenses (id, group_id, description, amount, currency, paid_by, created_by, category, date, created_at, updated_at)
+SELECT id, group_id, description, CAST(ROUND(amount * CASE
+    WHEN currency IN ('BIF', 'CLP', 'DJF', 'GNF', 'ISK', 'JPY', 'KMF', 'KRW', 'PYG', 'RWF', 'UGX', 'UYI', 'VND', 'VUV', 'XAF', 'XOF', 'XPF') THEN 1
+    WHEN currency IN ('BHD', 'IQD', 'JOD', 'KWD', 'LYD', 'OMR', 'TND') THEN 1000
+    WHEN currency IN ('CLF', 'UYW') THEN 10000
+    ELSE 100 END) AS INTEGER),
+    currency, paid_by, created_by, category, date, created_at, updated_at
+FROM expenses_old;
+
+CREATE TABLE expense_shares (
+    expense_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    amount INTEGER NOT NULL, -- minor units of the expense's currency
+    is_settled BOOLEAN DEFAULT FALSE,
+    PRIMARY KEY (expense_id, user_id),
+    FOREIGN KEY (expense_id) REFERENCES expenses(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+-- Shares are in their expense's currency
+INSERT INTO expense_shares (expense_id, user_id, amount, is_settled)
+SELECT s.expense_id, s.user_id, CAST(ROUND(s.amount * CASE
+    WHEN e.currency IN ('BIF', 'CLP', 'DJF', 'GNF', 'ISK', 'JPY', 'KMF', 'KRW', 'PYG', 'RWF', 'UGX', 'UYI', 'VND', 'VUV', 'XAF', 'XOF', 'XPF') THEN 1
+    WHEN e.currency IN ('BHD', 'IQD', 'JOD', 'KWD', 'LYD', 'OMR', 'TND') THEN 1000
+    WHEN e.currency IN ('CLF', 'UYW') THEN 10000
+    ELSE 100 END) AS INTEGER),
+    s.is_settled
+FROM expense_shares_old s LEFT JOIN expenses e ON e.id = s.expense_id;
+
+-- Rounding each share on its own can leave an expense a few units off its total; that
+-- rounding drift, and only that, goes to the largest share.
+UPDATE expense_shares SET amount = amount + (
+    SELECT e.amount - SUM(s.amount) FROM expenses e JOIN expense_shares s ON s.expense_id = e.id
+    WHERE e.id = expense_shares.expense_id)
+WHERE user_id = (
+    SELECT s.user_id FROM expense_shares s WHERE s.expense_id = expense_shares.expense_id
+    ORDER BY s.amount DESC, s.user_id LIMIT 1)
+AND (SELECT ABS(e.amount - SUM(s.amount)) FROM expenses e JOIN expense_shares s ON s.expense_id = e.id
+     WHERE e.id = expense_shares.expense_id)
+    <= (SELECT COUNT(*) FROM expense_shares s WHERE s.expense_id = expense_shares.expense_id);
+
+CREATE TABLE payments (
+    id TEXT PRIMARY KEY,
+    group_id TEXT NOT NULL,
+    from_user TEXT NOT NULL,
+    to_user TEXT NOT NULL,
+    amount INTEGER NOT NULL, -- minor units of the currency, e.g. cents
+    currency TEXT NOT NULL DEFAULT 'USD',
+    description TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups(id),
+    FOREIGN KEY (from_user) REFERENCES users(id),
+    FOREIGN KEY (to_user) REFERENCES users(id)
+);
+
+INSERT INTO payments (id, group_id, from_user, to_user, amount, currency, description, created_at)
+SELECT id, group_id, from_user, to_user, CAST(ROUND(amount * CASE
+    WHEN currency IN ('BIF', 'CLP', 'DJF', 'GNF', 'ISK', 'JPY', 'KMF', 'KRW', 'PYG', 'RWF', 'UGX', 'UYI', 'VND', 'VUV', 'XAF', 'XOF', 'XPF') THEN 1
+    WHEN currency IN ('BHD', 'IQD', 'JOD', 'KWD', 'LYD', 'OMR', 'TND') THEN 1000
+    WHEN currency IN ('CLF', 'UYW') THEN 10000
+    ELSE 100 END) AS INTEGER),
+    currency, description, created_at
+FROM payments_old;
+
+UPDATE ledger_transfers SET amount = CAST(ROUND(amount * CASE
+    WHEN currency IN ('BIF', 'CLP', 'DJF', 'GNF', 'ISK', 'JPY', 'KMF', 'KRW', 'PYG', 'RWF', 'UGX', 'UYI', 'VND', 'VUV', 'XAF', 'XOF', 'XPF') THEN 1
+    WHEN currency IN ('BHD', 'IQD', 'JOD', 'KWD', 'LYD', 'OMR', 'TND') THEN 1000
+    WHEN currency IN ('CLF', 'UYW') THEN 10000
+    ELSE 100 END) AS INTEGER);
+
+DROP TABLE expenses_old;
+DROP TABLE expense_shares_old;
+DROP TABLE payments_old;
+
+CREATE INDEX IF NOT EXISTS idx_expenses_group_id ON expenses(group_id);
+CREATE INDEX IF NOT EXISTS idx_expenses_paid_by ON expenses(paid_by);
+CREATE INDEX IF NOT EXISTS idx_expenses_created_by ON expenses(created_by);
+CREATE INDEX IF NOT EXISTS idx_expenses_date ON expenses(date);
+CREATE INDEX IF NOT EXISTS idx_expense_shares_expense_id ON expense_shares(expense_id);
+CREATE INDEX IF NOT EXISTS idx_expense_shares_user_id ON expense_shares(user_id);
+CREATE INDEX IF NOT EXISTS idx_payments_group_id ON payments(group_id);
+CREATE INDEX IF NOT EXISTS idx_payments_from_user ON payments(from_user);
+CREATE INDEX IF NOT EXISTS idx_payments_to_user ON payments(to_user);
+
+PRAGMA defer_foreign_keys = off;
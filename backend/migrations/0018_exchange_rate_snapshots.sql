@@ -0,0 +1,21 @@
+-- Every expense and payment keeps the rate that converted it into its group's base currency
+-- on its own date, and the converted amount in minor units. Shares keep their part of it.
+ALTER TABLE expenses ADD COLUMN exchange_rate REAL;
+ALTER TABLE expenses ADD COLUMN base_currency TEXT;
+ALTER TABLE expenses ADD COLUMN base_amount INTEGER;
+ALTER TABLE expense_shares ADD COLUMN base_amount INTEGER;
+ALTER TABLE payments ADD COLUMN exchange_rate REAL;
+ALTER TABLE payments ADD COLUMN base_currency TEXT;
+ALTER TABLE payments ADD COLUMN base_amount INTEGER;
+
+-- Rows already in the group's currency convert at 1. The rest need a rate this migration
+-- cannot look up: they stay NULL until the hourly sweep, or an earlier write to the group,
+-- snapshots them at the rate of their date. Reading balances never takes one.
+UPDATE expenses SET exchange_rate = 1, base_currency = currency, base_amount = amount
+WHERE currency = COALESCE((SELECT default_currency FROM group_settings WHERE group_id = expenses.group_id), 'USD');
+
+UPDATE expense_shares SET base_amount = amount
+WHERE expense_id IN (SELECT id FROM expenses WHERE base_amount IS NOT NULL);
+
+UPDATE payments SET exchange_rate = 1, base_currency = currency, base_amount = amount
+WHERE currency = COALESCE((SELECT default_currency FROM group_settings WHERE group_id = payments.group_id), 'USD');
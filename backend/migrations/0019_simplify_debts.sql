@@ -0,0 +1,2 @@
+-- Groups settle up with the fewest payments unless they switch to pairwise debts
+ALTER TABLE group_settings ADD COLUMN simplify_debts INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,76 @@
+-- Payments are booked against the debtor's oldest shares of what the creditor paid for.
+-- A share tracks how much of its base_amount has been paid back and when the last of it was.
+ALTER TABLE expense_shares ADD COLUMN settled_amount INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE expense_shares ADD COLUMN settled_at TEXT;
+
+-- Who entered a payment, so they can undo it, and whether it has been booked against shares
+ALTER TABLE payments ADD COLUMN recorded_by TEXT;
+ALTER TABLE payments ADD COLUMN allocated INTEGER NOT NULL DEFAULT 0;
+
+-- The part of a payment that went to each share, in minor units of the group's base
+-- currency. Undoing the payment takes exactly these amounts back off the shares.
+CREATE TABLE IF NOT EXISTS payment_allocations (
+    payment_id TEXT NOT NULL,
+    expense_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    PRIMARY KEY (payment_id, expense_id, user_id),
+    FOREIGN KEY (payment_id) REFERENCES payments(id) ON DELETE CASCADE,
+    -- Follows the share when a claimed placeholder's shares move to the claiming account
+    FOREIGN KEY (expense_id, user_id) REFERENCES expense_shares(expense_id, user_id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_allocations_share ON payment_allocations(expense_id, user_id);
+
+-- Existing payments are booked here the way the app books them: each pair's payments, oldest
+-- first, pay off the debtor's shares of what the creditor paid for, oldest expense first.
+-- Payment and share totals are laid end to end per pair, and each payment takes the overlap
+-- of its stretch with each share's. Groups with rows 0018 could not snapshot (money spent in
+-- another currency) are left unallocated; the hourly sweep snapshots and books them.
+INSERT INTO payment_allocations (payment_id, expense_id, user_id, amount)
+WITH booked_groups AS (
+    SELECT g.id FROM groups g
+    WHERE NOT EXISTS (SELECT 1 FROM expenses e WHERE e.group_id = g.id AND e.base_amount IS NULL)
+      AND NOT EXISTS (SELECT 1 FROM payments p WHERE p.group_id = g.id AND p.base_amount IS NULL)
+),
+paid AS (
+    SELECT id, group_id, from_user, to_user,
+           SUM(base_amount) OVER pair - base_amount AS start_at,
+           SUM(base_amount) OVER pair AS end_at
+    FROM payments
+    WHERE group_id IN (SELECT id FROM booked_groups)
+    WINDOW pair AS (PARTITION BY group_id, from_user, to_user ORDER BY created_at, id ROWS UNBOUNDED PRECEDING)
+),
+owed AS (
+    SELECT es.expense_id, es.user_id, e.group_id, e.paid_by,
+           SUM(es.base_amount) OVER pair - es.base_amount AS start_at,
+           SUM(es.base_amount) OVER pair AS end_at
+    FROM expense_shares es JOIN expenses e ON e.id = es.expense_id
+    WHERE e.group_id IN (SELECT id FROM booked_groups) AND es.user_id != e.paid_by
+    WINDOW pair AS (PARTITION BY e.group_id, es.user_id, e.paid_by ORDER BY e.date, e.created_at, e.id ROWS UNBOUNDED PRECEDING)
+)
+SELECT paid.id, owed.expense_id, owed.user_id, MIN(paid.end_at, owed.end_at) - MAX(paid.start_at, owed.start_at)
+FROM paid JOIN owed ON owed.group_id = paid.group_id AND owed.user_id = paid.from_user AND owed.paid_by = paid.to_user
+WHERE MIN(paid.end_at, owed.end_at) > MAX(paid.start_at, owed.start_at);
+
+-- A share is settled once its allocations cover it, as of the payment that finished it
+UPDATE expense_shares SET settled_amount = (
+    SELECT SUM(a.amount) FROM payment_allocations a
+    WHERE a.expense_id = expense_shares.expense_id AND a.user_id = expense_shares.user_id
+)
+WHERE EXISTS (
+    SELECT 1 FROM payment_allocations a
+    WHERE a.expense_id = expense_shares.expense_id AND a.user_id = expense_shares.user_id
+);
+
+UPDATE expense_shares SET is_settled = (settled_amount >= base_amount),
+    settled_at = CASE WHEN settled_amount >= base_amount THEN (
+        SELECT p.created_at FROM payment_allocations a JOIN payments p ON p.id = a.payment_id
+        WHERE a.expense_id = expense_shares.expense_id AND a.user_id = expense_shares.user_id
+        ORDER BY p.created_at DESC, p.id DESC LIMIT 1
+    ) END
+WHERE settled_amount > 0;
+
+UPDATE payments SET allocated = 1
+WHERE group_id NOT IN (SELECT group_id FROM expenses WHERE base_amount IS NULL)
+  AND group_id NOT IN (SELECT group_id FROM payments WHERE base_amount IS NULL);
@@ -0,0 +1,14 @@
+-- Every edit of an expense: who made it, when, and a JSON list of the fields it changed
+-- ([{"field": "amount", "before": 1250, "after": 1500}, ...]); amounts are in minor units
+CREATE TABLE IF NOT EXISTS expense_revisions (
+    id TEXT PRIMARY KEY,
+    expense_id TEXT NOT NULL,
+    group_id TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    edited_by TEXT NOT NULL,
+    edited_at TEXT NOT NULL, -- RFC 3339, like the expense's own timestamps
+    changes TEXT NOT NULL,
+    UNIQUE (expense_id, revision)
+);
+
+CREATE INDEX IF NOT EXISTS idx_expense_revisions_group ON expense_revisions(group_id);
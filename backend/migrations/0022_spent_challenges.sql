@@ -0,0 +1,8 @@
+-- Ids (jti) of 2FA challenge tokens that have been exchanged, so each challenge works once.
+-- Rows are only needed until the token itself would have expired.
+CREATE TABLE IF NOT EXISTS spent_challenges (
+    jti TEXT PRIMARY KEY,
+    expires_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_spent_challenges_expires ON spent_challenges(expires_at);
@@ -0,0 +1,27 @@
+-- group_members gets a role CHECK that includes 'owner'. SQLite cannot add or change a CHECK
+-- in place, so the table is copied aside, recreated under its own name and refilled. joined_at
+-- stays RFC 3339 text, as every writer stores it.
+PRAGMA defer_foreign_keys = on;
+
+CREATE TABLE group_members_old AS SELECT * FROM group_members;
+DROP TABLE group_members;
+
+CREATE TABLE group_members (
+    group_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'admin', 'member')),
+    joined_at TEXT NOT NULL,
+    PRIMARY KEY (group_id, user_id),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+INSERT INTO group_members (group_id, user_id, role, joined_at)
+SELECT group_id, user_id, role, joined_at FROM group_members_old;
+
+DROP TABLE group_members_old;
+
+CREATE INDEX IF NOT EXISTS idx_group_members_group_id ON group_members(group_id);
+CREATE INDEX IF NOT EXISTS idx_group_members_user_id ON group_members(user_id);
+
+PRAGMA defer_foreign_keys = off;
@@ -0,0 +1,4 @@
+-- Second-factor attempts per account, counted over a window that starts at the first one.
+-- Past the cap every code is refused until the window has passed; a good code clears them.
+ALTER TABLE user_totp ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE user_totp ADD COLUMN attempts_since INTEGER;
@@ -6,6 +6,10 @@ CREATE TABLE users (
     id TEXT PRIMARY KEY,
     username TEXT UNIQUE NOT NULL,
     password_hash TEXT NOT NULL,
+    email TEXT UNIQUE, -- optional, lowercase
+    email_verified INTEGER NOT NULL DEFAULT 0,
+    deleted_at INTEGER, -- set when the account is deleted; the row stays as an anonymous tombstone
+    is_placeholder INTEGER NOT NULL DEFAULT 0, -- a group member with a name only; cannot sign in
     created_at INTEGER NOT NULL,
     -- Index for fast login lookups
     UNIQUE(username)
@@ -18,15 +22,22 @@ CREATE TABLE groups (
     description TEXT,
     created_by TEXT NOT NULL,
     created_at INTEGER NOT NULL,
-    FOREIGN KEY (created_by) REFERENCES users(id)
+    deleted_at INTEGER, -- soft delete; purged with everything in it 30 days later
+    deleted_by TEXT,
+    archived_at INTEGER, -- read-only: history stays, nothing new can be added
+    archived_by TEXT,
+    parent_group_id TEXT, -- set on trip ledgers inside a household group
+    closed_at INTEGER, -- ledger balances carried into the parent
+    FOREIGN KEY (created_by) REFERENCES users(id),
+    FOREIGN KEY (parent_group_id) REFERENCES groups(id)
 );
 
 -- Group memberships (optimized for small groups)
 CREATE TABLE group_members (
     group_id TEXT NOT NULL,
     user_id TEXT NOT NULL,
-    role TEXT NOT NULL CHECK (role IN ('admin', 'member')),
-    joined_at INTEGER NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'admin', 'member')),
+    joined_at TEXT NOT NULL,
     PRIMARY KEY (group_id, user_id),
     FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
     FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
@@ -36,7 +47,10 @@ CREATE TABLE group_members (
 CREATE TABLE expenses (
     id TEXT PRIMARY KEY,
     description TEXT NOT NULL,
-    amount REAL NOT NULL,
+    amount INTEGER NOT NULL, -- minor units of the currency, e.g. cents
+    exchange_rate REAL, -- base currency per unit of the amount's currency, on its date
+    base_currency TEXT, -- the group's currency when the rate was taken
+    base_amount INTEGER, -- amount converted into base_currency, in minor units
     date INTEGER NOT NULL,
     paid_by TEXT NOT NULL,
     group_id TEXT NOT NULL,
@@ -53,26 +67,60 @@ CREATE TABLE expenses (
 CREATE TABLE expense_splits (
     expense_id TEXT NOT NULL,
     user_id TEXT NOT NULL,
-    amount REAL NOT NULL,
-    is_settled BOOLEAN DEFAULT FALSE,
-    settled_at INTEGER,
+    amount INTEGER NOT NULL, -- minor units of the currency, e.g. cents
+    base_amount INTEGER, -- the share converted into the expense's base_currency
+    settled_amount INTEGER NOT NULL DEFAULT 0, -- how much of base_amount payments have covered
+    is_settled BOOLEAN DEFAULT FALSE, -- settled_amount has reached base_amount
+    settled_at INTEGER, -- when the last of it was paid back
     PRIMARY KEY (expense_id, user_id),
     FOREIGN KEY (expense_id) REFERENCES expenses(id) ON DELETE CASCADE,
     FOREIGN KEY (user_id) REFERENCES users(id)
 );
 
+-- Every edit of an expense; changes is a JSON list of {field, before, after}
+CREATE TABLE expense_revisions (
+    id TEXT PRIMARY KEY,
+    expense_id TEXT NOT NULL,
+    group_id TEXT NOT NULL,
+    revision INTEGER NOT NULL, -- 1 for the first edit of the expense
+    edited_by TEXT NOT NULL,
+    edited_at TEXT NOT NULL, -- RFC 3339, like the expense's own timestamps
+    changes TEXT NOT NULL,
+    UNIQUE (expense_id, revision),
+    FOREIGN KEY (expense_id) REFERENCES expenses(id) ON DELETE CASCADE,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (edited_by) REFERENCES users(id)
+);
+
 -- Settlements (debt payments between users)
 CREATE TABLE settlements (
     id TEXT PRIMARY KEY,
     group_id TEXT NOT NULL,
     payer TEXT NOT NULL,
     payee TEXT NOT NULL,
-    amount REAL NOT NULL,
+    amount INTEGER NOT NULL, -- minor units of the currency, e.g. cents
+    exchange_rate REAL, -- base currency per unit of the amount's currency, on its date
+    base_currency TEXT, -- the group's currency when the rate was taken
+    base_amount INTEGER, -- amount converted into base_currency, in minor units
     description TEXT,
+    recorded_by TEXT, -- who entered the payment and can undo it
+    allocated INTEGER NOT NULL DEFAULT 0, -- 1 once booked against the payer's shares
     settled_at INTEGER NOT NULL,
     FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
     FOREIGN KEY (payer) REFERENCES users(id),
-    FOREIGN KEY (payee) REFERENCES users(id)
+    FOREIGN KEY (payee) REFERENCES users(id),
+    FOREIGN KEY (recorded_by) REFERENCES users(id)
+);
+
+-- The part of each payment booked against one of the payer's shares, in minor units of the base currency
+CREATE TABLE payment_allocations (
+    payment_id TEXT NOT NULL,
+    expense_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    PRIMARY KEY (payment_id, expense_id, user_id),
+    FOREIGN KEY (payment_id) REFERENCES settlements(id) ON DELETE CASCADE,
+    FOREIGN KEY (expense_id, user_id) REFERENCES expense_splits(expense_id, user_id) ON DELETE CASCADE ON UPDATE CASCADE
 );
 
 -- Chores table
@@ -127,12 +175,206 @@ CREATE TABLE push_tokens (
     FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
 );
 
+-- Login sessions, one per signed-in device
+CREATE TABLE sessions (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    device_name TEXT,
+    user_agent TEXT,
+    created_at INTEGER NOT NULL,
+    last_used_at INTEGER NOT NULL,
+    revoked_at INTEGER,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Opaque refresh tokens (SHA-256 hashed); all tokens of a session form one rotation family
+CREATE TABLE refresh_tokens (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    token_hash TEXT UNIQUE NOT NULL,
+    expires_at INTEGER NOT NULL,
+    used_at INTEGER,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+-- TOTP second factor; enabled_at stays NULL until the first code is confirmed
+CREATE TABLE user_totp (
+    user_id TEXT PRIMARY KEY,
+    secret TEXT NOT NULL,
+    enabled_at INTEGER,
+    last_used_step INTEGER,
+    attempts INTEGER NOT NULL DEFAULT 0, -- second-factor attempts since attempts_since
+    attempts_since INTEGER,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- One-time 2FA recovery codes (SHA-256 hashed)
+CREATE TABLE recovery_codes (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    used_at INTEGER,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Single-use password reset / email verification tokens (SHA-256 hashed)
+CREATE TABLE account_tokens (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    purpose TEXT NOT NULL CHECK (purpose IN ('password_reset', 'email_verification')),
+    token_hash TEXT UNIQUE NOT NULL,
+    email TEXT NOT NULL,
+    expires_at INTEGER NOT NULL,
+    used_at INTEGER,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Ids (jti) of exchanged 2FA challenge tokens, kept until the token would have expired
+CREATE TABLE spent_challenges (
+    jti TEXT PRIMARY KEY,
+    expires_at INTEGER NOT NULL
+);
+
+-- Sliding-window rate limit counters, keyed by "<scope>:<ip or account>"
+CREATE TABLE rate_limits (
+    key TEXT PRIMARY KEY,
+    window_start INTEGER NOT NULL,
+    current_count INTEGER NOT NULL DEFAULT 0,
+    previous_count INTEGER NOT NULL DEFAULT 0,
+    locked_until INTEGER,
+    expires_at INTEGER NOT NULL
+);
+
+-- Display preferences; a missing row means every default applies
+CREATE TABLE user_profiles (
+    user_id TEXT PRIMARY KEY,
+    display_name TEXT,
+    avatar_key TEXT,
+    locale TEXT NOT NULL DEFAULT 'en-US',
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    default_currency TEXT NOT NULL DEFAULT 'USD',
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Group invitations; only accepting one creates the membership
+CREATE TABLE group_invitations (
+    id TEXT PRIMARY KEY,
+    group_id TEXT NOT NULL,
+    invited_user_id TEXT NOT NULL,
+    invited_by TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'accepted', 'declined', 'expired', 'revoked')),
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    responded_at INTEGER,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (invited_user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (invited_by) REFERENCES users(id)
+);
+
+-- Invite links and join codes; only the hash of a join code is stored
+CREATE TABLE group_invite_links (
+    id TEXT PRIMARY KEY,
+    group_id TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('link', 'code')),
+    code_hash TEXT UNIQUE,
+    role TEXT NOT NULL DEFAULT 'member' CHECK (role IN ('admin', 'member')),
+    single_use INTEGER NOT NULL DEFAULT 0,
+    use_count INTEGER NOT NULL DEFAULT 0,
+    created_by TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    revoked_at INTEGER,
+    last_used_at INTEGER,
+    last_used_by TEXT,
+    placeholder_id TEXT, -- set on claim links; the redeemer takes over this placeholder
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+-- Per-group currency, timezone, week start, locale and module toggles; a missing row means every default applies
+CREATE TABLE group_settings (
+    group_id TEXT PRIMARY KEY,
+    default_currency TEXT NOT NULL DEFAULT 'USD',
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    week_start TEXT NOT NULL DEFAULT 'monday' CHECK (week_start IN ('monday', 'saturday', 'sunday')),
+    locale TEXT NOT NULL DEFAULT 'en-US',
+    expenses_enabled INTEGER NOT NULL DEFAULT 1,
+    chores_enabled INTEGER NOT NULL DEFAULT 1,
+    calendar_enabled INTEGER NOT NULL DEFAULT 1,
+    simplify_debts INTEGER NOT NULL DEFAULT 1, -- 0 settles up pairwise
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
+
+-- Balances carried from a closed ledger into its parent; they count for the parent and against the ledger
+CREATE TABLE ledger_transfers (
+    id TEXT PRIMARY KEY,
+    group_id TEXT NOT NULL,
+    child_group_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    amount INTEGER NOT NULL, -- minor units of the currency, e.g. cents
+    currency TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (child_group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+-- Append-only record of what happened in each group; seq orders the feed and serves as its cursor
+CREATE TABLE activity_log (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    group_id TEXT NOT NULL,
+    actor_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    subject_id TEXT,
+    summary TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (actor_id) REFERENCES users(id)
+);
+
+-- How far each member has read a group's activity
+CREATE TABLE activity_reads (
+    user_id TEXT NOT NULL,
+    group_id TEXT NOT NULL,
+    last_read_seq INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (user_id, group_id),
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
+
 -- Performance indexes for common queries
+CREATE INDEX idx_group_members_group_id ON group_members(group_id);
+CREATE INDEX idx_group_members_user_id ON group_members(user_id);
 CREATE INDEX idx_expenses_group_date ON expenses(group_id, date DESC);
 CREATE INDEX idx_expense_splits_user ON expense_splits(user_id);
 CREATE INDEX idx_chores_assigned_deadline ON chores(assigned_to, deadline);
 CREATE INDEX idx_events_group_time ON events(group_id, start_time);
 CREATE INDEX idx_settlements_group ON settlements(group_id, settled_at DESC);
+CREATE INDEX idx_payment_allocations_share ON payment_allocations(expense_id, user_id);
+CREATE INDEX idx_expense_revisions_group ON expense_revisions(group_id);
+CREATE INDEX idx_sessions_user ON sessions(user_id, revoked_at);
+CREATE INDEX idx_refresh_tokens_session ON refresh_tokens(session_id);
+CREATE INDEX idx_recovery_codes_user ON recovery_codes(user_id, code_hash);
+CREATE INDEX idx_account_tokens_user ON account_tokens(user_id, purpose);
+CREATE INDEX idx_rate_limits_expires ON rate_limits(expires_at);
+CREATE INDEX idx_spent_challenges_expires ON spent_challenges(expires_at);
+CREATE INDEX idx_group_invitations_invitee ON group_invitations(invited_user_id, status);
+CREATE INDEX idx_group_invitations_group ON group_invitations(group_id, created_at DESC);
+CREATE INDEX idx_group_invitations_expiry ON group_invitations(status, expires_at);
+CREATE INDEX idx_groups_deleted ON groups(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_group_invite_links_group ON group_invite_links(group_id, created_at DESC);
+CREATE INDEX idx_groups_parent ON groups(parent_group_id) WHERE parent_group_id IS NOT NULL;
+CREATE INDEX idx_ledger_transfers_group ON ledger_transfers(group_id);
+CREATE INDEX idx_ledger_transfers_child ON ledger_transfers(child_group_id);
+CREATE INDEX idx_activity_log_group ON activity_log(group_id, seq DESC);
+CREATE UNIQUE INDEX idx_group_invitations_one_pending ON group_invitations(group_id, invited_user_id) WHERE status = 'pending';
 
 -- View for expense balances (cached computation)
 CREATE VIEW expense_balances AS
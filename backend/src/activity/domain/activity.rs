@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+pub const ACTIVITY_PAGE_DEFAULT: usize = 50;
+pub const ACTIVITY_PAGE_MAX: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    ExpenseCreated,
+    ExpenseUpdated,
+    ChoreCompleted,
+    EventRsvp,
+    MemberJoined,
+}
+
+impl ActivityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityKind::ExpenseCreated => "expense_created",
+            ActivityKind::ExpenseUpdated => "expense_updated",
+            ActivityKind::ChoreCompleted => "chore_completed",
+            ActivityKind::EventRsvp => "event_rsvp",
+            ActivityKind::MemberJoined => "member_joined",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "expense_created" => Some(ActivityKind::ExpenseCreated),
+            "expense_updated" => Some(ActivityKind::ExpenseUpdated),
+            "chore_completed" => Some(ActivityKind::ChoreCompleted),
+            "event_rsvp" => Some(ActivityKind::EventRsvp),
+            "member_joined" => Some(ActivityKind::MemberJoined),
+            _ => None,
+        }
+    }
+}
+
+// What a use case hands over after a change. The log is append-only; rows are never edited.
+#[derive(Debug, Clone)]
+pub struct NewActivity {
+    pub group_id: Uuid,
+    pub actor_id: Uuid,
+    pub kind: ActivityKind,
+    pub subject_id: Option<Uuid>, // the expense, chore, event or member it is about
+    pub summary: String,          // short text for the feed, e.g. "Groceries (42.00 EUR)"
+    pub created_at: DateTime<Utc>,
+}
+
+impl NewActivity {
+    pub fn new(group_id: Uuid, actor_id: Uuid, kind: ActivityKind, subject_id: Option<Uuid>, summary: impl Into<String>) -> Self {
+        Self {
+            group_id,
+            actor_id,
+            kind,
+            subject_id,
+            summary: summary.into(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+// One feed row. `seq` orders the log and doubles as the pagination cursor.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActivityItem {
+    pub seq: i64,
+    pub group_id: Uuid,
+    pub group_name: String,
+    pub actor_id: Uuid,
+    pub actor_name: String,
+    pub kind: ActivityKind,
+    pub subject_id: Option<Uuid>,
+    pub summary: String,
+    pub created_at: DateTime<Utc>,
+    pub unread: bool, // newer than the caller's read marker and done by someone else
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActivityPage {
+    pub items: Vec<ActivityItem>,
+    // Pass back as ?cursor= for the next, older page; None on the last page
+    pub next_cursor: Option<String>,
+    pub unread_count: u64,
+}
+
+// Cursors are opaque to clients but are just the last `seq` seen
+pub fn encode_cursor(seq: i64) -> String {
+    seq.to_string()
+}
+
+pub fn parse_cursor(cursor: &str) -> Option<i64> {
+    cursor.trim().parse::<i64>().ok().filter(|seq| *seq > 0)
+}
+
+pub fn clamp_page_size(limit: Option<usize>) -> usize {
+    limit.unwrap_or(ACTIVITY_PAGE_DEFAULT).clamp(1, ACTIVITY_PAGE_MAX)
+}
@@ -0,0 +1,9 @@
+use async_trait::async_trait;
+use super::activity::NewActivity;
+
+// Appends to the activity log on behalf of application services. Recording is best effort:
+// a failed write is logged and never fails the change it describes.
+#[async_trait]
+pub trait ActivityRecorder: Send + Sync {
+    async fn record(&self, activity: &NewActivity);
+}
@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use worker::{console_log, D1Database, Error as WorkerError};
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+
+use crate::activity::domain::activity::NewActivity;
+use crate::activity::domain::ports::ActivityRecorder;
+
+async fn insert_activity(db: &D1Database, activity: &NewActivity) -> Result<(), WorkerError> {
+    db.prepare(
+        "INSERT INTO activity_log (group_id, actor_id, kind, subject_id, summary, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )
+    .bind(&[
+        activity.group_id.to_string().into(),
+        activity.actor_id.to_string().into(),
+        activity.kind.as_str().into(),
+        activity.subject_id.map(|id| JsValue::from(id.to_string())).unwrap_or(JsValue::NULL),
+        activity.summary.clone().into(),
+        (activity.created_at.timestamp() as f64).into(),
+    ])?
+    .run()
+    .await?;
+    Ok(())
+}
+
+// Appends one entry for the direct D1 services. The change it describes has already been
+// written, so a failure here is logged rather than returned.
+pub async fn record_activity(db: &D1Database, activity: &NewActivity) {
+    if let Err(e) = insert_activity(db, activity).await {
+        console_log!("Failed to record {} activity in group {}: {}", activity.kind.as_str(), activity.group_id, e);
+    }
+}
+
+// Port adapter for services that hold repositories rather than a raw D1 handle
+pub struct D1ActivityRecorder {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1ActivityRecorder {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+}
+
+#[async_trait]
+impl ActivityRecorder for D1ActivityRecorder {
+    async fn record(&self, activity: &NewActivity) {
+        SendFuture::new(record_activity(&self.db, activity)).await
+    }
+}
@@ -0,0 +1,185 @@
+use worker::D1Database;
+use worker::wasm_bindgen::JsValue;
+use uuid::Uuid;
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+
+use crate::activity::domain::activity::{clamp_page_size, encode_cursor, ActivityItem, ActivityKind, ActivityPage};
+use crate::groups::domain::permissions::GroupAction;
+use crate::error::ServiceError;
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::groups::infrastructure::load_group_names;
+use crate::profiles::infrastructure::{display_name, load_display_names};
+
+// Columns shared by the feed queries. `last_read_seq` is the caller's marker for the row's group.
+const ACTIVITY_COLUMNS: &str = "a.seq, a.group_id, a.actor_id, a.kind, a.subject_id, a.summary, a.created_at,
+                                COALESCE(r.last_read_seq, 0) AS last_read_seq";
+
+pub struct DirectD1ActivityService {
+    db: D1Database,
+    authorizer: GroupAuthorizer,
+}
+
+impl DirectD1ActivityService {
+    pub fn new(db: D1Database, authorizer: GroupAuthorizer) -> Self {
+        Self { db, authorizer }
+    }
+
+    fn parse_uuid(value: &Value) -> Result<Uuid, ServiceError> {
+        Uuid::parse_str(value.as_str().unwrap_or(""))
+            .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))
+    }
+
+    fn parse_i64(value: &Value) -> i64 {
+        value.as_i64().or_else(|| value.as_f64().map(|v| v as i64)).unwrap_or(0)
+    }
+
+    // Newest first. `before` is a cursor from an earlier page.
+    pub async fn get_group_activity(&self, group_id: &Uuid, user_id: &Uuid, before: Option<i64>, limit: Option<usize>) -> Result<ActivityPage, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        let limit = clamp_page_size(limit);
+
+        let query = format!(
+            "SELECT {} FROM activity_log a
+             LEFT JOIN activity_reads r ON r.group_id = a.group_id AND r.user_id = ?2
+             WHERE a.group_id = ?1 AND (?3 = 0 OR a.seq < ?3)
+             ORDER BY a.seq DESC LIMIT ?4",
+            ACTIVITY_COLUMNS
+        );
+        let rows = self
+            .db
+            .prepare(&query)
+            .bind(&[
+                group_id.to_string().into(),
+                user_id.to_string().into(),
+                (before.unwrap_or(0) as f64).into(),
+                ((limit + 1) as f64).into(),
+            ])?
+            .all()
+            .await?
+            .results::<Value>()?;
+
+        let unread_count = self.unread_count(user_id, Some(group_id)).await?;
+        self.build_page(rows, user_id, limit, unread_count).await
+    }
+
+    // Everything that happened in the caller's current groups, newest first
+    pub async fn get_feed(&self, user_id: &Uuid, before: Option<i64>, limit: Option<usize>) -> Result<ActivityPage, ServiceError> {
+        let limit = clamp_page_size(limit);
+
+        let query = format!(
+            "SELECT {} FROM activity_log a
+             JOIN group_members gm ON gm.group_id = a.group_id AND gm.user_id = ?1
+             JOIN groups g ON g.id = a.group_id AND g.deleted_at IS NULL
+             LEFT JOIN activity_reads r ON r.group_id = a.group_id AND r.user_id = ?1
+             WHERE (?2 = 0 OR a.seq < ?2)
+             ORDER BY a.seq DESC LIMIT ?3",
+            ACTIVITY_COLUMNS
+        );
+        let rows = self
+            .db
+            .prepare(&query)
+            .bind(&[
+                user_id.to_string().into(),
+                (before.unwrap_or(0) as f64).into(),
+                ((limit + 1) as f64).into(),
+            ])?
+            .all()
+            .await?
+            .results::<Value>()?;
+
+        let unread_count = self.unread_count(user_id, None).await?;
+        self.build_page(rows, user_id, limit, unread_count).await
+    }
+
+    // Moves the caller's read marker to the newest entry, in one group or in all of them.
+    // Markers only move forward. Returns what is still unread across every group.
+    pub async fn mark_read(&self, user_id: &Uuid, group_id: Option<&Uuid>) -> Result<u64, ServiceError> {
+        if let Some(group_id) = group_id {
+            self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        }
+
+        self.db
+            .prepare(
+                "INSERT INTO activity_reads (user_id, group_id, last_read_seq, updated_at)
+                 SELECT ?1, gm.group_id, COALESCE((SELECT MAX(seq) FROM activity_log WHERE group_id = gm.group_id), 0), ?2
+                 FROM group_members gm WHERE gm.user_id = ?1 AND (?3 IS NULL OR gm.group_id = ?3)
+                 ON CONFLICT(user_id, group_id) DO UPDATE SET
+                     last_read_seq = MAX(last_read_seq, excluded.last_read_seq),
+                     updated_at = excluded.updated_at",
+            )
+            .bind(&[
+                user_id.to_string().into(),
+                (Utc::now().timestamp() as f64).into(),
+                group_id.map(|id| JsValue::from(id.to_string())).unwrap_or(JsValue::NULL),
+            ])?
+            .run()
+            .await?;
+
+        self.unread_count(user_id, None).await
+    }
+
+    // Entries by other people that are newer than the caller's marker
+    async fn unread_count(&self, user_id: &Uuid, group_id: Option<&Uuid>) -> Result<u64, ServiceError> {
+        let row = self
+            .db
+            .prepare(
+                "SELECT COUNT(*) AS count FROM activity_log a
+                 JOIN group_members gm ON gm.group_id = a.group_id AND gm.user_id = ?1
+                 JOIN groups g ON g.id = a.group_id AND g.deleted_at IS NULL
+                 LEFT JOIN activity_reads r ON r.group_id = a.group_id AND r.user_id = ?1
+                 WHERE a.actor_id != ?1 AND a.seq > COALESCE(r.last_read_seq, 0) AND (?2 IS NULL OR a.group_id = ?2)",
+            )
+            .bind(&[
+                user_id.to_string().into(),
+                group_id.map(|id| JsValue::from(id.to_string())).unwrap_or(JsValue::NULL),
+            ])?
+            .first::<Value>(None)
+            .await?;
+        Ok(row.map(|row| Self::parse_i64(&row["count"]).max(0) as u64).unwrap_or(0))
+    }
+
+    // Rows were fetched one past `limit` so we know whether an older page exists
+    async fn build_page(&self, mut rows: Vec<Value>, user_id: &Uuid, limit: usize, unread_count: u64) -> Result<ActivityPage, ServiceError> {
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+
+        let mut actor_ids = Vec::with_capacity(rows.len());
+        let mut group_ids = Vec::with_capacity(rows.len());
+        for row in &rows {
+            actor_ids.push(Self::parse_uuid(&row["actor_id"])?);
+            group_ids.push(Self::parse_uuid(&row["group_id"])?);
+        }
+        let names = load_display_names(&self.db, &actor_ids).await?;
+        let group_names = load_group_names(&self.db, &group_ids).await?;
+
+        let mut items = Vec::with_capacity(rows.len());
+        for ((row, actor_id), group_id) in rows.iter().zip(actor_ids).zip(group_ids) {
+            // Kinds this build doesn't know about are skipped rather than failing the page
+            let kind = match row["kind"].as_str().and_then(ActivityKind::parse) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let seq = Self::parse_i64(&row["seq"]);
+            items.push(ActivityItem {
+                seq,
+                group_id,
+                group_name: group_names.get(&group_id).cloned().unwrap_or_default(),
+                actor_id,
+                actor_name: display_name(&names, &actor_id),
+                kind,
+                subject_id: row["subject_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+                summary: row["summary"].as_str().unwrap_or("").to_string(),
+                created_at: Utc.timestamp_opt(Self::parse_i64(&row["created_at"]), 0).single().unwrap_or_else(Utc::now),
+                unread: actor_id != *user_id && seq > Self::parse_i64(&row["last_read_seq"]),
+            });
+        }
+
+        let next_cursor = if has_more {
+            rows.last().map(|row| encode_cursor(Self::parse_i64(&row["seq"])))
+        } else {
+            None
+        };
+        Ok(ActivityPage { items, next_cursor, unread_count })
+    }
+}
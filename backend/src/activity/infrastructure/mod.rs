@@ -0,0 +1,5 @@
+pub mod d1_activity_repository;
+pub mod direct_d1_service;
+
+pub use d1_activity_repository::{record_activity, D1ActivityRecorder};
+pub use direct_d1_service::DirectD1ActivityService;
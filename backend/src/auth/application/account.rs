@@ -0,0 +1,117 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::domain::user::UserInfo;
+use crate::auth::domain::account::{
+    AccountDeletionSummary, AccountExport, DeleteAccountRequest, GroupDisposition, GroupMembershipSummary,
+};
+use crate::auth::domain::ports::{AccountDataRepository, Clock, PasswordService, UserRepository};
+use crate::auth::domain::clock::SystemClock;
+use crate::error::ServiceError;
+use std::error::Error;
+
+// Self-service account deletion and data export
+pub struct AccountService {
+    user_repository: Arc<dyn UserRepository>,
+    password_service: Arc<dyn PasswordService>,
+    account_data_repository: Arc<dyn AccountDataRepository>,
+    clock: Arc<dyn Clock>,
+}
+
+impl AccountService {
+    pub fn new(
+        user_repository: Arc<dyn UserRepository>,
+        password_service: Arc<dyn PasswordService>,
+        account_data_repository: Arc<dyn AccountDataRepository>,
+    ) -> Self {
+        Self {
+            user_repository,
+            password_service,
+            account_data_repository,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub async fn export_account(&self, user_id: &Uuid) -> Result<AccountExport, Box<dyn Error>> {
+        let user = self.user_repository.get_user_by_id(user_id).await?.ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+        let data = self.account_data_repository.export_data(user_id).await?;
+
+        Ok(AccountExport {
+            exported_at: self.clock.now(),
+            user: UserInfo::from(&user),
+            profile: data.profile,
+            sessions: data.sessions,
+            groups: data.groups,
+            group_memberships: data.group_memberships,
+            expenses: data.expenses,
+            expense_shares: data.expense_shares,
+            payments: data.payments,
+            chores: data.chores,
+            events: data.events,
+            event_attendance: data.event_attendance,
+            activity: data.activity,
+        })
+    }
+
+    // Requires the current password. Shared groups are handed over rather than deleted, and
+    // the user's expenses and payments stay in place under an anonymous name.
+    pub async fn delete_account(&self, user_id: &Uuid, request: DeleteAccountRequest) -> Result<AccountDeletionSummary, Box<dyn Error>> {
+        let user = self.user_repository.get_user_by_id(user_id).await?.ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+        if !self.password_service.verify_password(&request.password, &user.password_hash).await? {
+            return Err(ServiceError::Forbidden("Invalid credentials".to_string()).into());
+        }
+
+        let memberships = self.account_data_repository.group_memberships(user_id).await?;
+        let mut dispositions = Vec::with_capacity(memberships.len());
+        for membership in &memberships {
+            dispositions.push(Self::plan_group(membership, request.transfer_to.get(&membership.group_id))?);
+        }
+
+        let deleted_at = self.clock.now();
+        let tombstone_username = format!("deleted-{}", user_id.simple());
+        self.account_data_repository
+            .delete_account(user_id, &dispositions, &tombstone_username, deleted_at)
+            .await?;
+
+        Ok(AccountDeletionSummary {
+            user_id: *user_id,
+            groups: dispositions,
+            deleted_at,
+        })
+    }
+
+    fn plan_group(membership: &GroupMembershipSummary, requested: Option<&Uuid>) -> Result<GroupDisposition, Box<dyn Error>> {
+        let group_id = membership.group_id;
+        if membership.remaining_members.is_empty() {
+            return Ok(GroupDisposition::Delete { group_id });
+        }
+
+        if let Some(successor) = requested {
+            if !membership.is_admin {
+                return Err(ServiceError::Invalid(format!("Only admins can hand over group \"{}\"", membership.group_name)).into());
+            }
+            if !membership.remaining_members.iter().any(|m| &m.user_id == successor) {
+                return Err(ServiceError::Invalid(format!("User {} is not a member of group \"{}\"", successor, membership.group_name)).into());
+            }
+        }
+
+        // An owner or admin leaving must not leave the group without one; an owner hands over
+        // to the longest-standing admin before anyone else
+        let first_admin = membership.remaining_members.iter().find(|m| m.is_admin).map(|m| m.user_id);
+        let new_admin = match requested {
+            Some(successor) => Some(*successor),
+            None if membership.is_owner => first_admin.or_else(|| membership.remaining_members.first().map(|m| m.user_id)),
+            None if membership.is_admin && first_admin.is_none() => {
+                membership.remaining_members.first().map(|m| m.user_id)
+            }
+            None => None,
+        };
+
+        Ok(GroupDisposition::Leave { group_id, new_admin })
+    }
+}
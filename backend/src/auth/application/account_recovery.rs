@@ -0,0 +1,220 @@
+use std::sync::Arc;
+use uuid::Uuid;
+use chrono::Duration;
+
+use crate::auth::domain::user::{User, UserInfo};
+use crate::auth::domain::account_token::{AccountToken, AccountTokenPurpose, MailMessage};
+use crate::auth::domain::ports::{
+    UserRepository, UserRepositoryError, PasswordService, TokenService, SessionRepository,
+    AccountTokenRepository, MailSender, Clock,
+};
+use crate::auth::domain::clock::SystemClock;
+use crate::error::ServiceError;
+use std::error::Error;
+
+const PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+const EMAIL_VERIFICATION_TTL_HOURS: i64 = 24;
+
+// Email ownership and password reset. Kept apart from AuthService because it is the only
+// part of auth that talks to the outside world (mail) on behalf of unauthenticated callers.
+pub struct AccountRecoveryService {
+    user_repository: Arc<dyn UserRepository>,
+    password_service: Arc<dyn PasswordService>,
+    token_service: Arc<dyn TokenService>,
+    session_repository: Arc<dyn SessionRepository>,
+    account_token_repository: Arc<dyn AccountTokenRepository>,
+    mail_sender: Arc<dyn MailSender>,
+    clock: Arc<dyn Clock>,
+    app_base_url: String,
+}
+
+impl AccountRecoveryService {
+    pub fn new(
+        user_repository: Arc<dyn UserRepository>,
+        password_service: Arc<dyn PasswordService>,
+        token_service: Arc<dyn TokenService>,
+        session_repository: Arc<dyn SessionRepository>,
+        account_token_repository: Arc<dyn AccountTokenRepository>,
+        mail_sender: Arc<dyn MailSender>,
+        app_base_url: String,
+    ) -> Self {
+        Self {
+            user_repository,
+            password_service,
+            token_service,
+            session_repository,
+            account_token_repository,
+            mail_sender,
+            clock: Arc::new(SystemClock),
+            app_base_url: app_base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // Set (or change) the account email; it stays unverified until the mailed link is used
+    pub async fn set_email(&self, user_id: &Uuid, email: &str) -> Result<UserInfo, Box<dyn Error>> {
+        let email = Self::normalize_email(email)?;
+        if let Some(existing) = self.user_repository.get_user_by_email(&email).await? {
+            if &existing.id != user_id {
+                return Err(Box::new(UserRepositoryError::EmailTaken));
+            }
+            if existing.email_verified {
+                return Ok(UserInfo::from(&existing));
+            }
+        }
+
+        self.user_repository.update_email(user_id, Some(&email)).await?;
+        let user = self.user_repository.get_user_by_id(user_id).await?.ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+        self.send_verification(&user, &email).await?;
+
+        Ok(UserInfo::from(&user))
+    }
+
+    pub async fn resend_verification(&self, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
+        let user = self.user_repository.get_user_by_id(user_id).await?.ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+        let email = user.email.clone().ok_or("No email address on this account")?;
+        if user.email_verified {
+            return Err(ServiceError::Conflict("Email is already verified".to_string()).into());
+        }
+        self.send_verification(&user, &email).await
+    }
+
+    pub async fn verify_email(&self, token: &str) -> Result<UserInfo, Box<dyn Error>> {
+        let stored = self.redeem(token, AccountTokenPurpose::EmailVerification).await?;
+
+        // A link for an address the user has since replaced must not verify the new one
+        if !self.user_repository.mark_email_verified(&stored.user_id, &stored.email).await? {
+            return Err("Invalid or expired token".into());
+        }
+
+        let user = self.user_repository.get_user_by_id(&stored.user_id).await?.ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+        Ok(UserInfo::from(&user))
+    }
+
+    // Always succeeds from the caller's point of view so it cannot be used to probe for accounts
+    pub async fn request_password_reset(&self, email: &str) -> Result<(), Box<dyn Error>> {
+        let email = match Self::normalize_email(email) {
+            Ok(email) => email,
+            Err(_) => return Ok(()),
+        };
+
+        // Only verified addresses can take over an account
+        let user = match self.user_repository.get_user_by_email(&email).await? {
+            Some(user) if user.email_verified => user,
+            _ => return Ok(()),
+        };
+
+        let now = self.clock.now();
+        self.account_token_repository
+            .invalidate_tokens(&user.id, AccountTokenPurpose::PasswordReset, now)
+            .await?;
+        let token = self
+            .issue_token(&user, &email, AccountTokenPurpose::PasswordReset, Duration::minutes(PASSWORD_RESET_TTL_MINUTES))
+            .await?;
+
+        self.mail_sender
+            .send(&MailMessage {
+                to: email,
+                subject: "Reset your TwoDo password".to_string(),
+                text_body: format!(
+                    "Hi {},\n\nUse this link within {} minutes to choose a new password:\n{}/reset-password?token={}\n\nIf you did not ask for this, you can ignore this email.",
+                    user.username, PASSWORD_RESET_TTL_MINUTES, self.app_base_url, token
+                ),
+            })
+            .await
+    }
+
+    // Sets the new password and signs out every device
+    pub async fn reset_password(&self, token: &str, new_password: &str) -> Result<(), Box<dyn Error>> {
+        if new_password.len() < 8 {
+            return Err("Password must be at least 8 characters".into());
+        }
+
+        let stored = self.redeem(token, AccountTokenPurpose::PasswordReset).await?;
+        let password_hash = self.password_service.hash_password(new_password).await?;
+        self.user_repository.update_password_hash(&stored.user_id, &password_hash).await?;
+
+        let now = self.clock.now();
+        self.account_token_repository
+            .invalidate_tokens(&stored.user_id, AccountTokenPurpose::PasswordReset, now)
+            .await?;
+        self.session_repository.revoke_all_sessions(&stored.user_id, now).await
+    }
+
+    async fn send_verification(&self, user: &User, email: &str) -> Result<(), Box<dyn Error>> {
+        let now = self.clock.now();
+        self.account_token_repository
+            .invalidate_tokens(&user.id, AccountTokenPurpose::EmailVerification, now)
+            .await?;
+        let token = self
+            .issue_token(user, email, AccountTokenPurpose::EmailVerification, Duration::hours(EMAIL_VERIFICATION_TTL_HOURS))
+            .await?;
+
+        self.mail_sender
+            .send(&MailMessage {
+                to: email.to_string(),
+                subject: "Confirm your TwoDo email address".to_string(),
+                text_body: format!(
+                    "Hi {},\n\nConfirm this address for your TwoDo account:\n{}/verify-email?token={}\n\nThe link expires in {} hours.",
+                    user.username, self.app_base_url, token, EMAIL_VERIFICATION_TTL_HOURS
+                ),
+            })
+            .await
+    }
+
+    // Create a token, store its hash and return the plaintext for the mail
+    async fn issue_token(&self, user: &User, email: &str, purpose: AccountTokenPurpose, ttl: Duration) -> Result<String, Box<dyn Error>> {
+        let token = self.token_service.generate_opaque_token()?;
+        let now = self.clock.now();
+        self.account_token_repository
+            .create_token(&AccountToken {
+                id: Uuid::new_v4(),
+                user_id: user.id,
+                purpose,
+                token_hash: self.token_service.hash_opaque_token(&token),
+                email: email.to_string(),
+                expires_at: now + ttl,
+                used_at: None,
+                created_at: now,
+            })
+            .await?;
+        Ok(token)
+    }
+
+    // Look up, check and spend a token in one go
+    async fn redeem(&self, token: &str, purpose: AccountTokenPurpose) -> Result<AccountToken, Box<dyn Error>> {
+        let token_hash = self.token_service.hash_opaque_token(token.trim());
+        let stored = self
+            .account_token_repository
+            .get_token_by_hash(&token_hash, purpose)
+            .await?
+            .ok_or("Invalid or expired token")?;
+
+        let now = self.clock.now();
+        if stored.used_at.is_some() || stored.expires_at <= now {
+            return Err("Invalid or expired token".into());
+        }
+        if !self.account_token_repository.consume_token(&stored.id, now).await? {
+            return Err("Invalid or expired token".into());
+        }
+        Ok(stored)
+    }
+
+    fn normalize_email(email: &str) -> Result<String, Box<dyn Error>> {
+        let email = email.trim().to_lowercase();
+        let valid = email.len() <= 254
+            && match email.split_once('@') {
+                Some((local, domain)) => !local.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.'),
+                None => false,
+            }
+            && !email.contains(char::is_whitespace);
+        if !valid {
+            return Err("Invalid email address".into());
+        }
+        Ok(email)
+    }
+}
@@ -1 +1,4 @@
 pub mod use_cases;
+pub mod account_recovery;
+pub mod rate_limiter;
+pub mod account;
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::auth::domain::rate_limit::{RateLimitDecision, RateLimitPolicy, RateLimitState};
+use crate::auth::domain::ports::{Clock, RateLimitStore};
+use crate::auth::domain::clock::SystemClock;
+use std::error::Error;
+
+// Sliding-window rate limiter with progressive delays and temporary lockouts.
+// Keys are namespaced by policy, so one store serves login, registration and invitations.
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    clock: Arc<dyn Clock>,
+}
+
+impl RateLimiter {
+    pub fn new(store: Arc<dyn RateLimitStore>) -> Self {
+        Self {
+            store,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // Count one attempt for `subject` and decide whether it may proceed
+    pub async fn hit(&self, policy: &RateLimitPolicy, subject: &str) -> Result<RateLimitDecision, Box<dyn Error>> {
+        let key = policy.key(subject);
+        let now = self.clock.now();
+        let window = Duration::seconds(policy.window_secs);
+
+        let mut state = match self.store.get_state(&key).await? {
+            Some(state) => state,
+            None => Self::fresh_state(now),
+        };
+
+        match state.locked_until {
+            Some(until) if until > now => {
+                return Ok(RateLimitDecision::Limited {
+                    retry_after_secs: Self::seconds_until(now, until),
+                });
+            }
+            // A served lockout starts the key over
+            Some(_) => state = Self::fresh_state(now),
+            None => {}
+        }
+
+        Self::roll_window(&mut state, now, window);
+
+        if Self::estimated_count(&state, now, window) >= policy.max_attempts {
+            let until = now + Duration::seconds(policy.lockout_secs);
+            state.locked_until = Some(until);
+            self.store.put_state(&key, &state, until).await?;
+            return Ok(RateLimitDecision::Limited {
+                retry_after_secs: Self::seconds_until(now, until),
+            });
+        }
+
+        state.current_count = state.current_count.saturating_add(1);
+        let attempts = Self::estimated_count(&state, now, window);
+        self.store.put_state(&key, &state, state.window_start + window + window).await?;
+
+        Ok(RateLimitDecision::Allowed {
+            remaining: policy.max_attempts.saturating_sub(attempts),
+            delay_ms: Self::delay_for(policy, attempts),
+        })
+    }
+
+    // Forget every attempt for `subject`, e.g. after a successful login
+    pub async fn reset(&self, policy: &RateLimitPolicy, subject: &str) -> Result<(), Box<dyn Error>> {
+        self.store.clear(&policy.key(subject)).await
+    }
+
+    fn fresh_state(now: DateTime<Utc>) -> RateLimitState {
+        RateLimitState {
+            window_start: now,
+            current_count: 0,
+            previous_count: 0,
+            locked_until: None,
+        }
+    }
+
+    // Move the fixed window forward so it contains `now`, carrying the last full window over
+    fn roll_window(state: &mut RateLimitState, now: DateTime<Utc>, window: Duration) {
+        let elapsed = now - state.window_start;
+        if elapsed < window {
+            return;
+        }
+
+        let windows_passed = elapsed.num_seconds() / window.num_seconds().max(1);
+        state.previous_count = if windows_passed == 1 { state.current_count } else { 0 };
+        state.current_count = 0;
+        state.window_start = state.window_start + Duration::seconds(windows_passed * window.num_seconds());
+    }
+
+    // Attempts in the last `window`: all of the current window plus the overlapping share of the previous one
+    fn estimated_count(state: &RateLimitState, now: DateTime<Utc>, window: Duration) -> u32 {
+        let window_secs = window.num_seconds().max(1) as f64;
+        let elapsed = (now - state.window_start).num_seconds().clamp(0, window.num_seconds()) as f64;
+        let carried = state.previous_count as f64 * (window_secs - elapsed) / window_secs;
+        state.current_count.saturating_add(carried.floor() as u32)
+    }
+
+    // Doubles for every attempt past `delay_after`, up to the policy maximum
+    fn delay_for(policy: &RateLimitPolicy, attempts: u32) -> u64 {
+        if attempts <= policy.delay_after {
+            return 0;
+        }
+        let doublings = (attempts - policy.delay_after - 1).min(16);
+        policy.base_delay_ms.saturating_mul(1u64 << doublings).min(policy.max_delay_ms)
+    }
+
+    fn seconds_until(now: DateTime<Utc>, until: DateTime<Utc>) -> u64 {
+        (until - now).num_seconds().max(1) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::auth::domain::clock::FixedClock;
+    use crate::auth::infrastructure::InMemoryRateLimitStore;
+    use crate::test_support::block_on;
+
+    const START: i64 = 1_700_000_000;
+    const POLICY: RateLimitPolicy = RateLimitPolicy::LOGIN_ACCOUNT;
+
+    // Limiters share one store; each reads the time from its own fixed clock
+    fn limiter_at(store: &Arc<InMemoryRateLimitStore>, seconds: i64) -> RateLimiter {
+        let now = Utc.timestamp_opt(seconds, 0).unwrap();
+        RateLimiter::new(store.clone()).with_clock(Arc::new(FixedClock::new(now)))
+    }
+
+    fn hit(store: &Arc<InMemoryRateLimitStore>, seconds: i64, subject: &str) -> RateLimitDecision {
+        block_on(limiter_at(store, seconds).hit(&POLICY, subject)).unwrap()
+    }
+
+    fn hits(store: &Arc<InMemoryRateLimitStore>, seconds: i64, count: u32) {
+        for _ in 0..count {
+            assert!(!hit(store, seconds, "alice").is_limited());
+        }
+    }
+
+    #[test]
+    fn allows_attempts_up_to_the_limit() {
+        let store = Arc::new(InMemoryRateLimitStore::new());
+        for attempt in 1..=POLICY.max_attempts {
+            match hit(&store, START, "alice") {
+                RateLimitDecision::Allowed { remaining, .. } => assert_eq!(remaining, POLICY.max_attempts - attempt),
+                limited => panic!("attempt {} was {:?}", attempt, limited),
+            }
+        }
+    }
+
+    #[test]
+    fn locks_out_once_the_limit_is_reached() {
+        let store = Arc::new(InMemoryRateLimitStore::new());
+        hits(&store, START, POLICY.max_attempts);
+
+        assert_eq!(
+            hit(&store, START, "alice"),
+            RateLimitDecision::Limited { retry_after_secs: POLICY.lockout_secs as u64 }
+        );
+    }
+
+    #[test]
+    fn retry_after_counts_down_during_the_lockout() {
+        let store = Arc::new(InMemoryRateLimitStore::new());
+        hits(&store, START, POLICY.max_attempts);
+        assert!(hit(&store, START, "alice").is_limited());
+
+        assert_eq!(
+            hit(&store, START + 300, "alice"),
+            RateLimitDecision::Limited { retry_after_secs: POLICY.lockout_secs as u64 - 300 }
+        );
+        assert_eq!(
+            hit(&store, START + POLICY.lockout_secs - 1, "alice"),
+            RateLimitDecision::Limited { retry_after_secs: 1 }
+        );
+    }
+
+    #[test]
+    fn served_lockout_starts_the_key_over() {
+        let store = Arc::new(InMemoryRateLimitStore::new());
+        hits(&store, START, POLICY.max_attempts);
+        assert!(hit(&store, START, "alice").is_limited());
+
+        assert_eq!(
+            hit(&store, START + POLICY.lockout_secs + 1, "alice"),
+            RateLimitDecision::Allowed { remaining: POLICY.max_attempts - 1, delay_ms: 0 }
+        );
+    }
+
+    #[test]
+    fn delay_doubles_after_the_free_attempts_up_to_the_maximum() {
+        let store = Arc::new(InMemoryRateLimitStore::new());
+        // LOGIN_ACCOUNT: three free attempts, then 500 ms doubling to at most 8 s
+        let expected = [0, 0, 0, 500, 1_000, 2_000, 4_000, 8_000, 8_000, 8_000];
+        for (attempt, expected_delay) in expected.iter().enumerate() {
+            match hit(&store, START, "alice") {
+                RateLimitDecision::Allowed { delay_ms, .. } => {
+                    assert_eq!(delay_ms, *expected_delay, "attempt {}", attempt + 1)
+                }
+                limited => panic!("attempt {} was {:?}", attempt + 1, limited),
+            }
+        }
+    }
+
+    #[test]
+    fn previous_window_counts_by_its_remaining_overlap() {
+        let store = Arc::new(InMemoryRateLimitStore::new());
+        hits(&store, START, 8);
+
+        // Halfway through the next window, half of the previous 8 attempts still count
+        let halfway = START + POLICY.window_secs + POLICY.window_secs / 2;
+        assert_eq!(
+            hit(&store, halfway, "alice"),
+            RateLimitDecision::Allowed { remaining: POLICY.max_attempts - 5, delay_ms: 1_000 }
+        );
+    }
+
+    #[test]
+    fn attempts_are_forgotten_after_two_windows() {
+        let store = Arc::new(InMemoryRateLimitStore::new());
+        hits(&store, START, 9);
+
+        assert_eq!(
+            hit(&store, START + 2 * POLICY.window_secs, "alice"),
+            RateLimitDecision::Allowed { remaining: POLICY.max_attempts - 1, delay_ms: 0 }
+        );
+    }
+
+    #[test]
+    fn reset_clears_the_subject() {
+        let store = Arc::new(InMemoryRateLimitStore::new());
+        hits(&store, START, 9);
+        block_on(limiter_at(&store, START).reset(&POLICY, "alice")).unwrap();
+
+        assert_eq!(
+            hit(&store, START, "alice"),
+            RateLimitDecision::Allowed { remaining: POLICY.max_attempts - 1, delay_ms: 0 }
+        );
+    }
+
+    #[test]
+    fn subjects_are_counted_apart_and_case_insensitively() {
+        let store = Arc::new(InMemoryRateLimitStore::new());
+        hits(&store, START, POLICY.max_attempts);
+
+        assert!(hit(&store, START, " ALICE ").is_limited());
+        assert!(!hit(&store, START, "bob").is_limited());
+    }
+}
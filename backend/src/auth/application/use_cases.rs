@@ -1,15 +1,41 @@
 use std::sync::Arc;
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{Duration, TimeZone, Utc};
 
 use crate::auth::domain::user::{User, UserRegistration, UserLogin, AuthResult, UserInfo};
-use crate::auth::domain::ports::{UserRepository, PasswordService, TokenService};
+use crate::auth::domain::session::{Session, RefreshToken, DeviceInfo, SessionInfo};
+use crate::auth::domain::two_factor::{
+    TwoFactorSettings, TotpEnrollment, RecoveryCodes, LoginOutcome, TWO_FACTOR_CHALLENGE_PURPOSE,
+};
+use crate::auth::domain::ports::{
+    UserRepository, UserRepositoryError, PasswordService, TokenService, SessionRepository,
+    TwoFactorRepository, TotpService, Clock,
+};
+use crate::auth::domain::clock::SystemClock;
+use crate::error::ServiceError;
 use std::error::Error;
 
+// Refresh tokens outlive access tokens; each use rotates them
+const DEFAULT_REFRESH_TOKEN_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+// Time a user has to enter their second factor after the password check
+const TWO_FACTOR_CHALLENGE_TTL_SECS: u64 = 5 * 60;
+const RECOVERY_CODE_COUNT: usize = 10;
+// Second-factor attempts allowed per account within the window, whatever the challenge
+const MAX_SECOND_FACTOR_ATTEMPTS: u32 = 5;
+const SECOND_FACTOR_WINDOW_SECS: i64 = 15 * 60;
+// Verified when the username is unknown so that the response takes as long as for a wrong
+// password. Hash of a throwaway password with the current Argon2id parameters.
+const DUMMY_PASSWORD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$dHdvZG8tZHVtbXktc2FsdA$ZBkrBzxHJetNGGs+oHPASPo1+wmCTlVMZaDmA6rT9/4";
+
 pub struct AuthService {
     user_repository: Arc<dyn UserRepository>,
     password_service: Arc<dyn PasswordService>,
     token_service: Arc<dyn TokenService>,
+    session_repository: Arc<dyn SessionRepository>,
+    two_factor_repository: Arc<dyn TwoFactorRepository>,
+    totp_service: Arc<dyn TotpService>,
+    clock: Arc<dyn Clock>,
+    refresh_token_ttl: Duration,
 }
 
 impl AuthService {
@@ -17,14 +43,32 @@ impl AuthService {
         user_repository: Arc<dyn UserRepository>,
         password_service: Arc<dyn PasswordService>,
         token_service: Arc<dyn TokenService>,
+        session_repository: Arc<dyn SessionRepository>,
+        two_factor_repository: Arc<dyn TwoFactorRepository>,
+        totp_service: Arc<dyn TotpService>,
     ) -> Self {
         Self {
             user_repository,
             password_service,
             token_service,
+            session_repository,
+            two_factor_repository,
+            totp_service,
+            clock: Arc::new(SystemClock),
+            refresh_token_ttl: Duration::seconds(DEFAULT_REFRESH_TOKEN_TTL_SECS),
         }
     }
 
+    pub fn with_refresh_token_ttl(mut self, ttl_secs: u64) -> Self {
+        self.refresh_token_ttl = Duration::seconds(ttl_secs as i64);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     pub async fn register(&self, registration: UserRegistration) -> Result<UserInfo, Box<dyn Error>> {
         // Validate input
         if registration.username.len() < 3 || registration.username.len() > 50 {
@@ -36,67 +80,558 @@ impl AuthService {
 
         // Check if user already exists
         if self.user_repository.username_exists(&registration.username).await? {
-            return Err("User already exists".into());
+            return Err(Box::new(UserRepositoryError::UsernameTaken));
         }
 
         // Hash password
-        let hashed_password = self.password_service.hash_password(&registration.password).await?;
-        let password_hash = serde_json::to_string(&hashed_password)?;
+        let password_hash = self.password_service.hash_password(&registration.password).await?;
 
         // Create user
         let user = User {
             id: Uuid::new_v4(),
             username: registration.username.clone(),
             password_hash,
-            created_at: Utc::now(),
+            email: None,
+            email_verified: false,
+            created_at: self.clock.now(),
         };
 
         // Save user
         self.user_repository.create_user(&user).await?;
 
-        Ok(UserInfo {
-            id: user.id.to_string(),
-            username: user.username,
+        Ok(UserInfo::from(&user))
+    }
+
+    pub async fn login(&self, login: UserLogin, device: DeviceInfo) -> Result<LoginOutcome, Box<dyn Error>> {
+        // Find user. Unknown usernames still pay for a password check, so timing doesn't
+        // reveal which accounts exist.
+        let Some(user) = self.user_repository.get_user_by_username(&login.username).await? else {
+            let _ = self.password_service.verify_password(&login.password, DUMMY_PASSWORD_HASH).await;
+            return Err("Invalid credentials".into());
+        };
+
+        // Verify password
+        if !self.password_service.verify_password(&login.password, &user.password_hash).await? {
+            return Err("Invalid credentials".into());
+        }
+
+        // Upgrade legacy or outdated hashes now that we know the plaintext. Best effort:
+        // a failed upgrade must not block the login, it will be retried next time.
+        if self.password_service.needs_rehash(&user.password_hash) {
+            if let Ok(upgraded) = self.password_service.hash_password(&login.password).await {
+                let _ = self.user_repository.update_password_hash(&user.id, &upgraded).await;
+            }
+        }
+
+        // Accounts with 2FA get a challenge instead of tokens
+        if let Some(settings) = self.two_factor_repository.get_settings(&user.id).await? {
+            if settings.is_enabled() {
+                let challenge_token = self
+                    .token_service
+                    .generate_scoped_token(&user.id, &user.username, TWO_FACTOR_CHALLENGE_PURPOSE, TWO_FACTOR_CHALLENGE_TTL_SECS)
+                    .await?;
+                return Ok(LoginOutcome::SecondFactorRequired {
+                    challenge_token,
+                    expires_in: TWO_FACTOR_CHALLENGE_TTL_SECS,
+                });
+            }
+        }
+
+        Ok(LoginOutcome::Authenticated(self.start_session(&user, device).await?))
+    }
+
+    // Second step of a 2FA login: exchange the challenge token and a code for real tokens.
+    // The challenge is spent before the code is checked, so each one allows a single guess
+    // and a code is only used up by the request that owns the challenge.
+    pub async fn complete_two_factor_login(&self, challenge_token: &str, code: &str, device: DeviceInfo) -> Result<AuthResult, Box<dyn Error>> {
+        let claims = self
+            .token_service
+            .validate_scoped_token(challenge_token, TWO_FACTOR_CHALLENGE_PURPOSE)
+            .await
+            .map_err(|_| "Invalid or expired challenge")?;
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| "Invalid or expired challenge")?;
+        let jti = claims.jti.as_deref().ok_or("Invalid or expired challenge")?;
+        let expires_at = Utc.timestamp_opt(claims.exp as i64, 0).single().ok_or("Invalid or expired challenge")?;
+        let user = self
+            .user_repository
+            .get_user_by_id(&user_id)
+            .await?
+            .ok_or("Invalid or expired challenge")?;
+
+        if !self.two_factor_repository.spend_challenge(jti, expires_at, self.clock.now()).await? {
+            return Err("Invalid or expired challenge".into());
+        }
+        self.verify_second_factor(&user.id, code).await?;
+        self.start_session(&user, device).await
+    }
+
+    // Start 2FA enrolment; the secret is inactive until confirm_two_factor succeeds
+    pub async fn enroll_two_factor(&self, user_id: &Uuid) -> Result<TotpEnrollment, Box<dyn Error>> {
+        let user = self.user_repository.get_user_by_id(user_id).await?.ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+        if let Some(existing) = self.two_factor_repository.get_settings(user_id).await? {
+            if existing.is_enabled() {
+                return Err(ServiceError::Conflict("Two-factor authentication is already enabled".to_string()).into());
+            }
+        }
+
+        let secret = self.totp_service.generate_secret()?;
+        self.two_factor_repository
+            .save_pending(&TwoFactorSettings {
+                user_id: *user_id,
+                secret: secret.clone(),
+                enabled_at: None,
+                last_used_step: None,
+                created_at: self.clock.now(),
+            })
+            .await?;
+
+        Ok(TotpEnrollment {
+            otpauth_uri: self.totp_service.provisioning_uri(&secret, &user.username),
+            secret,
         })
     }
 
-    pub async fn login(&self, login: UserLogin) -> Result<AuthResult, Box<dyn Error>> {
-        // Find user
+    // Activate 2FA with a first valid code; recovery codes are shown exactly once
+    pub async fn confirm_two_factor(&self, user_id: &Uuid, code: &str) -> Result<RecoveryCodes, Box<dyn Error>> {
+        let settings = self
+            .two_factor_repository
+            .get_settings(user_id)
+            .await?
+            .ok_or("Two-factor enrolment has not been started")?;
+        if settings.is_enabled() {
+            return Err(ServiceError::Conflict("Two-factor authentication is already enabled".to_string()).into());
+        }
+
+        let now = self.clock.now();
+        let step = self
+            .totp_service
+            .verify_code(&settings.secret, code, now)?
+            .ok_or("Invalid verification code")?;
+        self.two_factor_repository.enable(user_id, now, step).await?;
+
+        let recovery_codes = self.totp_service.generate_recovery_codes(RECOVERY_CODE_COUNT)?;
+        let hashes: Vec<String> = recovery_codes.iter().map(|c| self.totp_service.hash_recovery_code(c)).collect();
+        self.two_factor_repository.replace_recovery_codes(user_id, &hashes, now).await?;
+
+        Ok(RecoveryCodes { recovery_codes })
+    }
+
+    // Turning 2FA off needs both the password and a current second factor
+    pub async fn disable_two_factor(&self, user_id: &Uuid, password: &str, code: &str) -> Result<(), Box<dyn Error>> {
+        let user = self.user_repository.get_user_by_id(user_id).await?.ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+        if !self.password_service.verify_password(password, &user.password_hash).await? {
+            return Err(ServiceError::Forbidden("Invalid credentials".to_string()).into());
+        }
+
+        self.verify_second_factor(user_id, code).await?;
+        self.two_factor_repository.delete(user_id).await
+    }
+
+    // Accepts a TOTP code (each time step at most once) or an unused recovery code. Every
+    // attempt is counted against the account before the code is looked at, so parallel
+    // guesses cannot get past the cap; a good code clears the count.
+    async fn verify_second_factor(&self, user_id: &Uuid, code: &str) -> Result<(), Box<dyn Error>> {
+        let settings = self
+            .two_factor_repository
+            .get_settings(user_id)
+            .await?
+            .filter(|s| s.is_enabled())
+            .ok_or("Two-factor authentication is not enabled")?;
+
+        let now = self.clock.now();
+        let window_start = now - Duration::seconds(SECOND_FACTOR_WINDOW_SECS);
+        if self.two_factor_repository.record_attempt(user_id, now, window_start).await? > MAX_SECOND_FACTOR_ATTEMPTS {
+            return Err("Too many verification attempts, please try again later".into());
+        }
+
+        if let Some(step) = self.totp_service.verify_code(&settings.secret, code, now)? {
+            if settings.last_used_step.map_or(false, |last| step <= last)
+                || !self.two_factor_repository.record_used_step(user_id, step).await?
+            {
+                return Err("Verification code has already been used".into());
+            }
+            self.clear_attempts(user_id).await;
+            return Ok(());
+        }
+
+        let code_hash = self.totp_service.hash_recovery_code(code);
+        if self.two_factor_repository.consume_recovery_code(user_id, &code_hash, now).await? {
+            self.clear_attempts(user_id).await;
+            return Ok(());
+        }
+
+        Err("Invalid verification code".into())
+    }
+
+    // Best effort: the code has been used up by now, so failing here must not fail the
+    // request; the count then runs out with its window
+    async fn clear_attempts(&self, user_id: &Uuid) {
+        let _ = self.two_factor_repository.clear_attempts(user_id).await;
+    }
+
+    // Start a session for this device and issue its first tokens
+    async fn start_session(&self, user: &User, device: DeviceInfo) -> Result<AuthResult, Box<dyn Error>> {
+        let now = self.clock.now();
+        let session = Session {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            device_name: device.device_name.map(|d| d.trim().chars().take(100).collect::<String>()).filter(|d| !d.is_empty()),
+            user_agent: device.user_agent.map(|ua| ua.chars().take(255).collect()),
+            created_at: now,
+            last_used_at: now,
+            revoked_at: None,
+        };
+        self.session_repository.create_session(&session).await?;
+
+        self.issue_tokens(user, &session.id).await
+    }
+
+    // Exchange a refresh token for a new access token and a rotated refresh token
+    pub async fn refresh(&self, refresh_token: &str) -> Result<AuthResult, Box<dyn Error>> {
+        let token_hash = self.token_service.hash_opaque_token(refresh_token);
+        let stored = self
+            .session_repository
+            .get_refresh_token_by_hash(&token_hash)
+            .await?
+            .ok_or("Invalid refresh token")?;
+
+        let session = self
+            .session_repository
+            .get_session(&stored.session_id)
+            .await?
+            .filter(|s| s.is_active())
+            .ok_or("Invalid refresh token")?;
+
+        let now = self.clock.now();
+
+        // A spent token coming back means it was copied: revoke the whole family
+        if stored.used_at.is_some() || !self.session_repository.mark_refresh_token_used(&stored.id, now).await? {
+            self.session_repository.revoke_session(&session.id, now).await?;
+            return Err("Refresh token reuse detected; session revoked".into());
+        }
+
+        if stored.expires_at <= now {
+            return Err("Refresh token has expired".into());
+        }
+
         let user = self
             .user_repository
-            .get_user_by_username(&login.username)
+            .get_user_by_id(&session.user_id)
             .await?
-            .ok_or("Invalid credentials")?;
+            .ok_or("Invalid refresh token")?;
 
-        // Parse stored password hash
-        let stored_password = serde_json::from_str(&user.password_hash)
-            .map_err(|_| "Invalid password data")?;
+        self.session_repository.touch_session(&session.id, now).await?;
+        self.issue_tokens(&user, &session.id).await
+    }
 
-        // Verify password
-        if !self.password_service.verify_password(&login.password, &stored_password).await? {
-            return Err("Invalid credentials".into());
+    pub async fn logout(&self, session_id: &Uuid) -> Result<(), Box<dyn Error>> {
+        self.session_repository.revoke_session(session_id, self.clock.now()).await
+    }
+
+    pub async fn list_sessions(&self, user_id: &Uuid, current_session_id: Option<&Uuid>) -> Result<Vec<SessionInfo>, Box<dyn Error>> {
+        let sessions = self.session_repository.get_active_sessions(user_id).await?;
+        Ok(sessions
+            .into_iter()
+            .map(|s| SessionInfo {
+                current: current_session_id == Some(&s.id),
+                id: s.id,
+                device_name: s.device_name,
+                user_agent: s.user_agent,
+                created_at: s.created_at,
+                last_used_at: s.last_used_at,
+            })
+            .collect())
+    }
+
+    pub async fn revoke_session(&self, user_id: &Uuid, session_id: &Uuid) -> Result<(), Box<dyn Error>> {
+        // Only the owner may revoke; other users' sessions look the same as missing ones
+        match self.session_repository.get_session(session_id).await? {
+            Some(session) if &session.user_id == user_id && session.is_active() => {
+                self.session_repository.revoke_session(session_id, self.clock.now()).await
+            }
+            _ => Err(ServiceError::NotFound("Session not found".to_string()).into()),
         }
+    }
+
+    pub async fn revoke_all_sessions(&self, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
+        self.session_repository.revoke_all_sessions(user_id, self.clock.now()).await
+    }
 
-        // Generate token
-        let token = self.token_service.generate_token(&user.id, &user.username).await?;
+    // Issue an access token plus a fresh refresh token within the given session
+    async fn issue_tokens(&self, user: &User, session_id: &Uuid) -> Result<AuthResult, Box<dyn Error>> {
+        let token = self.token_service.generate_token(&user.id, &user.username, session_id).await?;
+
+        let refresh_token = self.token_service.generate_opaque_token()?;
+        let now = self.clock.now();
+        self.session_repository
+            .create_refresh_token(&RefreshToken {
+                id: Uuid::new_v4(),
+                session_id: *session_id,
+                token_hash: self.token_service.hash_opaque_token(&refresh_token),
+                expires_at: now + self.refresh_token_ttl,
+                used_at: None,
+                created_at: now,
+            })
+            .await?;
 
         Ok(AuthResult {
-            user: UserInfo {
-                id: user.id.to_string(),
-                username: user.username,
-            },
+            user: UserInfo::from(user),
             token,
+            refresh_token,
+            session_id: session_id.to_string(),
         })
     }
 
     pub async fn get_user_by_id(&self, user_id: &Uuid) -> Result<Option<UserInfo>, Box<dyn Error>> {
         if let Some(user) = self.user_repository.get_user_by_id(user_id).await? {
-            Ok(Some(UserInfo {
-                id: user.id.to_string(),
-                username: user.username,
-            }))
+            Ok(Some(UserInfo::from(&user)))
         } else {
             Ok(None)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use chrono::DateTime;
+    use crate::auth::domain::clock::FixedClock;
+    use crate::auth::infrastructure::{
+        InMemorySessionRepository, InMemoryTwoFactorRepository, InMemoryUserRepository, KeyRing,
+        WasmTokenService, WasmTotpService,
+    };
+    use crate::test_support::block_on;
+
+    // RFC 6238 SHA-1 seed; "005924" is its code for step 41152263 (T = 1234567890)
+    const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+    const CODE: &str = "005924";
+    const CODE_AT: i64 = 1_234_567_890;
+    const RECOVERY_CODE: &str = "K7QF2-MZ4XD";
+
+    // Stores passwords as-is; hashing cost is not what these tests are about
+    struct PlainPasswordService;
+
+    #[async_trait]
+    impl PasswordService for PlainPasswordService {
+        async fn hash_password(&self, password: &str) -> Result<String, Box<dyn Error>> {
+            Ok(format!("plain:{}", password))
+        }
+
+        async fn verify_password(&self, password: &str, stored_hash: &str) -> Result<bool, Box<dyn Error>> {
+            Ok(stored_hash == format!("plain:{}", password))
+        }
+
+        fn needs_rehash(&self, _stored_hash: &str) -> bool {
+            false
+        }
+    }
+
+    struct Fixture {
+        users: Arc<InMemoryUserRepository>,
+        sessions: Arc<InMemorySessionRepository>,
+        two_factor: Arc<InMemoryTwoFactorRepository>,
+    }
+
+    impl Fixture {
+        // A user "alice" with 2FA enabled on SECRET and one recovery code
+        fn new() -> Self {
+            let fixture = Self {
+                users: Arc::new(InMemoryUserRepository::new()),
+                sessions: Arc::new(InMemorySessionRepository::new()),
+                two_factor: Arc::new(InMemoryTwoFactorRepository::new()),
+            };
+            let enrolled_at = Utc.timestamp_opt(CODE_AT - 3600, 0).unwrap();
+            let user = User {
+                id: Uuid::new_v4(),
+                username: "alice".to_string(),
+                password_hash: "plain:correct horse".to_string(),
+                email: None,
+                email_verified: false,
+                created_at: enrolled_at,
+            };
+            block_on(async {
+                fixture.users.create_user(&user).await.unwrap();
+                fixture
+                    .two_factor
+                    .save_pending(&TwoFactorSettings {
+                        user_id: user.id,
+                        secret: SECRET.to_string(),
+                        enabled_at: None,
+                        last_used_step: None,
+                        created_at: enrolled_at,
+                    })
+                    .await
+                    .unwrap();
+                fixture.two_factor.enable(&user.id, enrolled_at, (enrolled_at.timestamp() / 30) as u64).await.unwrap();
+                let hash = WasmTotpService::new().hash_recovery_code(RECOVERY_CODE);
+                fixture.two_factor.replace_recovery_codes(&user.id, &[hash], enrolled_at).await.unwrap();
+            });
+            fixture
+        }
+
+        // Every collaborator reads the time from one fixed clock
+        fn service_at(&self, seconds: i64) -> AuthService {
+            let clock: Arc<dyn Clock> = Arc::new(FixedClock::new(Utc.timestamp_opt(seconds, 0).unwrap()));
+            let token_service = WasmTokenService::new(KeyRing::parse("test-signing-secret").unwrap(), 900)
+                .with_clock(clock.clone());
+            AuthService::new(
+                self.users.clone(),
+                Arc::new(PlainPasswordService),
+                Arc::new(token_service),
+                self.sessions.clone(),
+                self.two_factor.clone(),
+                Arc::new(WasmTotpService::new()),
+            )
+            .with_clock(clock)
+        }
+    }
+
+    fn challenge(service: &AuthService) -> String {
+        let login = UserLogin {
+            username: "alice".to_string(),
+            password: "correct horse".to_string(),
+        };
+        match block_on(service.login(login, DeviceInfo::default())).unwrap() {
+            LoginOutcome::SecondFactorRequired { challenge_token, .. } => challenge_token,
+            LoginOutcome::Authenticated(_) => panic!("expected a 2FA challenge"),
+        }
+    }
+
+    fn complete(service: &AuthService, challenge_token: &str, code: &str) -> Result<AuthResult, String> {
+        block_on(service.complete_two_factor_login(challenge_token, code, DeviceInfo::default()))
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn totp_code_completes_the_login() {
+        let fixture = Fixture::new();
+        let service = fixture.service_at(CODE_AT);
+        let token = challenge(&service);
+        assert!(complete(&service, &token, CODE).is_ok());
+    }
+
+    #[test]
+    fn totp_code_is_accepted_one_step_either_side() {
+        for offset in [-30, 30] {
+            let fixture = Fixture::new();
+            let service = fixture.service_at(CODE_AT + offset);
+            let token = challenge(&service);
+            assert!(complete(&service, &token, CODE).is_ok(), "offset {}s", offset);
+        }
+    }
+
+    #[test]
+    fn totp_code_two_steps_away_is_rejected() {
+        let fixture = Fixture::new();
+        let service = fixture.service_at(CODE_AT + 60);
+        let token = challenge(&service);
+        assert_eq!(complete(&service, &token, CODE).unwrap_err(), "Invalid verification code");
+    }
+
+    #[test]
+    fn totp_code_cannot_be_replayed() {
+        let fixture = Fixture::new();
+        let service = fixture.service_at(CODE_AT);
+        let first = challenge(&service);
+        assert!(complete(&service, &first, CODE).is_ok());
+
+        let second = challenge(&service);
+        assert_eq!(complete(&service, &second, CODE).unwrap_err(), "Verification code has already been used");
+    }
+
+    #[test]
+    fn recovery_code_works_once() {
+        let fixture = Fixture::new();
+        let service = fixture.service_at(CODE_AT);
+        let first = challenge(&service);
+        assert!(complete(&service, &first, "k7qf2mz4xd").is_ok());
+
+        let second = challenge(&service);
+        assert_eq!(complete(&service, &second, RECOVERY_CODE).unwrap_err(), "Invalid verification code");
+    }
+
+    #[test]
+    fn challenge_token_is_single_use() {
+        let fixture = Fixture::new();
+        let service = fixture.service_at(CODE_AT);
+        let token = challenge(&service);
+        assert!(complete(&service, &token, CODE).is_ok());
+        assert_eq!(complete(&service, &token, RECOVERY_CODE).unwrap_err(), "Invalid or expired challenge");
+    }
+
+    #[test]
+    fn wrong_code_spends_the_challenge() {
+        let fixture = Fixture::new();
+        let service = fixture.service_at(CODE_AT);
+        let token = challenge(&service);
+        assert_eq!(complete(&service, &token, "000000").unwrap_err(), "Invalid verification code");
+        assert_eq!(complete(&service, &token, CODE).unwrap_err(), "Invalid or expired challenge");
+
+        // The code was not used up, so a new challenge takes it
+        let fresh = challenge(&service);
+        assert!(complete(&service, &fresh, CODE).is_ok());
+    }
+
+    #[test]
+    fn attempts_are_capped_per_account_across_challenges() {
+        let fixture = Fixture::new();
+        let service = fixture.service_at(CODE_AT);
+        for _ in 0..MAX_SECOND_FACTOR_ATTEMPTS {
+            let token = challenge(&service);
+            assert_eq!(complete(&service, &token, "000000").unwrap_err(), "Invalid verification code");
+        }
+
+        let token = challenge(&service);
+        assert_eq!(
+            complete(&service, &token, CODE).unwrap_err(),
+            "Too many verification attempts, please try again later"
+        );
+
+        // Once the window has passed the count starts over
+        let later = fixture.service_at(CODE_AT + SECOND_FACTOR_WINDOW_SECS + 1);
+        let token = challenge(&later);
+        assert!(complete(&later, &token, RECOVERY_CODE).is_ok());
+    }
+
+    #[test]
+    fn good_code_clears_the_attempts() {
+        let fixture = Fixture::new();
+        let service = fixture.service_at(CODE_AT);
+        for _ in 0..MAX_SECOND_FACTOR_ATTEMPTS - 1 {
+            let token = challenge(&service);
+            assert!(complete(&service, &token, "000000").is_err());
+        }
+        let token = challenge(&service);
+        assert!(complete(&service, &token, CODE).is_ok());
+
+        for _ in 0..MAX_SECOND_FACTOR_ATTEMPTS - 1 {
+            let token = challenge(&service);
+            assert_eq!(complete(&service, &token, "000000").unwrap_err(), "Invalid verification code");
+        }
+        let token = challenge(&service);
+        assert!(complete(&service, &token, RECOVERY_CODE).is_ok());
+    }
+
+    #[test]
+    fn challenge_expires_by_the_injected_clock() {
+        let fixture = Fixture::new();
+        let token = challenge(&fixture.service_at(CODE_AT));
+
+        // Past the challenge TTL plus the validator's clock-skew leeway
+        let later = fixture.service_at(CODE_AT + TWO_FACTOR_CHALLENGE_TTL_SECS as i64 + 61);
+        assert_eq!(complete(&later, &token, RECOVERY_CODE).unwrap_err(), "Invalid or expired challenge");
+    }
+
+    #[test]
+    fn spent_challenge_ids_are_forgotten_after_expiry() {
+        let repository = InMemoryTwoFactorRepository::new();
+        let at = |seconds: i64| -> DateTime<Utc> { Utc.timestamp_opt(seconds, 0).unwrap() };
+        block_on(async {
+            assert!(repository.spend_challenge("jti-1", at(400), at(100)).await.unwrap());
+            assert!(!repository.spend_challenge("jti-1", at(400), at(200)).await.unwrap());
+            // Once the token itself is expired its id no longer needs to be remembered
+            assert!(repository.spend_challenge("jti-1", at(900), at(500)).await.unwrap());
+        });
+    }
+}
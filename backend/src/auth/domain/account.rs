@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use super::user::UserInfo;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+    // Optional successor per group (group ID -> member ID); otherwise another admin,
+    // or failing that the longest-standing member, takes over
+    #[serde(default)]
+    pub transfer_to: HashMap<Uuid, Uuid>,
+}
+
+// One group the departing user belongs to, with the members who would remain
+#[derive(Debug, Clone)]
+pub struct GroupMembershipSummary {
+    pub group_id: Uuid,
+    pub group_name: String,
+    pub is_owner: bool,
+    pub is_admin: bool, // owners count as admins
+    pub remaining_members: Vec<RemainingMember>, // oldest membership first
+}
+
+#[derive(Debug, Clone)]
+pub struct RemainingMember {
+    pub user_id: Uuid,
+    pub is_admin: bool,
+}
+
+// What happens to each group when its member deletes their account
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum GroupDisposition {
+    // Other members keep the group; `new_admin` is set when the leaver was its owner or last
+    // admin, and inherits the leaver's role
+    Leave { group_id: Uuid, new_admin: Option<Uuid> },
+    // Nobody else is left, so the group and everything in it goes
+    Delete { group_id: Uuid },
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AccountDeletionSummary {
+    pub user_id: Uuid,
+    pub groups: Vec<GroupDisposition>,
+    pub deleted_at: DateTime<Utc>,
+}
+
+// Everything stored about or created by one user. Rows are exported as stored so the
+// bundle stays complete as tables gain columns.
+#[derive(Debug, Serialize, Clone)]
+pub struct AccountExport {
+    pub exported_at: DateTime<Utc>,
+    pub user: UserInfo,
+    pub profile: Vec<Value>,
+    pub sessions: Vec<Value>,
+    pub groups: Vec<Value>,
+    pub group_memberships: Vec<Value>,
+    pub expenses: Vec<Value>,
+    pub expense_shares: Vec<Value>,
+    pub payments: Vec<Value>,
+    pub chores: Vec<Value>,
+    pub events: Vec<Value>,
+    pub event_attendance: Vec<Value>,
+    pub activity: Vec<Value>,
+}
+
+// Export rows grouped by section, as read from storage
+#[derive(Debug, Clone, Default)]
+pub struct AccountData {
+    pub profile: Vec<Value>,
+    pub sessions: Vec<Value>,
+    pub groups: Vec<Value>,
+    pub group_memberships: Vec<Value>,
+    pub expenses: Vec<Value>,
+    pub expense_shares: Vec<Value>,
+    pub payments: Vec<Value>,
+    pub chores: Vec<Value>,
+    pub events: Vec<Value>,
+    pub event_attendance: Vec<Value>,
+    pub activity: Vec<Value>,
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountTokenPurpose {
+    PasswordReset,
+    EmailVerification,
+}
+
+impl AccountTokenPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountTokenPurpose::PasswordReset => "password_reset",
+            AccountTokenPurpose::EmailVerification => "email_verification",
+        }
+    }
+}
+
+// Single-use, time-limited token mailed to the user; only its hash is stored
+#[derive(Debug, Clone)]
+pub struct AccountToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub purpose: AccountTokenPurpose,
+    pub token_hash: String,
+    pub email: String, // address the token was sent to
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MailMessage {
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetEmailRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+
+use super::ports::Clock;
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// Always reports the same instant; lets TOTP windows and token expiry be checked offline
+pub struct FixedClock {
+    now: DateTime<Utc>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+}
@@ -1,2 +1,8 @@
 pub mod user;
+pub mod session;
+pub mod two_factor;
+pub mod account_token;
+pub mod rate_limit;
+pub mod account;
+pub mod clock;
 pub mod ports;
@@ -1,24 +1,146 @@
 use async_trait::async_trait;
 use uuid::Uuid;
-use super::user::{User, HashedPassword, JwtClaims};
+use chrono::{DateTime, Utc};
+use super::user::{User, JwtClaims};
+use super::session::{Session, RefreshToken};
+use super::two_factor::TwoFactorSettings;
+use super::account_token::{AccountToken, AccountTokenPurpose, MailMessage};
+use super::rate_limit::RateLimitState;
+use super::account::{AccountData, GroupDisposition, GroupMembershipSummary};
 use std::error::Error;
 
+// Failures callers are expected to handle, as opposed to storage errors
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserRepositoryError {
+    UsernameTaken,
+    EmailTaken,
+}
+
+impl std::fmt::Display for UserRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UserRepositoryError::UsernameTaken => write!(f, "User already exists"),
+            UserRepositoryError::EmailTaken => write!(f, "Email already in use"),
+        }
+    }
+}
+
+impl Error for UserRepositoryError {}
+
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn create_user(&self, user: &User) -> Result<(), Box<dyn Error>>;
     async fn get_user_by_username(&self, username: &str) -> Result<Option<User>, Box<dyn Error>>;
     async fn get_user_by_id(&self, user_id: &Uuid) -> Result<Option<User>, Box<dyn Error>>;
     async fn username_exists(&self, username: &str) -> Result<bool, Box<dyn Error>>;
+    async fn update_password_hash(&self, user_id: &Uuid, password_hash: &str) -> Result<(), Box<dyn Error>>;
+    async fn get_user_by_email(&self, email: &str) -> Result<Option<User>, Box<dyn Error>>;
+    // Setting or clearing the email always resets it to unverified
+    async fn update_email(&self, user_id: &Uuid, email: Option<&str>) -> Result<(), Box<dyn Error>>;
+    // Only verifies if the user's current email is still `email`; false otherwise
+    async fn mark_email_verified(&self, user_id: &Uuid, email: &str) -> Result<bool, Box<dyn Error>>;
 }
 
 #[async_trait]
 pub trait PasswordService: Send + Sync {
-    async fn hash_password(&self, password: &str) -> Result<HashedPassword, Box<dyn Error>>;
-    async fn verify_password(&self, password: &str, hash: &HashedPassword) -> Result<bool, Box<dyn Error>>;
+    // Returns a self-describing PHC string, e.g. "$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>"
+    async fn hash_password(&self, password: &str) -> Result<String, Box<dyn Error>>;
+    async fn verify_password(&self, password: &str, stored_hash: &str) -> Result<bool, Box<dyn Error>>;
+    // True when the stored hash uses a legacy scheme or outdated parameters
+    fn needs_rehash(&self, stored_hash: &str) -> bool;
 }
 
 #[async_trait] 
 pub trait TokenService: Send + Sync {
-    async fn generate_token(&self, user_id: &Uuid, username: &str) -> Result<String, Box<dyn Error>>;
+    // Short-lived access token bound to a session via the `sid` claim
+    async fn generate_token(&self, user_id: &Uuid, username: &str, session_id: &Uuid) -> Result<String, Box<dyn Error>>;
+    // Rejects tokens that carry a `purpose`; those are never access tokens
     async fn validate_token(&self, token: &str) -> Result<JwtClaims, Box<dyn Error>>;
+    // Single-purpose tokens (e.g. 2FA challenges) that the access guard refuses
+    async fn generate_scoped_token(&self, user_id: &Uuid, username: &str, purpose: &str, ttl_secs: u64) -> Result<String, Box<dyn Error>>;
+    async fn validate_scoped_token(&self, token: &str, purpose: &str) -> Result<JwtClaims, Box<dyn Error>>;
+    // Opaque random tokens (refresh, password reset, email verification); only hashes are stored
+    fn generate_opaque_token(&self) -> Result<String, Box<dyn Error>>;
+    fn hash_opaque_token(&self, token: &str) -> String;
+}
+
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    async fn create_session(&self, session: &Session) -> Result<(), Box<dyn Error>>;
+    async fn get_session(&self, session_id: &Uuid) -> Result<Option<Session>, Box<dyn Error>>;
+    async fn get_active_sessions(&self, user_id: &Uuid) -> Result<Vec<Session>, Box<dyn Error>>;
+    async fn touch_session(&self, session_id: &Uuid, at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
+    async fn revoke_session(&self, session_id: &Uuid, at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
+    async fn revoke_all_sessions(&self, user_id: &Uuid, at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
+    async fn create_refresh_token(&self, token: &RefreshToken) -> Result<(), Box<dyn Error>>;
+    async fn get_refresh_token_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>, Box<dyn Error>>;
+    // Marks the token used only if it was still unused; false means it was already spent
+    async fn mark_refresh_token_used(&self, token_id: &Uuid, at: DateTime<Utc>) -> Result<bool, Box<dyn Error>>;
+}
+
+#[async_trait]
+pub trait TwoFactorRepository: Send + Sync {
+    async fn get_settings(&self, user_id: &Uuid) -> Result<Option<TwoFactorSettings>, Box<dyn Error>>;
+    // Insert or replace a pending (not yet enabled) secret
+    async fn save_pending(&self, settings: &TwoFactorSettings) -> Result<(), Box<dyn Error>>;
+    async fn enable(&self, user_id: &Uuid, at: DateTime<Utc>, step: u64) -> Result<(), Box<dyn Error>>;
+    // Records the step only if it is newer than the last one; false means the code was replayed
+    async fn record_used_step(&self, user_id: &Uuid, step: u64) -> Result<bool, Box<dyn Error>>;
+    async fn delete(&self, user_id: &Uuid) -> Result<(), Box<dyn Error>>;
+    async fn replace_recovery_codes(&self, user_id: &Uuid, code_hashes: &[String], at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
+    // Marks a matching unused code as used; false when no such code exists
+    async fn consume_recovery_code(&self, user_id: &Uuid, code_hash: &str, at: DateTime<Utc>) -> Result<bool, Box<dyn Error>>;
+    // Records a challenge token id as spent at `at`; false when it was spent before
+    async fn spend_challenge(&self, jti: &str, expires_at: DateTime<Utc>, at: DateTime<Utc>) -> Result<bool, Box<dyn Error>>;
+    // Counts one second-factor attempt at `at` and returns the attempts made since the count
+    // started. A count started before `window_start` is forgotten and starts over at 1.
+    async fn record_attempt(&self, user_id: &Uuid, at: DateTime<Utc>, window_start: DateTime<Utc>) -> Result<u32, Box<dyn Error>>;
+    async fn clear_attempts(&self, user_id: &Uuid) -> Result<(), Box<dyn Error>>;
+}
+
+#[async_trait]
+pub trait AccountTokenRepository: Send + Sync {
+    async fn create_token(&self, token: &AccountToken) -> Result<(), Box<dyn Error>>;
+    async fn get_token_by_hash(&self, token_hash: &str, purpose: AccountTokenPurpose) -> Result<Option<AccountToken>, Box<dyn Error>>;
+    // Marks the token used only if it was still unused; false means it was already spent
+    async fn consume_token(&self, token_id: &Uuid, at: DateTime<Utc>) -> Result<bool, Box<dyn Error>>;
+    // Spends every outstanding token of this purpose, e.g. when a newer one is issued
+    async fn invalidate_tokens(&self, user_id: &Uuid, purpose: AccountTokenPurpose, at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
+}
+
+#[async_trait]
+pub trait MailSender: Send + Sync {
+    async fn send(&self, message: &MailMessage) -> Result<(), Box<dyn Error>>;
+}
+
+// Counter storage for the rate limiter. Entries may be dropped once `expires_at` passes.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    async fn get_state(&self, key: &str) -> Result<Option<RateLimitState>, Box<dyn Error>>;
+    async fn put_state(&self, key: &str, state: &RateLimitState, expires_at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
+    async fn clear(&self, key: &str) -> Result<(), Box<dyn Error>>;
+}
+
+// Cross-module data owned by one user, used for account export and deletion
+#[async_trait]
+pub trait AccountDataRepository: Send + Sync {
+    async fn group_memberships(&self, user_id: &Uuid) -> Result<Vec<GroupMembershipSummary>, Box<dyn Error>>;
+    async fn export_data(&self, user_id: &Uuid) -> Result<AccountData, Box<dyn Error>>;
+    // Applies the group dispositions, drops personal data and turns the user row into an
+    // anonymous tombstone so other members' expenses and balances still resolve. Atomic.
+    async fn delete_account(&self, user_id: &Uuid, groups: &[GroupDisposition], tombstone_username: &str, at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
+}
+
+pub trait TotpService: Send + Sync {
+    fn generate_secret(&self) -> Result<String, Box<dyn Error>>;
+    fn provisioning_uri(&self, secret: &str, account_name: &str) -> String;
+    // Returns the matching time step when the code is valid within one step of `at`
+    fn verify_code(&self, secret: &str, code: &str, at: DateTime<Utc>) -> Result<Option<u64>, Box<dyn Error>>;
+    fn generate_recovery_codes(&self, count: usize) -> Result<Vec<String>, Box<dyn Error>>;
+    fn hash_recovery_code(&self, code: &str) -> String;
+}
+
+// Source of the current time, replaceable so time-based checks can run with fixed clocks
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
 }
@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Limits for one kind of request. Attempts are counted over a sliding window; once
+// `delay_after` is passed each attempt waits a little longer, and reaching `max_attempts`
+// locks the key out for `lockout_secs`.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitPolicy {
+    pub scope: &'static str,
+    pub max_attempts: u32,
+    pub window_secs: i64,
+    pub lockout_secs: i64,
+    pub delay_after: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl RateLimitPolicy {
+    // Every login attempt from one client address
+    pub const LOGIN_IP: RateLimitPolicy = RateLimitPolicy {
+        scope: "login_ip",
+        max_attempts: 30,
+        window_secs: 15 * 60,
+        lockout_secs: 15 * 60,
+        delay_after: 10,
+        base_delay_ms: 250,
+        max_delay_ms: 4_000,
+    };
+
+    // Failed logins against one account, from anywhere; cleared by a successful login
+    pub const LOGIN_ACCOUNT: RateLimitPolicy = RateLimitPolicy {
+        scope: "login_account",
+        max_attempts: 10,
+        window_secs: 15 * 60,
+        lockout_secs: 15 * 60,
+        delay_after: 3,
+        base_delay_ms: 500,
+        max_delay_ms: 8_000,
+    };
+
+    // Second-factor codes against one account, from anywhere; cleared by a successful login.
+    // Keyed by user id, taken from the challenge token.
+    pub const TWO_FACTOR_ACCOUNT: RateLimitPolicy = RateLimitPolicy {
+        scope: "two_factor_account",
+        max_attempts: 10,
+        window_secs: 15 * 60,
+        lockout_secs: 15 * 60,
+        delay_after: 3,
+        base_delay_ms: 500,
+        max_delay_ms: 8_000,
+    };
+
+    pub const REGISTER_IP: RateLimitPolicy = RateLimitPolicy {
+        scope: "register_ip",
+        max_attempts: 5,
+        window_secs: 60 * 60,
+        lockout_secs: 60 * 60,
+        delay_after: u32::MAX,
+        base_delay_ms: 0,
+        max_delay_ms: 0,
+    };
+
+    // Invitations sent by one user
+    pub const INVITE_USER: RateLimitPolicy = RateLimitPolicy {
+        scope: "invite_user",
+        max_attempts: 20,
+        window_secs: 60 * 60,
+        lockout_secs: 60 * 60,
+        delay_after: u32::MAX,
+        base_delay_ms: 0,
+        max_delay_ms: 0,
+    };
+
+    // Invite link and join code redemptions from one client address. Join codes are short,
+    // so this is what keeps them from being guessed.
+    pub const JOIN_GROUP_IP: RateLimitPolicy = RateLimitPolicy {
+        scope: "join_group_ip",
+        max_attempts: 10,
+        window_secs: 60 * 60,
+        lockout_secs: 60 * 60,
+        delay_after: 3,
+        base_delay_ms: 500,
+        max_delay_ms: 4_000,
+    };
+
+    // Storage key for a client/account within this policy
+    pub fn key(&self, subject: &str) -> String {
+        format!("{}:{}", self.scope, subject.trim().to_lowercase())
+    }
+}
+
+// Counters for one key: the current fixed window plus the count carried over from the
+// previous one, which is weighted by how much of it still overlaps the sliding window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitState {
+    pub window_start: DateTime<Utc>,
+    pub current_count: u32,
+    pub previous_count: u32,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateLimitDecision {
+    // Go ahead, after waiting `delay_ms`
+    Allowed { remaining: u32, delay_ms: u64 },
+    // Refuse with 429; the client may try again after `retry_after_secs`
+    Limited { retry_after_secs: u64 },
+}
+
+impl RateLimitDecision {
+    pub fn is_limited(&self) -> bool {
+        matches!(self, RateLimitDecision::Limited { .. })
+    }
+}
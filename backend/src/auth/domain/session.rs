@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// A signed-in device. The session is also the refresh token family: reusing a
+// rotated refresh token revokes the whole session.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl Session {
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Client details recorded on login so users can recognise their devices
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeviceInfo {
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionInfo {
+    pub id: Uuid,
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
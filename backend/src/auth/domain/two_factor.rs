@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use super::user::AuthResult;
+
+// Purpose claim of the short-lived token handed out between password and TOTP checks
+pub const TWO_FACTOR_CHALLENGE_PURPOSE: &str = "2fa_challenge";
+
+// RFC 6238 settings for one user. The secret is stored as soon as enrolment starts,
+// but only takes effect once `enabled_at` is set by a confirmed code.
+#[derive(Debug, Clone)]
+pub struct TwoFactorSettings {
+    pub user_id: Uuid,
+    pub secret: String, // base32, as shown to authenticator apps
+    pub enabled_at: Option<DateTime<Utc>>,
+    pub last_used_step: Option<u64>, // replay guard: a time step is accepted at most once
+    pub created_at: DateTime<Utc>,
+}
+
+impl TwoFactorSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled_at.is_some()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfirmTwoFactor {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DisableTwoFactor {
+    pub password: String,
+    pub code: String, // TOTP code or an unused recovery code
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TwoFactorLogin {
+    pub challenge_token: String,
+    pub code: String, // TOTP code or an unused recovery code
+    #[serde(default)]
+    pub device_name: Option<String>,
+}
+
+// Result of a password login: either tokens, or a challenge to exchange with a second factor
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum LoginOutcome {
+    Authenticated(AuthResult),
+    SecondFactorRequired {
+        challenge_token: String,
+        expires_in: u64,
+    },
+}
@@ -8,11 +8,15 @@ pub struct User {
     pub username: String,
     #[serde(skip_serializing)]
     pub password_hash: String,
+    pub email: Option<String>, // stored lowercase
+    pub email_verified: bool,
     pub created_at: DateTime<Utc>,
 }
 
+// Pre-Argon2 format: JSON blob of SHA256(password + salt). Only read so accounts can be
+// upgraded on their next successful login; new hashes are PHC strings.
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct HashedPassword {
+pub struct LegacyHashedPassword {
     pub hash: String,
     pub salt: String,
 }
@@ -33,18 +37,93 @@ pub struct UserLogin {
 pub struct AuthResult {
     pub user: UserInfo,
     pub token: String,
+    pub refresh_token: String,
+    pub session_id: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserInfo {
     pub id: String,
     pub username: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl From<&User> for UserInfo {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id.to_string(),
+            username: user.username.clone(),
+            email: user.email.clone(),
+            email_verified: user.email_verified,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JwtClaims {
     pub sub: String, // user_id
     pub username: String,
     pub exp: u64,    // expiration timestamp
     pub iat: u64,    // issued at timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>, // not valid before timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>, // session the token was issued for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>, // set on single-purpose tokens, absent on access tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>, // unique id of a single-purpose token, recorded when it is spent
+}
+
+// Reasons a bearer token can be rejected; each maps to a stable error code in 401 responses
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenError {
+    Missing,
+    Malformed,
+    UnsupportedAlgorithm,
+    UnknownKey,
+    InvalidSignature,
+    Expired,
+    NotYetValid,
+    InvalidSubject,
+    WrongPurpose,
+    SessionRevoked,
 }
+
+impl TokenError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            TokenError::Missing => "missing_token",
+            TokenError::Malformed => "malformed_token",
+            TokenError::UnsupportedAlgorithm => "unsupported_algorithm",
+            TokenError::UnknownKey => "unknown_key",
+            TokenError::InvalidSignature => "invalid_signature",
+            TokenError::Expired => "token_expired",
+            TokenError::NotYetValid => "token_not_yet_valid",
+            TokenError::InvalidSubject => "invalid_subject",
+            TokenError::WrongPurpose => "wrong_token_purpose",
+            TokenError::SessionRevoked => "session_revoked",
+        }
+    }
+}
+
+impl std::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            TokenError::Missing => "Missing bearer token",
+            TokenError::Malformed => "Malformed token",
+            TokenError::UnsupportedAlgorithm => "Unsupported token algorithm",
+            TokenError::UnknownKey => "Token was signed with an unknown key",
+            TokenError::InvalidSignature => "Invalid token signature",
+            TokenError::Expired => "Token has expired",
+            TokenError::NotYetValid => "Token is not valid yet",
+            TokenError::InvalidSubject => "Token subject is not a valid user ID",
+            TokenError::WrongPurpose => "Token cannot be used for this request",
+            TokenError::SessionRevoked => "Session has ended; sign in again",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for TokenError {}
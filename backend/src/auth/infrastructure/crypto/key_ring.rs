@@ -0,0 +1,82 @@
+// JWT signing keys loaded from the JWT_SECRET Worker secret
+use std::error::Error;
+
+// Key id used when JWT_SECRET holds a single plain secret
+pub const DEFAULT_KEY_ID: &str = "default";
+
+pub struct SigningKey {
+    pub kid: String,
+    secret: Vec<u8>,
+}
+
+impl SigningKey {
+    pub fn new(kid: &str, secret: &str) -> Self {
+        Self {
+            kid: kid.to_string(),
+            secret: secret.as_bytes().to_vec(),
+        }
+    }
+
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+}
+
+// Ordered set of keys: the first one signs new tokens, all of them verify
+pub struct KeyRing {
+    keys: Vec<SigningKey>,
+}
+
+impl KeyRing {
+    pub fn new(keys: Vec<SigningKey>) -> Result<Self, Box<dyn Error>> {
+        if keys.is_empty() {
+            return Err("Key ring must contain at least one key".into());
+        }
+        for (index, key) in keys.iter().enumerate() {
+            if key.kid.is_empty() {
+                return Err("Key id cannot be empty".into());
+            }
+            if key.secret.is_empty() {
+                return Err(format!("Secret for key '{}' cannot be empty", key.kid).into());
+            }
+            if keys[..index].iter().any(|other| other.kid == key.kid) {
+                return Err(format!("Duplicate key id '{}'", key.kid).into());
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    // Prefix that marks a JWT_SECRET value as a key list rather than a plain secret
+    pub const KEY_LIST_PREFIX: &'static str = "keys:";
+
+    // Without the `keys:` prefix the whole value is one plain secret, whatever characters it
+    // contains. With it, the rest is a comma-separated list of `kid:secret` entries: the first
+    // is the active signing key, the others only verify older tokens,
+    // e.g. "keys:2024-06:new-secret,2024-01:old-secret".
+    pub fn parse(raw: &str) -> Result<Self, Box<dyn Error>> {
+        let raw = raw.trim();
+        let Some(list) = raw.strip_prefix(Self::KEY_LIST_PREFIX) else {
+            return Self::new(vec![SigningKey::new(DEFAULT_KEY_ID, raw)]);
+        };
+
+        let keys = list
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once(':') {
+                Some((kid, secret)) => Ok(SigningKey::new(kid.trim(), secret.trim())),
+                None => Err(format!("Key entry '{}' must use the form kid:secret", entry)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(keys)
+    }
+
+    pub fn active(&self) -> &SigningKey {
+        &self.keys[0]
+    }
+
+    pub fn find(&self, kid: &str) -> Option<&SigningKey> {
+        self.keys.iter().find(|key| key.kid == kid)
+    }
+}
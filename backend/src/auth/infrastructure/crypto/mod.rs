@@ -1,3 +1,7 @@
 pub mod wasm_crypto;
+pub mod key_ring;
+pub mod totp;
 
 pub use wasm_crypto::{WasmPasswordService, WasmTokenService};
+pub use key_ring::{KeyRing, SigningKey};
+pub use totp::WasmTotpService;
@@ -0,0 +1,239 @@
+// RFC 6238 TOTP (HMAC-SHA1, 6 digits, 30 second steps) as used by common authenticator apps
+use std::error::Error;
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{DateTime, Utc};
+use getrandom::getrandom;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Digest};
+
+use crate::auth::domain::ports::TotpService;
+use super::wasm_crypto::constant_time_eq;
+
+type HmacSha1 = Hmac<Sha1>;
+
+const ISSUER: &str = "TwoDo";
+const SECRET_BYTES: usize = 20;
+const STEP_SECS: i64 = 30;
+const DIGITS: u32 = 6;
+// Accept the previous and next step as well, to absorb phone clock drift
+const STEP_WINDOW: i64 = 1;
+const RECOVERY_CODE_LEN: usize = 10;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// RFC 4648 base32 without padding
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut output = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    output
+}
+
+fn base32_decode(input: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut output = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in input.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a as char == c.to_ascii_uppercase())
+            .ok_or("Invalid base32 secret")? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output.push(((buffer >> bits) & 0xff) as u8);
+        }
+    }
+    Ok(output)
+}
+
+// Percent-encode everything outside the RFC 3986 unreserved set
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+pub struct WasmTotpService;
+
+impl WasmTotpService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    // HOTP value (RFC 4226) for a single counter
+    fn hotp(key: &[u8], counter: u64) -> Result<String, Box<dyn Error>> {
+        let mut mac = HmacSha1::new_from_slice(key).map_err(|e| format!("Invalid TOTP key: {}", e))?;
+        mac.update(&counter.to_be_bytes());
+        let digest = mac.finalize().into_bytes();
+
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let binary = ((digest[offset] as u32 & 0x7f) << 24)
+            | ((digest[offset + 1] as u32) << 16)
+            | ((digest[offset + 2] as u32) << 8)
+            | (digest[offset + 3] as u32);
+
+        Ok(format!("{:0width$}", binary % 10u32.pow(DIGITS), width = DIGITS as usize))
+    }
+
+    fn normalize_recovery_code(code: &str) -> String {
+        code.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_uppercase())
+            .collect()
+    }
+}
+
+impl TotpService for WasmTotpService {
+    fn generate_secret(&self) -> Result<String, Box<dyn Error>> {
+        let mut secret = [0u8; SECRET_BYTES];
+        getrandom(&mut secret).map_err(|e| format!("Failed to generate random bytes: {}", e))?;
+        Ok(base32_encode(&secret))
+    }
+
+    fn provisioning_uri(&self, secret: &str, account_name: &str) -> String {
+        format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            ISSUER,
+            percent_encode(account_name),
+            secret,
+            ISSUER,
+            DIGITS,
+            STEP_SECS
+        )
+    }
+
+    fn verify_code(&self, secret: &str, code: &str, at: DateTime<Utc>) -> Result<Option<u64>, Box<dyn Error>> {
+        let code = code.trim();
+        if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(None);
+        }
+
+        let key = base32_decode(secret)?;
+        let current_step = at.timestamp() / STEP_SECS;
+        for step in (current_step - STEP_WINDOW)..=(current_step + STEP_WINDOW) {
+            if step < 0 {
+                continue;
+            }
+            let expected = Self::hotp(&key, step as u64)?;
+            if constant_time_eq(expected.as_bytes(), code.as_bytes()) {
+                return Ok(Some(step as u64));
+            }
+        }
+        Ok(None)
+    }
+
+    // Codes look like "K7QF2-MZ4XD" and carry 50 bits of entropy each
+    fn generate_recovery_codes(&self, count: usize) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut codes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut bytes = [0u8; RECOVERY_CODE_LEN];
+            getrandom(&mut bytes).map_err(|e| format!("Failed to generate random bytes: {}", e))?;
+            let chars: String = bytes.iter().map(|b| BASE32_ALPHABET[(b & 0x1f) as usize] as char).collect();
+            codes.push(format!("{}-{}", &chars[..5], &chars[5..]));
+        }
+        Ok(codes)
+    }
+
+    // High-entropy codes do not need a slow hash; SHA-256 keeps the lookup indexable
+    fn hash_recovery_code(&self, code: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(Self::normalize_recovery_code(code).as_bytes());
+        general_purpose::URL_SAFE_NO_PAD.encode(hasher.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // RFC 6238 appendix B: the SHA-1 seed is the ASCII string "12345678901234567890"
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    // Test vectors from RFC 6238 appendix B, cut to our 6 digits
+    const RFC_VECTORS: [(i64, &str); 6] = [
+        (59, "287082"),
+        (1_111_111_109, "081804"),
+        (1_111_111_111, "050471"),
+        (1_234_567_890, "005924"),
+        (2_000_000_000, "279037"),
+        (20_000_000_000, "353130"),
+    ];
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn base32_round_trips_the_rfc_seed() {
+        assert_eq!(base32_encode(b"12345678901234567890"), RFC_SECRET);
+        assert_eq!(base32_decode(RFC_SECRET).unwrap(), b"12345678901234567890");
+    }
+
+    #[test]
+    fn matches_rfc_6238_test_vectors() {
+        let totp = WasmTotpService::new();
+        for (seconds, code) in RFC_VECTORS {
+            assert_eq!(
+                totp.verify_code(RFC_SECRET, code, at(seconds)).unwrap(),
+                Some((seconds / STEP_SECS) as u64),
+                "vector at T={}",
+                seconds
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_one_step_either_side() {
+        let totp = WasmTotpService::new();
+        // "005924" belongs to step 41152263 (T = 1234567890)
+        let step = 1_234_567_890 / STEP_SECS;
+        for offset in [-1, 0, 1] {
+            let checked_at = at((step + offset) * STEP_SECS);
+            assert_eq!(totp.verify_code(RFC_SECRET, "005924", checked_at).unwrap(), Some(step as u64));
+        }
+    }
+
+    #[test]
+    fn rejects_codes_two_steps_away() {
+        let totp = WasmTotpService::new();
+        let step = 1_234_567_890 / STEP_SECS;
+        for offset in [-2, 2] {
+            let checked_at = at((step + offset) * STEP_SECS);
+            assert_eq!(totp.verify_code(RFC_SECRET, "005924", checked_at).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        let totp = WasmTotpService::new();
+        for code in ["", "28708", "2870822", "28708a"] {
+            assert_eq!(totp.verify_code(RFC_SECRET, code, at(59)).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn recovery_codes_hash_the_same_however_they_are_typed() {
+        let totp = WasmTotpService::new();
+        assert_eq!(totp.hash_recovery_code("K7QF2-MZ4XD"), totp.hash_recovery_code("k7qf2mz4xd"));
+        assert_ne!(totp.hash_recovery_code("K7QF2-MZ4XD"), totp.hash_recovery_code("K7QF2-MZ4XE"));
+    }
+}
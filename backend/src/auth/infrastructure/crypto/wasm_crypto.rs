@@ -1,13 +1,48 @@
-// WASM-compatible crypto services using sha2 crate
+// WASM-compatible crypto services using the argon2, hmac and sha2 crates
 use async_trait::async_trait;
 use uuid::Uuid;
 use std::error::Error;
+use std::sync::Arc;
 use base64::{Engine as _, engine::general_purpose};
 use getrandom::getrandom;
 use sha2::{Sha256, Digest};
+use hmac::{Hmac, Mac};
+use argon2::{Algorithm, Argon2, Params, Version};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use serde::{Deserialize, Serialize};
 
-use crate::auth::domain::user::{HashedPassword, JwtClaims};
-use crate::auth::domain::ports::{PasswordService, TokenService};
+use crate::auth::domain::user::{LegacyHashedPassword, JwtClaims, TokenError};
+use crate::auth::domain::ports::{PasswordService, TokenService, Clock};
+use crate::auth::domain::clock::SystemClock;
+use super::key_ring::{KeyRing, SigningKey, DEFAULT_KEY_ID};
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Argon2id parameters (OWASP baseline: 19 MiB memory, 2 iterations, 1 lane).
+// Raising these makes existing hashes report needs_rehash and upgrade on next login.
+const ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+const ARGON2_ITERATIONS: u32 = 2;
+const ARGON2_PARALLELISM: u32 = 1;
+
+// Tolerated clock difference between the issuing and validating isolates
+const CLOCK_SKEW_LEEWAY_SECS: u64 = 60;
+
+#[derive(Serialize, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+}
+
+// Compare byte slices without short-circuiting on the first mismatch
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
 
 pub struct WasmPasswordService;
 
@@ -16,71 +51,109 @@ impl WasmPasswordService {
         Self
     }
 
+    fn argon2() -> Result<Argon2<'static>, Box<dyn Error>> {
+        let params = Params::new(ARGON2_MEMORY_KIB, ARGON2_ITERATIONS, ARGON2_PARALLELISM, None)
+            .map_err(|e| format!("Invalid Argon2 parameters: {}", e))?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
     // Generate a random salt using getrandom (WASM compatible)
-    fn generate_salt() -> Result<String, Box<dyn Error>> {
-        let mut salt_bytes = [0u8; 32];
+    fn generate_salt() -> Result<SaltString, Box<dyn Error>> {
+        let mut salt_bytes = [0u8; 16];
         getrandom(&mut salt_bytes).map_err(|e| format!("Failed to generate random bytes: {}", e))?;
-        Ok(general_purpose::STANDARD.encode(salt_bytes))
+        SaltString::encode_b64(&salt_bytes).map_err(|e| format!("Failed to encode salt: {}", e).into())
     }
 
-    // Simple hash using SHA-256 via sha2 crate (WASM compatible)
-    fn hash_password_internal(password: &str, salt: &str) -> Result<HashedPassword, Box<dyn Error>> {
-        // Combine password and salt
-        let combined = format!("{}{}", password, salt);
-        
-        // Hash using SHA-256
-        let mut hasher = Sha256::new();
-        hasher.update(combined.as_bytes());
-        let result = hasher.finalize();
-        let hash = general_purpose::STANDARD.encode(result);
-        
-        Ok(HashedPassword {
-            hash,
-            salt: salt.to_string(),
-        })
+    // Legacy hashes are the JSON-encoded LegacyHashedPassword blob
+    fn parse_legacy(stored_hash: &str) -> Option<LegacyHashedPassword> {
+        if !stored_hash.starts_with('{') {
+            return None;
+        }
+        serde_json::from_str(stored_hash).ok()
     }
 
-    // Verify password against hash
-    fn verify_password_internal(password: &str, stored: &HashedPassword) -> Result<bool, Box<dyn Error>> {
-        let computed = Self::hash_password_internal(password, &stored.salt)?;
-        Ok(computed.hash == stored.hash)
+    // Single round of SHA-256 over password + salt, kept only to verify legacy hashes
+    fn verify_legacy(password: &str, stored: &LegacyHashedPassword) -> bool {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}{}", password, stored.salt).as_bytes());
+        let computed = general_purpose::STANDARD.encode(hasher.finalize());
+        constant_time_eq(computed.as_bytes(), stored.hash.as_bytes())
     }
 }
 
 #[async_trait]
 impl PasswordService for WasmPasswordService {
-    async fn hash_password(&self, password: &str) -> Result<HashedPassword, Box<dyn Error>> {
+    async fn hash_password(&self, password: &str) -> Result<String, Box<dyn Error>> {
         let salt = Self::generate_salt()?;
-        Self::hash_password_internal(password, &salt)
+        let hash = Self::argon2()?
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| format!("Failed to hash password: {}", e))?;
+        Ok(hash.to_string())
+    }
+
+    async fn verify_password(&self, password: &str, stored_hash: &str) -> Result<bool, Box<dyn Error>> {
+        if let Some(legacy) = Self::parse_legacy(stored_hash) {
+            return Ok(Self::verify_legacy(password, &legacy));
+        }
+
+        let parsed = PasswordHash::new(stored_hash).map_err(|_| "Invalid password data")?;
+        // The argon2 crate compares digests in constant time
+        Ok(Self::argon2()?.verify_password(password.as_bytes(), &parsed).is_ok())
     }
 
-    async fn verify_password(&self, password: &str, stored: &HashedPassword) -> Result<bool, Box<dyn Error>> {
-        Self::verify_password_internal(password, stored)
+    fn needs_rehash(&self, stored_hash: &str) -> bool {
+        let parsed = match PasswordHash::new(stored_hash) {
+            Ok(parsed) => parsed,
+            Err(_) => return true,
+        };
+        if parsed.algorithm != Algorithm::Argon2id.ident() || parsed.version != Some(Version::V0x13.into()) {
+            return true;
+        }
+        match Params::try_from(&parsed) {
+            Ok(params) => {
+                params.m_cost() != ARGON2_MEMORY_KIB
+                    || params.t_cost() != ARGON2_ITERATIONS
+                    || params.p_cost() != ARGON2_PARALLELISM
+            }
+            Err(_) => true,
+        }
     }
 }
 
 pub struct WasmTokenService {
-    secret: String,
+    key_ring: KeyRing,
+    token_ttl_secs: u64,
+    clock: Arc<dyn Clock>,
 }
 
 impl WasmTokenService {
-    pub fn new(secret: String) -> Self {
-        Self { secret }
+    pub fn new(key_ring: KeyRing, token_ttl_secs: u64) -> Self {
+        Self {
+            key_ring,
+            token_ttl_secs,
+            clock: Arc::new(SystemClock),
+        }
     }
 
-    // Simple HMAC-SHA256 using sha2 crate (WASM compatible)
-    fn hmac_sha256(data: &str, key: &str) -> Result<Vec<u8>, Box<dyn Error>> {
-        let key_bytes = key.as_bytes();
-        let mut hasher = Sha256::new();
-        hasher.update(key_bytes);
-        hasher.update(data.as_bytes());
-        let result = hasher.finalize();
-        Ok(result.to_vec())
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // HMAC-SHA256 (RFC 2104) via the hmac crate (WASM compatible)
+    fn hmac_sha256(data: &str, key: &[u8]) -> Result<HmacSha256, TokenError> {
+        let mut mac = HmacSha256::new_from_slice(key).map_err(|_| TokenError::InvalidSignature)?;
+        mac.update(data.as_bytes());
+        Ok(mac)
     }
 
-    // Generate JWT token using HMAC-SHA256
-    fn generate_jwt_internal(claims: &JwtClaims, secret: &str) -> Result<String, Box<dyn Error>> {
-        let header = r#"{"alg":"HS256","typ":"JWT"}"#;
+    // Generate JWT token signed with the given key, advertising its kid in the header
+    fn generate_jwt_internal(claims: &JwtClaims, key: &SigningKey) -> Result<String, Box<dyn Error>> {
+        let header = serde_json::to_string(&JwtHeader {
+            alg: "HS256".to_string(),
+            typ: Some("JWT".to_string()),
+            kid: Some(key.kid.clone()),
+        })?;
         let payload = serde_json::to_string(claims)?;
         
         // Base64 encode header and payload
@@ -91,38 +164,133 @@ impl WasmTokenService {
         let signature_base = format!("{}.{}", encoded_header, encoded_payload);
         
         // Generate signature
-        let signature_bytes = Self::hmac_sha256(&signature_base, secret)?;
+        let signature_bytes = Self::hmac_sha256(&signature_base, key.secret())?.finalize().into_bytes();
         let encoded_signature = general_purpose::URL_SAFE_NO_PAD.encode(signature_bytes);
         
         // Combine all parts
         Ok(format!("{}.{}.{}", encoded_header, encoded_payload, encoded_signature))
     }
 
-    // Get current timestamp in seconds (WASM compatible)
-    fn current_timestamp() -> u64 {
-        // Use JavaScript Date.now() for better WASM compatibility
-        (js_sys::Date::now() / 1000.0) as u64
+    fn decode_segment(segment: &str) -> Result<Vec<u8>, TokenError> {
+        general_purpose::URL_SAFE_NO_PAD
+            .decode(segment)
+            .map_err(|_| TokenError::Malformed)
+    }
+
+    // Verify signature and registered claims of an HS256 JWT against the key ring
+    fn validate_jwt_internal(token: &str, key_ring: &KeyRing, now: u64) -> Result<JwtClaims, TokenError> {
+        let mut parts = token.split('.');
+        let (encoded_header, encoded_payload, encoded_signature) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(header), Some(payload), Some(signature), None) => (header, payload, signature),
+                _ => return Err(TokenError::Malformed),
+            };
+
+        // Only accept the algorithm we issue, never "none" or anything asymmetric
+        let header: JwtHeader = serde_json::from_slice(&Self::decode_segment(encoded_header)?)
+            .map_err(|_| TokenError::Malformed)?;
+        if header.alg != "HS256" {
+            return Err(TokenError::UnsupportedAlgorithm);
+        }
+
+        // Tokens without a kid were signed by a single-secret deployment
+        let kid = header.kid.as_deref().unwrap_or(DEFAULT_KEY_ID);
+        let key = key_ring.find(kid).ok_or(TokenError::UnknownKey)?;
+
+        // Check the signature (constant-time) before trusting anything in the payload
+        let signature = Self::decode_segment(encoded_signature)?;
+        let signature_base = format!("{}.{}", encoded_header, encoded_payload);
+        Self::hmac_sha256(&signature_base, key.secret())?
+            .verify_slice(&signature)
+            .map_err(|_| TokenError::InvalidSignature)?;
+
+        let claims: JwtClaims = serde_json::from_slice(&Self::decode_segment(encoded_payload)?)
+            .map_err(|_| TokenError::Malformed)?;
+
+        // Time-based claims, allowing for clock skew between isolates
+        if now >= claims.exp.saturating_add(CLOCK_SKEW_LEEWAY_SECS) {
+            return Err(TokenError::Expired);
+        }
+        if let Some(nbf) = claims.nbf {
+            if nbf > now.saturating_add(CLOCK_SKEW_LEEWAY_SECS) {
+                return Err(TokenError::NotYetValid);
+            }
+        }
+        if claims.iat > now.saturating_add(CLOCK_SKEW_LEEWAY_SECS) {
+            return Err(TokenError::NotYetValid);
+        }
+
+        Ok(claims)
+    }
+
+    // Current time in epoch seconds from the injected clock
+    fn current_timestamp(&self) -> u64 {
+        self.clock.now().timestamp().max(0) as u64
     }
 }
 
 #[async_trait]
 impl TokenService for WasmTokenService {
-    async fn generate_token(&self, user_id: &Uuid, username: &str) -> Result<String, Box<dyn Error>> {
-        let now = Self::current_timestamp();
-        let exp = now + (24 * 60 * 60); // 24 hours from now
+    async fn generate_token(&self, user_id: &Uuid, username: &str, session_id: &Uuid) -> Result<String, Box<dyn Error>> {
+        let now = self.current_timestamp();
+        let exp = now + self.token_ttl_secs;
         
         let claims = JwtClaims {
             sub: user_id.to_string(),
             username: username.to_string(),
             iat: now,
             exp,
+            nbf: Some(now),
+            sid: Some(session_id.to_string()),
+            purpose: None,
+            jti: None,
         };
         
-        Self::generate_jwt_internal(&claims, &self.secret)
+        Self::generate_jwt_internal(&claims, self.key_ring.active())
+    }
+
+    async fn validate_token(&self, token: &str) -> Result<JwtClaims, Box<dyn Error>> {
+        let claims = Self::validate_jwt_internal(token, &self.key_ring, self.current_timestamp())?;
+        if claims.purpose.is_some() {
+            return Err(Box::new(TokenError::WrongPurpose));
+        }
+        Ok(claims)
     }
 
-    async fn validate_token(&self, _token: &str) -> Result<JwtClaims, Box<dyn Error>> {
-        // TODO: Implement JWT validation
-        Err("JWT validation not implemented yet".into())
+    async fn generate_scoped_token(&self, user_id: &Uuid, username: &str, purpose: &str, ttl_secs: u64) -> Result<String, Box<dyn Error>> {
+        let now = self.current_timestamp();
+        let claims = JwtClaims {
+            sub: user_id.to_string(),
+            username: username.to_string(),
+            iat: now,
+            exp: now + ttl_secs,
+            nbf: Some(now),
+            sid: None,
+            purpose: Some(purpose.to_string()),
+            jti: Some(Uuid::new_v4().to_string()),
+        };
+
+        Self::generate_jwt_internal(&claims, self.key_ring.active())
+    }
+
+    async fn validate_scoped_token(&self, token: &str, purpose: &str) -> Result<JwtClaims, Box<dyn Error>> {
+        let claims = Self::validate_jwt_internal(token, &self.key_ring, self.current_timestamp())?;
+        if claims.purpose.as_deref() != Some(purpose) {
+            return Err(Box::new(TokenError::WrongPurpose));
+        }
+        Ok(claims)
+    }
+
+    fn generate_opaque_token(&self) -> Result<String, Box<dyn Error>> {
+        let mut token_bytes = [0u8; 32];
+        getrandom(&mut token_bytes).map_err(|e| format!("Failed to generate random bytes: {}", e))?;
+        Ok(general_purpose::URL_SAFE_NO_PAD.encode(token_bytes))
+    }
+
+    // Opaque tokens carry 256 bits of entropy, so a plain SHA-256 is enough to store them
+    fn hash_opaque_token(&self, token: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(token.as_bytes());
+        general_purpose::URL_SAFE_NO_PAD.encode(hasher.finalize())
     }
 }
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use worker::{console_log, Fetch, Headers, Method, Request, RequestInit};
+use worker::send::SendFuture;
+
+use crate::auth::domain::account_token::MailMessage;
+use crate::auth::domain::ports::MailSender;
+
+// Refused mail delivery because no transport is configured
+pub const MAIL_UNAVAILABLE: &str = "Email delivery is not configured";
+
+// Logs that mail would have been sent, for local development (MAIL_CONSOLE). The body is never
+// logged: its links carry live reset and verification tokens.
+pub struct ConsoleMailSender;
+
+impl ConsoleMailSender {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl MailSender for ConsoleMailSender {
+    async fn send(&self, message: &MailMessage) -> Result<(), Box<dyn Error>> {
+        console_log!("[mail] to={} subject={}", message.to, message.subject);
+        Ok(())
+    }
+}
+
+// Stands in when no mail transport is configured: every send fails
+pub struct UnavailableMailSender;
+
+#[async_trait]
+impl MailSender for UnavailableMailSender {
+    async fn send(&self, _message: &MailMessage) -> Result<(), Box<dyn Error>> {
+        Err(MAIL_UNAVAILABLE.into())
+    }
+}
+
+// Keeps sent messages in memory so tests can read the links they contain
+#[derive(Clone, Default)]
+pub struct InMemoryMailSender {
+    sent: Arc<Mutex<Vec<MailMessage>>>,
+}
+
+impl InMemoryMailSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sent(&self) -> Vec<MailMessage> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl MailSender for InMemoryMailSender {
+    async fn send(&self, message: &MailMessage) -> Result<(), Box<dyn Error>> {
+        self.sent.lock().unwrap().push(message.clone());
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct HttpMailPayload<'a> {
+    from: &'a str,
+    to: Vec<&'a str>,
+    subject: &'a str,
+    text: &'a str,
+}
+
+// Sends through a Resend-compatible JSON API (POST {from, to, subject, text} with a bearer key)
+pub struct HttpMailSender {
+    api_url: String,
+    api_key: String,
+    from: String,
+}
+
+impl HttpMailSender {
+    pub fn new(api_url: String, api_key: String, from: String) -> Self {
+        Self { api_url, api_key, from }
+    }
+}
+
+#[async_trait]
+impl MailSender for HttpMailSender {
+    async fn send(&self, message: &MailMessage) -> Result<(), Box<dyn Error>> {
+        let body = serde_json::to_string(&HttpMailPayload {
+            from: &self.from,
+            to: vec![&message.to],
+            subject: &message.subject,
+            text: &message.text_body,
+        })?;
+
+        // Fetch handles are not Send; keep them inside the wrapped future
+        let status = SendFuture::new(async {
+            let headers = Headers::new();
+            headers.set("Authorization", &format!("Bearer {}", self.api_key))?;
+            headers.set("Content-Type", "application/json")?;
+
+            let request = Request::new_with_init(
+                &self.api_url,
+                RequestInit::new()
+                    .with_method(Method::Post)
+                    .with_headers(headers)
+                    .with_body(Some(body.into())),
+            )?;
+
+            let response = Fetch::Request(request).send().await?;
+            Ok::<u16, worker::Error>(response.status_code())
+        })
+        .await
+        .map_err(|e| format!("Failed to send mail: {}", e))?;
+
+        if !(200..300).contains(&status) {
+            return Err(format!("Mail API responded with status {}", status).into());
+        }
+        Ok(())
+    }
+}
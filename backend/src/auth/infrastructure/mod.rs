@@ -1,10 +1,16 @@
 pub mod web;
 pub mod persistence;
 pub mod crypto;
+pub mod mail;
 
 // Export all implementations
-pub use persistence::persistent_memory_repository::PersistentMemoryUserRepository;
-pub use persistence::in_memory_repository::InMemoryUserRepository;
-pub use crypto::{WasmPasswordService, WasmTokenService};
-// D1 temporarily disabled until import issues resolved
-// pub use persistence::d1_repository::D1UserRepository;
+pub use persistence::in_memory_repository::{InMemoryUserRepository, InMemorySessionRepository, InMemoryTwoFactorRepository};
+pub use persistence::d1_repository::D1UserRepository;
+pub use persistence::d1_session_repository::D1SessionRepository;
+pub use persistence::d1_two_factor_repository::D1TwoFactorRepository;
+pub use persistence::d1_account_token_repository::D1AccountTokenRepository;
+pub use persistence::in_memory_rate_limit_store::InMemoryRateLimitStore;
+pub use persistence::d1_rate_limit_store::D1RateLimitStore;
+pub use persistence::d1_account_data_repository::D1AccountDataRepository;
+pub use crypto::{KeyRing, SigningKey, WasmPasswordService, WasmTokenService, WasmTotpService};
+pub use mail::{ConsoleMailSender, HttpMailSender, InMemoryMailSender, UnavailableMailSender, MAIL_UNAVAILABLE};
@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{DateTime, Utc};
+use std::future::Future;
+
+use crate::auth::domain::account::{AccountData, GroupDisposition, GroupMembershipSummary, RemainingMember};
+use crate::auth::domain::ports::AccountDataRepository;
+use crate::groups::infrastructure::group_purge_statements;
+
+// Reads and removes one user's data across the group, expense, chore and calendar tables.
+// Those modules own the tables; this only needs to know which rows belong to a user.
+pub struct D1AccountDataRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1AccountDataRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+
+    // JsValue params are not Send either, so they move straight into the wrapped future
+    fn query_all<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<Vec<Value>>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.all().await?.results::<Value>() })
+    }
+
+    async fn rows(&self, query: &str, user_id: &Uuid) -> std::result::Result<Vec<Value>, Box<dyn std::error::Error>> {
+        Ok(self
+            .query_all(query, vec![user_id.to_string().into()])
+            .await
+            .map_err(|e| format!("Query error: {}", e))?)
+    }
+}
+
+#[async_trait]
+impl AccountDataRepository for D1AccountDataRepository {
+    async fn group_memberships(&self, user_id: &Uuid) -> std::result::Result<Vec<GroupMembershipSummary>, Box<dyn std::error::Error>> {
+        // Placeholders can't run a group, so they neither keep it alive nor take it over
+        let rows = self
+            .rows(
+                "SELECT g.id AS group_id, g.name AS group_name, gm.role AS role,
+                        other.user_id AS other_user_id, other.role AS other_role
+                 FROM group_members gm
+                 JOIN groups g ON g.id = gm.group_id
+                 LEFT JOIN group_members other ON other.group_id = gm.group_id AND other.user_id != gm.user_id
+                     AND other.user_id NOT IN (SELECT id FROM users WHERE is_placeholder = 1)
+                 WHERE gm.user_id = ?1
+                 ORDER BY g.id, other.joined_at ASC",
+                user_id,
+            )
+            .await?;
+
+        let mut memberships: Vec<GroupMembershipSummary> = Vec::new();
+        for row in rows {
+            let group_id = Uuid::parse_str(row["group_id"].as_str().ok_or("Invalid group ID")?)
+                .map_err(|e| format!("UUID parse error: {}", e))?;
+
+            if memberships.last().map(|m| m.group_id) != Some(group_id) {
+                memberships.push(GroupMembershipSummary {
+                    group_id,
+                    group_name: row["group_name"].as_str().unwrap_or("").to_string(),
+                    is_owner: row["role"].as_str() == Some("owner"),
+                    is_admin: matches!(row["role"].as_str(), Some("owner") | Some("admin")),
+                    remaining_members: Vec::new(),
+                });
+            }
+
+            if let Some(other) = row["other_user_id"].as_str() {
+                let membership = memberships.last_mut().ok_or("Membership missing")?;
+                membership.remaining_members.push(RemainingMember {
+                    user_id: Uuid::parse_str(other).map_err(|e| format!("UUID parse error: {}", e))?,
+                    is_admin: matches!(row["other_role"].as_str(), Some("owner") | Some("admin")),
+                });
+            }
+        }
+
+        Ok(memberships)
+    }
+
+    async fn export_data(&self, user_id: &Uuid) -> std::result::Result<AccountData, Box<dyn std::error::Error>> {
+        // One query at a time into locals; a struct literal would hold the non-Send errors across awaits
+        let profile = self.rows("SELECT * FROM user_profiles WHERE user_id = ?1", user_id).await?;
+        let sessions = self.rows("SELECT id, device_name, user_agent, created_at, last_used_at, revoked_at FROM sessions WHERE user_id = ?1 ORDER BY created_at", user_id).await?;
+        let groups = self.rows("SELECT g.* FROM groups g JOIN group_members gm ON gm.group_id = g.id WHERE gm.user_id = ?1 ORDER BY g.created_at", user_id).await?;
+        let group_memberships = self.rows("SELECT * FROM group_members WHERE user_id = ?1", user_id).await?;
+        let expenses = self.rows("SELECT * FROM expenses WHERE paid_by = ?1 OR created_by = ?1 ORDER BY date", user_id).await?;
+        let expense_shares = self.rows("SELECT * FROM expense_shares WHERE user_id = ?1", user_id).await?;
+        let payments = self.rows("SELECT * FROM payments WHERE from_user = ?1 OR to_user = ?1 ORDER BY created_at", user_id).await?;
+        let chores = self.rows("SELECT * FROM chores WHERE created_by = ?1 OR assigned_to = ?1 ORDER BY created_at", user_id).await?;
+        let events = self.rows("SELECT * FROM events WHERE created_by = ?1 ORDER BY start_time", user_id).await?;
+        let event_attendance = self.rows("SELECT * FROM event_attendees WHERE user_id = ?1", user_id).await?;
+        let activity = self.rows("SELECT * FROM activity_log WHERE actor_id = ?1 ORDER BY seq", user_id).await?;
+
+        Ok(AccountData {
+            profile,
+            sessions,
+            groups,
+            group_memberships,
+            expenses,
+            expense_shares,
+            payments,
+            chores,
+            events,
+            event_attendance,
+            activity,
+        })
+    }
+
+    async fn delete_account(&self, user_id: &Uuid, groups: &[GroupDisposition], tombstone_username: &str, at: DateTime<Utc>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let user_id = user_id.to_string();
+
+        // One batch, so a failure part-way leaves the account untouched
+        SendFuture::new(async {
+            let mut statements = Vec::new();
+
+            for disposition in groups {
+                match disposition {
+                    GroupDisposition::Delete { group_id } => {
+                        statements.extend(group_purge_statements(&self.db, group_id)?);
+                    }
+                    GroupDisposition::Leave { group_id, new_admin } => {
+                        if let Some(new_admin) = new_admin {
+                            statements.push(
+                                self.db
+                                    .prepare(
+                                        "UPDATE group_members SET role = CASE
+                                             WHEN role = 'owner' THEN 'owner'
+                                             WHEN (SELECT role FROM group_members WHERE group_id = ?1 AND user_id = ?3) = 'owner' THEN 'owner'
+                                             ELSE 'admin' END
+                                         WHERE group_id = ?1 AND user_id = ?2",
+                                    )
+                                    .bind(&[group_id.to_string().into(), new_admin.to_string().into(), user_id.clone().into()])?,
+                            );
+                            statements.push(
+                                self.db
+                                    .prepare("UPDATE groups SET created_by = ?1 WHERE id = ?2 AND created_by = ?3")
+                                    .bind(&[new_admin.to_string().into(), group_id.to_string().into(), user_id.clone().into()])?,
+                            );
+                        }
+                        statements.push(
+                            self.db
+                                .prepare("DELETE FROM group_members WHERE group_id = ?1 AND user_id = ?2")
+                                .bind(&[group_id.to_string().into(), user_id.clone().into()])?,
+                        );
+                    }
+                }
+            }
+
+            // Personal data goes; shared records keep pointing at the tombstone row
+            let personal = [
+                "UPDATE chores SET assigned_to = NULL WHERE assigned_to = ?1",
+                "DELETE FROM event_attendees WHERE user_id = ?1",
+                "DELETE FROM push_tokens WHERE user_id = ?1",
+                "DELETE FROM refresh_tokens WHERE session_id IN (SELECT id FROM sessions WHERE user_id = ?1)",
+                "DELETE FROM sessions WHERE user_id = ?1",
+                "DELETE FROM recovery_codes WHERE user_id = ?1",
+                "DELETE FROM user_totp WHERE user_id = ?1",
+                "DELETE FROM account_tokens WHERE user_id = ?1",
+                "DELETE FROM user_profiles WHERE user_id = ?1",
+                "DELETE FROM group_invitations WHERE invited_user_id = ?1",
+                "DELETE FROM activity_reads WHERE user_id = ?1",
+            ];
+            for query in personal {
+                statements.push(self.db.prepare(query).bind(&[user_id.clone().into()])?);
+            }
+
+            statements.push(
+                self.db
+                    .prepare("UPDATE users SET username = ?1, password_hash = '', email = NULL, email_verified = 0, deleted_at = ?2 WHERE id = ?3")
+                    .bind(&[
+                        tombstone_username.into(),
+                        (at.timestamp() as f64).into(),
+                        user_id.clone().into(),
+                    ])?,
+            );
+
+            self.db.batch(statements).await
+        })
+        .await
+        .map_err(|e| format!("Failed to delete account: {}", e))?;
+
+        Ok(())
+    }
+}
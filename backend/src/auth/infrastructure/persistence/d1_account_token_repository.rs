@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{DateTime, TimeZone, Utc};
+use std::future::Future;
+
+use crate::auth::domain::account_token::{AccountToken, AccountTokenPurpose};
+use crate::auth::domain::ports::AccountTokenRepository;
+
+// Password reset and email verification tokens in D1, timestamps stored as INTEGER epoch seconds
+pub struct D1AccountTokenRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1AccountTokenRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+
+    fn timestamp_value(at: &DateTime<Utc>) -> JsValue {
+        (at.timestamp() as f64).into()
+    }
+
+    fn parse_optional_timestamp(value: &Value) -> std::result::Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
+        match value.as_i64().or_else(|| value.as_f64().map(|s| s as i64)) {
+            Some(seconds) => Ok(Some(Utc.timestamp_opt(seconds, 0).single().ok_or("Invalid timestamp")?)),
+            None => Ok(None),
+        }
+    }
+
+    fn row_to_token(row: &Value, purpose: AccountTokenPurpose) -> std::result::Result<AccountToken, Box<dyn std::error::Error>> {
+        Ok(AccountToken {
+            id: Uuid::parse_str(row["id"].as_str().ok_or("Invalid token ID")?)
+                .map_err(|e| format!("UUID parse error: {}", e))?,
+            user_id: Uuid::parse_str(row["user_id"].as_str().ok_or("Invalid user ID")?)
+                .map_err(|e| format!("UUID parse error: {}", e))?,
+            purpose,
+            token_hash: row["token_hash"].as_str().ok_or("Invalid token hash")?.to_string(),
+            email: row["email"].as_str().ok_or("Invalid email")?.to_string(),
+            expires_at: Self::parse_optional_timestamp(&row["expires_at"])?.ok_or("Invalid expires_at")?,
+            used_at: Self::parse_optional_timestamp(&row["used_at"])?,
+            created_at: Self::parse_optional_timestamp(&row["created_at"])?.ok_or("Invalid created_at")?,
+        })
+    }
+
+    // JsValue params are not Send either, so they move straight into the wrapped future
+    fn execute<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<D1Result>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.run().await })
+    }
+}
+
+#[async_trait]
+impl AccountTokenRepository for D1AccountTokenRepository {
+    async fn create_token(&self, token: &AccountToken) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "INSERT INTO account_tokens (id, user_id, purpose, token_hash, email, expires_at, used_at, created_at) VALUES (?, ?, ?, ?, ?, ?, NULL, ?)",
+            vec![
+                token.id.to_string().into(),
+                token.user_id.to_string().into(),
+                token.purpose.as_str().into(),
+                token.token_hash.clone().into(),
+                token.email.clone().into(),
+                Self::timestamp_value(&token.expires_at),
+                Self::timestamp_value(&token.created_at),
+            ],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn get_token_by_hash(&self, token_hash: &str, purpose: AccountTokenPurpose) -> std::result::Result<Option<AccountToken>, Box<dyn std::error::Error>> {
+        let result = SendFuture::new(async {
+            self.db
+                .prepare("SELECT id, user_id, token_hash, email, expires_at, used_at, created_at FROM account_tokens WHERE token_hash = ? AND purpose = ?")
+                .bind(&[token_hash.into(), purpose.as_str().into()])?
+                .first::<Value>(None)
+                .await
+        })
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
+        match result {
+            Some(row) => Ok(Some(Self::row_to_token(&row, purpose)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn consume_token(&self, token_id: &Uuid, at: DateTime<Utc>) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let result = self
+            .execute(
+                "UPDATE account_tokens SET used_at = ? WHERE id = ? AND used_at IS NULL",
+                vec![Self::timestamp_value(&at), token_id.to_string().into()],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+
+        let changes = result.meta()?.and_then(|meta| meta.changes).unwrap_or(0);
+        Ok(changes > 0)
+    }
+
+    async fn invalidate_tokens(&self, user_id: &Uuid, purpose: AccountTokenPurpose, at: DateTime<Utc>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "UPDATE account_tokens SET used_at = ? WHERE user_id = ? AND purpose = ? AND used_at IS NULL",
+            vec![Self::timestamp_value(&at), user_id.to_string().into(), purpose.as_str().into()],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{DateTime, TimeZone, Utc};
+use std::future::Future;
+
+use crate::auth::domain::rate_limit::RateLimitState;
+use crate::auth::domain::ports::RateLimitStore;
+
+// Rate limit counters in D1, one row per key, timestamps stored as INTEGER epoch seconds.
+// Rows past expires_at are dead weight only; the limiter treats stale windows as empty.
+pub struct D1RateLimitStore {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1RateLimitStore {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+
+    fn timestamp_value(at: &DateTime<Utc>) -> JsValue {
+        (at.timestamp() as f64).into()
+    }
+
+    fn parse_optional_timestamp(value: &Value) -> std::result::Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
+        match value.as_i64().or_else(|| value.as_f64().map(|s| s as i64)) {
+            Some(seconds) => Ok(Some(Utc.timestamp_opt(seconds, 0).single().ok_or("Invalid timestamp")?)),
+            None => Ok(None),
+        }
+    }
+
+    fn parse_count(value: &Value) -> u32 {
+        value.as_u64().or_else(|| value.as_f64().map(|v| v as u64)).unwrap_or(0) as u32
+    }
+
+    // JsValue params are not Send either, so they move straight into the wrapped future
+    fn execute<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<D1Result>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.run().await })
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for D1RateLimitStore {
+    async fn get_state(&self, key: &str) -> std::result::Result<Option<RateLimitState>, Box<dyn std::error::Error>> {
+        let result = SendFuture::new(async {
+            self.db
+                .prepare("SELECT window_start, current_count, previous_count, locked_until FROM rate_limits WHERE key = ?")
+                .bind(&[key.into()])?
+                .first::<Value>(None)
+                .await
+        })
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
+        match result {
+            Some(row) => Ok(Some(RateLimitState {
+                window_start: Self::parse_optional_timestamp(&row["window_start"])?.ok_or("Invalid window_start")?,
+                current_count: Self::parse_count(&row["current_count"]),
+                previous_count: Self::parse_count(&row["previous_count"]),
+                locked_until: Self::parse_optional_timestamp(&row["locked_until"])?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    async fn put_state(&self, key: &str, state: &RateLimitState, expires_at: DateTime<Utc>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "INSERT INTO rate_limits (key, window_start, current_count, previous_count, locked_until, expires_at) VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(key) DO UPDATE SET window_start = excluded.window_start, current_count = excluded.current_count,
+                 previous_count = excluded.previous_count, locked_until = excluded.locked_until, expires_at = excluded.expires_at",
+            vec![
+                key.into(),
+                Self::timestamp_value(&state.window_start),
+                (state.current_count as f64).into(),
+                (state.previous_count as f64).into(),
+                state.locked_until.as_ref().map(Self::timestamp_value).unwrap_or(JsValue::NULL),
+                Self::timestamp_value(&expires_at),
+            ],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute("DELETE FROM rate_limits WHERE key = ?", vec![key.into()])
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+}
@@ -1,100 +1,252 @@
 use async_trait::async_trait;
 use uuid::Uuid;
 use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
 use serde_json::Value;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 
 use crate::auth::domain::user::User;
-use crate::auth::domain::ports::UserRepository;
+use crate::auth::domain::ports::{UserRepository, UserRepositoryError};
 
+// D1 handles are not Send, so they are wrapped to satisfy the Send + Sync ports.
+// Workers run single-threaded, which is what makes the wrapper sound.
 pub struct D1UserRepository {
-    db: D1Database,
+    db: SendWrapper<D1Database>,
 }
 
 impl D1UserRepository {
     pub fn new(db: D1Database) -> Self {
-        Self { db }
+        Self { db: SendWrapper::new(db) }
+    }
+
+    // schema.sql stores created_at as INTEGER seconds; rows written before that
+    // change still hold RFC 3339 text, so accept both
+    fn parse_created_at(value: &Value) -> std::result::Result<DateTime<Utc>, Box<dyn std::error::Error>> {
+        if let Some(seconds) = value.as_i64().or_else(|| value.as_f64().map(|s| s as i64)) {
+            return Utc
+                .timestamp_opt(seconds, 0)
+                .single()
+                .ok_or_else(|| "Invalid created_at timestamp".into());
+        }
+
+        let text = value.as_str().ok_or("Invalid created_at")?;
+        Ok(DateTime::parse_from_rfc3339(text)
+            .map_err(|e| format!("DateTime parse error: {}", e))?
+            .with_timezone(&Utc))
+    }
+
+    fn row_to_user(row: &Value) -> std::result::Result<User, Box<dyn std::error::Error>> {
+        Ok(User {
+            id: Uuid::parse_str(row["id"].as_str().ok_or("Invalid user ID")?)
+                .map_err(|e| format!("UUID parse error: {}", e))?,
+            username: row["username"].as_str().ok_or("Invalid username")?.to_string(),
+            password_hash: row["password_hash"].as_str().ok_or("Invalid password hash")?.to_string(),
+            email: row["email"].as_str().map(|e| e.to_string()),
+            email_verified: row["email_verified"].as_i64().or_else(|| row["email_verified"].as_f64().map(|v| v as i64)) == Some(1),
+            created_at: Self::parse_created_at(&row["created_at"])?,
+        })
+    }
+
+    // Two registrations can race past username_exists; the UNIQUE indexes decide, and the
+    // column they name says which one was taken
+    fn create_user_error(message: &str) -> Box<dyn std::error::Error> {
+        if message.contains("UNIQUE constraint failed: users.username") {
+            Box::new(UserRepositoryError::UsernameTaken)
+        } else if message.contains("UNIQUE constraint failed: users.email") {
+            Box::new(UserRepositoryError::EmailTaken)
+        } else {
+            format!("Failed to create user: {}", message).into()
+        }
+    }
+
+    async fn find_user(&self, query: &str, key: &str) -> std::result::Result<Option<User>, Box<dyn std::error::Error>> {
+        let result = SendFuture::new(async {
+            self.db
+                .prepare(query)
+                .bind(&[key.into()])?
+                .first::<Value>(None)
+                .await
+        })
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
+        match result {
+            Some(row) => Ok(Some(Self::row_to_user(&row)?)),
+            None => Ok(None),
+        }
     }
 }
 
 #[async_trait]
 impl UserRepository for D1UserRepository {
     async fn create_user(&self, user: &User) -> std::result::Result<(), Box<dyn std::error::Error>> {
-        let stmt = self.db.prepare("INSERT INTO users (id, username, password_hash, created_at) VALUES (?, ?, ?, ?)");
-        
-        stmt.bind(&[
-            user.id.to_string().into(),
-            user.username.clone().into(),
-            user.password_hash.clone().into(),
-            user.created_at.to_rfc3339().into(),
-        ])
-        .map_err(|e| format!("Bind error: {}", e))?
-        .run()
-        .await
-        .map_err(|e| format!("Run error: {}", e))?;
-        
-        Ok(())
+        let result = SendFuture::new(async {
+            self.db
+                .prepare("INSERT INTO users (id, username, password_hash, email, email_verified, created_at) VALUES (?, ?, ?, ?, ?, ?)")
+                .bind(&[
+                    user.id.to_string().into(),
+                    user.username.clone().into(),
+                    user.password_hash.clone().into(),
+                    user.email.clone().map(JsValue::from).unwrap_or(JsValue::NULL),
+                    (if user.email_verified { 1.0 } else { 0.0 }).into(),
+                    (user.created_at.timestamp() as f64).into(),
+                ])?
+                .run()
+                .await
+        })
+        .await;
+
+        result.map(|_| ()).map_err(|e| Self::create_user_error(&e.to_string()))
     }
 
     async fn get_user_by_username(&self, username: &str) -> std::result::Result<Option<User>, Box<dyn std::error::Error>> {
-        let stmt = self.db.prepare("SELECT id, username, password_hash, created_at FROM users WHERE username = ?");
-        
-        let result = stmt.bind(&[username.into()])
-            .map_err(|e| format!("Bind error: {}", e))?
-            .first::<Value>(None)
-            .await
-            .map_err(|e| format!("Query error: {}", e))?;
-        
-        if let Some(row) = result {
-            let user = User {
-                id: Uuid::parse_str(row["id"].as_str().ok_or("Invalid user ID")?)
-                    .map_err(|e| format!("UUID parse error: {}", e))?,
-                username: row["username"].as_str().ok_or("Invalid username")?.to_string(),
-                password_hash: row["password_hash"].as_str().ok_or("Invalid password hash")?.to_string(),
-                created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().ok_or("Invalid created_at")?)
-                    .map_err(|e| format!("DateTime parse error: {}", e))?
-                    .with_timezone(&Utc),
-            };
-            Ok(Some(user))
-        } else {
-            Ok(None)
-        }
+        self.find_user("SELECT id, username, password_hash, email, email_verified, created_at FROM users WHERE username = ? AND deleted_at IS NULL AND is_placeholder = 0", username).await
     }
 
     async fn get_user_by_id(&self, user_id: &Uuid) -> std::result::Result<Option<User>, Box<dyn std::error::Error>> {
-        let stmt = self.db.prepare("SELECT id, username, password_hash, created_at FROM users WHERE id = ?");
-        
-        let result = stmt.bind(&[user_id.to_string().into()])
-            .map_err(|e| format!("Bind error: {}", e))?
-            .first::<Value>(None)
-            .await
-            .map_err(|e| format!("Query error: {}", e))?;
-        
-        if let Some(row) = result {
-            let user = User {
-                id: Uuid::parse_str(row["id"].as_str().ok_or("Invalid user ID")?)
-                    .map_err(|e| format!("UUID parse error: {}", e))?,
-                username: row["username"].as_str().ok_or("Invalid username")?.to_string(),
-                password_hash: row["password_hash"].as_str().ok_or("Invalid password hash")?.to_string(),
-                created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().ok_or("Invalid created_at")?)
-                    .map_err(|e| format!("DateTime parse error: {}", e))?
-                    .with_timezone(&Utc),
-            };
-            Ok(Some(user))
-        } else {
-            Ok(None)
-        }
+        self.find_user("SELECT id, username, password_hash, email, email_verified, created_at FROM users WHERE id = ?", &user_id.to_string()).await
     }
 
     async fn username_exists(&self, username: &str) -> std::result::Result<bool, Box<dyn std::error::Error>> {
-        let stmt = self.db.prepare("SELECT 1 FROM users WHERE username = ? LIMIT 1");
-        
-        let result = stmt.bind(&[username.into()])
-            .map_err(|e| format!("Bind error: {}", e))?
-            .first::<Value>(None)
-            .await
-            .map_err(|e| format!("Query error: {}", e))?;
-        
+        let result = SendFuture::new(async {
+            self.db
+                .prepare("SELECT 1 FROM users WHERE username = ? AND deleted_at IS NULL AND is_placeholder = 0 LIMIT 1")
+                .bind(&[username.into()])?
+                .first::<Value>(None)
+                .await
+        })
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
         Ok(result.is_some())
     }
+
+    async fn update_password_hash(&self, user_id: &Uuid, password_hash: &str) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        SendFuture::new(async {
+            self.db
+                .prepare("UPDATE users SET password_hash = ? WHERE id = ?")
+                .bind(&[password_hash.into(), user_id.to_string().into()])?
+                .run()
+                .await
+        })
+        .await
+        .map_err(|e| format!("Failed to update password hash: {}", e))?;
+
+        Ok(())
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> std::result::Result<Option<User>, Box<dyn std::error::Error>> {
+        self.find_user("SELECT id, username, password_hash, email, email_verified, created_at FROM users WHERE email = ? AND deleted_at IS NULL AND is_placeholder = 0", email).await
+    }
+
+    async fn update_email(&self, user_id: &Uuid, email: Option<&str>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let result = SendFuture::new(async {
+            self.db
+                .prepare("UPDATE users SET email = ?, email_verified = 0 WHERE id = ?")
+                .bind(&[
+                    email.map(JsValue::from).unwrap_or(JsValue::NULL),
+                    user_id.to_string().into(),
+                ])?
+                .run()
+                .await
+        })
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) if e.to_string().contains("UNIQUE constraint failed") => {
+                Err(Box::new(UserRepositoryError::EmailTaken))
+            }
+            Err(e) => Err(format!("Failed to update email: {}", e).into()),
+        }
+    }
+
+    async fn mark_email_verified(&self, user_id: &Uuid, email: &str) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let changes = SendFuture::new(async {
+            let result = self.db
+                .prepare("UPDATE users SET email_verified = 1 WHERE id = ? AND email = ?")
+                .bind(&[user_id.to_string().into(), email.into()])?
+                .run()
+                .await?;
+            Ok::<usize, Error>(result.meta()?.and_then(|meta| meta.changes).unwrap_or(0))
+        })
+        .await
+        .map_err(|e| format!("Failed to verify email: {}", e))?;
+
+        Ok(changes > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn user_row(created_at: Value) -> Value {
+        json!({
+            "id": "6f1c1c2e-3b5a-4f7e-9a8b-2d6e4c1f0a11",
+            "username": "alice",
+            "password_hash": "$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA",
+            "email": null,
+            "email_verified": 0,
+            "created_at": created_at,
+        })
+    }
+
+    #[test]
+    fn created_at_round_trips_through_integer_seconds() {
+        let created_at = Utc.timestamp_opt(1_718_000_000, 0).unwrap();
+        // create_user binds the timestamp as a JS number, D1 hands it back as one
+        let stored = json!(created_at.timestamp() as f64);
+
+        let user = D1UserRepository::row_to_user(&user_row(stored)).unwrap();
+        assert_eq!(user.created_at, created_at);
+
+        let user = D1UserRepository::row_to_user(&user_row(json!(1_718_000_000))).unwrap();
+        assert_eq!(user.created_at, created_at);
+    }
+
+    #[test]
+    fn created_at_still_reads_legacy_rfc3339_text() {
+        let user = D1UserRepository::row_to_user(&user_row(json!("2024-06-10T06:13:20Z"))).unwrap();
+        assert_eq!(user.created_at, Utc.timestamp_opt(1_718_000_000, 0).unwrap());
+    }
+
+    #[test]
+    fn created_at_rejects_garbage() {
+        assert!(D1UserRepository::row_to_user(&user_row(json!("yesterday"))).is_err());
+        assert!(D1UserRepository::row_to_user(&user_row(Value::Null)).is_err());
+    }
+
+    #[test]
+    fn unique_violation_maps_to_username_taken() {
+        let error = D1UserRepository::create_user_error(
+            "D1_ERROR: UNIQUE constraint failed: users.username: SQLITE_CONSTRAINT",
+        );
+        assert!(matches!(
+            error.downcast_ref::<UserRepositoryError>(),
+            Some(UserRepositoryError::UsernameTaken)
+        ));
+        assert_eq!(error.to_string(), "User already exists");
+    }
+
+    #[test]
+    fn email_unique_violation_maps_to_email_taken() {
+        let error = D1UserRepository::create_user_error(
+            "D1_ERROR: UNIQUE constraint failed: users.email: SQLITE_CONSTRAINT",
+        );
+        assert!(matches!(
+            error.downcast_ref::<UserRepositoryError>(),
+            Some(UserRepositoryError::EmailTaken)
+        ));
+        assert_eq!(error.to_string(), "Email already in use");
+    }
+
+    #[test]
+    fn other_failures_stay_generic() {
+        let error = D1UserRepository::create_user_error("D1_ERROR: no such table: users");
+        assert!(error.downcast_ref::<UserRepositoryError>().is_none());
+        assert_eq!(error.to_string(), "Failed to create user: D1_ERROR: no such table: users");
+    }
 }
@@ -0,0 +1,207 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{DateTime, TimeZone, Utc};
+use std::future::Future;
+
+use crate::auth::domain::session::{Session, RefreshToken};
+use crate::auth::domain::ports::SessionRepository;
+
+// Sessions and refresh tokens in D1, timestamps stored as INTEGER epoch seconds
+pub struct D1SessionRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1SessionRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+
+    fn timestamp_value(at: &DateTime<Utc>) -> JsValue {
+        (at.timestamp() as f64).into()
+    }
+
+    fn optional_text(value: &Option<String>) -> JsValue {
+        match value {
+            Some(text) => text.clone().into(),
+            None => JsValue::NULL,
+        }
+    }
+
+    fn parse_timestamp(value: &Value) -> std::result::Result<DateTime<Utc>, Box<dyn std::error::Error>> {
+        let seconds = value.as_i64().or_else(|| value.as_f64().map(|s| s as i64)).ok_or("Invalid timestamp")?;
+        Utc.timestamp_opt(seconds, 0).single().ok_or_else(|| "Invalid timestamp".into())
+    }
+
+    fn parse_optional_timestamp(value: &Value) -> std::result::Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Self::parse_timestamp(value)?))
+    }
+
+    fn parse_uuid(value: &Value, field: &str) -> std::result::Result<Uuid, Box<dyn std::error::Error>> {
+        let text = value.as_str().ok_or_else(|| format!("Invalid {}", field))?;
+        Ok(Uuid::parse_str(text).map_err(|e| format!("UUID parse error: {}", e))?)
+    }
+
+    fn row_to_session(row: &Value) -> std::result::Result<Session, Box<dyn std::error::Error>> {
+        Ok(Session {
+            id: Self::parse_uuid(&row["id"], "session ID")?,
+            user_id: Self::parse_uuid(&row["user_id"], "user ID")?,
+            device_name: row["device_name"].as_str().map(|s| s.to_string()),
+            user_agent: row["user_agent"].as_str().map(|s| s.to_string()),
+            created_at: Self::parse_timestamp(&row["created_at"])?,
+            last_used_at: Self::parse_timestamp(&row["last_used_at"])?,
+            revoked_at: Self::parse_optional_timestamp(&row["revoked_at"])?,
+        })
+    }
+
+    fn row_to_refresh_token(row: &Value) -> std::result::Result<RefreshToken, Box<dyn std::error::Error>> {
+        Ok(RefreshToken {
+            id: Self::parse_uuid(&row["id"], "refresh token ID")?,
+            session_id: Self::parse_uuid(&row["session_id"], "session ID")?,
+            token_hash: row["token_hash"].as_str().ok_or("Invalid token hash")?.to_string(),
+            expires_at: Self::parse_timestamp(&row["expires_at"])?,
+            used_at: Self::parse_optional_timestamp(&row["used_at"])?,
+            created_at: Self::parse_timestamp(&row["created_at"])?,
+        })
+    }
+
+    // JsValue params are not Send either, so they move straight into the wrapped future
+    fn execute<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<D1Result>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.run().await })
+    }
+}
+
+#[async_trait]
+impl SessionRepository for D1SessionRepository {
+    async fn create_session(&self, session: &Session) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "INSERT INTO sessions (id, user_id, device_name, user_agent, created_at, last_used_at, revoked_at) VALUES (?, ?, ?, ?, ?, ?, NULL)",
+            vec![
+                session.id.to_string().into(),
+                session.user_id.to_string().into(),
+                Self::optional_text(&session.device_name),
+                Self::optional_text(&session.user_agent),
+                Self::timestamp_value(&session.created_at),
+                Self::timestamp_value(&session.last_used_at),
+            ],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn get_session(&self, session_id: &Uuid) -> std::result::Result<Option<Session>, Box<dyn std::error::Error>> {
+        let result = SendFuture::new(async {
+            self.db
+                .prepare("SELECT id, user_id, device_name, user_agent, created_at, last_used_at, revoked_at FROM sessions WHERE id = ?")
+                .bind(&[session_id.to_string().into()])?
+                .first::<Value>(None)
+                .await
+        })
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
+        match result {
+            Some(row) => Ok(Some(Self::row_to_session(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_active_sessions(&self, user_id: &Uuid) -> std::result::Result<Vec<Session>, Box<dyn std::error::Error>> {
+        let rows = SendFuture::new(async {
+            self.db
+                .prepare("SELECT id, user_id, device_name, user_agent, created_at, last_used_at, revoked_at FROM sessions WHERE user_id = ? AND revoked_at IS NULL ORDER BY last_used_at DESC")
+                .bind(&[user_id.to_string().into()])?
+                .all()
+                .await?
+                .results::<Value>()
+        })
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
+        rows.iter().map(Self::row_to_session).collect()
+    }
+
+    async fn touch_session(&self, session_id: &Uuid, at: DateTime<Utc>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "UPDATE sessions SET last_used_at = ? WHERE id = ?",
+            vec![Self::timestamp_value(&at), session_id.to_string().into()],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn revoke_session(&self, session_id: &Uuid, at: DateTime<Utc>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "UPDATE sessions SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL",
+            vec![Self::timestamp_value(&at), session_id.to_string().into()],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn revoke_all_sessions(&self, user_id: &Uuid, at: DateTime<Utc>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "UPDATE sessions SET revoked_at = ? WHERE user_id = ? AND revoked_at IS NULL",
+            vec![Self::timestamp_value(&at), user_id.to_string().into()],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn create_refresh_token(&self, token: &RefreshToken) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "INSERT INTO refresh_tokens (id, session_id, token_hash, expires_at, used_at, created_at) VALUES (?, ?, ?, ?, NULL, ?)",
+            vec![
+                token.id.to_string().into(),
+                token.session_id.to_string().into(),
+                token.token_hash.clone().into(),
+                Self::timestamp_value(&token.expires_at),
+                Self::timestamp_value(&token.created_at),
+            ],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn get_refresh_token_by_hash(&self, token_hash: &str) -> std::result::Result<Option<RefreshToken>, Box<dyn std::error::Error>> {
+        let result = SendFuture::new(async {
+            self.db
+                .prepare("SELECT id, session_id, token_hash, expires_at, used_at, created_at FROM refresh_tokens WHERE token_hash = ?")
+                .bind(&[token_hash.into()])?
+                .first::<Value>(None)
+                .await
+        })
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
+        match result {
+            Some(row) => Ok(Some(Self::row_to_refresh_token(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn mark_refresh_token_used(&self, token_id: &Uuid, at: DateTime<Utc>) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        // Conditional update so two concurrent refreshes cannot both spend the same token
+        let result = self
+            .execute(
+                "UPDATE refresh_tokens SET used_at = ? WHERE id = ? AND used_at IS NULL",
+                vec![Self::timestamp_value(&at), token_id.to_string().into()],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+
+        let changes = result.meta()?.and_then(|meta| meta.changes).unwrap_or(0);
+        Ok(changes > 0)
+    }
+}
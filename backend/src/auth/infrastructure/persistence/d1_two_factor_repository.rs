@@ -0,0 +1,215 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{DateTime, TimeZone, Utc};
+use std::future::Future;
+
+use crate::auth::domain::two_factor::TwoFactorSettings;
+use crate::auth::domain::ports::TwoFactorRepository;
+
+// TOTP secrets and hashed recovery codes in D1, timestamps stored as INTEGER epoch seconds
+pub struct D1TwoFactorRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1TwoFactorRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+
+    fn timestamp_value(at: &DateTime<Utc>) -> JsValue {
+        (at.timestamp() as f64).into()
+    }
+
+    fn parse_optional_timestamp(value: &Value) -> std::result::Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
+        match value.as_i64().or_else(|| value.as_f64().map(|s| s as i64)) {
+            Some(seconds) => Ok(Some(Utc.timestamp_opt(seconds, 0).single().ok_or("Invalid timestamp")?)),
+            None => Ok(None),
+        }
+    }
+
+    fn row_to_settings(row: &Value) -> std::result::Result<TwoFactorSettings, Box<dyn std::error::Error>> {
+        Ok(TwoFactorSettings {
+            user_id: Uuid::parse_str(row["user_id"].as_str().ok_or("Invalid user ID")?)
+                .map_err(|e| format!("UUID parse error: {}", e))?,
+            secret: row["secret"].as_str().ok_or("Invalid TOTP secret")?.to_string(),
+            enabled_at: Self::parse_optional_timestamp(&row["enabled_at"])?,
+            last_used_step: row["last_used_step"].as_u64().or_else(|| row["last_used_step"].as_f64().map(|s| s as u64)),
+            created_at: Self::parse_optional_timestamp(&row["created_at"])?.ok_or("Invalid created_at")?,
+        })
+    }
+
+    // JsValue params are not Send either, so they move straight into the wrapped future
+    fn execute<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<D1Result>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.run().await })
+    }
+
+    fn changes(result: &D1Result) -> usize {
+        result.meta().ok().flatten().and_then(|meta| meta.changes).unwrap_or(0)
+    }
+}
+
+#[async_trait]
+impl TwoFactorRepository for D1TwoFactorRepository {
+    async fn get_settings(&self, user_id: &Uuid) -> std::result::Result<Option<TwoFactorSettings>, Box<dyn std::error::Error>> {
+        let result = SendFuture::new(async {
+            self.db
+                .prepare("SELECT user_id, secret, enabled_at, last_used_step, created_at FROM user_totp WHERE user_id = ?")
+                .bind(&[user_id.to_string().into()])?
+                .first::<Value>(None)
+                .await
+        })
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
+        match result {
+            Some(row) => Ok(Some(Self::row_to_settings(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn save_pending(&self, settings: &TwoFactorSettings) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        // Never overwrite an enabled secret; enrolment must be disabled first
+        self.execute(
+            "INSERT INTO user_totp (user_id, secret, enabled_at, last_used_step, created_at) VALUES (?, ?, NULL, NULL, ?)
+             ON CONFLICT(user_id) DO UPDATE SET secret = excluded.secret, created_at = excluded.created_at, last_used_step = NULL
+             WHERE user_totp.enabled_at IS NULL",
+            vec![
+                settings.user_id.to_string().into(),
+                settings.secret.clone().into(),
+                Self::timestamp_value(&settings.created_at),
+            ],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn enable(&self, user_id: &Uuid, at: DateTime<Utc>, step: u64) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "UPDATE user_totp SET enabled_at = ?, last_used_step = ? WHERE user_id = ?",
+            vec![Self::timestamp_value(&at), (step as f64).into(), user_id.to_string().into()],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn record_used_step(&self, user_id: &Uuid, step: u64) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let result = self
+            .execute(
+                "UPDATE user_totp SET last_used_step = ? WHERE user_id = ? AND (last_used_step IS NULL OR last_used_step < ?)",
+                vec![(step as f64).into(), user_id.to_string().into(), (step as f64).into()],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+        Ok(Self::changes(&result) > 0)
+    }
+
+    async fn delete(&self, user_id: &Uuid) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let user_id = user_id.to_string();
+        SendFuture::new(async {
+            let statements = vec![
+                self.db.prepare("DELETE FROM recovery_codes WHERE user_id = ?").bind(&[user_id.clone().into()])?,
+                self.db.prepare("DELETE FROM user_totp WHERE user_id = ?").bind(&[user_id.clone().into()])?,
+            ];
+            self.db.batch(statements).await
+        })
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(&self, user_id: &Uuid, code_hashes: &[String], at: DateTime<Utc>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let user_id = user_id.to_string();
+        SendFuture::new(async {
+            let mut statements = vec![
+                self.db.prepare("DELETE FROM recovery_codes WHERE user_id = ?").bind(&[user_id.clone().into()])?,
+            ];
+            for code_hash in code_hashes {
+                statements.push(
+                    self.db
+                        .prepare("INSERT INTO recovery_codes (id, user_id, code_hash, used_at, created_at) VALUES (?, ?, ?, NULL, ?)")
+                        .bind(&[
+                            Uuid::new_v4().to_string().into(),
+                            user_id.clone().into(),
+                            code_hash.clone().into(),
+                            Self::timestamp_value(&at),
+                        ])?,
+                );
+            }
+            self.db.batch(statements).await
+        })
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn consume_recovery_code(&self, user_id: &Uuid, code_hash: &str, at: DateTime<Utc>) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let result = self
+            .execute(
+                "UPDATE recovery_codes SET used_at = ? WHERE user_id = ? AND code_hash = ? AND used_at IS NULL",
+                vec![Self::timestamp_value(&at), user_id.to_string().into(), code_hash.into()],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+        Ok(Self::changes(&result) > 0)
+    }
+
+    async fn spend_challenge(&self, jti: &str, expires_at: DateTime<Utc>, at: DateTime<Utc>) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        // Rows past their token's expiry can no longer matter, so each spend sweeps them
+        let results = SendFuture::new(async {
+            let statements = vec![
+                self.db
+                    .prepare("DELETE FROM spent_challenges WHERE expires_at < ?")
+                    .bind(&[Self::timestamp_value(&at)])?,
+                self.db
+                    .prepare("INSERT OR IGNORE INTO spent_challenges (jti, expires_at) VALUES (?, ?)")
+                    .bind(&[jti.into(), Self::timestamp_value(&expires_at)])?,
+            ];
+            self.db.batch(statements).await
+        })
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(results.last().map_or(0, Self::changes) > 0)
+    }
+
+    async fn record_attempt(&self, user_id: &Uuid, at: DateTime<Utc>, window_start: DateTime<Utc>) -> std::result::Result<u32, Box<dyn std::error::Error>> {
+        // One statement, so parallel attempts each see their own count
+        let row = SendFuture::new(async {
+            self.db
+                .prepare(
+                    "UPDATE user_totp SET
+                         attempts = CASE WHEN attempts_since IS NULL OR attempts_since < ? THEN 1 ELSE attempts + 1 END,
+                         attempts_since = CASE WHEN attempts_since IS NULL OR attempts_since < ? THEN ? ELSE attempts_since END
+                     WHERE user_id = ?
+                     RETURNING attempts",
+                )
+                .bind(&[
+                    Self::timestamp_value(&window_start),
+                    Self::timestamp_value(&window_start),
+                    Self::timestamp_value(&at),
+                    user_id.to_string().into(),
+                ])?
+                .first::<Value>(None)
+                .await
+        })
+        .await
+        .map_err(|e| format!("Run error: {}", e))?
+        .ok_or("Two-factor authentication is not enabled")?;
+        Ok(row["attempts"].as_u64().or_else(|| row["attempts"].as_f64().map(|n| n as u64)).unwrap_or(0) as u32)
+    }
+
+    async fn clear_attempts(&self, user_id: &Uuid) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "UPDATE user_totp SET attempts = 0, attempts_since = NULL WHERE user_id = ?",
+            vec![user_id.to_string().into()],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+}
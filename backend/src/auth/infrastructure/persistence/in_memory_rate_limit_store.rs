@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::auth::domain::rate_limit::RateLimitState;
+use crate::auth::domain::ports::RateLimitStore;
+use std::error::Error;
+
+// Process-local counters; pair with a FixedClock to exercise the limiter without D1
+pub struct InMemoryRateLimitStore {
+    entries: Arc<Mutex<HashMap<String, (RateLimitState, DateTime<Utc>)>>>,
+}
+
+impl InMemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn get_state(&self, key: &str) -> Result<Option<RateLimitState>, Box<dyn Error>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries.get(key).map(|(state, _)| state.clone()))
+    }
+
+    async fn put_state(&self, key: &str, state: &RateLimitState, expires_at: DateTime<Utc>) -> Result<(), Box<dyn Error>> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(key.to_string(), (state.clone(), expires_at));
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), Box<dyn Error>> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(key);
+        Ok(())
+    }
+}
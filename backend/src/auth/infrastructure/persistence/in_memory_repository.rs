@@ -1,10 +1,13 @@
 use async_trait::async_trait;
 use uuid::Uuid;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use crate::auth::domain::user::User;
-use crate::auth::domain::ports::UserRepository;
+use crate::auth::domain::session::{Session, RefreshToken};
+use crate::auth::domain::two_factor::TwoFactorSettings;
+use crate::auth::domain::ports::{UserRepository, UserRepositoryError, SessionRepository, TwoFactorRepository};
 use std::error::Error;
 
 pub struct InMemoryUserRepository {
@@ -23,6 +26,9 @@ impl InMemoryUserRepository {
 impl UserRepository for InMemoryUserRepository {
     async fn create_user(&self, user: &User) -> Result<(), Box<dyn Error>> {
         let mut users = self.users.lock().unwrap();
+        if users.contains_key(&user.username) {
+            return Err(Box::new(UserRepositoryError::UsernameTaken));
+        }
         users.insert(user.username.clone(), user.clone());
         Ok(())
     }
@@ -41,4 +47,223 @@ impl UserRepository for InMemoryUserRepository {
         let users = self.users.lock().unwrap();
         Ok(users.contains_key(username))
     }
+
+    async fn update_password_hash(&self, user_id: &Uuid, password_hash: &str) -> Result<(), Box<dyn Error>> {
+        let mut users = self.users.lock().unwrap();
+        let user = users.values_mut().find(|u| &u.id == user_id).ok_or("User not found")?;
+        user.password_hash = password_hash.to_string();
+        Ok(())
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> Result<Option<User>, Box<dyn Error>> {
+        let users = self.users.lock().unwrap();
+        Ok(users.values().find(|u| u.email.as_deref() == Some(email)).cloned())
+    }
+
+    async fn update_email(&self, user_id: &Uuid, email: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let mut users = self.users.lock().unwrap();
+        if let Some(email) = email {
+            if users.values().any(|u| &u.id != user_id && u.email.as_deref() == Some(email)) {
+                return Err(Box::new(UserRepositoryError::EmailTaken));
+            }
+        }
+        let user = users.values_mut().find(|u| &u.id == user_id).ok_or("User not found")?;
+        user.email = email.map(|e| e.to_string());
+        user.email_verified = false;
+        Ok(())
+    }
+
+    async fn mark_email_verified(&self, user_id: &Uuid, email: &str) -> Result<bool, Box<dyn Error>> {
+        let mut users = self.users.lock().unwrap();
+        match users.values_mut().find(|u| &u.id == user_id && u.email.as_deref() == Some(email)) {
+            Some(user) => {
+                user.email_verified = true;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+pub struct InMemorySessionRepository {
+    sessions: Arc<Mutex<HashMap<Uuid, Session>>>,
+    refresh_tokens: Arc<Mutex<HashMap<Uuid, RefreshToken>>>,
+}
+
+impl InMemorySessionRepository {
+    pub fn new() -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            refresh_tokens: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl SessionRepository for InMemorySessionRepository {
+    async fn create_session(&self, session: &Session) -> Result<(), Box<dyn Error>> {
+        self.sessions.lock().unwrap().insert(session.id, session.clone());
+        Ok(())
+    }
+
+    async fn get_session(&self, session_id: &Uuid) -> Result<Option<Session>, Box<dyn Error>> {
+        Ok(self.sessions.lock().unwrap().get(session_id).cloned())
+    }
+
+    async fn get_active_sessions(&self, user_id: &Uuid) -> Result<Vec<Session>, Box<dyn Error>> {
+        let sessions = self.sessions.lock().unwrap();
+        let mut active: Vec<Session> = sessions
+            .values()
+            .filter(|s| &s.user_id == user_id && s.is_active())
+            .cloned()
+            .collect();
+        active.sort_by(|a, b| b.last_used_at.cmp(&a.last_used_at));
+        Ok(active)
+    }
+
+    async fn touch_session(&self, session_id: &Uuid, at: DateTime<Utc>) -> Result<(), Box<dyn Error>> {
+        if let Some(session) = self.sessions.lock().unwrap().get_mut(session_id) {
+            session.last_used_at = at;
+        }
+        Ok(())
+    }
+
+    async fn revoke_session(&self, session_id: &Uuid, at: DateTime<Utc>) -> Result<(), Box<dyn Error>> {
+        if let Some(session) = self.sessions.lock().unwrap().get_mut(session_id) {
+            session.revoked_at.get_or_insert(at);
+        }
+        Ok(())
+    }
+
+    async fn revoke_all_sessions(&self, user_id: &Uuid, at: DateTime<Utc>) -> Result<(), Box<dyn Error>> {
+        for session in self.sessions.lock().unwrap().values_mut().filter(|s| &s.user_id == user_id) {
+            session.revoked_at.get_or_insert(at);
+        }
+        Ok(())
+    }
+
+    async fn create_refresh_token(&self, token: &RefreshToken) -> Result<(), Box<dyn Error>> {
+        self.refresh_tokens.lock().unwrap().insert(token.id, token.clone());
+        Ok(())
+    }
+
+    async fn get_refresh_token_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>, Box<dyn Error>> {
+        let tokens = self.refresh_tokens.lock().unwrap();
+        Ok(tokens.values().find(|t| t.token_hash == token_hash).cloned())
+    }
+
+    async fn mark_refresh_token_used(&self, token_id: &Uuid, at: DateTime<Utc>) -> Result<bool, Box<dyn Error>> {
+        match self.refresh_tokens.lock().unwrap().get_mut(token_id) {
+            Some(token) if token.used_at.is_none() => {
+                token.used_at = Some(at);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+pub struct InMemoryTwoFactorRepository {
+    settings: Arc<Mutex<HashMap<Uuid, TwoFactorSettings>>>,
+    // user -> (code hash, used)
+    recovery_codes: Arc<Mutex<HashMap<Uuid, Vec<(String, bool)>>>>,
+    spent_challenges: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+    // user -> (attempts, counted since)
+    attempts: Arc<Mutex<HashMap<Uuid, (u32, DateTime<Utc>)>>>,
+}
+
+impl InMemoryTwoFactorRepository {
+    pub fn new() -> Self {
+        Self {
+            settings: Arc::new(Mutex::new(HashMap::new())),
+            recovery_codes: Arc::new(Mutex::new(HashMap::new())),
+            spent_challenges: Arc::new(Mutex::new(HashMap::new())),
+            attempts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl TwoFactorRepository for InMemoryTwoFactorRepository {
+    async fn get_settings(&self, user_id: &Uuid) -> Result<Option<TwoFactorSettings>, Box<dyn Error>> {
+        Ok(self.settings.lock().unwrap().get(user_id).cloned())
+    }
+
+    async fn save_pending(&self, settings: &TwoFactorSettings) -> Result<(), Box<dyn Error>> {
+        let mut all = self.settings.lock().unwrap();
+        if all.get(&settings.user_id).map_or(true, |existing| !existing.is_enabled()) {
+            all.insert(settings.user_id, TwoFactorSettings { enabled_at: None, last_used_step: None, ..settings.clone() });
+        }
+        Ok(())
+    }
+
+    async fn enable(&self, user_id: &Uuid, at: DateTime<Utc>, step: u64) -> Result<(), Box<dyn Error>> {
+        if let Some(settings) = self.settings.lock().unwrap().get_mut(user_id) {
+            settings.enabled_at = Some(at);
+            settings.last_used_step = Some(step);
+        }
+        Ok(())
+    }
+
+    async fn record_used_step(&self, user_id: &Uuid, step: u64) -> Result<bool, Box<dyn Error>> {
+        match self.settings.lock().unwrap().get_mut(user_id) {
+            Some(settings) if settings.last_used_step.map_or(true, |last| last < step) => {
+                settings.last_used_step = Some(step);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn delete(&self, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
+        self.recovery_codes.lock().unwrap().remove(user_id);
+        self.attempts.lock().unwrap().remove(user_id);
+        self.settings.lock().unwrap().remove(user_id);
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(&self, user_id: &Uuid, code_hashes: &[String], _at: DateTime<Utc>) -> Result<(), Box<dyn Error>> {
+        let codes = code_hashes.iter().map(|hash| (hash.clone(), false)).collect();
+        self.recovery_codes.lock().unwrap().insert(*user_id, codes);
+        Ok(())
+    }
+
+    async fn consume_recovery_code(&self, user_id: &Uuid, code_hash: &str, _at: DateTime<Utc>) -> Result<bool, Box<dyn Error>> {
+        let mut all = self.recovery_codes.lock().unwrap();
+        let unused = all
+            .get_mut(user_id)
+            .and_then(|codes| codes.iter_mut().find(|(hash, used)| hash == code_hash && !*used));
+        match unused {
+            Some(code) => {
+                code.1 = true;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn spend_challenge(&self, jti: &str, expires_at: DateTime<Utc>, at: DateTime<Utc>) -> Result<bool, Box<dyn Error>> {
+        let mut spent = self.spent_challenges.lock().unwrap();
+        spent.retain(|_, expiry| *expiry >= at);
+        if spent.contains_key(jti) {
+            return Ok(false);
+        }
+        spent.insert(jti.to_string(), expires_at);
+        Ok(true)
+    }
+
+    async fn record_attempt(&self, user_id: &Uuid, at: DateTime<Utc>, window_start: DateTime<Utc>) -> Result<u32, Box<dyn Error>> {
+        let mut all = self.attempts.lock().unwrap();
+        let entry = all.entry(*user_id).or_insert((0, at));
+        if entry.1 < window_start {
+            *entry = (0, at);
+        }
+        entry.0 += 1;
+        Ok(entry.0)
+    }
+
+    async fn clear_attempts(&self, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
+        self.attempts.lock().unwrap().remove(user_id);
+        Ok(())
+    }
 }
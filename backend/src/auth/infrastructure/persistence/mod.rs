@@ -1,4 +1,8 @@
 pub mod in_memory_repository;
-pub mod persistent_memory_repository;
-// D1 temporarily disabled until import issues resolved
-// pub mod d1_repository;
+pub mod d1_repository;
+pub mod d1_session_repository;
+pub mod d1_two_factor_repository;
+pub mod d1_account_token_repository;
+pub mod in_memory_rate_limit_store;
+pub mod d1_rate_limit_store;
+pub mod d1_account_data_repository;
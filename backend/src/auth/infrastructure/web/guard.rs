@@ -0,0 +1,212 @@
+use worker::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::auth::domain::ports::{SessionRepository, TokenService};
+use crate::auth::domain::user::TokenError;
+
+// Identity of the caller, taken from a validated access token
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub user_id: Uuid,
+    pub username: String,
+    pub session_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnauthorizedResponse {
+    pub error: String,
+    pub code: String,
+}
+
+// Extract the token from an "Authorization: Bearer <token>" header
+fn bearer_token(req: &Request) -> std::result::Result<String, TokenError> {
+    let header = match req.headers().get("Authorization") {
+        Ok(Some(header)) => header,
+        Ok(None) => return Err(TokenError::Missing),
+        Err(_) => return Err(TokenError::Malformed),
+    };
+
+    match header.trim().split_once(' ') {
+        Some((scheme, token)) if scheme.eq_ignore_ascii_case("Bearer") && !token.trim().is_empty() => {
+            Ok(token.trim().to_string())
+        }
+        _ => Err(TokenError::Malformed),
+    }
+}
+
+// Authentication guard shared by every protected route. Access tokens are only honoured while
+// the session named by their `sid` claim exists and has not been revoked, so logging out or
+// revoking a device takes effect immediately. Errors reading the session are returned as
+// `Err` rather than letting the request through.
+pub async fn authenticate(
+    req: &Request,
+    token_service: &dyn TokenService,
+    session_repository: &dyn SessionRepository,
+) -> Result<std::result::Result<AuthenticatedUser, TokenError>> {
+    match bearer_token(req) {
+        Ok(token) => authenticate_token(&token, token_service, session_repository).await,
+        Err(e) => Ok(Err(e)),
+    }
+}
+
+// The checks `authenticate` runs once the bearer token is out of the request
+pub async fn authenticate_token(
+    token: &str,
+    token_service: &dyn TokenService,
+    session_repository: &dyn SessionRepository,
+) -> Result<std::result::Result<AuthenticatedUser, TokenError>> {
+    let claims = match token_service.validate_token(token).await {
+        Ok(claims) => claims,
+        Err(e) => return Ok(Err(e.downcast_ref::<TokenError>().cloned().unwrap_or(TokenError::Malformed))),
+    };
+
+    let Ok(user_id) = Uuid::parse_str(&claims.sub) else {
+        return Ok(Err(TokenError::InvalidSubject));
+    };
+    let Some(Ok(session_id)) = claims.sid.as_deref().map(Uuid::parse_str) else {
+        return Ok(Err(TokenError::SessionRevoked));
+    };
+
+    let session = session_repository
+        .get_session(&session_id)
+        .await
+        .map_err(|e| Error::RustError(format!("Failed to load session: {}", e)))?;
+    match session {
+        Some(session) if session.user_id == user_id && session.is_active() => Ok(Ok(AuthenticatedUser {
+            user_id,
+            username: claims.username,
+            session_id,
+        })),
+        _ => Ok(Err(TokenError::SessionRevoked)),
+    }
+}
+
+// Structured 401 with a machine-readable code and an RFC 6750 challenge header
+pub fn unauthorized_response(err: &TokenError) -> Result<Response> {
+    let challenge = match err {
+        TokenError::Missing => "Bearer realm=\"twodo\"".to_string(),
+        _ => format!(
+            "Bearer realm=\"twodo\", error=\"invalid_token\", error_description=\"{}\"",
+            err
+        ),
+    };
+
+    let mut response = Response::from_json(&UnauthorizedResponse {
+        error: err.to_string(),
+        code: err.code().to_string(),
+    })?
+    .with_status(401);
+    response.headers_mut().set("WWW-Authenticate", &challenge)?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone, Utc};
+    use std::sync::Arc;
+
+    use crate::auth::domain::clock::FixedClock;
+    use crate::auth::domain::ports::Clock;
+    use crate::auth::domain::session::Session;
+    use crate::auth::infrastructure::{InMemorySessionRepository, KeyRing, WasmTokenService};
+    use crate::test_support::block_on;
+
+    const ISSUED_AT: i64 = 1_718_000_000;
+    const TTL_SECS: u64 = 900;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    fn tokens(keys: &str, seconds: i64) -> WasmTokenService {
+        let clock: Arc<dyn Clock> = Arc::new(FixedClock::new(at(seconds)));
+        WasmTokenService::new(KeyRing::parse(keys).unwrap(), TTL_SECS).with_clock(clock)
+    }
+
+    // A session for a new user and an access token for it, issued at ISSUED_AT with `keys`
+    fn signed_in(sessions: &InMemorySessionRepository, keys: &str) -> (Session, String) {
+        let session = Session {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            device_name: None,
+            user_agent: None,
+            created_at: at(ISSUED_AT),
+            last_used_at: at(ISSUED_AT),
+            revoked_at: None,
+        };
+        block_on(sessions.create_session(&session)).unwrap();
+        let token = block_on(tokens(keys, ISSUED_AT).generate_token(&session.user_id, "alice", &session.id)).unwrap();
+        (session, token)
+    }
+
+    fn check(token: &str, keys: &str, seconds: i64, sessions: &InMemorySessionRepository) -> std::result::Result<AuthenticatedUser, TokenError> {
+        block_on(authenticate_token(token, &tokens(keys, seconds), sessions)).unwrap()
+    }
+
+    #[test]
+    fn live_token_on_an_active_session_is_accepted() {
+        let sessions = InMemorySessionRepository::new();
+        let (session, token) = signed_in(&sessions, "keys:2024-06:new-secret,2024-01:old-secret");
+
+        let user = check(&token, "keys:2024-06:new-secret,2024-01:old-secret", ISSUED_AT + 60, &sessions).unwrap();
+        assert_eq!(user.user_id, session.user_id);
+        assert_eq!(user.session_id, session.id);
+        assert_eq!(user.username, "alice");
+
+        // Still verifies once its key has been rotated out of the signing slot
+        assert!(check(&token, "keys:2024-09:newer-secret,2024-06:new-secret", ISSUED_AT + 60, &sessions).is_ok());
+    }
+
+    #[test]
+    fn expired_token_is_rejected() {
+        let sessions = InMemorySessionRepository::new();
+        let (_, token) = signed_in(&sessions, "secret");
+
+        // Clock skew leeway is one minute past `exp`
+        assert!(check(&token, "secret", ISSUED_AT + TTL_SECS as i64 + 59, &sessions).is_ok());
+        assert_eq!(check(&token, "secret", ISSUED_AT + TTL_SECS as i64 + 60, &sessions).unwrap_err(), TokenError::Expired);
+    }
+
+    #[test]
+    fn token_signed_with_another_secret_is_rejected() {
+        let sessions = InMemorySessionRepository::new();
+        let (_, token) = signed_in(&sessions, "secret");
+        assert_eq!(check(&token, "other-secret", ISSUED_AT, &sessions).unwrap_err(), TokenError::InvalidSignature);
+
+        // A payload swapped under an untouched signature fails the same way
+        let (_, other) = signed_in(&sessions, "secret");
+        let parts: Vec<&str> = token.split('.').collect();
+        let other_parts: Vec<&str> = other.split('.').collect();
+        let forged = format!("{}.{}.{}", parts[0], other_parts[1], parts[2]);
+        assert_eq!(check(&forged, "secret", ISSUED_AT, &sessions).unwrap_err(), TokenError::InvalidSignature);
+    }
+
+    #[test]
+    fn token_from_a_retired_key_is_rejected() {
+        let sessions = InMemorySessionRepository::new();
+        let (_, token) = signed_in(&sessions, "keys:2024-01:old-secret");
+        assert_eq!(check(&token, "keys:2024-06:new-secret", ISSUED_AT, &sessions).unwrap_err(), TokenError::UnknownKey);
+    }
+
+    #[test]
+    fn token_of_a_revoked_or_unknown_session_is_rejected() {
+        let sessions = InMemorySessionRepository::new();
+        let (session, token) = signed_in(&sessions, "secret");
+        block_on(sessions.revoke_session(&session.id, at(ISSUED_AT + 30))).unwrap();
+        assert_eq!(check(&token, "secret", ISSUED_AT + 60, &sessions).unwrap_err(), TokenError::SessionRevoked);
+
+        // A `sid` that names no session at all, e.g. one deleted with its account
+        let token = block_on(tokens("secret", ISSUED_AT).generate_token(&session.user_id, "alice", &Uuid::new_v4())).unwrap();
+        assert_eq!(check(&token, "secret", ISSUED_AT, &sessions).unwrap_err(), TokenError::SessionRevoked);
+    }
+
+    #[test]
+    fn token_naming_someone_elses_session_is_rejected() {
+        let sessions = InMemorySessionRepository::new();
+        let (session, _) = signed_in(&sessions, "secret");
+        let token = block_on(tokens("secret", ISSUED_AT).generate_token(&Uuid::new_v4(), "mallory", &session.id)).unwrap();
+        assert_eq!(check(&token, "secret", ISSUED_AT, &sessions).unwrap_err(), TokenError::SessionRevoked);
+    }
+}
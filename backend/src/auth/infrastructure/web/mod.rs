@@ -1 +1,6 @@
 pub mod routes;
+pub mod guard;
+pub mod rate_limit;
+
+pub use guard::{authenticate, unauthorized_response, AuthenticatedUser};
+pub use rate_limit::{client_ip, rate_limit_unavailable_response, too_many_requests_response};
@@ -0,0 +1,44 @@
+use worker::*;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct TooManyRequestsResponse {
+    pub error: String,
+    pub retry_after: u64,
+}
+
+// Address of the caller as seen by Cloudflare; requests without it (local dev) share one bucket
+pub fn client_ip(req: &Request) -> String {
+    req.headers()
+        .get("CF-Connecting-IP")
+        .ok()
+        .flatten()
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// How long clients are asked to wait when the limiter's storage cannot be reached
+const UNAVAILABLE_RETRY_AFTER_SECS: u64 = 30;
+
+// 429 with a Retry-After header in seconds
+pub fn too_many_requests_response(retry_after_secs: u64) -> Result<Response> {
+    let mut response = Response::from_json(&TooManyRequestsResponse {
+        error: "Too many attempts, please try again later".to_string(),
+        retry_after: retry_after_secs,
+    })?
+    .with_status(429);
+    response.headers_mut().set("Retry-After", &retry_after_secs.to_string())?;
+    Ok(response)
+}
+
+// 503 for when attempts cannot be counted; limited endpoints fail closed rather than go unthrottled
+pub fn rate_limit_unavailable_response() -> Result<Response> {
+    let mut response = Response::from_json(&TooManyRequestsResponse {
+        error: "Service temporarily unavailable, please try again shortly".to_string(),
+        retry_after: UNAVAILABLE_RETRY_AFTER_SECS,
+    })?
+    .with_status(503);
+    response.headers_mut().set("Retry-After", &UNAVAILABLE_RETRY_AFTER_SECS.to_string())?;
+    Ok(response)
+}
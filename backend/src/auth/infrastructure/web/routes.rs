@@ -4,11 +4,22 @@ use std::sync::Arc;
 
 use crate::auth::application::use_cases::AuthService;
 use crate::auth::domain::user::{UserRegistration, UserLogin};
+use crate::auth::domain::session::DeviceInfo;
 
 #[derive(Debug, Deserialize)]
 pub struct AuthPayload {
     pub username: String,
     pub password: String,
+    #[serde(default)]
+    pub device_name: Option<String>,
+}
+
+// Device details for a new session: the client-chosen name plus the User-Agent header
+pub fn device_info_from_request(req: &Request, device_name: Option<String>) -> DeviceInfo {
+    DeviceInfo {
+        device_name,
+        user_agent: req.headers().get("User-Agent").ok().flatten(),
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -65,14 +76,15 @@ pub async fn handle_login(
     };
 
     // Create login request
+    let device = device_info_from_request(&req, payload.device_name);
     let login = UserLogin {
         username: payload.username,
         password: payload.password,
     };
 
     // Authenticate user
-    match auth_service.login(login).await {
-        Ok(auth_result) => Response::from_json(&auth_result),
+    match auth_service.login(login, device).await {
+        Ok(outcome) => Response::from_json(&outcome),
         Err(e) => {
             let status = if e.to_string().contains("Invalid credentials") { 401 } else { 400 };
             let response = Response::from_json(&ErrorResponse {
@@ -12,6 +12,15 @@ use crate::calendar::domain::ports::{
     RecurrenceService, ReminderService, EventIntegrationService,
     RecurrenceUpdateScope, RecurrenceDeleteScope
 };
+use crate::activity::domain::activity::{ActivityKind, NewActivity};
+use crate::activity::domain::ports::ActivityRecorder;
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::groups::domain::permissions::GroupAction;
+use crate::groups::domain::ports::GroupSettingsRepository;
+use crate::groups::domain::settings::GroupModule;
+use crate::groups::domain::ports::GroupNameLookup;
+use crate::profiles::domain::ports::DisplayNameLookup;
+use crate::profiles::domain::profile::UNKNOWN_USER_NAME;
 use std::error::Error;
 
 pub struct CalendarService {
@@ -22,6 +31,11 @@ pub struct CalendarService {
     recurrence_service: Arc<dyn RecurrenceService>,
     reminder_service: Arc<dyn ReminderService>,
     integration_service: Arc<dyn EventIntegrationService>,
+    display_names: Arc<dyn DisplayNameLookup>,
+    group_names: Arc<dyn GroupNameLookup>,
+    settings_repository: Arc<dyn GroupSettingsRepository>,
+    activity: Arc<dyn ActivityRecorder>,
+    authorizer: Arc<GroupAuthorizer>,
 }
 
 impl CalendarService {
@@ -33,6 +47,11 @@ impl CalendarService {
         recurrence_service: Arc<dyn RecurrenceService>,
         reminder_service: Arc<dyn ReminderService>,
         integration_service: Arc<dyn EventIntegrationService>,
+        display_names: Arc<dyn DisplayNameLookup>,
+        group_names: Arc<dyn GroupNameLookup>,
+        settings_repository: Arc<dyn GroupSettingsRepository>,
+        activity: Arc<dyn ActivityRecorder>,
+        authorizer: Arc<GroupAuthorizer>,
     ) -> Self {
         Self {
             event_repository,
@@ -42,10 +61,18 @@ impl CalendarService {
             recurrence_service,
             reminder_service,
             integration_service,
+            display_names,
+            group_names,
+            settings_repository,
+            activity,
+            authorizer,
         }
     }
 
     pub async fn create_event(&self, creation: EventCreation, created_by: Uuid) -> Result<EventInfo, Box<dyn Error>> {
+        self.authorizer.authorize(&creation.group_id, &created_by, GroupAction::CreateEvent, None).await?;
+        self.settings_repository.get_settings(&creation.group_id).await?.require_enabled(GroupModule::Calendar)?;
+
         // Validate input
         if creation.title.trim().is_empty() {
             return Err("Event title cannot be empty".into());
@@ -148,19 +175,34 @@ impl CalendarService {
             Some(e) => e,
             None => return Ok(None),
         };
+        let access = self.authorizer.authorize(&event.group_id, user_id, GroupAction::ViewGroup, None).await?;
 
         let attendees = self.attendee_repository.get_event_attendees(event_id).await?;
         let user_status = attendees.iter()
             .find(|a| a.user_id == *user_id)
             .map(|a| a.status.clone());
 
-        let can_edit = event.created_by == *user_id || 
+        let is_author = event.created_by == *user_id ||
                       attendees.iter().any(|a| a.user_id == *user_id && a.is_organizer);
+        let can_edit = access.allows(GroupAction::EditEvent, is_author);
+
+        let created_by_name = self
+            .display_names
+            .display_names(&[event.created_by])
+            .await?
+            .remove(&event.created_by)
+            .unwrap_or_else(|| UNKNOWN_USER_NAME.to_string());
+        let group_name = self
+            .group_names
+            .group_names(&[event.group_id])
+            .await?
+            .remove(&event.group_id)
+            .unwrap_or_else(|| "Unknown Group".to_string());
 
         Ok(Some(EventInfo {
             id: event.id,
             group_id: event.group_id,
-            group_name: "Group".to_string(), // TODO: Lookup group name
+            group_name,
             title: event.title,
             description: event.description,
             location: event.location,
@@ -168,7 +210,7 @@ impl CalendarService {
             end_time: event.end_time,
             is_all_day: event.is_all_day,
             created_by: event.created_by,
-            created_by_name: "User".to_string(), // TODO: Lookup username
+            created_by_name,
             category: event.category,
             color: event.color,
             recurrence: event.recurrence,
@@ -185,17 +227,7 @@ impl CalendarService {
     }
 
     pub async fn update_event(&self, event_id: &Uuid, user_id: &Uuid, update: EventUpdate) -> Result<(), Box<dyn Error>> {
-        // Verify user has permission to update
-        let event = self.event_repository.get_event_by_id(event_id).await?
-            .ok_or("Event not found")?;
-        
-        let attendees = self.attendee_repository.get_event_attendees(event_id).await?;
-        let can_edit = event.created_by == *user_id || 
-                      attendees.iter().any(|a| a.user_id == *user_id && a.is_organizer);
-        
-        if !can_edit {
-            return Err("Insufficient permissions to update event".into());
-        }
+        let event = self.authorize_event(event_id, user_id, GroupAction::EditEvent).await?;
 
         // Validate updates
         if let (Some(start), Some(end)) = (&update.start_time, &update.end_time) {
@@ -216,13 +248,7 @@ impl CalendarService {
     }
 
     pub async fn delete_event(&self, event_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
-        // Verify user has permission
-        let event = self.event_repository.get_event_by_id(event_id).await?
-            .ok_or("Event not found")?;
-        
-        if event.created_by != *user_id {
-            return Err("Only the event creator can delete the event".into());
-        }
+        let event = self.authorize_event(event_id, user_id, GroupAction::DeleteEvent).await?;
 
         // Handle recurring events
         if event.recurrence.is_some() {
@@ -235,13 +261,9 @@ impl CalendarService {
     }
 
     pub async fn invite_users(&self, event_id: &Uuid, inviter_id: &Uuid, invite: InviteUsers) -> Result<(), Box<dyn Error>> {
-        // Verify user has permission to invite
+        // Inviting people to an event is editing it
+        self.authorize_event(event_id, inviter_id, GroupAction::EditEvent).await?;
         let attendees = self.attendee_repository.get_event_attendees(event_id).await?;
-        let can_invite = attendees.iter().any(|a| a.user_id == *inviter_id && a.is_organizer);
-        
-        if !can_invite {
-            return Err("Insufficient permissions to invite users".into());
-        }
 
         let now = Utc::now();
         let new_attendees: Vec<EventAttendee> = invite.user_ids.into_iter()
@@ -264,23 +286,38 @@ impl CalendarService {
     }
 
     pub async fn respond_to_event(&self, event_id: &Uuid, user_id: &Uuid, response: RespondToEvent) -> Result<(), Box<dyn Error>> {
+        let event = self.authorize_event(event_id, user_id, GroupAction::RespondToEvent).await?;
         // Check if user is invited
         if !self.attendee_repository.is_user_invited(event_id, user_id).await? {
             return Err("User is not invited to this event".into());
         }
 
-        self.attendee_repository.update_attendee_status(event_id, user_id, response.status).await
+        let summary = format!("{}: {}", event.title, response.status.as_str());
+        self.attendee_repository.update_attendee_status(event_id, user_id, response.status).await?;
+        self.activity
+            .record(&NewActivity::new(event.group_id, *user_id, ActivityKind::EventRsvp, Some(event.id), summary))
+            .await;
+        Ok(())
     }
 
     pub async fn get_day_view(&self, date: &DateTime<Utc>, user_id: &Uuid, group_id: Option<&Uuid>) -> Result<CalendarView, Box<dyn Error>> {
+        if let Some(group_id) = group_id {
+            self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        }
         self.view_service.get_day_view(date, user_id, group_id).await
     }
 
     pub async fn get_week_view(&self, date: &DateTime<Utc>, user_id: &Uuid, group_id: Option<&Uuid>) -> Result<CalendarView, Box<dyn Error>> {
+        if let Some(group_id) = group_id {
+            self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        }
         self.view_service.get_week_view(date, user_id, group_id).await
     }
 
     pub async fn get_month_view(&self, date: &DateTime<Utc>, user_id: &Uuid, group_id: Option<&Uuid>) -> Result<CalendarView, Box<dyn Error>> {
+        if let Some(group_id) = group_id {
+            self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        }
         self.view_service.get_month_view(date, user_id, group_id).await
     }
 
@@ -300,15 +337,30 @@ impl CalendarService {
     }
 
     pub async fn link_to_chore(&self, event_id: &Uuid, chore_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
-        // TODO: Verify user has permission
+        self.authorize_event(event_id, user_id, GroupAction::EditEvent).await?;
         self.integration_service.link_to_chore(event_id, chore_id).await
     }
 
     pub async fn link_to_expense(&self, event_id: &Uuid, expense_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
-        // TODO: Verify user has permission
+        self.authorize_event(event_id, user_id, GroupAction::EditEvent).await?;
         self.integration_service.link_to_expense(event_id, expense_id).await
     }
 
+    // Loads an event and checks the action against the caller's role in its group. The
+    // event's organizers count as its authors.
+    async fn authorize_event(&self, event_id: &Uuid, user_id: &Uuid, action: GroupAction) -> Result<Event, Box<dyn Error>> {
+        let event = self.event_repository.get_event_by_id(event_id).await?.ok_or("Event not found")?;
+        let is_organizer = self
+            .attendee_repository
+            .get_event_attendees(event_id)
+            .await?
+            .iter()
+            .any(|a| a.user_id == *user_id && a.is_organizer);
+        let author = if is_organizer { *user_id } else { event.created_by };
+        self.authorizer.authorize(&event.group_id, user_id, action, Some(&author)).await?;
+        Ok(event)
+    }
+
     pub async fn process_reminders(&self) -> Result<(), Box<dyn Error>> {
         // Background task to send pending reminders
         self.reminder_service.send_reminder_notifications().await
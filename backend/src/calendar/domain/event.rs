@@ -79,6 +79,17 @@ pub enum AttendeeStatus {
     Tentative,
 }
 
+impl AttendeeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttendeeStatus::Pending => "pending",
+            AttendeeStatus::Accepted => "accepted",
+            AttendeeStatus::Declined => "declined",
+            AttendeeStatus::Tentative => "tentative",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EventCreation {
     pub group_id: Uuid,
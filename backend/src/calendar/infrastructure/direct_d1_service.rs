@@ -1,44 +1,91 @@
-use worker::{D1Database, Error as WorkerError};
+use worker::D1Database;
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde_json::Value;
 
+use crate::activity::domain::activity::{ActivityKind, NewActivity};
+use crate::activity::infrastructure::record_activity;
+use crate::profiles::infrastructure::{display_name, load_display_names};
+use crate::error::ServiceError;
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::groups::infrastructure::{group_name, load_group_names, load_group_settings, require_module};
+use crate::groups::domain::settings::GroupModule;
+use crate::groups::domain::permissions::GroupAction;
 use crate::calendar::domain::event::{
-    Event, EventInfo, EventCreation, EventVisibility, AttendeeStatus, EventAttendeeInfo,
+    Event, EventInfo, EventCreation, EventVisibility, AttendeeStatus, EventAttendeeInfo, ViewType,
 };
 
 pub struct DirectD1CalendarService {
     db: D1Database,
+    authorizer: GroupAuthorizer,
 }
 
 impl DirectD1CalendarService {
-    pub fn new(db: D1Database) -> Self {
-        Self { db }
+    pub fn new(db: D1Database, authorizer: GroupAuthorizer) -> Self {
+        Self { db, authorizer }
     }
 
-    async fn get_username(&self, user_id: &Uuid) -> Result<String, WorkerError> {
-        let stmt = self.db.prepare("SELECT username FROM users WHERE id = ?1");
-        let result = stmt.bind(&[user_id.to_string().into()])?.first::<Value>(None).await?;
-        
-        if let Some(row) = result {
-            Ok(row["username"].as_str().unwrap_or("Unknown User").to_string())
-        } else {
-            Ok("Unknown User".to_string())
-        }
+    fn parse_uuid(value: &Value) -> Result<Uuid, ServiceError> {
+        Uuid::parse_str(value.as_str().unwrap_or(""))
+            .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))
     }
 
-    async fn get_group_name(&self, group_id: &Uuid) -> Result<String, WorkerError> {
-        let stmt = self.db.prepare("SELECT name FROM groups WHERE id = ?1");
-        let result = stmt.bind(&[group_id.to_string().into()])?.first::<Value>(None).await?;
-        
-        if let Some(row) = result {
-            Ok(row["name"].as_str().unwrap_or("Unknown Group").to_string())
-        } else {
-            Ok("Unknown Group".to_string())
+    fn parse_datetime(value: &Value) -> Result<DateTime<Utc>, ServiceError> {
+        Ok(DateTime::parse_from_rfc3339(value.as_str().unwrap_or(""))
+            .map_err(|e| ServiceError::Internal(format!("Date parse error: {}", e)))?
+            .with_timezone(&Utc))
+    }
+
+    // Event rows -> list DTOs (without attendees), resolving names with one lookup each
+    async fn build_event_infos(&self, rows: Vec<Value>, user_id: &Uuid) -> Result<Vec<EventInfo>, ServiceError> {
+        let mut user_ids = Vec::with_capacity(rows.len());
+        let mut group_ids = Vec::with_capacity(rows.len());
+        for row in &rows {
+            user_ids.push(Self::parse_uuid(&row["created_by"])?);
+            group_ids.push(Self::parse_uuid(&row["group_id"])?);
         }
+        let names = load_display_names(&self.db, &user_ids).await?;
+        let group_names = load_group_names(&self.db, &group_ids).await?;
+
+        let mut events = Vec::with_capacity(rows.len());
+        for row in rows {
+            let created_by = Self::parse_uuid(&row["created_by"])?;
+            let group_id = Self::parse_uuid(&row["group_id"])?;
+
+            events.push(EventInfo {
+                id: Self::parse_uuid(&row["id"])?,
+                group_id,
+                group_name: group_name(&group_names, &group_id),
+                title: row["title"].as_str().unwrap_or("").to_string(),
+                description: Some(row["description"].as_str().unwrap_or("").to_string()),
+                location: Some(row["location"].as_str().unwrap_or("").to_string()),
+                start_time: Self::parse_datetime(&row["start_time"])?,
+                end_time: Self::parse_datetime(&row["end_time"])?,
+                is_all_day: false, // Simplified
+                created_by,
+                created_by_name: display_name(&names, &created_by),
+                category: None, // Simplified
+                color: None, // Simplified
+                recurrence: None,
+                reminder_minutes: vec![],
+                visibility: EventVisibility::Public,
+                attendees: vec![], // Filled in by get_event_by_id only
+                user_status: None,
+                can_edit: created_by == *user_id,
+                linked_chore_id: None,
+                linked_expense_id: None,
+                created_at: Self::parse_datetime(&row["created_at"])?,
+                updated_at: Self::parse_datetime(&row["updated_at"])?,
+            });
+        }
+
+        Ok(events)
     }
 
-    pub async fn create_event_from_creation(&self, creation: EventCreation, created_by: Uuid) -> Result<EventInfo, WorkerError> {
+    pub async fn create_event_from_creation(&self, creation: EventCreation, created_by: Uuid) -> Result<EventInfo, ServiceError> {
+        self.authorizer.authorize(&creation.group_id, &created_by, GroupAction::CreateEvent, None).await?;
+        require_module(&self.db, &creation.group_id, GroupModule::Calendar).await?;
+
         let event = Event {
             id: Uuid::new_v4(),
             group_id: creation.group_id,
@@ -67,13 +114,13 @@ impl DirectD1CalendarService {
         }
 
         // Return event info
-        let created_by_name = self.get_username(&created_by).await.unwrap_or_else(|_| "Unknown User".to_string());
-        let group_name = self.get_group_name(&event.group_id).await.unwrap_or_else(|_| "Unknown Group".to_string());
-        
+        let names = load_display_names(&self.db, &[created_by]).await?;
+        let group_names = load_group_names(&self.db, &[event.group_id]).await?;
+
         Ok(EventInfo {
             id: event.id,
             group_id: event.group_id,
-            group_name,
+            group_name: group_name(&group_names, &event.group_id),
             title: event.title,
             description: event.description,
             location: event.location,
@@ -81,7 +128,7 @@ impl DirectD1CalendarService {
             end_time: event.end_time,
             is_all_day: event.is_all_day,
             created_by,
-            created_by_name,
+            created_by_name: display_name(&names, &created_by),
             category: event.category,
             color: event.color,
             recurrence: event.recurrence,
@@ -97,7 +144,7 @@ impl DirectD1CalendarService {
         })
     }
 
-    pub async fn create_event(&self, event: &Event) -> Result<(), WorkerError> {
+    pub async fn create_event(&self, event: &Event) -> Result<(), ServiceError> {
         let visibility_str = match event.visibility {
             EventVisibility::Public => "public",
             EventVisibility::Private => "private",
@@ -124,7 +171,7 @@ impl DirectD1CalendarService {
         Ok(())
     }
 
-    pub async fn add_attendee(&self, event_id: &Uuid, user_id: &Uuid) -> Result<(), WorkerError> {
+    pub async fn add_attendee(&self, event_id: &Uuid, user_id: &Uuid) -> Result<(), ServiceError> {
         let stmt = self.db.prepare("INSERT INTO event_attendees (event_id, user_id, status, responded_at) VALUES (?1, ?2, ?3, ?4)");
         
         stmt.bind(&[
@@ -139,63 +186,64 @@ impl DirectD1CalendarService {
         Ok(())
     }
 
-    pub async fn get_event_by_id(&self, event_id: &Uuid, user_id: &Uuid) -> Result<Option<EventInfo>, WorkerError> {
-        let stmt = self.db.prepare("SELECT * FROM events WHERE id = ?1");
-        let result = stmt.bind(&[event_id.to_string().into()])?.first::<Value>(None).await?;
-
-        if let Some(row) = result {
-            let created_by = Uuid::parse_str(row["created_by"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let group_id = Uuid::parse_str(row["group_id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-
-            let created_by_name = self.get_username(&created_by).await.unwrap_or_else(|_| "Unknown User".to_string());
-            let group_name = self.get_group_name(&group_id).await.unwrap_or_else(|_| "Unknown Group".to_string());
-            let attendees = self.get_event_attendee_info(event_id).await?;
-            let user_status = self.get_user_event_status(event_id, user_id).await?;
-            let can_edit = created_by == *user_id;
-
-            let event_info = EventInfo {
-                id: *event_id,
-                group_id,
-                group_name,
-                title: row["title"].as_str().unwrap_or("").to_string(),
-                description: Some(row["description"].as_str().unwrap_or("").to_string()),
-                location: Some(row["location"].as_str().unwrap_or("").to_string()),
-                start_time: DateTime::parse_from_rfc3339(row["start_time"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                end_time: DateTime::parse_from_rfc3339(row["end_time"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                is_all_day: false, // Simplified
-                created_by,
-                created_by_name,
-                category: None, // Simplified
-                color: None, // Simplified
-                recurrence: None,
-                reminder_minutes: vec![],
-                visibility: EventVisibility::Public,
-                attendees,
-                user_status,
-                can_edit,
-                linked_chore_id: None,
-                linked_expense_id: None,
-                created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(row["updated_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-            };
+    // RSVP from any member of the event's group; answering again replaces the earlier answer
+    pub async fn respond_to_event(&self, event_id: &Uuid, user_id: &Uuid, status: AttendeeStatus) -> Result<(), ServiceError> {
+        let row = self
+            .db
+            .prepare("SELECT group_id, title FROM events WHERE id = ?1")
+            .bind(&[event_id.to_string().into()])?
+            .first::<Value>(None)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Event not found".to_string()))?;
+        let group_id = Self::parse_uuid(&row["group_id"])?;
+        self.authorizer.authorize(&group_id, user_id, GroupAction::RespondToEvent, None).await?;
+        require_module(&self.db, &group_id, GroupModule::Calendar).await?;
+        if matches!(status, AttendeeStatus::Pending) {
+            return Err(ServiceError::Invalid("Respond with accepted, declined or tentative".to_string()));
+        }
 
-            Ok(Some(event_info))
-        } else {
-            Ok(None)
+        let responded_at = Utc::now().to_rfc3339();
+        let updated = self
+            .db
+            .prepare("UPDATE event_attendees SET status = ?1, responded_at = ?2 WHERE event_id = ?3 AND user_id = ?4")
+            .bind(&[status.as_str().into(), responded_at.clone().into(), event_id.to_string().into(), user_id.to_string().into()])?
+            .run()
+            .await?
+            .meta()?
+            .and_then(|meta| meta.changes)
+            .unwrap_or(0);
+        if updated == 0 {
+            self.db
+                .prepare("INSERT INTO event_attendees (event_id, user_id, status, responded_at) VALUES (?1, ?2, ?3, ?4)")
+                .bind(&[event_id.to_string().into(), user_id.to_string().into(), status.as_str().into(), responded_at.into()])?
+                .run()
+                .await?;
         }
+
+        let summary = format!("{}: {}", row["title"].as_str().unwrap_or(""), status.as_str());
+        record_activity(&self.db, &NewActivity::new(group_id, *user_id, ActivityKind::EventRsvp, Some(*event_id), summary)).await;
+        Ok(())
+    }
+
+    pub async fn get_event_by_id(&self, event_id: &Uuid, user_id: &Uuid) -> Result<Option<EventInfo>, ServiceError> {
+        let stmt = self.db.prepare("SELECT * FROM events WHERE id = ?1");
+        let row = match stmt.bind(&[event_id.to_string().into()])?.first::<Value>(None).await? {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        self.authorizer.authorize(&Self::parse_uuid(&row["group_id"])?, user_id, GroupAction::ViewGroup, None).await?;
+
+        let mut event_info = match self.build_event_infos(vec![row], user_id).await?.pop() {
+            Some(event_info) => event_info,
+            None => return Ok(None),
+        };
+        event_info.attendees = self.get_event_attendee_info(event_id).await?;
+        event_info.user_status = self.get_user_event_status(event_id, user_id).await?;
+
+        Ok(Some(event_info))
     }
 
-    pub async fn get_event_attendees(&self, event_id: &Uuid) -> Result<Vec<Uuid>, WorkerError> {
+    pub async fn get_event_attendees(&self, event_id: &Uuid) -> Result<Vec<Uuid>, ServiceError> {
         let stmt = self.db.prepare("SELECT user_id FROM event_attendees WHERE event_id = ?1");
         let results = stmt.bind(&[event_id.to_string().into()])?.all().await?;
 
@@ -209,46 +257,43 @@ impl DirectD1CalendarService {
         Ok(attendees)
     }
 
-    pub async fn get_event_attendee_info(&self, event_id: &Uuid) -> Result<Vec<EventAttendeeInfo>, WorkerError> {
+    pub async fn get_event_attendee_info(&self, event_id: &Uuid) -> Result<Vec<EventAttendeeInfo>, ServiceError> {
         let stmt = self.db.prepare("SELECT ea.user_id, ea.status, ea.responded_at FROM event_attendees ea WHERE ea.event_id = ?1");
-        let results = stmt.bind(&[event_id.to_string().into()])?.all().await?;
+        let rows = stmt.bind(&[event_id.to_string().into()])?.all().await?.results::<Value>()?;
 
-        let mut attendees = Vec::new();
-        for row in results.results::<Value>()? {
-            if let Ok(user_id) = Uuid::parse_str(row["user_id"].as_str().unwrap_or("")) {
-                let username = self.get_username(&user_id).await.unwrap_or_else(|_| "Unknown User".to_string());
-                let status = match row["status"].as_str().unwrap_or("pending") {
-                    "accepted" => AttendeeStatus::Accepted,
-                    "declined" => AttendeeStatus::Declined,
-                    "tentative" => AttendeeStatus::Tentative,
-                    _ => AttendeeStatus::Pending,
-                };
-                let responded_at = if let Some(time_str) = row["responded_at"].as_str() {
-                    if !time_str.is_empty() {
-                        Some(DateTime::parse_from_rfc3339(time_str)
-                            .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                            .with_timezone(&Utc))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                attendees.push(EventAttendeeInfo {
-                    user_id,
-                    username,
-                    status,
-                    is_organizer: false, // We'll determine this separately
-                    responded_at,
-                });
-            }
+        let rows: Vec<(Uuid, Value)> = rows
+            .into_iter()
+            .filter_map(|row| Uuid::parse_str(row["user_id"].as_str().unwrap_or("")).ok().map(|user_id| (user_id, row)))
+            .collect();
+        let user_ids: Vec<Uuid> = rows.iter().map(|(user_id, _)| *user_id).collect();
+        let names = load_display_names(&self.db, &user_ids).await?;
+
+        let mut attendees = Vec::with_capacity(rows.len());
+        for (user_id, row) in rows {
+            let status = match row["status"].as_str().unwrap_or("pending") {
+                "accepted" => AttendeeStatus::Accepted,
+                "declined" => AttendeeStatus::Declined,
+                "tentative" => AttendeeStatus::Tentative,
+                _ => AttendeeStatus::Pending,
+            };
+            let responded_at = match row["responded_at"].as_str() {
+                Some(time_str) if !time_str.is_empty() => Some(Self::parse_datetime(&row["responded_at"])?),
+                _ => None,
+            };
+
+            attendees.push(EventAttendeeInfo {
+                user_id,
+                username: display_name(&names, &user_id),
+                status,
+                is_organizer: false, // We'll determine this separately
+                responded_at,
+            });
         }
 
         Ok(attendees)
     }
 
-    pub async fn get_user_event_status(&self, event_id: &Uuid, user_id: &Uuid) -> Result<Option<AttendeeStatus>, WorkerError> {
+    pub async fn get_user_event_status(&self, event_id: &Uuid, user_id: &Uuid) -> Result<Option<AttendeeStatus>, ServiceError> {
         let stmt = self.db.prepare("SELECT status FROM event_attendees WHERE event_id = ?1 AND user_id = ?2");
         let results = stmt.bind(&[event_id.to_string().into(), user_id.to_string().into()])?.all().await?;
 
@@ -265,62 +310,24 @@ impl DirectD1CalendarService {
         }
     }
 
-    pub async fn get_group_events(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Vec<EventInfo>, WorkerError> {
-        let stmt = self.db.prepare("SELECT * FROM events WHERE group_id = ?1 ORDER BY start_time ASC");
-        let results = stmt.bind(&[group_id.to_string().into()])?.all().await?;
-
-        let mut events = Vec::new();
-        for row in results.results::<Value>()? {
-            let event_id = Uuid::parse_str(row["id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let created_by = Uuid::parse_str(row["created_by"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-
-            let created_by_name = self.get_username(&created_by).await.unwrap_or_else(|_| "Unknown User".to_string());
-            let group_name = self.get_group_name(group_id).await.unwrap_or_else(|_| "Unknown Group".to_string());
-            let attendees = self.get_event_attendees(&event_id).await?;
+    pub async fn get_group_events(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Vec<EventInfo>, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
 
-            events.push(EventInfo {
-                id: event_id,
-                group_id: *group_id,
-                group_name,
-                title: row["title"].as_str().unwrap_or("").to_string(),
-                description: Some(row["description"].as_str().unwrap_or("").to_string()),
-                location: Some(row["location"].as_str().unwrap_or("").to_string()),
-                start_time: DateTime::parse_from_rfc3339(row["start_time"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                end_time: DateTime::parse_from_rfc3339(row["end_time"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                is_all_day: false,
-                created_by,
-                created_by_name,
-                category: None,
-                color: None,
-                recurrence: None,
-                reminder_minutes: vec![],
-                visibility: EventVisibility::Public,
-                attendees: vec![], // Simplified for now
-                user_status: None,
-                can_edit: true,
-                linked_chore_id: None,
-                linked_expense_id: None,
-                created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(row["updated_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-            });
-        }
-
-        Ok(events)
+        let stmt = self.db.prepare("SELECT * FROM events WHERE group_id = ?1 ORDER BY start_time ASC");
+        let rows = stmt.bind(&[group_id.to_string().into()])?.all().await?.results::<Value>()?;
+        self.build_event_infos(rows, user_id).await
     }
 
-    pub async fn delete_event(&self, event_id: &Uuid, user_id: &Uuid) -> Result<(), WorkerError> {
-        // For now, allow anyone to delete (in production, should check permissions)
-        
+    pub async fn delete_event(&self, event_id: &Uuid, user_id: &Uuid) -> Result<(), ServiceError> {
+        let stmt = self.db.prepare("SELECT group_id, created_by FROM events WHERE id = ?1");
+        let row = stmt
+            .bind(&[event_id.to_string().into()])?
+            .first::<Value>(None)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Event not found".to_string()))?;
+        let created_by = Self::parse_uuid(&row["created_by"])?;
+        self.authorizer.authorize(&Self::parse_uuid(&row["group_id"])?, user_id, GroupAction::DeleteEvent, Some(&created_by)).await?;
+
         // Delete attendees first
         let delete_attendees_stmt = self.db.prepare("DELETE FROM event_attendees WHERE event_id = ?1");
         delete_attendees_stmt.bind(&[event_id.to_string().into()])?.run().await?;
@@ -332,60 +339,32 @@ impl DirectD1CalendarService {
         Ok(())
     }
 
-    pub async fn get_events_in_date_range(&self, group_id: &Uuid, start_date: &DateTime<Utc>, end_date: &DateTime<Utc>, _user_id: &Uuid) -> Result<Vec<EventInfo>, WorkerError> {
-        let stmt = self.db.prepare("SELECT * FROM events WHERE group_id = ?1 AND start_time >= ?2 AND start_time <= ?3 ORDER BY start_time ASC");
-        let results = stmt.bind(&[
+    pub async fn get_events_in_date_range(&self, group_id: &Uuid, start_date: &DateTime<Utc>, end_date: &DateTime<Utc>, user_id: &Uuid) -> Result<Vec<EventInfo>, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+
+        let stmt = self.db.prepare("SELECT * FROM events WHERE group_id = ?1 AND start_time >= ?2 AND start_time < ?3 ORDER BY start_time ASC");
+        let rows = stmt.bind(&[
             group_id.to_string().into(),
             start_date.to_rfc3339().into(),
             end_date.to_rfc3339().into(),
-        ])?.all().await?;
-
-        let mut events = Vec::new();
-        for row in results.results::<Value>()? {
-            let event_id = Uuid::parse_str(row["id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let created_by = Uuid::parse_str(row["created_by"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-
-            let created_by_name = self.get_username(&created_by).await.unwrap_or_else(|_| "Unknown User".to_string());
-            let group_name = self.get_group_name(group_id).await.unwrap_or_else(|_| "Unknown Group".to_string());
-            let attendees = self.get_event_attendees(&event_id).await?;
+        ])?.all().await?.results::<Value>()?;
+        self.build_event_infos(rows, user_id).await
+    }
 
-            events.push(EventInfo {
-                id: event_id,
-                group_id: *group_id,
-                group_name,
-                title: row["title"].as_str().unwrap_or("").to_string(),
-                description: Some(row["description"].as_str().unwrap_or("").to_string()),
-                location: Some(row["location"].as_str().unwrap_or("").to_string()),
-                start_time: DateTime::parse_from_rfc3339(row["start_time"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                end_time: DateTime::parse_from_rfc3339(row["end_time"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                is_all_day: false,
-                created_by,
-                created_by_name,
-                category: None,
-                color: None,
-                recurrence: None,
-                reminder_minutes: vec![],
-                visibility: EventVisibility::Public,
-                attendees: vec![], // Simplified for now
-                user_status: None,
-                can_edit: true,
-                linked_chore_id: None,
-                linked_expense_id: None,
-                created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(row["updated_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-            });
-        }
+    // Events in the day, week or month containing `date`, with the range taken in the group's
+    // timezone and week start. `date` defaults to today in the group.
+    pub async fn get_events_for_view(&self, group_id: &Uuid, view: &ViewType, date: Option<NaiveDate>, user_id: &Uuid) -> Result<(DateTime<Utc>, DateTime<Utc>, Vec<EventInfo>), ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        let settings = load_group_settings(&self.db, group_id).await?;
+
+        let date = date.unwrap_or_else(|| settings.local_date(Utc::now()));
+        let (start, end) = match view {
+            ViewType::Day => settings.day_range(date),
+            ViewType::Week => settings.week_range(date),
+            _ => settings.month_range(date),
+        };
 
-        Ok(events)
+        let events = self.get_events_in_date_range(group_id, &start, &end, user_id).await?;
+        Ok((start, end, events))
     }
 }
@@ -7,6 +7,15 @@ use crate::chores::domain::chore::{
     ChoreComment, ChoreCommentInfo, AddComment, ChoreStatus, Priority
 };
 use crate::chores::domain::ports::{ChoreRepository, ChoreStatsRepository, ChoreCommentRepository, RecurrenceService};
+use crate::activity::domain::activity::{ActivityKind, NewActivity};
+use crate::activity::domain::ports::ActivityRecorder;
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::groups::domain::permissions::GroupAction;
+use crate::groups::domain::ports::GroupSettingsRepository;
+use crate::groups::domain::settings::GroupModule;
+use crate::groups::domain::ports::GroupNameLookup;
+use crate::profiles::domain::ports::DisplayNameLookup;
+use crate::profiles::domain::profile::UNKNOWN_USER_NAME;
 use std::error::Error;
 
 pub struct ChoreService {
@@ -14,6 +23,11 @@ pub struct ChoreService {
     stats_repository: Arc<dyn ChoreStatsRepository>,
     comment_repository: Arc<dyn ChoreCommentRepository>,
     recurrence_service: Arc<dyn RecurrenceService>,
+    display_names: Arc<dyn DisplayNameLookup>,
+    group_names: Arc<dyn GroupNameLookup>,
+    settings_repository: Arc<dyn GroupSettingsRepository>,
+    activity: Arc<dyn ActivityRecorder>,
+    authorizer: Arc<GroupAuthorizer>,
 }
 
 impl ChoreService {
@@ -22,16 +36,30 @@ impl ChoreService {
         stats_repository: Arc<dyn ChoreStatsRepository>,
         comment_repository: Arc<dyn ChoreCommentRepository>,
         recurrence_service: Arc<dyn RecurrenceService>,
+        display_names: Arc<dyn DisplayNameLookup>,
+        group_names: Arc<dyn GroupNameLookup>,
+        settings_repository: Arc<dyn GroupSettingsRepository>,
+        activity: Arc<dyn ActivityRecorder>,
+        authorizer: Arc<GroupAuthorizer>,
     ) -> Self {
         Self {
             chore_repository,
             stats_repository,
             comment_repository,
             recurrence_service,
+            display_names,
+            group_names,
+            settings_repository,
+            activity,
+            authorizer,
         }
     }
 
     pub async fn create_chore(&self, creation: ChoreCreation, created_by: Uuid) -> Result<ChoreInfo, Box<dyn Error>> {
+        self.authorizer.authorize(&creation.group_id, &created_by, GroupAction::CreateChore, None).await?;
+        let settings = self.settings_repository.get_settings(&creation.group_id).await?;
+        settings.require_enabled(GroupModule::Chores)?;
+
         // Validate input
         if creation.title.trim().is_empty() {
             return Err("Chore title cannot be empty".into());
@@ -54,7 +82,7 @@ impl ChoreService {
             category: creation.category.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()),
             priority: creation.priority,
             status: ChoreStatus::Pending,
-            due_date: creation.due_date,
+            due_date: creation.due_date.or_else(|| creation.due_on.map(|date| settings.end_of_day(date))),
             estimated_duration: creation.estimated_duration,
             recurrence: creation.recurrence.clone(),
             created_at: now,
@@ -75,25 +103,35 @@ impl ChoreService {
     }
 
     pub async fn get_chore(&self, chore_id: &Uuid, user_id: &Uuid) -> Result<Option<ChoreInfo>, Box<dyn Error>> {
-        // TODO: Verify user has access to this chore through group membership
         let chore = match self.chore_repository.get_chore_by_id(chore_id).await? {
             Some(c) => c,
             None => return Ok(None),
         };
+        self.authorizer.authorize(&chore.group_id, user_id, GroupAction::ViewGroup, None).await?;
 
-        // Convert to ChoreInfo (would need user/group name lookups in real implementation)
         let is_overdue = chore.due_date.map_or(false, |due| due < Utc::now() && chore.status != ChoreStatus::Completed);
 
+        let mut user_ids = vec![chore.created_by];
+        user_ids.extend(chore.assigned_to);
+        let names = self.display_names.display_names(&user_ids).await?;
+        let group_name = self
+            .group_names
+            .group_names(&[chore.group_id])
+            .await?
+            .remove(&chore.group_id)
+            .unwrap_or_else(|| "Unknown Group".to_string());
+        let name_of = |id: &Uuid| names.get(id).cloned().unwrap_or_else(|| UNKNOWN_USER_NAME.to_string());
+
         Ok(Some(ChoreInfo {
             id: chore.id,
             group_id: chore.group_id,
-            group_name: "Group".to_string(), // TODO: Lookup group name
+            group_name,
             title: chore.title,
             description: chore.description,
             assigned_to: chore.assigned_to,
-            assigned_to_name: chore.assigned_to.map(|_| "User".to_string()), // TODO: Lookup username
+            assigned_to_name: chore.assigned_to.as_ref().map(name_of),
             created_by: chore.created_by,
-            created_by_name: "User".to_string(), // TODO: Lookup username
+            created_by_name: name_of(&chore.created_by),
             category: chore.category,
             priority: chore.priority,
             status: chore.status,
@@ -108,8 +146,8 @@ impl ChoreService {
     }
 
     pub async fn update_chore(&self, chore_id: &Uuid, user_id: &Uuid, update: ChoreUpdate) -> Result<(), Box<dyn Error>> {
-        // TODO: Verify user has permission to update this chore
-        
+        self.authorize_chore(chore_id, user_id, GroupAction::EditChore).await?;
+
         // Validate updates
         if let Some(ref title) = update.title {
             if title.trim().is_empty() {
@@ -124,7 +162,7 @@ impl ChoreService {
     }
 
     pub async fn complete_chore(&self, chore_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
-        // TODO: Verify user has permission
+        let chore = self.authorize_chore(chore_id, user_id, GroupAction::CompleteChore).await?;
         let update = ChoreUpdate {
             status: Some(ChoreStatus::Completed),
             title: None,
@@ -137,11 +175,19 @@ impl ChoreService {
             recurrence: None,
         };
 
-        self.chore_repository.update_chore(chore_id, &update).await
+        self.chore_repository.update_chore(chore_id, &update).await?;
+        self.activity
+            .record(&NewActivity::new(chore.group_id, *user_id, ActivityKind::ChoreCompleted, Some(chore.id), chore.title))
+            .await;
+        Ok(())
     }
 
     pub async fn assign_chore(&self, chore_id: &Uuid, assignee_id: &Uuid, assigner_id: &Uuid) -> Result<(), Box<dyn Error>> {
-        // TODO: Verify assigner has permission
+        let chore = self.authorize_chore(chore_id, assigner_id, GroupAction::AssignChore).await?;
+        self.authorizer
+            .authorize(&chore.group_id, assignee_id, GroupAction::ViewGroup, None)
+            .await
+            .map_err(|_| "Assignee is not a member of this group")?;
         let update = ChoreUpdate {
             assigned_to: Some(*assignee_id),
             status: Some(ChoreStatus::Pending),
@@ -162,18 +208,35 @@ impl ChoreService {
     }
 
     pub async fn get_group_chores(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Vec<ChoreInfo>, Box<dyn Error>> {
-        // TODO: Verify user is member of group
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
         self.chore_repository.get_group_chores(group_id).await
     }
 
-    pub async fn search_chores(&self, filter: ChoreFilter, user_id: &Uuid) -> Result<Vec<ChoreInfo>, Box<dyn Error>> {
-        // TODO: Verify user has access to requested groups
+    pub async fn search_chores(&self, mut filter: ChoreFilter, user_id: &Uuid) -> Result<Vec<ChoreInfo>, Box<dyn Error>> {
+        // Within one group the caller must be a member; across groups they only see their own
+        match filter.group_id {
+            Some(group_id) => {
+                self.authorizer.authorize(&group_id, user_id, GroupAction::ViewGroup, None).await?;
+            }
+            None => filter.assigned_to = Some(*user_id),
+        }
         self.chore_repository.get_chores(&filter).await
     }
 
     pub async fn get_overdue_chores(&self, group_id: Option<&Uuid>, user_id: &Uuid) -> Result<Vec<ChoreInfo>, Box<dyn Error>> {
-        // TODO: Verify user has access
-        self.chore_repository.get_overdue_chores(group_id).await
+        match group_id {
+            Some(group_id) => {
+                self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+                self.chore_repository.get_overdue_chores(Some(group_id)).await
+            }
+            None => Ok(self
+                .chore_repository
+                .get_overdue_chores(None)
+                .await?
+                .into_iter()
+                .filter(|chore| chore.assigned_to == Some(*user_id))
+                .collect()),
+        }
     }
 
     pub async fn get_user_stats(&self, user_id: &Uuid, group_id: Option<&Uuid>) -> Result<ChoreStats, Box<dyn Error>> {
@@ -181,12 +244,12 @@ impl ChoreService {
     }
 
     pub async fn get_group_stats(&self, group_id: &Uuid, user_id: &Uuid) -> Result<ChoreStats, Box<dyn Error>> {
-        // TODO: Verify user is member of group
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
         self.stats_repository.get_group_stats(group_id).await
     }
 
     pub async fn add_comment(&self, chore_id: &Uuid, user_id: &Uuid, add_comment: AddComment) -> Result<(), Box<dyn Error>> {
-        // TODO: Verify user has access to this chore
+        self.authorize_chore(chore_id, user_id, GroupAction::ViewGroup).await?;
         if add_comment.content.trim().is_empty() {
             return Err("Comment cannot be empty".into());
         }
@@ -203,15 +266,22 @@ impl ChoreService {
     }
 
     pub async fn get_chore_comments(&self, chore_id: &Uuid, user_id: &Uuid) -> Result<Vec<ChoreCommentInfo>, Box<dyn Error>> {
-        // TODO: Verify user has access to this chore
+        self.authorize_chore(chore_id, user_id, GroupAction::ViewGroup).await?;
         self.comment_repository.get_chore_comments(chore_id).await
     }
 
     pub async fn delete_chore(&self, chore_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
-        // TODO: Verify user has permission (creator, assignee, or group admin)
+        self.authorize_chore(chore_id, user_id, GroupAction::DeleteChore).await?;
         self.chore_repository.delete_chore(chore_id).await
     }
 
+    // Loads a chore and checks the action against the caller's role in its group
+    async fn authorize_chore(&self, chore_id: &Uuid, user_id: &Uuid, action: GroupAction) -> Result<Chore, Box<dyn Error>> {
+        let chore = self.chore_repository.get_chore_by_id(chore_id).await?.ok_or("Chore not found")?;
+        self.authorizer.authorize(&chore.group_id, user_id, action, Some(&chore.created_by)).await?;
+        Ok(chore)
+    }
+
     pub async fn process_recurring_chores(&self) -> Result<(), Box<dyn Error>> {
         // Background task to create next instances of recurring chores
         self.recurrence_service.check_and_create_next_instances().await
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Chore {
@@ -75,6 +75,9 @@ pub struct ChoreCreation {
     pub category: Option<String>,
     pub priority: Priority,
     pub due_date: Option<DateTime<Utc>>,
+    // A calendar day in the group's timezone, due by the end of it. Ignored when due_date is set.
+    #[serde(default)]
+    pub due_on: Option<NaiveDate>,
     pub estimated_duration: Option<u32>,
     pub recurrence: Option<RecurrencePattern>,
 }
@@ -1,44 +1,131 @@
-use worker::{D1Database, Error as WorkerError};
+use worker::D1Database;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde_json::Value;
 
+use crate::activity::domain::activity::{ActivityKind, NewActivity};
+use crate::activity::infrastructure::record_activity;
+use crate::profiles::infrastructure::{display_name, load_display_names};
+use crate::error::ServiceError;
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::groups::infrastructure::{group_name, load_group_names, require_module};
+use crate::groups::domain::settings::GroupModule;
+use crate::groups::domain::permissions::GroupAction;
 use crate::chores::domain::chore::{
     Chore, ChoreInfo, ChoreCreation, ChoreStatus, Priority, ChoreAssignment,
 };
 
 pub struct DirectD1ChoreService {
     db: D1Database,
+    authorizer: GroupAuthorizer,
 }
 
 impl DirectD1ChoreService {
-    pub fn new(db: D1Database) -> Self {
-        Self { db }
+    pub fn new(db: D1Database, authorizer: GroupAuthorizer) -> Self {
+        Self { db, authorizer }
     }
 
-    async fn get_username(&self, user_id: &Uuid) -> Result<String, WorkerError> {
-        let stmt = self.db.prepare("SELECT username FROM users WHERE id = ?1");
-        let result = stmt.bind(&[user_id.to_string().into()])?.first::<Value>(None).await?;
-        
-        if let Some(row) = result {
-            Ok(row["username"].as_str().unwrap_or("Unknown User").to_string())
-        } else {
-            Ok("Unknown User".to_string())
+    fn parse_uuid(value: &Value) -> Result<Uuid, ServiceError> {
+        Uuid::parse_str(value.as_str().unwrap_or(""))
+            .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))
+    }
+
+    // Empty strings stand for "not set" in the TEXT columns
+    fn parse_optional_uuid(value: &Value) -> Result<Option<Uuid>, ServiceError> {
+        match value.as_str() {
+            Some(text) if !text.is_empty() => Ok(Some(Self::parse_uuid(value)?)),
+            _ => Ok(None),
         }
     }
 
-    async fn get_group_name(&self, group_id: &Uuid) -> Result<String, WorkerError> {
-        let stmt = self.db.prepare("SELECT name FROM groups WHERE id = ?1");
-        let result = stmt.bind(&[group_id.to_string().into()])?.first::<Value>(None).await?;
-        
-        if let Some(row) = result {
-            Ok(row["name"].as_str().unwrap_or("Unknown Group").to_string())
-        } else {
-            Ok("Unknown Group".to_string())
+    fn parse_datetime(value: &Value) -> Result<DateTime<Utc>, ServiceError> {
+        Ok(DateTime::parse_from_rfc3339(value.as_str().unwrap_or(""))
+            .map_err(|e| ServiceError::Internal(format!("Date parse error: {}", e)))?
+            .with_timezone(&Utc))
+    }
+
+    fn parse_optional_datetime(value: &Value) -> Result<Option<DateTime<Utc>>, ServiceError> {
+        match value.as_str() {
+            Some(text) if !text.is_empty() => Ok(Some(Self::parse_datetime(value)?)),
+            _ => Ok(None),
+        }
+    }
+
+    // Chore rows -> DTOs, resolving all user and group names with one lookup each
+    async fn build_chore_infos(&self, rows: Vec<Value>) -> Result<Vec<ChoreInfo>, ServiceError> {
+        let mut user_ids = Vec::new();
+        let mut group_ids = Vec::new();
+        for row in &rows {
+            user_ids.push(Self::parse_uuid(&row["created_by"])?);
+            user_ids.extend(Self::parse_optional_uuid(&row["assigned_to"])?);
+            group_ids.push(Self::parse_uuid(&row["group_id"])?);
+        }
+        let names = load_display_names(&self.db, &user_ids).await?;
+        let group_names = load_group_names(&self.db, &group_ids).await?;
+
+        let mut chores = Vec::with_capacity(rows.len());
+        for row in rows {
+            let created_by = Self::parse_uuid(&row["created_by"])?;
+            let group_id = Self::parse_uuid(&row["group_id"])?;
+            let assigned_to = Self::parse_optional_uuid(&row["assigned_to"])?;
+
+            let status = match row["status"].as_str().unwrap_or("pending") {
+                "in_progress" => ChoreStatus::InProgress,
+                "completed" => ChoreStatus::Completed,
+                "overdue" => ChoreStatus::Overdue,
+                "cancelled" => ChoreStatus::Cancelled,
+                _ => ChoreStatus::Pending,
+            };
+
+            let priority = match row["priority"].as_str().unwrap_or("medium") {
+                "low" => Priority::Low,
+                "high" => Priority::High,
+                "urgent" => Priority::Urgent,
+                _ => Priority::Medium,
+            };
+
+            chores.push(ChoreInfo {
+                id: Self::parse_uuid(&row["id"])?,
+                group_id,
+                group_name: group_name(&group_names, &group_id),
+                title: row["title"].as_str().unwrap_or("").to_string(),
+                description: Some(row["description"].as_str().unwrap_or("").to_string()),
+                assigned_to,
+                assigned_to_name: assigned_to.map(|user_id| display_name(&names, &user_id)),
+                created_by,
+                created_by_name: display_name(&names, &created_by),
+                status,
+                priority,
+                due_date: Self::parse_optional_datetime(&row["due_date"])?,
+                category: Some(row["category"].as_str().unwrap_or("").to_string()),
+                estimated_duration: Some(row["estimated_duration"].as_i64().unwrap_or(0) as u32),
+                recurrence: None, // Simplified for now
+                created_at: Self::parse_datetime(&row["created_at"])?,
+                updated_at: Self::parse_datetime(&row["updated_at"])?,
+                is_overdue: false, // Simplified for now
+                completed_at: Self::parse_optional_datetime(&row["completed_at"])?,
+            });
         }
+
+        Ok(chores)
+    }
+
+    // Group and creator of a chore, for permission checks
+    async fn chore_owner(&self, chore_id: &Uuid) -> Result<(Uuid, Uuid), ServiceError> {
+        let stmt = self.db.prepare("SELECT group_id, created_by FROM chores WHERE id = ?1");
+        let row = stmt
+            .bind(&[chore_id.to_string().into()])?
+            .first::<Value>(None)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Chore not found".to_string()))?;
+        Ok((Self::parse_uuid(&row["group_id"])?, Self::parse_uuid(&row["created_by"])?))
     }
 
-    pub async fn create_chore_from_creation(&self, creation: ChoreCreation, created_by: Uuid) -> Result<ChoreInfo, WorkerError> {
+    pub async fn create_chore_from_creation(&self, creation: ChoreCreation, created_by: Uuid) -> Result<ChoreInfo, ServiceError> {
+        self.authorizer.authorize(&creation.group_id, &created_by, GroupAction::CreateChore, None).await?;
+        let settings = require_module(&self.db, &creation.group_id, GroupModule::Chores).await?;
+        let due_date = creation.due_date.or_else(|| creation.due_on.map(|date| settings.end_of_day(date)));
+
         let chore = Chore {
             id: Uuid::new_v4(),
             group_id: creation.group_id,
@@ -48,7 +135,7 @@ impl DirectD1ChoreService {
             created_by,
             status: ChoreStatus::Pending,
             priority: creation.priority,
-            due_date: creation.due_date,
+            due_date,
             category: creation.category.clone(),
             estimated_duration: creation.estimated_duration,
             recurrence: creation.recurrence.clone(),
@@ -61,24 +148,21 @@ impl DirectD1ChoreService {
         self.create_chore(&chore).await?;
 
         // Return chore info
-        let created_by_name = self.get_username(&created_by).await.unwrap_or_else(|_| "Unknown User".to_string());
-        let group_name = self.get_group_name(&chore.group_id).await.unwrap_or_else(|_| "Unknown Group".to_string());
-        let assigned_to_name = if let Some(assigned_to) = &chore.assigned_to {
-            Some(self.get_username(assigned_to).await.unwrap_or_else(|_| "Unknown User".to_string()))
-        } else {
-            None
-        };
-        
+        let mut user_ids = vec![created_by];
+        user_ids.extend(chore.assigned_to);
+        let names = load_display_names(&self.db, &user_ids).await?;
+        let group_names = load_group_names(&self.db, &[chore.group_id]).await?;
+
         Ok(ChoreInfo {
             id: chore.id,
             group_id: chore.group_id,
-            group_name,
+            group_name: group_name(&group_names, &chore.group_id),
             title: chore.title,
             description: chore.description,
             assigned_to: chore.assigned_to,
-            assigned_to_name,
+            assigned_to_name: chore.assigned_to.map(|user_id| display_name(&names, &user_id)),
             created_by,
-            created_by_name,
+            created_by_name: display_name(&names, &created_by),
             status: chore.status,
             priority: chore.priority,
             due_date: chore.due_date,
@@ -92,7 +176,7 @@ impl DirectD1ChoreService {
         })
     }
 
-    pub async fn create_chore(&self, chore: &Chore) -> Result<(), WorkerError> {
+    pub async fn create_chore(&self, chore: &Chore) -> Result<(), ServiceError> {
         let status_str = match chore.status {
             ChoreStatus::Pending => "pending",
             ChoreStatus::InProgress => "in_progress",
@@ -132,196 +216,29 @@ impl DirectD1ChoreService {
         Ok(())
     }
 
-    pub async fn get_chore_by_id(&self, chore_id: &Uuid, _user_id: &Uuid) -> Result<Option<ChoreInfo>, WorkerError> {
+    pub async fn get_chore_by_id(&self, chore_id: &Uuid, user_id: &Uuid) -> Result<Option<ChoreInfo>, ServiceError> {
         let stmt = self.db.prepare("SELECT * FROM chores WHERE id = ?1");
-        let result = stmt.bind(&[chore_id.to_string().into()])?.first::<Value>(None).await?;
-
-        if let Some(row) = result {
-            let created_by = Uuid::parse_str(row["created_by"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let group_id = Uuid::parse_str(row["group_id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let assigned_to = if let Some(assigned_str) = row["assigned_to"].as_str() {
-                if !assigned_str.is_empty() {
-                    Some(Uuid::parse_str(assigned_str)
-                        .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            let status = match row["status"].as_str().unwrap_or("pending") {
-                "in_progress" => ChoreStatus::InProgress,
-                "completed" => ChoreStatus::Completed,
-                "overdue" => ChoreStatus::Overdue,
-                "cancelled" => ChoreStatus::Cancelled,
-                _ => ChoreStatus::Pending,
-            };
-
-            let priority = match row["priority"].as_str().unwrap_or("medium") {
-                "low" => Priority::Low,
-                "high" => Priority::High,
-                "urgent" => Priority::Urgent,
-                _ => Priority::Medium,
-            };
-
-            let created_by_name = self.get_username(&created_by).await.unwrap_or_else(|_| "Unknown User".to_string());
-            let group_name = self.get_group_name(&group_id).await.unwrap_or_else(|_| "Unknown Group".to_string());
-            let assigned_to_name = if let Some(assigned_to) = &assigned_to {
-                Some(self.get_username(assigned_to).await.unwrap_or_else(|_| "Unknown User".to_string()))
-            } else {
-                None
-            };
-
-            let chore_info = ChoreInfo {
-                id: *chore_id,
-                group_id,
-                group_name,
-                title: row["title"].as_str().unwrap_or("").to_string(),
-                description: Some(row["description"].as_str().unwrap_or("").to_string()),
-                assigned_to,
-                assigned_to_name,
-                created_by,
-                created_by_name,
-                status,
-                priority,
-                due_date: if let Some(due_str) = row["due_date"].as_str() {
-                    if !due_str.is_empty() {
-                        Some(DateTime::parse_from_rfc3339(due_str)
-                            .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                            .with_timezone(&Utc))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                },
-                category: Some(row["category"].as_str().unwrap_or("").to_string()),
-                estimated_duration: Some(row["estimated_duration"].as_i64().unwrap_or(0) as u32),
-                recurrence: None, // Simplified for now
-                created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(row["updated_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                is_overdue: false, // Simplified for now
-                completed_at: if let Some(completed_str) = row["completed_at"].as_str() {
-                    if !completed_str.is_empty() {
-                        Some(DateTime::parse_from_rfc3339(completed_str)
-                            .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                            .with_timezone(&Utc))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            };
-
-            Ok(Some(chore_info))
-        } else {
-            Ok(None)
+        match stmt.bind(&[chore_id.to_string().into()])?.first::<Value>(None).await? {
+            Some(row) => {
+                self.authorizer.authorize(&Self::parse_uuid(&row["group_id"])?, user_id, GroupAction::ViewGroup, None).await?;
+                Ok(self.build_chore_infos(vec![row]).await?.pop())
+            }
+            None => Ok(None),
         }
     }
 
-    pub async fn get_group_chores(&self, group_id: &Uuid, _user_id: &Uuid) -> Result<Vec<ChoreInfo>, WorkerError> {
-        let stmt = self.db.prepare("SELECT * FROM chores WHERE group_id = ?1 ORDER BY created_at DESC");
-        let results = stmt.bind(&[group_id.to_string().into()])?.all().await?;
-
-        let mut chores = Vec::new();
-        for row in results.results::<Value>()? {
-            let chore_id = Uuid::parse_str(row["id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let created_by = Uuid::parse_str(row["created_by"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let assigned_to = if let Some(assigned_str) = row["assigned_to"].as_str() {
-                if !assigned_str.is_empty() {
-                    Some(Uuid::parse_str(assigned_str)
-                        .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            let status = match row["status"].as_str().unwrap_or("pending") {
-                "in_progress" => ChoreStatus::InProgress,
-                "completed" => ChoreStatus::Completed,
-                "overdue" => ChoreStatus::Overdue,
-                "cancelled" => ChoreStatus::Cancelled,
-                _ => ChoreStatus::Pending,
-            };
-
-            let priority = match row["priority"].as_str().unwrap_or("medium") {
-                "low" => Priority::Low,
-                "high" => Priority::High,
-                "urgent" => Priority::Urgent,
-                _ => Priority::Medium,
-            };
-
-            let created_by_name = self.get_username(&created_by).await.unwrap_or_else(|_| "Unknown User".to_string());
-            let group_name = self.get_group_name(group_id).await.unwrap_or_else(|_| "Unknown Group".to_string());
-            let assigned_to_name = if let Some(assigned_to) = &assigned_to {
-                Some(self.get_username(assigned_to).await.unwrap_or_else(|_| "Unknown User".to_string()))
-            } else {
-                None
-            };
-
-            chores.push(ChoreInfo {
-                id: chore_id,
-                group_id: *group_id,
-                group_name,
-                title: row["title"].as_str().unwrap_or("").to_string(),
-                description: Some(row["description"].as_str().unwrap_or("").to_string()),
-                assigned_to,
-                assigned_to_name,
-                created_by,
-                created_by_name,
-                status,
-                priority,
-                due_date: if let Some(due_str) = row["due_date"].as_str() {
-                    if !due_str.is_empty() {
-                        Some(DateTime::parse_from_rfc3339(due_str)
-                            .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                            .with_timezone(&Utc))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                },
-                category: Some(row["category"].as_str().unwrap_or("").to_string()),
-                estimated_duration: Some(row["estimated_duration"].as_i64().unwrap_or(0) as u32),
-                recurrence: None, // Simplified for now
-                created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(row["updated_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                is_overdue: false, // Simplified for now
-                completed_at: if let Some(completed_str) = row["completed_at"].as_str() {
-                    if !completed_str.is_empty() {
-                        Some(DateTime::parse_from_rfc3339(completed_str)
-                            .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                            .with_timezone(&Utc))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            });
-        }
+    pub async fn get_group_chores(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Vec<ChoreInfo>, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
 
-        Ok(chores)
+        let stmt = self.db.prepare("SELECT * FROM chores WHERE group_id = ?1 ORDER BY created_at DESC");
+        let rows = stmt.bind(&[group_id.to_string().into()])?.all().await?.results::<Value>()?;
+        self.build_chore_infos(rows).await
     }
 
-    pub async fn update_chore_status(&self, chore_id: &Uuid, status: ChoreStatus, _user_id: &Uuid) -> Result<(), WorkerError> {
+    pub async fn update_chore_status(&self, chore_id: &Uuid, status: ChoreStatus, user_id: &Uuid) -> Result<(), ServiceError> {
+        let (group_id, created_by) = self.chore_owner(chore_id).await?;
+        self.authorizer.authorize(&group_id, user_id, GroupAction::CompleteChore, Some(&created_by)).await?;
+
         let status_str = match status {
             ChoreStatus::Pending => "pending",
             ChoreStatus::InProgress => "in_progress",
@@ -354,16 +271,37 @@ impl DirectD1ChoreService {
         };
 
         stmt.run().await?;
+
+        if status == ChoreStatus::Completed {
+            let title = self
+                .db
+                .prepare("SELECT title FROM chores WHERE id = ?1")
+                .bind(&[chore_id.to_string().into()])?
+                .first::<Value>(None)
+                .await?
+                .and_then(|row| row["title"].as_str().map(|title| title.to_string()))
+                .unwrap_or_default();
+            record_activity(&self.db, &NewActivity::new(group_id, *user_id, ActivityKind::ChoreCompleted, Some(*chore_id), title)).await;
+        }
         Ok(())
     }
 
-    pub async fn delete_chore(&self, chore_id: &Uuid, _user_id: &Uuid) -> Result<(), WorkerError> {
+    pub async fn delete_chore(&self, chore_id: &Uuid, user_id: &Uuid) -> Result<(), ServiceError> {
+        let (group_id, created_by) = self.chore_owner(chore_id).await?;
+        self.authorizer.authorize(&group_id, user_id, GroupAction::DeleteChore, Some(&created_by)).await?;
+
         let stmt = self.db.prepare("DELETE FROM chores WHERE id = ?1");
         stmt.bind(&[chore_id.to_string().into()])?.run().await?;
         Ok(())
     }
 
-    pub async fn assign_chore(&self, assignment: ChoreAssignment, _user_id: &Uuid) -> Result<(), WorkerError> {
+    pub async fn assign_chore(&self, assignment: ChoreAssignment, user_id: &Uuid) -> Result<(), ServiceError> {
+        let (group_id, created_by) = self.chore_owner(&assignment.chore_id).await?;
+        self.authorizer.authorize(&group_id, user_id, GroupAction::AssignChore, Some(&created_by)).await?;
+        if self.authorizer.member_access(&group_id, &assignment.assigned_to).await?.is_none() {
+            return Err(ServiceError::Invalid("Assignee is not a member of this group".to_string()));
+        }
+
         let stmt = self.db.prepare("UPDATE chores SET assigned_to = ?1, updated_at = ?2 WHERE id = ?3");
         stmt.bind(&[
             assignment.assigned_to.to_string().into(),
@@ -376,93 +314,18 @@ impl DirectD1ChoreService {
         Ok(())
     }
 
-    pub async fn get_user_chores(&self, user_id: &Uuid, group_id: Option<&Uuid>) -> Result<Vec<ChoreInfo>, WorkerError> {
+    pub async fn get_user_chores(&self, user_id: &Uuid, group_id: Option<&Uuid>) -> Result<Vec<ChoreInfo>, ServiceError> {
         let (query, bind_params) = if let Some(group_id) = group_id {
             ("SELECT * FROM chores WHERE assigned_to = ?1 AND group_id = ?2 ORDER BY created_at DESC", 
              vec![user_id.to_string(), group_id.to_string()])
         } else {
-            ("SELECT * FROM chores WHERE assigned_to = ?1 ORDER BY created_at DESC",
+            ("SELECT * FROM chores WHERE assigned_to = ?1 AND group_id IN (SELECT id FROM groups WHERE deleted_at IS NULL) ORDER BY created_at DESC",
              vec![user_id.to_string()])
         };
 
         let stmt = self.db.prepare(query);
         let bind_values: Vec<_> = bind_params.into_iter().map(|s| s.into()).collect();
-        let results = stmt.bind(&bind_values)?.all().await?;
-
-        let mut chores = Vec::new();
-        for row in results.results::<Value>()? {
-            let chore_id = Uuid::parse_str(row["id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let created_by = Uuid::parse_str(row["created_by"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let group_id = Uuid::parse_str(row["group_id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-
-            let status = match row["status"].as_str().unwrap_or("pending") {
-                "in_progress" => ChoreStatus::InProgress,
-                "completed" => ChoreStatus::Completed,
-                "overdue" => ChoreStatus::Overdue,
-                "cancelled" => ChoreStatus::Cancelled,
-                _ => ChoreStatus::Pending,
-            };
-
-            let priority = match row["priority"].as_str().unwrap_or("medium") {
-                "low" => Priority::Low,
-                "high" => Priority::High,
-                "urgent" => Priority::Urgent,
-                _ => Priority::Medium,
-            };
-
-            let created_by_name = self.get_username(&created_by).await.unwrap_or_else(|_| "Unknown User".to_string());
-            let group_name = self.get_group_name(&group_id).await.unwrap_or_else(|_| "Unknown Group".to_string());
-
-            chores.push(ChoreInfo {
-                id: chore_id,
-                group_id,
-                group_name,
-                title: row["title"].as_str().unwrap_or("").to_string(),
-                description: Some(row["description"].as_str().unwrap_or("").to_string()),
-                assigned_to: Some(*user_id),
-                assigned_to_name: Some(self.get_username(user_id).await.unwrap_or_else(|_| "Unknown User".to_string())),
-                created_by,
-                created_by_name,
-                status,
-                priority,
-                due_date: if let Some(due_str) = row["due_date"].as_str() {
-                    if !due_str.is_empty() {
-                        Some(DateTime::parse_from_rfc3339(due_str)
-                            .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                            .with_timezone(&Utc))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                },
-                category: Some(row["category"].as_str().unwrap_or("").to_string()),
-                estimated_duration: Some(row["estimated_duration"].as_i64().unwrap_or(0) as u32),
-                recurrence: None, // Simplified for now
-                created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(row["updated_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                is_overdue: false, // Simplified for now
-                completed_at: if let Some(completed_str) = row["completed_at"].as_str() {
-                    if !completed_str.is_empty() {
-                        Some(DateTime::parse_from_rfc3339(completed_str)
-                            .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                            .with_timezone(&Utc))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            });
-        }
-
-        Ok(chores)
+        let rows = stmt.bind(&bind_values)?.all().await?.results::<Value>()?;
+        self.build_chore_infos(rows).await
     }
 }
@@ -5,7 +5,15 @@ pub struct Config {
     pub host: String,
     pub port: u16,
     pub jwt_secret: String,
-    pub jwt_expiration_hours: u64,
+    pub access_token_ttl_minutes: u64,
+    pub refresh_token_ttl_days: u64,
+    pub app_base_url: String,
+    pub mail_api_url: Option<String>,
+    pub mail_api_key: Option<String>,
+    pub mail_from: String,
+    pub mail_console: bool, // dev only: stand in for a missing mail API by logging to the console
+    pub exchange_rate_api_url: Option<String>,
+    pub exchange_rates: Option<String>, // JSON rate table used when no rate API is set
 }
 
 impl Config {
@@ -20,10 +28,74 @@ impl Config {
                 eprintln!("WARNING: Using default JWT secret. Set JWT_SECRET environment variable in production!");
                 "your-super-secure-jwt-secret-key-here-min-256-bits-change-in-production".to_string()
             }),
-            jwt_expiration_hours: env::var("JWT_EXPIRATION_HOURS")
-                .unwrap_or_else(|_| "24".to_string())
+            access_token_ttl_minutes: env::var("ACCESS_TOKEN_TTL_MINUTES")
+                .unwrap_or_else(|_| "15".to_string())
                 .parse()
-                .expect("JWT_EXPIRATION_HOURS must be a valid number"),
+                .expect("ACCESS_TOKEN_TTL_MINUTES must be a valid number"),
+            refresh_token_ttl_days: env::var("REFRESH_TOKEN_TTL_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .expect("REFRESH_TOKEN_TTL_DAYS must be a valid number"),
+            app_base_url: env::var("APP_BASE_URL").unwrap_or_else(|_| "http://localhost:3000".to_string()),
+            mail_api_url: env::var("MAIL_API_URL").ok(),
+            mail_api_key: env::var("MAIL_API_KEY").ok(),
+            mail_from: env::var("MAIL_FROM").unwrap_or_else(|_| "TwoDo <no-reply@localhost>".to_string()),
+            mail_console: env::var("MAIL_CONSOLE").map(|v| v == "true").unwrap_or(false),
+            exchange_rate_api_url: env::var("EXCHANGE_RATE_API_URL").ok(),
+            exchange_rates: env::var("EXCHANGE_RATES").ok(),
+        }
+    }
+
+    // Build config from Worker bindings. JWT_SECRET is required: without it we refuse to
+    // issue or accept tokens instead of falling back to a well-known default.
+    pub fn from_worker_env(env: &worker::Env) -> worker::Result<Self> {
+        let jwt_secret = env
+            .secret("JWT_SECRET")
+            .map(|secret| secret.to_string())
+            .map_err(|_| worker::Error::RustError("JWT_SECRET secret is not configured".to_string()))?;
+        if jwt_secret.trim().is_empty() {
+            return Err(worker::Error::RustError("JWT_SECRET secret is empty".to_string()));
         }
+
+        Ok(Self {
+            host: "127.0.0.1".to_string(),
+            port: 3000,
+            jwt_secret,
+            access_token_ttl_minutes: Self::worker_var_u64(env, "ACCESS_TOKEN_TTL_MINUTES", 15)?,
+            refresh_token_ttl_days: Self::worker_var_u64(env, "REFRESH_TOKEN_TTL_DAYS", 30)?,
+            app_base_url: env
+                .var("APP_BASE_URL")
+                .map(|v| v.to_string())
+                .unwrap_or_else(|_| "http://localhost:3000".to_string()),
+            // Without a mail API, flows that send mail are unavailable unless MAIL_CONSOLE is set
+            mail_api_url: env.var("MAIL_API_URL").map(|v| v.to_string()).ok(),
+            mail_api_key: env.secret("MAIL_API_KEY").map(|v| v.to_string()).ok(),
+            mail_from: env
+                .var("MAIL_FROM")
+                .map(|v| v.to_string())
+                .unwrap_or_else(|_| "TwoDo <no-reply@localhost>".to_string()),
+            mail_console: env.var("MAIL_CONSOLE").map(|v| v.to_string() == "true").unwrap_or(false),
+            // Without a rate API, only the EXCHANGE_RATES table (if any) can convert currencies
+            exchange_rate_api_url: env.var("EXCHANGE_RATE_API_URL").map(|v| v.to_string()).ok(),
+            exchange_rates: env.var("EXCHANGE_RATES").map(|v| v.to_string()).ok(),
+        })
+    }
+
+    fn worker_var_u64(env: &worker::Env, name: &str, default: u64) -> worker::Result<u64> {
+        match env.var(name) {
+            Ok(value) => value
+                .to_string()
+                .parse()
+                .map_err(|_| worker::Error::RustError(format!("{} must be a valid number", name))),
+            Err(_) => Ok(default),
+        }
+    }
+
+    pub fn access_token_ttl_secs(&self) -> u64 {
+        self.access_token_ttl_minutes * 60
+    }
+
+    pub fn refresh_token_ttl_secs(&self) -> u64 {
+        self.refresh_token_ttl_days * 24 * 60 * 60
     }
 }
@@ -0,0 +1,112 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::auth::domain::ports::UserRepositoryError;
+use crate::groups::domain::permissions::PermissionDenied;
+
+// What a service tells the HTTP layer when it refuses or fails a request. The variant picks
+// the status code, so handlers never have to read the message to decide.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceError {
+    // The request breaks a rule: bad input, or a change the current data does not allow
+    Invalid(String),
+    Forbidden(String),
+    NotFound(String),
+    // The request clashes with what is already there, e.g. something that already exists
+    Conflict(String),
+    // Storage and other failures the caller can do nothing about
+    Internal(String),
+}
+
+impl ServiceError {
+    pub fn status(&self) -> u16 {
+        match self {
+            ServiceError::Invalid(_) => 400,
+            ServiceError::Forbidden(_) => 403,
+            ServiceError::NotFound(_) => 404,
+            ServiceError::Conflict(_) => 409,
+            ServiceError::Internal(_) => 500,
+        }
+    }
+
+    // Status for an error from one of the `Box<dyn Error>` application services. Typed errors
+    // keep their own status; anything else gets the handler's `fallback`.
+    pub fn status_of(error: &(dyn Error + 'static), fallback: u16) -> u16 {
+        if let Some(error) = error.downcast_ref::<ServiceError>() {
+            error.status()
+        } else if error.is::<PermissionDenied>() {
+            403
+        } else if error.is::<UserRepositoryError>() {
+            409
+        } else {
+            fallback
+        }
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::Invalid(message)
+            | ServiceError::Forbidden(message)
+            | ServiceError::NotFound(message)
+            | ServiceError::Conflict(message)
+            | ServiceError::Internal(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for ServiceError {}
+
+impl From<PermissionDenied> for ServiceError {
+    fn from(denied: PermissionDenied) -> Self {
+        ServiceError::Forbidden(denied.to_string())
+    }
+}
+
+impl From<worker::Error> for ServiceError {
+    fn from(error: worker::Error) -> Self {
+        ServiceError::Internal(error.to_string())
+    }
+}
+
+// Repository ports report failures as `Box<dyn Error>`; typed errors survive the trip
+impl From<Box<dyn Error>> for ServiceError {
+    fn from(error: Box<dyn Error>) -> Self {
+        match error.downcast::<ServiceError>() {
+            Ok(error) => *error,
+            Err(error) => match error.downcast::<PermissionDenied>() {
+                Ok(denied) => (*denied).into(),
+                Err(error) => ServiceError::Internal(error.to_string()),
+            },
+        }
+    }
+}
+
+impl From<ServiceError> for worker::Error {
+    fn from(error: ServiceError) -> Self {
+        worker::Error::RustError(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::groups::domain::permissions::{DenialReason, GroupAction};
+
+    #[test]
+    fn boxed_errors_keep_their_status() {
+        let not_found: Box<dyn Error> = Box::new(ServiceError::NotFound("Expense not found".to_string()));
+        let denied: Box<dyn Error> = Box::new(PermissionDenied { action: GroupAction::EditExpense, reason: DenialReason::Role });
+        let taken: Box<dyn Error> = Box::new(UserRepositoryError::EmailTaken);
+        let untyped: Box<dyn Error> = "Run error: disk full".into();
+
+        assert_eq!(ServiceError::status_of(not_found.as_ref(), 500), 404);
+        assert_eq!(ServiceError::status_of(denied.as_ref(), 500), 403);
+        assert_eq!(ServiceError::status_of(taken.as_ref(), 400), 409);
+        assert_eq!(ServiceError::status_of(untyped.as_ref(), 400), 400);
+
+        assert_eq!(ServiceError::from(denied).status(), 403);
+        assert_eq!(ServiceError::from(untyped), ServiceError::Internal("Run error: disk full".to_string()));
+    }
+}
@@ -3,13 +3,27 @@ use std::collections::HashMap;
 use uuid::Uuid;
 use chrono::Utc;
 
+use crate::expenses::domain::exchange::snapshot_rate;
 use crate::expenses::domain::expense::{
-    Expense, ExpenseShare, ExpenseCreation, ExpenseInfo, SplitType, 
-    UserBalance, GroupBalance, DebtSummary, SettleDebt, Payment, ExpenseFilter
+    split_shares, validate_expense, ConvertedAmount, Expense, ExpenseShare, ExpenseCreation, ExpenseInfo, ExpenseUpdate, SplitType,
+    UserBalance, GroupBalance, DebtSummary, SettleDebt, Payment, ExpenseFilter, UnsettledShare
 };
+use crate::expenses::domain::money::{minor_to_major, Money};
+use crate::expenses::domain::revision::{ExpenseRevision, ExpenseState, SETTLED_EXPENSE_EDIT};
+use crate::expenses::domain::settle_up::allocate_payment;
 use crate::expenses::domain::ports::{
-    ExpenseRepository, ExpenseShareRepository, BalanceRepository, PaymentRepository
+    ExpenseRepository, ExpenseShareRepository, BalanceRepository, PaymentRepository, ExchangeRateProvider
 };
+use crate::activity::domain::activity::{ActivityKind, NewActivity};
+use crate::error::ServiceError;
+use crate::activity::domain::ports::ActivityRecorder;
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::groups::domain::permissions::GroupAction;
+use crate::groups::domain::ledger::{ledger_transfers, merge_balances, ClosedLedger};
+use crate::groups::domain::ports::{GroupLedgerRepository, GroupSettingsRepository};
+use crate::groups::domain::settings::GroupModule;
+use crate::profiles::domain::ports::DisplayNameLookup;
+use crate::profiles::domain::profile::{normalize_currency, UNKNOWN_USER_NAME};
 use std::error::Error;
 
 pub struct ExpenseService {
@@ -17,6 +31,12 @@ pub struct ExpenseService {
     share_repository: Arc<dyn ExpenseShareRepository>,
     balance_repository: Arc<dyn BalanceRepository>,
     payment_repository: Arc<dyn PaymentRepository>,
+    exchange_rates: Arc<dyn ExchangeRateProvider>,
+    display_names: Arc<dyn DisplayNameLookup>,
+    settings_repository: Arc<dyn GroupSettingsRepository>,
+    ledger_repository: Arc<dyn GroupLedgerRepository>,
+    activity: Arc<dyn ActivityRecorder>,
+    authorizer: Arc<GroupAuthorizer>,
 }
 
 impl ExpenseService {
@@ -25,198 +45,384 @@ impl ExpenseService {
         share_repository: Arc<dyn ExpenseShareRepository>,
         balance_repository: Arc<dyn BalanceRepository>,
         payment_repository: Arc<dyn PaymentRepository>,
+        exchange_rates: Arc<dyn ExchangeRateProvider>,
+        display_names: Arc<dyn DisplayNameLookup>,
+        settings_repository: Arc<dyn GroupSettingsRepository>,
+        ledger_repository: Arc<dyn GroupLedgerRepository>,
+        activity: Arc<dyn ActivityRecorder>,
+        authorizer: Arc<GroupAuthorizer>,
     ) -> Self {
         Self {
             expense_repository,
             share_repository,
             balance_repository,
             payment_repository,
+            exchange_rates,
+            display_names,
+            settings_repository,
+            ledger_repository,
+            activity,
+            authorizer,
         }
     }
 
     pub async fn create_expense(&self, creation: ExpenseCreation, created_by: Uuid) -> Result<ExpenseInfo, Box<dyn Error>> {
-        // Validate input
-        if creation.description.trim().is_empty() {
-            return Err("Expense description cannot be empty".into());
-        }
-        if creation.amount <= 0.0 {
+        self.authorizer.authorize(&creation.group_id, &created_by, GroupAction::CreateExpense, None).await?;
+        let settings = self.settings_repository.get_settings(&creation.group_id).await?;
+        settings.require_enabled(GroupModule::Expenses)?;
+
+        let members = self.authorizer.members_among(&creation.group_id, &creation.participants).await?;
+        validate_expense(&creation.description, &creation.paid_by, &creation.participants, &members).map_err(ServiceError::Invalid)?;
+        let currency = match creation.currency.trim() {
+            "" => settings.default_currency.clone(),
+            currency => normalize_currency(currency)?,
+        };
+        let amount = Money::from_major(creation.amount, currency)?;
+        if !amount.is_positive() {
             return Err("Expense amount must be positive".into());
         }
-        if creation.participants.is_empty() {
-            return Err("Expense must have at least one participant".into());
-        }
-        if !creation.participants.contains(&creation.paid_by) {
-            return Err("The person who paid must be included in participants".into());
-        }
 
         let now = Utc::now();
         let expense_id = Uuid::new_v4();
 
+        // Converted at the rate of the day the money was spent, not the day it was entered
+        let date = creation.date.unwrap_or(now);
+        let rate_snapshot = snapshot_rate(self.exchange_rates.as_ref(), &amount, &settings.default_currency, settings.local_date(date)).await?;
+
         // Create expense
         let expense = Expense {
             id: expense_id,
             group_id: creation.group_id,
             description: creation.description.trim().to_string(),
-            amount: creation.amount,
-            currency: creation.currency.clone(),
+            amount,
+            rate_snapshot,
             paid_by: creation.paid_by,
             created_by,
             category: creation.category.clone(),
-            date: creation.date.unwrap_or(now),
+            date,
             created_at: now,
             updated_at: now,
         };
 
-        self.expense_repository.create_expense(&expense).await?;
+        // Shares are worked out first so a bad split never leaves an expense without them
+        let shares = self.calculate_shares(&expense, &creation.split_type, &creation.participants)?;
 
-        // Calculate shares based on split type
-        let mut shares = self.calculate_shares(&creation)?;
-        
-        // Fix expense_id for all shares (critical bug fix)
-        for share in &mut shares {
-            share.expense_id = expense_id;
-        }
-        
+        self.expense_repository.create_expense(&expense).await?;
         self.share_repository.create_shares(&shares).await?;
+        // Credit left over from earlier payments goes onto the new shares
+        self.rebook_payments(&expense.group_id).await?;
+
+        let summary = format!("{} ({})", expense.description, expense.amount);
+        self.activity
+            .record(&NewActivity::new(expense.group_id, created_by, ActivityKind::ExpenseCreated, Some(expense_id), summary))
+            .await;
 
         // Return expense info
         self.get_expense(&expense_id, &created_by).await?.ok_or("Failed to retrieve created expense".into())
     }
 
-    fn calculate_shares(&self, creation: &ExpenseCreation) -> Result<Vec<ExpenseShare>, Box<dyn Error>> {
+    // Splits the expense's total exactly, to the minor unit, between the participants; the
+    // converted total is spread over the same shares
+    fn calculate_shares(&self, expense: &Expense, split_type: &SplitType, participants: &[Uuid]) -> Result<Vec<ExpenseShare>, Box<dyn Error>> {
+        let shares = split_shares(&expense.amount, split_type, participants)?;
+        let amounts: Vec<Money> = shares.iter().map(|(_, amount)| amount.clone()).collect();
+        let base_amounts = expense.rate_snapshot.share_amounts(&amounts)?;
+        Ok(shares
+            .into_iter()
+            .zip(base_amounts)
+            .map(|((user_id, amount), base_amount)| ExpenseShare {
+                expense_id: expense.id,
+                user_id,
+                amount,
+                settled_amount: Money::zero(base_amount.currency.clone()),
+                base_amount,
+                is_settled: false,
+                settled_at: None,
+            })
+            .collect())
+    }
+
+    // A full edit, with the shares split again. When payments already paid off part of the
+    // old shares the edit needs `confirm_settled`. The group's payments are then booked again
+    // against the new shares. Every edit that changes something is kept as a revision.
+    pub async fn update_expense(&self, expense_id: &Uuid, update: ExpenseUpdate, user_id: &Uuid) -> Result<ExpenseInfo, Box<dyn Error>> {
+        let mut expense = self.expense_repository.get_expense_by_id(expense_id).await?.ok_or("Expense not found")?;
+        self.authorizer
+            .authorize(&expense.group_id, user_id, GroupAction::EditExpense, Some(&expense.created_by))
+            .await?;
+        let settings = self.settings_repository.get_settings(&expense.group_id).await?;
+        settings.require_enabled(GroupModule::Expenses)?;
+
+        let members = self.authorizer.members_among(&expense.group_id, &update.participants).await?;
+        validate_expense(&update.description, &update.paid_by, &update.participants, &members).map_err(ServiceError::Invalid)?;
+        let old_shares = self.share_repository.get_expense_shares(expense_id).await?;
+        let settled = old_shares.iter().any(|share| share.settled_amount.is_positive());
+        if settled && !update.confirm_settled {
+            return Err(SETTLED_EXPENSE_EDIT.into());
+        }
+        let currency = match update.currency.trim() {
+            "" => settings.default_currency.clone(),
+            currency => normalize_currency(currency)?,
+        };
+        let amount = Money::from_major(update.amount, currency)?;
+        if !amount.is_positive() {
+            return Err("Expense amount must be positive".into());
+        }
+
+        let now = Utc::now();
+        let date = update.date.unwrap_or(expense.date);
+        let before = ExpenseState::new(&expense, &old_shares);
+        expense.rate_snapshot = snapshot_rate(self.exchange_rates.as_ref(), &amount, &settings.default_currency, settings.local_date(date)).await?;
+        expense.description = update.description.trim().to_string();
+        expense.amount = amount;
+        expense.paid_by = update.paid_by;
+        expense.category = update.category;
+        expense.date = date;
+        expense.updated_at = now;
+
+        let shares = self.calculate_shares(&expense, &update.split_type, &update.participants)?;
+        let changes = ExpenseState::new(&expense, &shares).changes_since(&before);
+        if changes.is_empty() {
+            return self.get_expense(expense_id, user_id).await?.ok_or("Expense not found".into());
+        }
+        let revision = ExpenseRevision {
+            id: Uuid::new_v4(),
+            expense_id: *expense_id,
+            revision: 0, // Numbered by the repository when saved
+            edited_by: *user_id,
+            edited_by_name: String::new(), // Looked up when revisions are read
+            edited_at: now,
+            changes,
+        };
+
+        self.expense_repository.save_edit(&expense, &shares, &revision).await?;
+        self.rebook_payments(&expense.group_id).await?;
+
+        let summary = format!("{} ({})", expense.description, expense.amount);
+        self.activity
+            .record(&NewActivity::new(expense.group_id, *user_id, ActivityKind::ExpenseUpdated, Some(*expense_id), summary))
+            .await;
+
+        self.get_expense(expense_id, user_id).await?.ok_or("Failed to retrieve updated expense".into())
+    }
+
+    // Takes every payment in the group off the shares and books them again in the order they
+    // were made, as if the shares had always been what they are now
+    async fn rebook_payments(&self, group_id: &Uuid) -> Result<(), Box<dyn Error>> {
         let mut shares = Vec::new();
+        for expense in self.expense_repository.get_group_expenses(group_id, None, None).await? {
+            for mut share in self.share_repository.get_expense_shares(&expense.id).await? {
+                share.settled_amount.minor_units = 0;
+                share.is_settled = false;
+                share.settled_at = None;
+                shares.push(((expense.date, expense.created_at, expense.id), expense.paid_by, share));
+            }
+        }
+        shares.sort_by(|a, b| a.0.cmp(&b.0));
 
-        match &creation.split_type {
-            SplitType::Equal => {
-                let share_amount = creation.amount / creation.participants.len() as f64;
-                for user_id in &creation.participants {
-                    shares.push(ExpenseShare {
-                        expense_id: creation.group_id, // Will be set to expense_id by caller
-                        user_id: *user_id,
-                        amount: share_amount,
-                        is_settled: false,
-                    });
-                }
-            },
-            SplitType::Exact(amounts) => {
-                let total: f64 = amounts.values().sum();
-                if (total - creation.amount).abs() > 0.01 {
-                    return Err("Exact amounts must sum to total expense amount".into());
-                }
-                for user_id in &creation.participants {
-                    if let Some(&amount) = amounts.get(user_id) {
-                        shares.push(ExpenseShare {
-                            expense_id: creation.group_id,
-                            user_id: *user_id,
-                            amount,
-                            is_settled: false,
-                        });
-                    } else {
-                        return Err("All participants must have exact amounts specified".into());
-                    }
-                }
-            },
-            SplitType::Percentage(percentages) => {
-                let total_percent: f64 = percentages.values().sum();
-                if (total_percent - 100.0).abs() > 0.01 {
-                    return Err("Percentages must sum to 100%".into());
-                }
-                for user_id in &creation.participants {
-                    if let Some(&percent) = percentages.get(user_id) {
-                        let amount = creation.amount * (percent / 100.0);
-                        shares.push(ExpenseShare {
-                            expense_id: creation.group_id,
-                            user_id: *user_id,
-                            amount,
-                            is_settled: false,
-                        });
-                    } else {
-                        return Err("All participants must have percentages specified".into());
-                    }
+        let mut payments = self.payment_repository.get_group_payments(group_id).await?;
+        payments.sort_by(|a, b| (a.created_at, a.id).cmp(&(b.created_at, b.id)));
+        for payment in payments {
+            let owed: Vec<ExpenseShare> = shares
+                .iter()
+                .filter(|(_, paid_by, share)| *paid_by == payment.to_user && share.user_id == payment.from_user)
+                .map(|(_, _, share)| share.clone())
+                .collect();
+            let allocations = allocate_payment(&payment.rate_snapshot.base_amount, &owed);
+            for allocation in &allocations {
+                if let Some((_, _, share)) = shares
+                    .iter_mut()
+                    .find(|(_, _, share)| share.expense_id == allocation.expense_id && share.user_id == payment.from_user)
+                {
+                    share.apply_settlement(allocation.amount.minor_units, payment.created_at);
                 }
-            },
-            SplitType::ByShares(share_counts) => {
-                let total_shares: u32 = share_counts.values().sum();
-                if total_shares == 0 {
-                    return Err("Total shares cannot be zero".into());
-                }
-                for user_id in &creation.participants {
-                    if let Some(&user_shares) = share_counts.get(user_id) {
-                        let amount = creation.amount * (user_shares as f64 / total_shares as f64);
-                        shares.push(ExpenseShare {
-                            expense_id: creation.group_id,
-                            user_id: *user_id,
-                            amount,
-                            is_settled: false,
-                        });
-                    } else {
-                        return Err("All participants must have share counts specified".into());
-                    }
-                }
-            },
+            }
+            self.payment_repository.set_allocations(&payment.id, &allocations).await?;
         }
 
-        // Note: expense_id will be set correctly by the caller
+        for (_, _, share) in &shares {
+            self.share_repository.update_share(share).await?;
+        }
+        Ok(())
+    }
+
+    // Every edit of the expense, oldest first
+    pub async fn get_expense_revisions(&self, expense_id: &Uuid, user_id: &Uuid) -> Result<Vec<ExpenseRevision>, Box<dyn Error>> {
+        let expense = self.expense_repository.get_expense_by_id(expense_id).await?.ok_or("Expense not found")?;
+        self.authorizer.authorize(&expense.group_id, user_id, GroupAction::ViewGroup, None).await?;
 
-        Ok(shares)
+        let mut revisions = self.expense_repository.get_revisions(expense_id).await?;
+        let user_ids: Vec<Uuid> = revisions.iter().map(|revision| revision.edited_by).collect();
+        let names = self.display_names.display_names(&user_ids).await?;
+        for revision in &mut revisions {
+            revision.edited_by_name = names.get(&revision.edited_by).cloned().unwrap_or_else(|| UNKNOWN_USER_NAME.to_string());
+        }
+        Ok(revisions)
     }
 
-    pub async fn get_expense(&self, expense_id: &Uuid, _user_id: &Uuid) -> Result<Option<ExpenseInfo>, Box<dyn Error>> {
-        // TODO: Verify user has access to this expense through group membership
+    pub async fn get_expense(&self, expense_id: &Uuid, user_id: &Uuid) -> Result<Option<ExpenseInfo>, Box<dyn Error>> {
         let expense = match self.expense_repository.get_expense_by_id(expense_id).await? {
             Some(e) => e,
             None => return Ok(None),
         };
+        self.authorizer.authorize(&expense.group_id, user_id, GroupAction::ViewGroup, None).await?;
 
         let shares = self.share_repository.get_expense_shares(expense_id).await?;
-        
-        // Convert to ExpenseInfo (would need user name lookups in real implementation)
+
+        let mut user_ids = vec![expense.paid_by, expense.created_by];
+        user_ids.extend(shares.iter().map(|s| s.user_id));
+        let names = self.display_names.display_names(&user_ids).await?;
+        let name_of = |id: &Uuid| names.get(id).cloned().unwrap_or_else(|| UNKNOWN_USER_NAME.to_string());
+
         Ok(Some(ExpenseInfo {
             id: expense.id,
             group_id: expense.group_id,
             description: expense.description,
-            amount: expense.amount,
-            currency: expense.currency,
+            amount: expense.amount.to_major(),
+            currency: expense.amount.currency,
+            converted: Some(ConvertedAmount {
+                amount: expense.rate_snapshot.base_amount.to_major(),
+                currency: expense.rate_snapshot.base_amount.currency,
+                rate: expense.rate_snapshot.rate,
+            }),
             paid_by: expense.paid_by,
-            paid_by_name: "User".to_string(), // TODO: Lookup username
+            paid_by_name: name_of(&expense.paid_by),
             created_by: expense.created_by,
-            created_by_name: "User".to_string(), // TODO: Lookup username
+            created_by_name: name_of(&expense.created_by),
             category: expense.category,
             date: expense.date,
             shares: shares.into_iter().map(|s| crate::expenses::domain::expense::ExpenseShareInfo {
                 user_id: s.user_id,
-                username: "User".to_string(), // TODO: Lookup username
-                amount: s.amount,
+                username: name_of(&s.user_id),
+                amount: s.amount.to_major(),
+                settled_amount: s.settled_amount.to_major(),
                 is_settled: s.is_settled,
             }).collect(),
             created_at: expense.created_at,
         }))
     }
 
-    pub async fn get_group_expenses(&self, group_id: &Uuid, _user_id: &Uuid, limit: Option<usize>, offset: Option<usize>) -> Result<Vec<ExpenseInfo>, Box<dyn Error>> {
-        // TODO: Verify user is member of group
+    pub async fn get_group_expenses(&self, group_id: &Uuid, user_id: &Uuid, limit: Option<usize>, offset: Option<usize>) -> Result<Vec<ExpenseInfo>, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
         self.expense_repository.get_group_expenses(group_id, limit, offset).await
     }
 
-    pub async fn get_group_balances(&self, group_id: &Uuid, _user_id: &Uuid) -> Result<GroupBalance, Box<dyn Error>> {
-        // TODO: Verify user is member of group
-        self.balance_repository.calculate_group_balances(group_id).await
+    // With `include_children`, balances in the group's open ledgers are added in as well.
+    // Closed ledgers already count through their transfers.
+    pub async fn get_group_balances(&self, group_id: &Uuid, user_id: &Uuid, include_children: bool) -> Result<GroupBalance, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        let mut group_balance = self.balance_repository.calculate_group_balances(group_id).await?;
+        let mut balances = self.net_balances(&group_balance).await?;
+        if include_children {
+            for child_id in self.ledger_repository.get_open_child_ledgers(group_id).await? {
+                let child_balance = self.balance_repository.calculate_group_balances(&child_id).await?;
+                merge_balances(&mut balances, self.net_balances(&child_balance).await?);
+            }
+        }
+
+        let user_ids: Vec<Uuid> = balances.keys().copied().collect();
+        let names = self.display_names.display_names(&user_ids).await?;
+        group_balance.balances = balances
+            .into_iter()
+            .map(|(user_id, net_balance)| UserBalance {
+                user_id,
+                username: names.get(&user_id).cloned().unwrap_or_else(|| UNKNOWN_USER_NAME.to_string()),
+                net_balance: minor_to_major(net_balance, &group_balance.currency),
+            })
+            .collect();
+        Ok(group_balance)
+    }
+
+    // A group's own balances, in minor units, plus whatever closed ledgers moved in or out of it
+    async fn net_balances(&self, group_balance: &GroupBalance) -> Result<HashMap<Uuid, i64>, Box<dyn Error>> {
+        let mut balances = HashMap::with_capacity(group_balance.balances.len());
+        for balance in &group_balance.balances {
+            balances.insert(balance.user_id, Money::from_major(balance.net_balance, group_balance.currency.clone())?.minor_units);
+        }
+        merge_balances(&mut balances, self.ledger_repository.get_transfer_totals(&group_balance.group_id).await?);
+        Ok(balances)
+    }
+
+    // Carries every member's balance in a ledger into its parent group, then closes and
+    // archives the ledger
+    pub async fn close_ledger(&self, group_id: &Uuid, user_id: &Uuid) -> Result<ClosedLedger, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::CloseLedger, None).await?;
+        let link = self.ledger_repository.get_ledger_link(group_id).await?.ok_or("Group not found")?;
+        let parent_group_id = link.parent_group_id.ok_or("Only a ledger inside another group can be closed")?;
+        if link.closed_at.is_some() {
+            return Err("Ledger is already closed".into());
+        }
+        if self.ledger_repository.get_ledger_link(&parent_group_id).await?.is_none() {
+            return Err("Parent group not found".into());
+        }
+
+        let settings = self.settings_repository.get_settings(group_id).await?;
+        let parent_settings = self.settings_repository.get_settings(&parent_group_id).await?;
+        if settings.default_currency != parent_settings.default_currency {
+            return Err(format!(
+                "Ledger currency {} does not match the parent group's {}",
+                settings.default_currency, parent_settings.default_currency
+            )
+            .into());
+        }
+
+        let now = Utc::now();
+        let group_balance = self.balance_repository.calculate_group_balances(group_id).await?;
+        let balances = self.net_balances(&group_balance).await?;
+        let ledger = ClosedLedger {
+            group_id: *group_id,
+            parent_group_id,
+            closed_at: now,
+            transfers: ledger_transfers(*group_id, parent_group_id, &balances, &settings.default_currency, now),
+        };
+        if !self.ledger_repository.close_ledger(&ledger, user_id).await? {
+            return Err("Ledger is already closed".into());
+        }
+        Ok(ledger)
     }
 
     pub async fn get_user_balance(&self, user_id: &Uuid, group_id: &Uuid) -> Result<f64, Box<dyn Error>> {
         self.balance_repository.calculate_user_balance(user_id, group_id).await
     }
 
-    pub async fn get_debt_summary(&self, group_id: &Uuid, _user_id: &Uuid) -> Result<Vec<DebtSummary>, Box<dyn Error>> {
-        // TODO: Verify user is member of group
+    pub async fn get_debt_summary(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Vec<DebtSummary>, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
         self.balance_repository.get_debt_summary(group_id).await
     }
 
-    pub async fn settle_debt(&self, group_id: &Uuid, settle: SettleDebt, _settled_by: Uuid) -> Result<(), Box<dyn Error>> {
-        // TODO: Verify user has permission
-        if settle.amount <= 0.0 {
+    // Records a payment and books it against the debtor's oldest shares of what the creditor
+    // paid for, oldest first
+    pub async fn settle_debt(&self, group_id: &Uuid, settle: SettleDebt, settled_by: Uuid) -> Result<Payment, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, &settled_by, GroupAction::SettleDebt, None).await?;
+        let settings = self.settings_repository.get_settings(group_id).await?;
+        settings.require_enabled(GroupModule::Expenses)?;
+        if settle.debtor_id == settle.creditor_id {
+            return Err("Cannot settle a debt with yourself".into());
+        }
+        let members = self.authorizer.members_among(group_id, &[settle.debtor_id, settle.creditor_id]).await?;
+        if !members.contains(&settle.debtor_id) || !members.contains(&settle.creditor_id) {
+            return Err(ServiceError::Invalid("Both sides of a settlement must be members of the group".to_string()).into());
+        }
+        let currency = match settle.currency.trim() {
+            "" => settings.default_currency.clone(),
+            currency => normalize_currency(currency)?,
+        };
+        let amount = Money::from_major(settle.amount, currency)?;
+        if !amount.is_positive() {
             return Err("Settlement amount must be positive".into());
         }
+        let now = Utc::now();
+        let rate_snapshot = snapshot_rate(self.exchange_rates.as_ref(), &amount, &settings.default_currency, settings.local_date(now)).await?;
+
+        let mut shares: Vec<ExpenseShare> = self
+            .share_repository
+            .get_unsettled_shares(group_id)
+            .await?
+            .into_iter()
+            .filter(|(expense, share)| share.user_id == settle.debtor_id && expense.paid_by == settle.creditor_id)
+            .map(|(_, share)| share)
+            .collect();
+        let allocations = allocate_payment(&rate_snapshot.base_amount, &shares);
 
         // Create payment record
         let payment = Payment {
@@ -224,37 +430,103 @@ impl ExpenseService {
             group_id: *group_id,
             from_user: settle.debtor_id,
             to_user: settle.creditor_id,
-            amount: settle.amount,
-            currency: "USD".to_string(), // TODO: Get from group settings
-            description: format!("Debt settlement: ${:.2}", settle.amount),
-            created_at: Utc::now(),
+            description: format!("Debt settlement: {}", amount),
+            amount,
+            rate_snapshot,
+            recorded_by: settled_by,
+            allocations,
+            created_at: now,
         };
 
         self.payment_repository.create_payment(&payment).await?;
+        for allocation in &payment.allocations {
+            if let Some(share) = shares.iter_mut().find(|share| share.expense_id == allocation.expense_id) {
+                share.apply_settlement(allocation.amount.minor_units, now);
+                self.share_repository.update_share(share).await?;
+            }
+        }
 
-        // TODO: Update expense shares to mark relevant portions as settled
+        Ok(payment)
+    }
 
-        Ok(())
+    // Takes a payment back and the balances move back. The remaining payments are booked
+    // again, so later ones pay off what this one had covered.
+    pub async fn undo_settlement(&self, payment_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
+        let payment = self.payment_repository.get_payment(payment_id).await?.ok_or("Payment not found")?;
+        self.authorizer
+            .authorize(&payment.group_id, user_id, GroupAction::UndoSettlement, Some(&payment.recorded_by))
+            .await?;
+
+        self.payment_repository.delete_payment(payment_id).await?;
+        self.rebook_payments(&payment.group_id).await
+    }
+
+    // What is still owed on each share in the group, oldest first; with `involving`, only the
+    // shares that person owes or is owed
+    pub async fn get_unsettled_shares(&self, group_id: &Uuid, user_id: &Uuid, involving: Option<&Uuid>) -> Result<Vec<UnsettledShare>, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        let unsettled: Vec<(Expense, ExpenseShare)> = self
+            .share_repository
+            .get_unsettled_shares(group_id)
+            .await?
+            .into_iter()
+            .filter(|(expense, share)| involving.map_or(true, |id| share.user_id == *id || expense.paid_by == *id))
+            .collect();
+
+        let mut user_ids = Vec::with_capacity(unsettled.len() * 2);
+        for (expense, share) in &unsettled {
+            user_ids.push(share.user_id);
+            user_ids.push(expense.paid_by);
+        }
+        let names = self.display_names.display_names(&user_ids).await?;
+        let name_of = |id: &Uuid| names.get(id).cloned().unwrap_or_else(|| UNKNOWN_USER_NAME.to_string());
+
+        Ok(unsettled
+            .into_iter()
+            .map(|(expense, share)| UnsettledShare {
+                expense_id: expense.id,
+                description: expense.description,
+                date: expense.date,
+                debtor_id: share.user_id,
+                debtor_name: name_of(&share.user_id),
+                creditor_id: expense.paid_by,
+                creditor_name: name_of(&expense.paid_by),
+                amount: share.base_amount.to_major(),
+                settled_amount: share.settled_amount.to_major(),
+                outstanding: minor_to_major(share.outstanding(), &share.base_amount.currency),
+                currency: share.base_amount.currency,
+            })
+            .collect())
     }
 
     pub async fn delete_expense(&self, expense_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
-        // TODO: Verify user has permission (creator or group admin)
-        
+        let expense = self.expense_repository.get_expense_by_id(expense_id).await?.ok_or("Expense not found")?;
+        self.authorizer
+            .authorize(&expense.group_id, user_id, GroupAction::DeleteExpense, Some(&expense.created_by))
+            .await?;
+
         // Delete shares first
         self.share_repository.delete_expense_shares(expense_id).await?;
         
         // Delete expense
         self.expense_repository.delete_expense(expense_id).await?;
 
-        Ok(())
+        // What payments put towards it goes onto the debtors' other shares
+        self.rebook_payments(&expense.group_id).await
     }
 
     pub async fn get_user_debts(&self, user_id: &Uuid) -> Result<Vec<DebtSummary>, Box<dyn Error>> {
         self.balance_repository.get_user_debts(user_id).await
     }
 
-    pub async fn search_expenses(&self, filter: ExpenseFilter, user_id: &Uuid) -> Result<Vec<ExpenseInfo>, Box<dyn Error>> {
-        // TODO: Verify user has access to requested groups
+    pub async fn search_expenses(&self, mut filter: ExpenseFilter, user_id: &Uuid) -> Result<Vec<ExpenseInfo>, Box<dyn Error>> {
+        // Within one group the caller must be a member; across groups they only see their own
+        match filter.group_id {
+            Some(group_id) => {
+                self.authorizer.authorize(&group_id, user_id, GroupAction::ViewGroup, None).await?;
+            }
+            None => filter.involving_user = Some(*user_id),
+        }
         self.expense_repository.get_expenses(&filter).await
     }
 }
\ No newline at end of file
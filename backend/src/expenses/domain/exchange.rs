@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+use std::error::Error;
+
+use super::money::Money;
+use super::ports::ExchangeRateProvider;
+
+// One unit of `from` bought `rate` units of `to` on `date`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExchangeRate {
+    pub from: String,
+    pub to: String,
+    pub date: NaiveDate,
+    pub rate: f64,
+}
+
+// An amount converted into the group's base currency at the rate of its day. Kept with the
+// expense or payment so later rate moves never change old balances.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RateSnapshot {
+    pub rate: f64,
+    pub base_amount: Money,
+}
+
+impl RateSnapshot {
+    pub fn new(amount: &Money, rate: f64, base_currency: &str) -> Result<Self, String> {
+        Ok(Self { rate, base_amount: amount.convert(rate, base_currency)? })
+    }
+
+    // Spreads the converted total over shares in proportion to their original amounts, so the
+    // converted shares still add up to the converted total
+    pub fn share_amounts(&self, shares: &[Money]) -> Result<Vec<Money>, String> {
+        let weights: Vec<u64> = shares.iter().map(|share| share.minor_units.max(0) as u64).collect();
+        self.base_amount.allocate(&weights)
+    }
+}
+
+// Snapshot of `amount` in `base_currency` on `on`. Amounts already in the base currency keep
+// a rate of 1 without asking the provider.
+pub async fn snapshot_rate(
+    rates: &dyn ExchangeRateProvider,
+    amount: &Money,
+    base_currency: &str,
+    on: NaiveDate,
+) -> Result<RateSnapshot, Box<dyn Error>> {
+    if amount.currency == base_currency {
+        return Ok(RateSnapshot { rate: 1.0, base_amount: amount.clone() });
+    }
+    let rate = rates.rate(&amount.currency, base_currency, on).await?;
+    Ok(RateSnapshot::new(amount, rate, base_currency)?)
+}
@@ -1,15 +1,21 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use super::exchange::RateSnapshot;
+use super::money::Money;
+
+// Percentages are weighed in ten-thousandths of a percent, so 33.3333% still splits cleanly
+const PERCENT_WEIGHT_SCALE: f64 = 10_000.0;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Expense {
     pub id: Uuid,
     pub group_id: Uuid,
     pub description: String,
-    pub amount: f64, // Total amount in cents to avoid floating point issues in production
-    pub currency: String,
+    pub amount: Money, // Total, in the expense's own currency
+    pub rate_snapshot: RateSnapshot, // `amount` in the group's base currency on the expense date
     pub paid_by: Uuid, // User who paid the expense
     pub created_by: Uuid, // User who created the expense entry
     pub category: Option<String>,
@@ -22,14 +28,36 @@ pub struct Expense {
 pub struct ExpenseShare {
     pub expense_id: Uuid,
     pub user_id: Uuid,
-    pub amount: f64, // Amount this user owes for this expense
+    pub amount: Money, // Amount this user owes for this expense, in the expense's currency
+    pub base_amount: Money, // The same share in the group's base currency
+    pub settled_amount: Money, // How much of `base_amount` payments have covered so far
     pub is_settled: bool,
+    pub settled_at: Option<DateTime<Utc>>, // When the last of it was paid back
+}
+
+impl ExpenseShare {
+    // What is still owed on the share, in minor units of the group's base currency
+    pub fn outstanding(&self) -> i64 {
+        (self.base_amount.minor_units - self.settled_amount.minor_units).max(0)
+    }
+
+    // Books part of a payment made at `at` against the share; a negative amount takes it back
+    pub fn apply_settlement(&mut self, amount: i64, at: DateTime<Utc>) {
+        self.settled_amount.minor_units += amount;
+        let was_settled = self.is_settled;
+        self.is_settled = self.settled_amount.minor_units >= self.base_amount.minor_units;
+        self.settled_at = match (was_settled, self.is_settled) {
+            (false, true) => Some(at),
+            (_, false) => None,
+            (true, true) => self.settled_at,
+        };
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum SplitType {
     Equal, // Split equally among participants
-    Exact(HashMap<Uuid, f64>), // Exact amounts per person
+    Exact(HashMap<Uuid, f64>), // Exact amounts per person, as decimals like `amount`
     Percentage(HashMap<Uuid, f64>), // Percentage per person (must sum to 100)
     ByShares(HashMap<Uuid, u32>), // Split by shares (e.g., 2 shares for Alice, 1 share for Bob)
 }
@@ -38,8 +66,9 @@ pub enum SplitType {
 pub struct ExpenseCreation {
     pub group_id: Uuid,
     pub description: String,
-    pub amount: f64,
-    pub currency: String,
+    pub amount: f64, // Decimal in major units, e.g. 12.34; at most the currency's decimal places
+    #[serde(default)]
+    pub currency: String, // Empty means the group's default currency
     pub paid_by: Uuid,
     pub split_type: SplitType,
     pub participants: Vec<Uuid>, // Users involved in the expense
@@ -47,13 +76,32 @@ pub struct ExpenseCreation {
     pub date: Option<DateTime<Utc>>, // Optional, defaults to now
 }
 
+// A full edit: everything an expense was created with except its group. Shares are split
+// again from `split_type` and `participants`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExpenseUpdate {
+    pub description: String,
+    pub amount: f64,
+    #[serde(default)]
+    pub currency: String, // Empty means the group's default currency
+    pub paid_by: Uuid,
+    pub split_type: SplitType,
+    pub participants: Vec<Uuid>,
+    pub category: Option<String>,
+    pub date: Option<DateTime<Utc>>, // Optional, keeps the current date
+    // Payments already paid off part of the shares; they are booked again against the new ones
+    #[serde(default)]
+    pub confirm_settled: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ExpenseInfo {
     pub id: Uuid,
     pub group_id: Uuid,
     pub description: String,
-    pub amount: f64,
+    pub amount: f64, // Major units of `currency`, converted from the stored minor units
     pub currency: String,
+    pub converted: Option<ConvertedAmount>, // None until the expense has a rate snapshot
     pub paid_by: Uuid,
     pub paid_by_name: String,
     pub created_by: Uuid,
@@ -64,14 +112,39 @@ pub struct ExpenseInfo {
     pub created_at: DateTime<Utc>,
 }
 
+// An amount in the group's base currency, with the rate it was converted at
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConvertedAmount {
+    pub amount: f64,
+    pub currency: String,
+    pub rate: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ExpenseShareInfo {
     pub user_id: Uuid,
     pub username: String,
     pub amount: f64,
+    pub settled_amount: f64, // Paid back so far, in the group's base currency like `converted`
     pub is_settled: bool,
 }
 
+// A share someone still owes the person who paid for the expense, in the group's base currency
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UnsettledShare {
+    pub expense_id: Uuid,
+    pub description: String,
+    pub date: DateTime<Utc>,
+    pub debtor_id: Uuid,
+    pub debtor_name: String,
+    pub creditor_id: Uuid,
+    pub creditor_name: String,
+    pub amount: f64,
+    pub settled_amount: f64,
+    pub outstanding: f64,
+    pub currency: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserBalance {
     pub user_id: Uuid,
@@ -83,6 +156,16 @@ pub struct UserBalance {
 pub struct GroupBalance {
     pub group_id: Uuid,
     pub group_name: String,
+    pub currency: String, // what `net_balance` is in: the group's base currency
+    pub balances: Vec<UserBalance>,
+    // The same balances kept apart by the currency money was spent in, when asked for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub by_currency: Vec<CurrencyBalance>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CurrencyBalance {
+    pub currency: String,
     pub balances: Vec<UserBalance>,
 }
 
@@ -96,11 +179,22 @@ pub struct DebtSummary {
     pub currency: String,
 }
 
+// The payments that would settle a group, in its base currency
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SettleUpPlan {
+    pub group_id: Uuid,
+    pub currency: String,
+    pub simplified: bool, // false when the group settles pairwise debts
+    pub debts: Vec<DebtSummary>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SettleDebt {
     pub creditor_id: Uuid,
     pub debtor_id: Uuid,
     pub amount: f64,
+    #[serde(default)]
+    pub currency: String, // Empty means the group's default currency
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -109,12 +203,21 @@ pub struct Payment {
     pub group_id: Uuid,
     pub from_user: Uuid,
     pub to_user: Uuid,
-    pub amount: f64,
-    pub currency: String,
+    pub amount: Money,
+    pub rate_snapshot: RateSnapshot, // `amount` in the group's base currency on the payment date
     pub description: String,
+    pub recorded_by: Uuid, // Who entered the payment; they can undo it
+    pub allocations: Vec<PaymentAllocation>, // The debtor's shares the payment paid back
     pub created_at: DateTime<Utc>,
 }
 
+// Part of a payment booked against one of the debtor's shares, in the group's base currency
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PaymentAllocation {
+    pub expense_id: Uuid,
+    pub amount: Money,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ExpenseFilter {
     pub group_id: Option<Uuid>,
@@ -125,4 +228,185 @@ pub struct ExpenseFilter {
     pub date_to: Option<DateTime<Utc>>,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
-}
\ No newline at end of file
+}
+
+// Rules an expense has to meet whenever it is created or edited. `members` are the
+// participants who belong to the expense's group; the payer is always one of the participants.
+pub fn validate_expense(description: &str, paid_by: &Uuid, participants: &[Uuid], members: &HashSet<Uuid>) -> Result<(), String> {
+    if description.trim().is_empty() {
+        return Err("Expense description cannot be empty".to_string());
+    }
+    if participants.is_empty() {
+        return Err("Expense must have at least one participant".to_string());
+    }
+    if !participants.contains(paid_by) {
+        return Err("The person who paid must be included in participants".to_string());
+    }
+    if !participants.iter().all(|user_id| members.contains(user_id)) {
+        return Err("Everyone on an expense must be a member of the group".to_string());
+    }
+    Ok(())
+}
+
+// Turns a split into exact shares of `total`, in participant order. Equal, percentage and
+// share-count splits go through Money::allocate, so the shares always add up to the total;
+// exact amounts have to add up on their own.
+pub fn split_shares(total: &Money, split_type: &SplitType, participants: &[Uuid]) -> Result<Vec<(Uuid, Money)>, String> {
+    if participants.is_empty() {
+        return Err("Expense must have at least one participant".to_string());
+    }
+    let mut seen = HashSet::with_capacity(participants.len());
+    if !participants.iter().all(|user_id| seen.insert(*user_id)) {
+        return Err("Each participant can only be listed once".to_string());
+    }
+
+    let amounts = match split_type {
+        SplitType::Equal => total.allocate(&vec![1; participants.len()])?,
+        SplitType::Exact(amounts) => {
+            let mut shares = Vec::with_capacity(participants.len());
+            for user_id in participants {
+                let amount = amounts.get(user_id).ok_or("All participants must have exact amounts specified")?;
+                let share = Money::from_major(*amount, total.currency.clone())?;
+                if share.minor_units < 0 {
+                    return Err("Exact amounts cannot be negative".to_string());
+                }
+                shares.push(share);
+            }
+            if shares.iter().map(|share| share.minor_units).sum::<i64>() != total.minor_units {
+                return Err("Exact amounts must sum to total expense amount".to_string());
+            }
+            shares
+        }
+        SplitType::Percentage(percentages) => {
+            let mut weights = Vec::with_capacity(participants.len());
+            let mut total_percent = 0.0;
+            for user_id in participants {
+                let percent = *percentages.get(user_id).ok_or("All participants must have percentages specified")?;
+                if !percent.is_finite() || percent < 0.0 {
+                    return Err("Percentages cannot be negative".to_string());
+                }
+                total_percent += percent;
+                weights.push((percent * PERCENT_WEIGHT_SCALE).round() as u64);
+            }
+            if (total_percent - 100.0).abs() > 0.01 {
+                return Err("Percentages must sum to 100%".to_string());
+            }
+            total.allocate(&weights)?
+        }
+        SplitType::ByShares(share_counts) => {
+            let mut weights = Vec::with_capacity(participants.len());
+            for user_id in participants {
+                let count = share_counts.get(user_id).ok_or("All participants must have share counts specified")?;
+                weights.push(*count as u64);
+            }
+            if weights.iter().all(|weight| *weight == 0) {
+                return Err("Total shares cannot be zero".to_string());
+            }
+            total.allocate(&weights)?
+        }
+    };
+
+    Ok(participants.iter().copied().zip(amounts).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users(count: usize) -> Vec<Uuid> {
+        (0..count).map(|_| Uuid::new_v4()).collect()
+    }
+
+    fn amounts(shares: &[(Uuid, Money)]) -> Vec<i64> {
+        shares.iter().map(|(_, share)| share.minor_units).collect()
+    }
+
+    #[test]
+    fn expenses_need_a_description_a_paying_participant_and_members_only() {
+        let participants = users(2);
+        let members: HashSet<Uuid> = participants.iter().copied().collect();
+        assert!(validate_expense("Groceries", &participants[0], &participants, &members).is_ok());
+        assert!(validate_expense("  ", &participants[0], &participants, &members).is_err());
+        assert!(validate_expense("Groceries", &Uuid::new_v4(), &participants, &members).is_err());
+
+        let outsider = Uuid::new_v4();
+        let with_outsider = vec![participants[0], outsider];
+        assert_eq!(
+            validate_expense("Groceries", &participants[0], &with_outsider, &members),
+            Err("Everyone on an expense must be a member of the group".to_string())
+        );
+    }
+
+    #[test]
+    fn equal_split_of_ten_dollars_three_ways_adds_up() {
+        let participants = users(3);
+        let shares = split_shares(&Money::new(1000, "USD"), &SplitType::Equal, &participants).unwrap();
+        assert_eq!(amounts(&shares), vec![334, 333, 333]);
+        assert_eq!(shares.iter().map(|(user_id, _)| *user_id).collect::<Vec<_>>(), participants);
+    }
+
+    #[test]
+    fn percentage_split_adds_up_exactly() {
+        let participants = users(3);
+        let percentages = participants.iter().copied().zip([33.33, 33.33, 33.34]).collect();
+        let shares = split_shares(&Money::new(1000, "USD"), &SplitType::Percentage(percentages), &participants).unwrap();
+        assert_eq!(amounts(&shares).iter().sum::<i64>(), 1000);
+        assert_eq!(amounts(&shares), vec![333, 333, 334]);
+    }
+
+    #[test]
+    fn percentages_must_reach_one_hundred() {
+        let participants = users(2);
+        let percentages = participants.iter().copied().zip([50.0, 40.0]).collect();
+        assert!(split_shares(&Money::new(1000, "USD"), &SplitType::Percentage(percentages), &participants).is_err());
+    }
+
+    #[test]
+    fn share_count_split_adds_up_exactly() {
+        let participants = users(3);
+        let counts = participants.iter().copied().zip([2, 1, 1]).collect();
+        let shares = split_shares(&Money::new(1001, "USD"), &SplitType::ByShares(counts), &participants).unwrap();
+        assert_eq!(amounts(&shares), vec![501, 250, 250]);
+    }
+
+    #[test]
+    fn exact_split_must_match_the_total() {
+        let participants = users(2);
+        let exact: HashMap<Uuid, f64> = participants.iter().copied().zip([6.5, 3.5]).collect();
+        let shares = split_shares(&Money::new(1000, "USD"), &SplitType::Exact(exact), &participants).unwrap();
+        assert_eq!(amounts(&shares), vec![650, 350]);
+
+        let short: HashMap<Uuid, f64> = participants.iter().copied().zip([6.5, 3.49]).collect();
+        assert!(split_shares(&Money::new(1000, "USD"), &SplitType::Exact(short), &participants).is_err());
+    }
+
+    #[test]
+    fn zero_decimal_currency_splits_into_whole_yen() {
+        let participants = users(3);
+        let shares = split_shares(&Money::new(1000, "JPY"), &SplitType::Equal, &participants).unwrap();
+        assert_eq!(amounts(&shares), vec![334, 333, 333]);
+        assert!(shares.iter().all(|(_, share)| share.currency == "JPY"));
+
+        let exact: HashMap<Uuid, f64> = participants.iter().copied().zip([500.5, 249.5, 250.0]).collect();
+        assert!(split_shares(&Money::new(1000, "JPY"), &SplitType::Exact(exact), &participants).is_err());
+    }
+
+    #[test]
+    fn three_decimal_currency_splits_into_fils() {
+        let participants = users(3);
+        let total = Money::from_major(10.0, "BHD").unwrap();
+        let shares = split_shares(&total, &SplitType::Equal, &participants).unwrap();
+        assert_eq!(amounts(&shares), vec![3334, 3333, 3333]);
+
+        let exact: HashMap<Uuid, f64> = participants.iter().copied().zip([3.334, 3.333, 3.333]).collect();
+        let shares = split_shares(&total, &SplitType::Exact(exact), &participants).unwrap();
+        assert_eq!(amounts(&shares), vec![3334, 3333, 3333]);
+    }
+
+    #[test]
+    fn participants_must_be_listed_once() {
+        let user_id = Uuid::new_v4();
+        assert!(split_shares(&Money::new(1000, "USD"), &SplitType::Equal, &[user_id, user_id]).is_err());
+        assert!(split_shares(&Money::new(1000, "USD"), &SplitType::Equal, &[]).is_err());
+    }
+}
@@ -1,2 +1,6 @@
+pub mod exchange;
 pub mod expense;
-pub mod ports;
\ No newline at end of file
+pub mod money;
+pub mod ports;
+pub mod revision;
+pub mod settle_up;
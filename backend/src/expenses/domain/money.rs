@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// ISO 4217 currencies whose minor unit isn't a hundredth. Everything else uses two decimals.
+const ZERO_DECIMAL_CURRENCIES: &[&str] = &[
+    "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "UYI", "VND", "VUV", "XAF", "XOF", "XPF",
+];
+const THREE_DECIMAL_CURRENCIES: &[&str] = &["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+const FOUR_DECIMAL_CURRENCIES: &[&str] = &["CLF", "UYW"];
+
+// Largest amount of minor units that survives the trip through a JS number unchanged
+const MAX_SAFE_MINOR_UNITS: i64 = (1 << 53) - 1;
+
+// Decimal places of the currency's minor unit, e.g. 2 for USD and 0 for JPY
+pub fn currency_exponent(currency: &str) -> u32 {
+    if ZERO_DECIMAL_CURRENCIES.contains(&currency) {
+        0
+    } else if THREE_DECIMAL_CURRENCIES.contains(&currency) {
+        3
+    } else if FOUR_DECIMAL_CURRENCIES.contains(&currency) {
+        4
+    } else {
+        2
+    }
+}
+
+fn minor_per_major(currency: &str) -> i64 {
+    10_i64.pow(currency_exponent(currency))
+}
+
+// An exact amount in the currency's smallest unit. All expense arithmetic happens on
+// `minor_units`; decimals only appear at the API edge.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Money {
+    pub minor_units: i64,
+    pub currency: String, // ISO 4217 code, upper-case
+}
+
+impl Money {
+    pub fn new(minor_units: i64, currency: impl Into<String>) -> Self {
+        Self { minor_units, currency: currency.into() }
+    }
+
+    pub fn zero(currency: impl Into<String>) -> Self {
+        Self::new(0, currency)
+    }
+
+    // Reads a decimal amount from a request. Anything finer than the currency's minor unit is
+    // refused rather than rounded, so 10.005 USD is an error and not 10.01.
+    pub fn from_major(amount: f64, currency: impl Into<String>) -> Result<Self, String> {
+        let currency = currency.into();
+        if !amount.is_finite() {
+            return Err("Amount must be a number".to_string());
+        }
+
+        let scaled = amount * minor_per_major(&currency) as f64;
+        let minor_units = scaled.round();
+        if (scaled - minor_units).abs() > 1e-6 * scaled.abs().max(1.0) {
+            return Err(format!("{} has more decimal places than {} allows", amount, currency));
+        }
+        if minor_units.abs() > MAX_SAFE_MINOR_UNITS as f64 {
+            return Err("Amount is too large".to_string());
+        }
+        Ok(Self::new(minor_units as i64, currency))
+    }
+
+    // For responses only; never feed the result back into arithmetic
+    pub fn to_major(&self) -> f64 {
+        minor_to_major(self.minor_units, &self.currency)
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.minor_units > 0
+    }
+
+    pub fn checked_add(&self, other: &Money) -> Result<Money, String> {
+        if self.currency != other.currency {
+            return Err(format!("Cannot add {} to {}", other.currency, self.currency));
+        }
+        let minor_units = self
+            .minor_units
+            .checked_add(other.minor_units)
+            .filter(|total| total.abs() <= MAX_SAFE_MINOR_UNITS)
+            .ok_or("Amount is too large")?;
+        Ok(Self::new(minor_units, self.currency.clone()))
+    }
+
+    // Converts at `rate` units of `currency` per unit of this one, rounding half away from
+    // zero to the target's minor unit
+    pub fn convert(&self, rate: f64, currency: &str) -> Result<Money, String> {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(format!("Invalid exchange rate {} from {} to {}", rate, self.currency, currency));
+        }
+        let scale = 10_f64.powi(currency_exponent(currency) as i32 - currency_exponent(&self.currency) as i32);
+        let minor_units = (self.minor_units as f64 * rate * scale).round();
+        if minor_units.abs() > MAX_SAFE_MINOR_UNITS as f64 {
+            return Err("Amount is too large".to_string());
+        }
+        Ok(Self::new(minor_units as i64, currency))
+    }
+
+    // Splits the amount in proportion to `weights` with the largest-remainder method. Every
+    // part is a whole number of minor units and the parts always add up to the original. The
+    // units left over after rounding down go to the largest remainders; ties go to the
+    // earlier weight, so the same input always splits the same way.
+    pub fn allocate(&self, weights: &[u64]) -> Result<Vec<Money>, String> {
+        let total_weight: u128 = weights.iter().map(|weight| *weight as u128).sum();
+        if total_weight == 0 {
+            return Err("Cannot split an amount by zero weights".to_string());
+        }
+
+        let amount = self.minor_units.unsigned_abs() as u128;
+        let mut parts = Vec::with_capacity(weights.len());
+        let mut remainders = Vec::with_capacity(weights.len());
+        let mut allocated: u128 = 0;
+        for (index, weight) in weights.iter().enumerate() {
+            let exact = amount * *weight as u128;
+            parts.push(exact / total_weight);
+            remainders.push((exact % total_weight, index));
+            allocated += exact / total_weight;
+        }
+
+        remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        for (_, index) in remainders.into_iter().take((amount - allocated) as usize) {
+            parts[index] += 1;
+        }
+
+        let sign = if self.minor_units < 0 { -1 } else { 1 };
+        Ok(parts
+            .into_iter()
+            .map(|part| Self::new(sign * part as i64, self.currency.clone()))
+            .collect())
+    }
+}
+
+// "12.50 EUR", "1200 JPY", "-0.05 USD"
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let exponent = currency_exponent(&self.currency) as usize;
+        let per_major = minor_per_major(&self.currency).unsigned_abs();
+        let sign = if self.minor_units < 0 { "-" } else { "" };
+        let units = self.minor_units.unsigned_abs();
+        if exponent == 0 {
+            write!(f, "{}{} {}", sign, units, self.currency)
+        } else {
+            write!(f, "{}{}.{:0width$} {}", sign, units / per_major, units % per_major, self.currency, width = exponent)
+        }
+    }
+}
+
+// Minor units of `currency` as a decimal, for response bodies
+pub fn minor_to_major(minor_units: i64, currency: &str) -> f64 {
+    minor_units as f64 / minor_per_major(currency) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn units(parts: &[Money]) -> Vec<i64> {
+        parts.iter().map(|part| part.minor_units).collect()
+    }
+
+    #[test]
+    fn ten_dollars_in_three_adds_up_to_ten() {
+        let parts = Money::new(1000, "USD").allocate(&[1, 1, 1]).unwrap();
+        assert_eq!(units(&parts), vec![334, 333, 333]);
+        assert_eq!(parts.iter().map(|part| part.minor_units).sum::<i64>(), 1000);
+        assert!(parts.iter().all(|part| part.currency == "USD"));
+    }
+
+    #[test]
+    fn leftover_units_go_to_the_largest_remainders() {
+        // 100 split 1:2:3 is 16.67, 33.33, 50: the one leftover unit goes to the first
+        assert_eq!(units(&Money::new(100, "USD").allocate(&[1, 2, 3]).unwrap()), vec![17, 33, 50]);
+        // Equal remainders break towards the earlier weight
+        assert_eq!(units(&Money::new(5, "USD").allocate(&[1, 1, 1, 1]).unwrap()), vec![2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn allocate_keeps_the_sign_and_skips_zero_weights() {
+        assert_eq!(units(&Money::new(-1000, "USD").allocate(&[1, 1, 1]).unwrap()), vec![-334, -333, -333]);
+        assert_eq!(units(&Money::new(1000, "USD").allocate(&[0, 1, 1]).unwrap()), vec![0, 500, 500]);
+        assert!(Money::new(1000, "USD").allocate(&[0, 0]).is_err());
+        assert!(Money::new(1000, "USD").allocate(&[]).is_err());
+    }
+
+    #[test]
+    fn from_major_uses_the_currency_exponent() {
+        assert_eq!(Money::from_major(12.34, "USD").unwrap().minor_units, 1234);
+        assert_eq!(Money::from_major(1200.0, "JPY").unwrap().minor_units, 1200);
+        assert_eq!(Money::from_major(1.234, "BHD").unwrap().minor_units, 1234);
+        assert_eq!(Money::from_major(0.1 + 0.2, "USD").unwrap().minor_units, 30);
+    }
+
+    #[test]
+    fn from_major_refuses_finer_amounts_than_the_currency_allows() {
+        assert!(Money::from_major(10.005, "USD").is_err());
+        assert!(Money::from_major(12.5, "JPY").is_err());
+        assert!(Money::from_major(1.2345, "BHD").is_err());
+        assert!(Money::from_major(f64::NAN, "USD").is_err());
+        assert!(Money::from_major(f64::INFINITY, "USD").is_err());
+        assert!(Money::from_major(1e15, "USD").is_err());
+    }
+
+    #[test]
+    fn displays_in_major_units() {
+        assert_eq!(Money::new(1250, "EUR").to_string(), "12.50 EUR");
+        assert_eq!(Money::new(1200, "JPY").to_string(), "1200 JPY");
+        assert_eq!(Money::new(1234, "BHD").to_string(), "1.234 BHD");
+        assert_eq!(Money::new(-5, "USD").to_string(), "-0.05 USD");
+        assert_eq!(Money::new(1234, "BHD").to_major(), 1.234);
+    }
+
+    #[test]
+    fn convert_rounds_into_the_target_minor_unit() {
+        // 10.00 USD at 150 JPY per dollar is 1500 yen
+        assert_eq!(Money::new(1000, "USD").convert(150.0, "JPY").unwrap(), Money::new(1500, "JPY"));
+        // 1500 yen at 0.0067 dollars per yen is 10.05 USD
+        assert_eq!(Money::new(1500, "JPY").convert(0.0067, "USD").unwrap(), Money::new(1005, "USD"));
+        assert!(Money::new(1000, "USD").convert(0.0, "EUR").is_err());
+    }
+
+    #[test]
+    fn checked_add_needs_the_same_currency() {
+        assert_eq!(Money::new(1, "USD").checked_add(&Money::new(2, "USD")).unwrap(), Money::new(3, "USD"));
+        assert!(Money::new(1, "USD").checked_add(&Money::new(2, "EUR")).is_err());
+    }
+}
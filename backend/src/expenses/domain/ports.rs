@@ -1,6 +1,8 @@
 use async_trait::async_trait;
+use chrono::NaiveDate;
 use uuid::Uuid;
-use super::expense::{Expense, ExpenseShare, ExpenseInfo, UserBalance, GroupBalance, DebtSummary, Payment, ExpenseFilter};
+use super::expense::{Expense, ExpenseShare, ExpenseInfo, UserBalance, GroupBalance, DebtSummary, Payment, PaymentAllocation, ExpenseFilter};
+use super::revision::ExpenseRevision;
 use std::error::Error;
 
 #[async_trait]
@@ -11,6 +13,11 @@ pub trait ExpenseRepository: Send + Sync {
     async fn delete_expense(&self, expense_id: &Uuid) -> Result<(), Box<dyn Error>>;
     async fn get_expenses(&self, filter: &ExpenseFilter) -> Result<Vec<ExpenseInfo>, Box<dyn Error>>;
     async fn get_group_expenses(&self, group_id: &Uuid, limit: Option<usize>, offset: Option<usize>) -> Result<Vec<ExpenseInfo>, Box<dyn Error>>;
+    // Saves an edit in one step: the expense, its new shares in place of the old ones and the
+    // revision recording the edit, numbered one past the expense's latest. Returns that number.
+    async fn save_edit(&self, expense: &Expense, shares: &[ExpenseShare], revision: &ExpenseRevision) -> Result<u32, Box<dyn Error>>;
+    // Oldest first
+    async fn get_revisions(&self, expense_id: &Uuid) -> Result<Vec<ExpenseRevision>, Box<dyn Error>>;
 }
 
 #[async_trait]
@@ -20,6 +27,9 @@ pub trait ExpenseShareRepository: Send + Sync {
     async fn update_share(&self, share: &ExpenseShare) -> Result<(), Box<dyn Error>>;
     async fn delete_expense_shares(&self, expense_id: &Uuid) -> Result<(), Box<dyn Error>>;
     async fn get_user_shares(&self, user_id: &Uuid, group_id: Option<&Uuid>) -> Result<Vec<ExpenseShare>, Box<dyn Error>>;
+    // Shares in the group not yet paid back in full, with their expense, oldest expense first.
+    // A payer's share of their own expense is never owed to anyone and is left out.
+    async fn get_unsettled_shares(&self, group_id: &Uuid) -> Result<Vec<(Expense, ExpenseShare)>, Box<dyn Error>>;
 }
 
 #[async_trait]
@@ -33,6 +43,16 @@ pub trait BalanceRepository: Send + Sync {
 #[async_trait]
 pub trait PaymentRepository: Send + Sync {
     async fn create_payment(&self, payment: &Payment) -> Result<(), Box<dyn Error>>;
+    async fn get_payment(&self, payment_id: &Uuid) -> Result<Option<Payment>, Box<dyn Error>>;
+    async fn delete_payment(&self, payment_id: &Uuid) -> Result<(), Box<dyn Error>>;
+    async fn set_allocations(&self, payment_id: &Uuid, allocations: &[PaymentAllocation]) -> Result<(), Box<dyn Error>>;
     async fn get_group_payments(&self, group_id: &Uuid) -> Result<Vec<Payment>, Box<dyn Error>>;
     async fn get_user_payments(&self, user_id: &Uuid) -> Result<Vec<Payment>, Box<dyn Error>>;
-}
\ No newline at end of file
+}
+
+// Source of exchange rates for converting expenses into a group's base currency
+#[async_trait]
+pub trait ExchangeRateProvider: Send + Sync {
+    // Units of `to` per unit of `from` on `on`; an error when no rate is known
+    async fn rate(&self, from: &str, to: &str, on: NaiveDate) -> Result<f64, Box<dyn Error>>;
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use super::expense::{Expense, ExpenseShare};
+
+// Refused edit of an expense that payments already paid off in part; with confirmation what
+// they paid is moved onto the new shares
+pub const SETTLED_EXPENSE_EDIT: &str = "Payments have already been made towards this expense; set confirm_settled to edit it anyway";
+
+// The parts of an expense an edit can change. Amounts are minor units of `currency`, the way
+// they are stored. Split types are not stored, so a new split shows up as changed shares.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExpenseState {
+    pub description: String,
+    pub amount: i64,
+    pub currency: String,
+    pub paid_by: Uuid,
+    pub category: Option<String>,
+    pub date: DateTime<Utc>,
+    pub shares: BTreeMap<Uuid, i64>, // user -> share, in the expense's currency
+}
+
+impl ExpenseState {
+    pub fn new(expense: &Expense, shares: &[ExpenseShare]) -> Self {
+        Self {
+            description: expense.description.clone(),
+            amount: expense.amount.minor_units,
+            currency: expense.amount.currency.clone(),
+            paid_by: expense.paid_by,
+            category: expense.category.clone().filter(|category| !category.is_empty()),
+            date: expense.date,
+            shares: shares.iter().map(|share| (share.user_id, share.amount.minor_units)).collect(),
+        }
+    }
+
+    // What differs from `before`, one entry per field, in a fixed order
+    pub fn changes_since(&self, before: &ExpenseState) -> Vec<FieldChange> {
+        let fields = [
+            ("description", json!(before.description), json!(self.description)),
+            ("amount", json!(before.amount), json!(self.amount)),
+            ("currency", json!(before.currency), json!(self.currency)),
+            ("paid_by", json!(before.paid_by), json!(self.paid_by)),
+            ("category", json!(before.category), json!(self.category)),
+            ("date", json!(before.date), json!(self.date)),
+            ("shares", json!(before.shares), json!(self.shares)),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, before, after)| before != after)
+            .map(|(field, before, after)| FieldChange { field: field.to_string(), before, after })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+// One edit of an expense: who made it, when, and what it changed. Revisions are numbered
+// from 1 per expense and never rewritten.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExpenseRevision {
+    pub id: Uuid,
+    pub expense_id: Uuid,
+    pub revision: u32,
+    pub edited_by: Uuid,
+    pub edited_by_name: String,
+    pub edited_at: DateTime<Utc>,
+    pub changes: Vec<FieldChange>,
+}
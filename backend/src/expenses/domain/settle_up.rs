@@ -0,0 +1,114 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::expense::{ExpenseShare, PaymentAllocation};
+use super::money::Money;
+
+// One payment in a settle-up plan, in minor units of the group's base currency
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: Uuid,
+    pub to: Uuid,
+    pub amount: i64,
+}
+
+// Largest amount first; ties by user id so the same balances always give the same plan
+fn by_amount_desc(a: &(Uuid, i64), b: &(Uuid, i64)) -> Ordering {
+    b.1.cmp(&a.1).then(a.0.cmp(&b.0))
+}
+
+// Few payments that bring every net balance (positive = owed money) to zero. The true
+// minimum is NP-hard to find, so this pairs up debtors and creditors whose amounts match
+// exactly first, then has the largest debtor pay the largest creditor until everyone is
+// square. For n people who are not square that is at most n - 1 payments.
+pub fn simplify_debts(balances: &HashMap<Uuid, i64>) -> Vec<Transfer> {
+    let mut debtors: Vec<(Uuid, i64)> = balances.iter().filter(|(_, b)| **b < 0).map(|(u, b)| (*u, -*b)).collect();
+    let mut creditors: Vec<(Uuid, i64)> = balances.iter().filter(|(_, b)| **b > 0).map(|(u, b)| (*u, *b)).collect();
+    debtors.sort_by(by_amount_desc);
+    creditors.sort_by(by_amount_desc);
+
+    // An exact match settles two people with one payment
+    let mut transfers = Vec::new();
+    for debtor in debtors.iter_mut() {
+        if let Some(creditor) = creditors.iter_mut().find(|creditor| creditor.1 == debtor.1) {
+            transfers.push(Transfer { from: debtor.0, to: creditor.0, amount: debtor.1 });
+            creditor.1 = 0;
+            debtor.1 = 0;
+        }
+    }
+    debtors.retain(|debtor| debtor.1 > 0);
+    creditors.retain(|creditor| creditor.1 > 0);
+
+    while !debtors.is_empty() && !creditors.is_empty() {
+        let amount = debtors[0].1.min(creditors[0].1);
+        transfers.push(Transfer { from: debtors[0].0, to: creditors[0].0, amount });
+        debtors[0].1 -= amount;
+        creditors[0].1 -= amount;
+        debtors.retain(|debtor| debtor.1 > 0);
+        creditors.retain(|creditor| creditor.1 > 0);
+        debtors.sort_by(by_amount_desc);
+        creditors.sort_by(by_amount_desc);
+    }
+    transfers
+}
+
+// Debts as they were run up: everyone pays back the people who paid for them.
+// `owed[(debtor, creditor)]` is what one owes the other directly, and the two directions of
+// a pair cancel out. Whatever `balances` holds beyond those pairs, such as balances carried
+// in from a closed ledger, has no counterpart and is settled with `simplify_debts`.
+pub fn pairwise_debts(owed: &HashMap<(Uuid, Uuid), i64>, balances: &HashMap<Uuid, i64>) -> Vec<Transfer> {
+    let mut net: HashMap<(Uuid, Uuid), i64> = HashMap::new();
+    for ((debtor, creditor), amount) in owed {
+        if debtor == creditor {
+            continue;
+        }
+        // Keyed by the lower id first; positive means the lower id owes the higher one
+        if debtor < creditor {
+            *net.entry((*debtor, *creditor)).or_insert(0) += amount;
+        } else {
+            *net.entry((*creditor, *debtor)).or_insert(0) -= amount;
+        }
+    }
+
+    let mut transfers: Vec<Transfer> = net
+        .into_iter()
+        .filter(|(_, amount)| *amount != 0)
+        .map(|((low, high), amount)| {
+            if amount > 0 {
+                Transfer { from: low, to: high, amount }
+            } else {
+                Transfer { from: high, to: low, amount: -amount }
+            }
+        })
+        .collect();
+    transfers.sort_by(|a, b| b.amount.cmp(&a.amount).then(a.from.cmp(&b.from)).then(a.to.cmp(&b.to)));
+
+    let mut residual = balances.clone();
+    for transfer in &transfers {
+        *residual.entry(transfer.to).or_insert(0) -= transfer.amount;
+        *residual.entry(transfer.from).or_insert(0) += transfer.amount;
+    }
+    transfers.extend(simplify_debts(&residual));
+    transfers
+}
+
+// Books a payment, in the group's base currency, against the debtor's shares of what the
+// creditor paid for. `shares` must be oldest first; each is paid off in full before the next
+// one is touched, so a partial payment leaves only the newest share part-settled. Whatever is
+// left once every share is covered stays on the balance as credit.
+pub fn allocate_payment(amount: &Money, shares: &[ExpenseShare]) -> Vec<PaymentAllocation> {
+    let mut remaining = amount.minor_units;
+    let mut allocations = Vec::new();
+    for share in shares {
+        if remaining <= 0 {
+            break;
+        }
+        let applied = share.outstanding().min(remaining);
+        if applied > 0 {
+            allocations.push(PaymentAllocation { expense_id: share.expense_id, amount: Money::new(applied, amount.currency.clone()) });
+            remaining -= applied;
+        }
+    }
+    allocations
+}
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use std::error::Error;
+use std::sync::Arc;
+use uuid::Uuid;
+use worker::D1Database;
+use worker::send::{SendFuture, SendWrapper};
+
+use crate::expenses::domain::expense::{DebtSummary, GroupBalance};
+use crate::expenses::domain::ports::{BalanceRepository, ExchangeRateProvider};
+use crate::expenses::infrastructure::DirectD1ExpenseService;
+use crate::groups::application::authorization::GroupAuthorizer;
+
+// Port adapter for services that hold repositories rather than a raw D1 handle. Balances and
+// settle-up plans come from the direct service, so both paths agree to the minor unit.
+pub struct D1BalanceRepository {
+    service: SendWrapper<DirectD1ExpenseService>,
+}
+
+impl D1BalanceRepository {
+    pub fn new(db: D1Database, authorizer: GroupAuthorizer, rates: Arc<dyn ExchangeRateProvider>) -> Self {
+        Self { service: SendWrapper::new(DirectD1ExpenseService::new(db, authorizer, rates)) }
+    }
+}
+
+#[async_trait]
+impl BalanceRepository for D1BalanceRepository {
+    async fn calculate_group_balances(&self, group_id: &Uuid) -> Result<GroupBalance, Box<dyn Error>> {
+        let balance = SendFuture::new(self.service.calculate_group_balances(group_id))
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+        Ok(balance)
+    }
+
+    async fn calculate_user_balance(&self, user_id: &Uuid, group_id: &Uuid) -> Result<f64, Box<dyn Error>> {
+        let group_balance = self.calculate_group_balances(group_id).await?;
+        Ok(group_balance
+            .balances
+            .iter()
+            .find(|balance| balance.user_id == *user_id)
+            .map(|balance| balance.net_balance)
+            .unwrap_or(0.0))
+    }
+
+    async fn get_debt_summary(&self, group_id: &Uuid) -> Result<Vec<DebtSummary>, Box<dyn Error>> {
+        let plan = SendFuture::new(self.service.settle_up_plan(group_id))
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+        Ok(plan.debts)
+    }
+
+    async fn get_user_debts(&self, user_id: &Uuid) -> Result<Vec<DebtSummary>, Box<dyn Error>> {
+        let debts = SendFuture::new(self.service.user_debts(user_id))
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+        Ok(debts)
+    }
+}
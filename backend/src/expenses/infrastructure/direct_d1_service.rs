@@ -1,85 +1,201 @@
-use worker::{D1Database, Error as WorkerError};
+use worker::{console_log, D1Database, D1PreparedStatement, Error as WorkerError};
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
+use crate::activity::domain::activity::{ActivityKind, NewActivity};
+use crate::activity::infrastructure::record_activity;
+use crate::error::ServiceError;
+use crate::profiles::domain::profile::normalize_currency;
+use crate::profiles::infrastructure::{display_name, load_display_names};
+use crate::groups::domain::ledger::{ledger_transfers, merge_balances, ClosedLedger};
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::groups::domain::permissions::GroupAction;
+use crate::groups::domain::settings::{GroupModule, GroupSettings};
+use crate::groups::infrastructure::{
+    close_ledger, ledger_link, ledger_transfer_totals, load_group_settings, open_child_ledgers, require_module,
+};
+use crate::expenses::domain::exchange::{snapshot_rate, RateSnapshot};
 use crate::expenses::domain::expense::{
-    Expense, ExpenseInfo, ExpenseCreation, ExpenseShare, Payment, UserBalance, GroupBalance, SettleDebt, SplitType,
+    split_shares, validate_expense, ConvertedAmount, CurrencyBalance, DebtSummary, Expense, ExpenseInfo, ExpenseCreation, ExpenseShare, ExpenseUpdate,
+    Payment, PaymentAllocation, SplitType, UserBalance, GroupBalance, SettleDebt, SettleUpPlan, UnsettledShare,
 };
-use crate::auth::infrastructure::PersistentMemoryUserRepository;
-use crate::auth::domain::ports::UserRepository;
+use crate::expenses::domain::money::{minor_to_major, Money};
+use crate::expenses::domain::ports::ExchangeRateProvider;
+use crate::expenses::domain::revision::{ExpenseRevision, ExpenseState, SETTLED_EXPENSE_EDIT};
+use crate::expenses::domain::settle_up::{allocate_payment, pairwise_debts, simplify_debts};
+
+// Outcome of one catch_up_books sweep
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CatchUpSummary {
+    pub caught_up: u64,
+    pub failed: u64, // left as they were; the next sweep tries them again
+}
 
 pub struct DirectD1ExpenseService {
     db: D1Database,
-    user_repo: PersistentMemoryUserRepository,
+    authorizer: GroupAuthorizer,
+    rates: Arc<dyn ExchangeRateProvider>,
 }
 
 impl DirectD1ExpenseService {
-    pub fn new(db: D1Database) -> Self {
-        Self {
-            db,
-            user_repo: PersistentMemoryUserRepository::new(),
+    pub fn new(db: D1Database, authorizer: GroupAuthorizer, rates: Arc<dyn ExchangeRateProvider>) -> Self {
+        Self { db, authorizer, rates }
+    }
+
+    fn parse_uuid(value: &Value) -> Result<Uuid, ServiceError> {
+        Uuid::parse_str(value.as_str().unwrap_or(""))
+            .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))
+    }
+
+    // Amounts are INTEGER minor units; D1 can still hand them back as floats
+    fn parse_minor(value: &Value) -> i64 {
+        value.as_i64().or_else(|| value.as_f64().map(|v| v.round() as i64)).unwrap_or(0)
+    }
+
+    fn parse_datetime(value: &Value) -> Result<DateTime<Utc>, ServiceError> {
+        Ok(DateTime::parse_from_rfc3339(value.as_str().unwrap_or(""))
+            .map_err(|e| ServiceError::Internal(format!("Date parse error: {}", e)))?
+            .with_timezone(&Utc))
+    }
+
+    async fn get_group_name(&self, group_id: &Uuid) -> Result<String, ServiceError> {
+        let stmt = self.db.prepare("SELECT name FROM groups WHERE id = ?1");
+        let result = stmt.bind(&[group_id.to_string().into()])?.first::<Value>(None).await?;
+
+        Ok(result
+            .and_then(|row| row["name"].as_str().map(|name| name.to_string()))
+            .unwrap_or_else(|| "Unknown Group".to_string()))
+    }
+
+    // Group and creator of an expense, for permission checks
+    async fn expense_owner(&self, expense_id: &Uuid) -> Result<Option<(Uuid, Uuid)>, ServiceError> {
+        let stmt = self.db.prepare("SELECT group_id, created_by FROM expenses WHERE id = ?1");
+        match stmt.bind(&[expense_id.to_string().into()])?.first::<Value>(None).await? {
+            Some(row) => Ok(Some((Self::parse_uuid(&row["group_id"])?, Self::parse_uuid(&row["created_by"])?))),
+            None => Ok(None),
         }
     }
 
-    async fn get_username(&self, user_id: &Uuid) -> String {
-        match self.user_repo.get_user_by_id(user_id).await {
-            Ok(Some(user)) => user.username,
-            Ok(None) => format!("Unknown User ({})", user_id),
-            Err(_) => format!("Error loading user ({})", user_id),
+    // Expense rows plus their share rows -> DTOs, with every name resolved in one lookup
+    async fn build_expense_infos(&self, expense_rows: Vec<Value>, share_rows: Vec<Value>) -> Result<Vec<ExpenseInfo>, ServiceError> {
+        let mut user_ids = Vec::new();
+        let mut shares_by_expense: HashMap<Uuid, Vec<(Uuid, Value)>> = HashMap::new();
+        for row in share_rows {
+            let expense_id = Self::parse_uuid(&row["expense_id"])?;
+            let user_id = Self::parse_uuid(&row["user_id"])?;
+            user_ids.push(user_id);
+            shares_by_expense.entry(expense_id).or_default().push((user_id, row));
         }
+        for row in &expense_rows {
+            user_ids.push(Self::parse_uuid(&row["paid_by"])?);
+            user_ids.push(Self::parse_uuid(&row["created_by"])?);
+        }
+
+        let names = load_display_names(&self.db, &user_ids).await?;
+
+        let mut expense_infos = Vec::with_capacity(expense_rows.len());
+        for row in expense_rows {
+            let expense_id = Self::parse_uuid(&row["id"])?;
+            let paid_by = Self::parse_uuid(&row["paid_by"])?;
+            let created_by = Self::parse_uuid(&row["created_by"])?;
+            let currency = row["currency"].as_str().unwrap_or("USD").to_string();
+            let base_currency = row["base_currency"].as_str().unwrap_or(&currency).to_string();
+
+            let shares = shares_by_expense
+                .remove(&expense_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(user_id, share)| crate::expenses::domain::expense::ExpenseShareInfo {
+                    user_id,
+                    username: display_name(&names, &user_id),
+                    amount: minor_to_major(Self::parse_minor(&share["amount"]), &currency),
+                    settled_amount: minor_to_major(Self::parse_minor(&share["settled_amount"]), &base_currency),
+                    is_settled: share["is_settled"].as_i64().unwrap_or(0) != 0,
+                })
+                .collect();
+
+            let converted = match row["base_currency"].as_str() {
+                Some(base_currency) if !row["base_amount"].is_null() => Some(ConvertedAmount {
+                    amount: minor_to_major(Self::parse_minor(&row["base_amount"]), base_currency),
+                    currency: base_currency.to_string(),
+                    rate: row["exchange_rate"].as_f64().unwrap_or(1.0),
+                }),
+                _ => None,
+            };
+
+            expense_infos.push(ExpenseInfo {
+                id: expense_id,
+                group_id: Self::parse_uuid(&row["group_id"])?,
+                description: row["description"].as_str().unwrap_or("").to_string(),
+                amount: minor_to_major(Self::parse_minor(&row["amount"]), &currency),
+                currency,
+                converted,
+                paid_by,
+                created_by,
+                category: Some(row["category"].as_str().unwrap_or("").to_string()),
+                date: Self::parse_datetime(&row["date"])?,
+                paid_by_name: display_name(&names, &paid_by),
+                created_by_name: display_name(&names, &created_by),
+                shares,
+                created_at: Self::parse_datetime(&row["created_at"])?,
+            });
+        }
+
+        Ok(expense_infos)
     }
 
-    pub async fn create_expense(&self, expense: &Expense) -> Result<(), WorkerError> {
-        let stmt = self.db.prepare("INSERT INTO expenses (id, group_id, description, amount, currency, paid_by, created_by, category, date, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)");
+    fn insert_expense(&self, expense: &Expense) -> Result<D1PreparedStatement, WorkerError> {
+        let stmt = self.db.prepare("INSERT INTO expenses (id, group_id, description, amount, currency, exchange_rate, base_currency, base_amount, paid_by, created_by, category, date, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)");
         
         stmt.bind(&[
             expense.id.to_string().into(),
             expense.group_id.to_string().into(),
             expense.description.clone().into(),
-            expense.amount.into(),
-            expense.currency.clone().into(),
+            (expense.amount.minor_units as f64).into(),
+            expense.amount.currency.clone().into(),
+            expense.rate_snapshot.rate.into(),
+            expense.rate_snapshot.base_amount.currency.clone().into(),
+            (expense.rate_snapshot.base_amount.minor_units as f64).into(),
             expense.paid_by.to_string().into(),
             expense.created_by.to_string().into(),
             expense.category.clone().unwrap_or_default().into(),
             expense.date.to_rfc3339().into(),
             expense.created_at.to_rfc3339().into(),
             expense.updated_at.to_rfc3339().into(),
-        ])?
-        .run()
-        .await?;
-
-        Ok(())
+        ])
     }
 
-    pub async fn create_shares(&self, shares: &[ExpenseShare]) -> Result<(), WorkerError> {
-        for share in shares {
-            let stmt = self.db.prepare("INSERT INTO expense_shares (expense_id, user_id, amount, is_settled) VALUES (?1, ?2, ?3, ?4)");
-            
-            stmt.bind(&[
-                share.expense_id.to_string().into(),
-                share.user_id.to_string().into(),
-                share.amount.into(),
-                (share.is_settled as i32).into(),
-            ])?
-            .run()
-            .await?;
-        }
+    fn insert_share(&self, share: &ExpenseShare) -> Result<D1PreparedStatement, WorkerError> {
+        let stmt = self.db.prepare("INSERT INTO expense_shares (expense_id, user_id, amount, base_amount, settled_amount, is_settled) VALUES (?1, ?2, ?3, ?4, ?5, ?6)");
 
-        Ok(())
+        stmt.bind(&[
+            share.expense_id.to_string().into(),
+            share.user_id.to_string().into(),
+            (share.amount.minor_units as f64).into(),
+            (share.base_amount.minor_units as f64).into(),
+            (share.settled_amount.minor_units as f64).into(),
+            (share.is_settled as i32).into(),
+        ])
     }
 
-    pub async fn create_payment(&self, payment: &Payment) -> Result<(), WorkerError> {
-        let stmt = self.db.prepare("INSERT INTO payments (id, group_id, from_user, to_user, amount, currency, description, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)");
+    pub async fn create_payment(&self, payment: &Payment) -> Result<(), ServiceError> {
+        let stmt = self.db.prepare("INSERT INTO payments (id, group_id, from_user, to_user, amount, currency, exchange_rate, base_currency, base_amount, description, recorded_by, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)");
         
         stmt.bind(&[
             payment.id.to_string().into(),
             payment.group_id.to_string().into(),
             payment.from_user.to_string().into(),
             payment.to_user.to_string().into(),
-            payment.amount.into(),
-            payment.currency.clone().into(),
+            (payment.amount.minor_units as f64).into(),
+            payment.amount.currency.clone().into(),
+            payment.rate_snapshot.rate.into(),
+            payment.rate_snapshot.base_amount.currency.clone().into(),
+            (payment.rate_snapshot.base_amount.minor_units as f64).into(),
             payment.description.clone().into(),
+            payment.recorded_by.to_string().into(),
             payment.created_at.to_rfc3339().into(),
         ])?
         .run()
@@ -88,309 +204,1085 @@ impl DirectD1ExpenseService {
         Ok(())
     }
 
-    pub async fn get_group_expenses(&self, group_id: &Uuid) -> Result<Vec<ExpenseInfo>, WorkerError> {
+    pub async fn get_group_expenses(&self, group_id: &Uuid) -> Result<Vec<ExpenseInfo>, ServiceError> {
         let stmt = self.db.prepare("SELECT * FROM expenses WHERE group_id = ?1 ORDER BY created_at DESC");
-        let results = stmt.bind(&[group_id.to_string().into()])?.all().await?;
+        let expense_rows = stmt.bind(&[group_id.to_string().into()])?.all().await?.results::<Value>()?;
 
-        let mut expense_infos = Vec::new();
-        
-        // Using the worker v0.6.0 API
-        for row in results.results::<Value>()? {
-            let expense_id = Uuid::parse_str(row["id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let paid_by = Uuid::parse_str(row["paid_by"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let created_by = Uuid::parse_str(row["created_by"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
+        // All shares of the group in one query rather than one per expense
+        let share_stmt = self.db.prepare("SELECT es.* FROM expense_shares es JOIN expenses e ON es.expense_id = e.id WHERE e.group_id = ?1");
+        let share_rows = share_stmt.bind(&[group_id.to_string().into()])?.all().await?.results::<Value>()?;
 
-            let paid_by_name = self.get_username(&paid_by).await;
-            let created_by_name = self.get_username(&created_by).await;
+        self.build_expense_infos(expense_rows, share_rows).await
+    }
 
-            // Get shares for this expense
-            let shares = self.get_expense_shares(&expense_id).await?;
+    pub async fn get_expense_shares(&self, expense_id: &Uuid) -> Result<Vec<crate::expenses::domain::expense::ExpenseShareInfo>, ServiceError> {
+        let stmt = self.db.prepare(
+            "SELECT es.*, e.currency, e.base_currency FROM expense_shares es JOIN expenses e ON e.id = es.expense_id WHERE es.expense_id = ?1",
+        );
+        let rows = stmt.bind(&[expense_id.to_string().into()])?.all().await?.results::<Value>()?;
 
-            expense_infos.push(ExpenseInfo {
-                id: expense_id,
-                group_id: *group_id,
-                description: row["description"].as_str().unwrap_or("").to_string(),
-                amount: row["amount"].as_f64().unwrap_or(0.0),
-                currency: row["currency"].as_str().unwrap_or("USD").to_string(),
-                paid_by,
-                created_by,
-                category: Some(row["category"].as_str().unwrap_or("").to_string()),
-                date: DateTime::parse_from_rfc3339(row["date"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                paid_by_name,
-                created_by_name,
-                shares,
-                created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-            });
+        let mut user_ids = Vec::with_capacity(rows.len());
+        for row in &rows {
+            user_ids.push(Self::parse_uuid(&row["user_id"])?);
         }
+        let names = load_display_names(&self.db, &user_ids).await?;
 
-        Ok(expense_infos)
+        Ok(rows
+            .iter()
+            .zip(user_ids)
+            .map(|(row, user_id)| crate::expenses::domain::expense::ExpenseShareInfo {
+                user_id,
+                username: display_name(&names, &user_id),
+                amount: minor_to_major(Self::parse_minor(&row["amount"]), row["currency"].as_str().unwrap_or("USD")),
+                settled_amount: minor_to_major(
+                    Self::parse_minor(&row["settled_amount"]),
+                    row["base_currency"].as_str().or(row["currency"].as_str()).unwrap_or("USD"),
+                ),
+                is_settled: row["is_settled"].as_i64().unwrap_or(0) != 0,
+            })
+            .collect())
     }
 
-    pub async fn get_expense_shares(&self, expense_id: &Uuid) -> Result<Vec<crate::expenses::domain::expense::ExpenseShareInfo>, WorkerError> {
-        let stmt = self.db.prepare("SELECT * FROM expense_shares WHERE expense_id = ?1");
-        let results = stmt.bind(&[expense_id.to_string().into()])?.all().await?;
+    pub async fn calculate_group_balances(&self, group_id: &Uuid) -> Result<GroupBalance, ServiceError> {
+        let settings = load_group_settings(&self.db, group_id).await?;
+        let balances_map = self.net_balances(group_id).await?;
+        self.group_balance(group_id, &settings.default_currency, balances_map, Vec::new()).await
+    }
 
-        let mut shares = Vec::new();
-        for row in results.results::<Value>()? {
-            let user_id = Uuid::parse_str(row["user_id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let username = self.get_username(&user_id).await;
+    // Brings the group's books up to date: snapshots what lacks a rate in the base currency,
+    // then books payments still waiting. Only writes and the hourly catch-up call this, so
+    // reading balances never writes to the database or fetches rates.
+    pub async fn update_books(&self, group_id: &Uuid, settings: &GroupSettings) -> Result<(), ServiceError> {
+        self.refresh_rate_snapshots(group_id, settings).await?;
+        self.apply_pending_settlements(group_id, settings).await
+    }
 
-            shares.push(crate::expenses::domain::expense::ExpenseShareInfo {
-                user_id,
-                username,
-                amount: row["amount"].as_f64().unwrap_or(0.0),
-                is_settled: row["is_settled"].as_i64().unwrap_or(0) != 0,
-            });
+    // Brings the books of every group up to date that still has expenses or payments without
+    // a snapshot in its base currency, or payments not booked yet. Those are rows from before
+    // snapshots were recorded that the migrations could not convert; the hourly sweep handles
+    // them so balances do not wait for the group's next write. A group that fails is logged
+    // and skipped so it cannot hold up the others.
+    pub async fn catch_up_books(&self) -> Result<CatchUpSummary, ServiceError> {
+        let rows = self
+            .db
+            .prepare(
+                "SELECT g.id FROM groups g LEFT JOIN group_settings s ON s.group_id = g.id
+                 WHERE g.deleted_at IS NULL AND (
+                     EXISTS (SELECT 1 FROM expenses e WHERE e.group_id = g.id
+                             AND (e.base_amount IS NULL OR e.base_currency IS NOT COALESCE(s.default_currency, 'USD')))
+                     OR EXISTS (SELECT 1 FROM payments p WHERE p.group_id = g.id
+                                AND (p.allocated = 0 OR p.base_amount IS NULL OR p.base_currency IS NOT COALESCE(s.default_currency, 'USD'))))",
+            )
+            .all()
+            .await?
+            .results::<Value>()?;
+
+        let mut summary = CatchUpSummary::default();
+        for row in rows {
+            let Some(group_id) = row["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else {
+                continue;
+            };
+            let result = match load_group_settings(&self.db, &group_id).await {
+                Ok(settings) => self.update_books(&group_id, &settings).await,
+                Err(e) => Err(e.into()),
+            };
+            match result {
+                Ok(()) => summary.caught_up += 1,
+                Err(e) => {
+                    console_log!("Failed to catch up the books of group {}: {}", group_id, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    // Gives every expense and payment that lacks a snapshot in the group's base currency one,
+    // at the rate of its own day in the group's timezone. That covers rows from before rates
+    // were recorded and a change of the group's currency; existing snapshots are never redone.
+    // Every rate is fetched before anything is written, and the new snapshots land in one batch.
+    async fn refresh_rate_snapshots(&self, group_id: &Uuid, settings: &GroupSettings) -> Result<(), ServiceError> {
+        let base_currency = settings.default_currency.as_str();
+
+        let expense_rows = self
+            .db
+            .prepare("SELECT id, amount, currency, base_currency, date FROM expenses WHERE group_id = ?1 AND (base_amount IS NULL OR base_currency IS NOT ?2)")
+            .bind(&[group_id.to_string().into(), base_currency.into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+        let payment_rows = self
+            .db
+            .prepare("SELECT id, amount, currency, base_currency, created_at FROM payments WHERE group_id = ?1 AND (base_amount IS NULL OR base_currency IS NOT ?2)")
+            .bind(&[group_id.to_string().into(), base_currency.into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+        if expense_rows.is_empty() && payment_rows.is_empty() {
+            return Ok(());
+        }
+
+        // Settled parts of shares are kept in the old base currency; take every payment back
+        // off the shares, in the same batch, so apply_pending_settlements books them again in
+        // the new one
+        let mut statements = Vec::new();
+        if expense_rows.iter().chain(&payment_rows).any(|row| !row["base_currency"].is_null()) {
+            statements.extend(self.unallocate_statements(group_id)?);
+        }
+        for row in expense_rows {
+            let expense_id = row["id"].as_str().unwrap_or("").to_string();
+            let amount = Money::new(Self::parse_minor(&row["amount"]), row["currency"].as_str().unwrap_or(base_currency));
+            let on = settings.local_date(Self::parse_datetime(&row["date"])?);
+            let snapshot = self.snapshot(&amount, base_currency, on).await?;
+
+            let share_rows = self
+                .db
+                .prepare("SELECT user_id, amount FROM expense_shares WHERE expense_id = ?1 ORDER BY user_id")
+                .bind(&[expense_id.clone().into()])?
+                .all()
+                .await?
+                .results::<Value>()?;
+            let shares: Vec<Money> = share_rows
+                .iter()
+                .map(|share| Money::new(Self::parse_minor(&share["amount"]), amount.currency.clone()))
+                .collect();
+            let base_shares = if shares.is_empty() { Vec::new() } else { snapshot.share_amounts(&shares).map_err(ServiceError::Internal)? };
+
+            statements.push(
+                self.db
+                    .prepare("UPDATE expenses SET exchange_rate = ?2, base_currency = ?3, base_amount = ?4 WHERE id = ?1")
+                    .bind(&[
+                        expense_id.clone().into(),
+                        snapshot.rate.into(),
+                        base_currency.into(),
+                        (snapshot.base_amount.minor_units as f64).into(),
+                    ])?,
+            );
+            for (share, base_amount) in share_rows.iter().zip(base_shares) {
+                statements.push(
+                    self.db
+                        .prepare("UPDATE expense_shares SET base_amount = ?3 WHERE expense_id = ?1 AND user_id = ?2")
+                        .bind(&[expense_id.clone().into(), share["user_id"].as_str().unwrap_or("").into(), (base_amount.minor_units as f64).into()])?,
+                );
+            }
+        }
+
+        for row in payment_rows {
+            let amount = Money::new(Self::parse_minor(&row["amount"]), row["currency"].as_str().unwrap_or(base_currency));
+            let on = settings.local_date(Self::parse_datetime(&row["created_at"])?);
+            let snapshot = self.snapshot(&amount, base_currency, on).await?;
+            statements.push(
+                self.db
+                    .prepare("UPDATE payments SET exchange_rate = ?2, base_currency = ?3, base_amount = ?4 WHERE id = ?1")
+                    .bind(&[
+                        row["id"].as_str().unwrap_or("").into(),
+                        snapshot.rate.into(),
+                        base_currency.into(),
+                        (snapshot.base_amount.minor_units as f64).into(),
+                    ])?,
+            );
+        }
+        self.db.batch(statements).await?;
+
+        Ok(())
+    }
+
+    // Takes every payment in the group back off the shares, for apply_pending_settlements to
+    // book them all again
+    fn unallocate_statements(&self, group_id: &Uuid) -> Result<Vec<D1PreparedStatement>, WorkerError> {
+        let group_id = group_id.to_string();
+        [
+            "DELETE FROM payment_allocations WHERE payment_id IN (SELECT id FROM payments WHERE group_id = ?1)",
+            "UPDATE expense_shares SET settled_amount = 0, is_settled = 0, settled_at = NULL
+             WHERE expense_id IN (SELECT id FROM expenses WHERE group_id = ?1)",
+            "UPDATE payments SET allocated = 0 WHERE group_id = ?1",
+        ]
+        .iter()
+        .map(|query| self.db.prepare(*query).bind(&[group_id.clone().into()]))
+        .collect()
+    }
+
+    // Takes the debtor's payments to the creditor back off the debtor's shares of what the
+    // creditor paid for, for apply_pending_settlements to book again oldest first. Run whenever
+    // what one owes the other changes, so credit left over from a payment reaches shares added
+    // later and the payments after an undone one move onto the shares it had paid off.
+    fn unallocate_pair_statements(&self, group_id: &Uuid, debtor: &Uuid, creditor: &Uuid) -> Result<Vec<D1PreparedStatement>, WorkerError> {
+        let (group_id, debtor, creditor) = (group_id.to_string(), debtor.to_string(), creditor.to_string());
+        [
+            "DELETE FROM payment_allocations WHERE payment_id IN (
+                 SELECT id FROM payments WHERE group_id = ?1 AND from_user = ?2 AND to_user = ?3)",
+            "UPDATE expense_shares SET settled_amount = 0, is_settled = 0, settled_at = NULL
+             WHERE user_id = ?2 AND expense_id IN (SELECT id FROM expenses WHERE group_id = ?1 AND paid_by = ?3)",
+            "UPDATE payments SET allocated = 0 WHERE group_id = ?1 AND from_user = ?2 AND to_user = ?3",
+        ]
+        .iter()
+        .map(|query| self.db.prepare(*query).bind(&[group_id.clone().into(), debtor.clone().into(), creditor.clone().into()]))
+        .collect()
+    }
+
+    // The same for every debtor who owes `creditor` a share of an expense
+    fn unallocate_debtor_statements(&self, group_id: &Uuid, creditor: &Uuid, debtors: &[Uuid]) -> Result<Vec<D1PreparedStatement>, WorkerError> {
+        let mut statements = Vec::new();
+        for debtor in debtors.iter().filter(|debtor| *debtor != creditor) {
+            statements.extend(self.unallocate_pair_statements(group_id, debtor, creditor)?);
         }
+        Ok(statements)
+    }
 
-        Ok(shares)
+    async fn snapshot(&self, amount: &Money, base_currency: &str, on: NaiveDate) -> Result<RateSnapshot, ServiceError> {
+        snapshot_rate(self.rates.as_ref(), amount, base_currency, on)
+            .await
+            .map_err(ServiceError::from)
     }
 
-    pub async fn calculate_group_balances(&self, group_id: &Uuid) -> Result<GroupBalance, WorkerError> {
-        let mut balances_map = std::collections::HashMap::new();
+    // Per-user net balance in minor units of the group's base currency, from expenses, shares,
+    // payments and closed ledgers. Read-only: uses the snapshots update_books left behind.
+    async fn net_balances(&self, group_id: &Uuid) -> Result<HashMap<Uuid, i64>, ServiceError> {
+        let mut balances_map = HashMap::new();
 
         // Get all expenses for this group (add to paid_by user)
-        let expense_stmt = self.db.prepare("SELECT paid_by, amount FROM expenses WHERE group_id = ?1");
+        let expense_stmt = self.db.prepare("SELECT paid_by, base_amount AS amount FROM expenses WHERE group_id = ?1");
         let expense_results = expense_stmt.bind(&[group_id.to_string().into()])?.all().await?;
 
         for row in expense_results.results::<Value>()? {
             let paid_by_str = row["paid_by"].as_str().unwrap_or("");
-            let amount = row["amount"].as_f64().unwrap_or(0.0);
+            let amount = Self::parse_minor(&row["amount"]);
             
             let paid_by = Uuid::parse_str(paid_by_str)
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
+                .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))?;
             
-            *balances_map.entry(paid_by).or_insert(0.0) += amount;
+            *balances_map.entry(paid_by).or_insert(0) += amount;
         }
 
         // Get all shares for this group (subtract from user_id)
-        let share_stmt = self.db.prepare("SELECT es.user_id, es.amount FROM expense_shares es JOIN expenses e ON es.expense_id = e.id WHERE e.group_id = ?1");
+        let share_stmt = self.db.prepare("SELECT es.user_id, es.base_amount AS amount FROM expense_shares es JOIN expenses e ON es.expense_id = e.id WHERE e.group_id = ?1");
         let share_results = share_stmt.bind(&[group_id.to_string().into()])?.all().await?;
 
         for row in share_results.results::<Value>()? {
             let user_id_str = row["user_id"].as_str().unwrap_or("");
-            let amount = row["amount"].as_f64().unwrap_or(0.0);
+            let amount = Self::parse_minor(&row["amount"]);
             
             let user_id = Uuid::parse_str(user_id_str)
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
+                .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))?;
             
-            *balances_map.entry(user_id).or_insert(0.0) -= amount;
+            *balances_map.entry(user_id).or_insert(0) -= amount;
         }
 
         // Get all payments for this group (accounting for debt settlement)
-        let payment_stmt = self.db.prepare("SELECT from_user, to_user, amount FROM payments WHERE group_id = ?1");
+        let payment_stmt = self.db.prepare("SELECT from_user, to_user, base_amount AS amount FROM payments WHERE group_id = ?1");
         let payment_results = payment_stmt.bind(&[group_id.to_string().into()])?.all().await?;
 
         for row in payment_results.results::<Value>()? {
             let from_user_str = row["from_user"].as_str().unwrap_or("");
             let to_user_str = row["to_user"].as_str().unwrap_or("");
-            let amount = row["amount"].as_f64().unwrap_or(0.0);
+            let amount = Self::parse_minor(&row["amount"]);
             
             let from_user = Uuid::parse_str(from_user_str)
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
+                .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))?;
             let to_user = Uuid::parse_str(to_user_str)
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
+                .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))?;
             
             // Add to payer (reduces their debt, makes balance more positive)
-            *balances_map.entry(from_user).or_insert(0.0) += amount;
+            *balances_map.entry(from_user).or_insert(0) += amount;
             // Subtract from receiver (reduces what they're owed, makes balance less positive)
-            *balances_map.entry(to_user).or_insert(0.0) -= amount;
+            *balances_map.entry(to_user).or_insert(0) -= amount;
         }
 
-        // Convert to UserBalance vec with usernames
-        let mut balances = Vec::new();
-        for (user_id, net_balance) in balances_map {
-            let username = self.get_username(&user_id).await;
-            balances.push(UserBalance {
-                user_id,
-                username,
-                net_balance,
-            });
+        // Balances carried in from closed ledgers, or out to the parent when this is one
+        merge_balances(&mut balances_map, ledger_transfer_totals(&self.db, group_id).await?);
+
+        Ok(balances_map)
+    }
+
+    // Per-user net balance in minor units, kept apart by the currency the money moved in.
+    // Ledger transfers were made in the base currency and count there.
+    async fn net_balances_by_currency(&self, group_id: &Uuid, base_currency: &str) -> Result<BTreeMap<String, HashMap<Uuid, i64>>, ServiceError> {
+        let rows = self
+            .db
+            .prepare(
+                "SELECT paid_by AS user_id, currency, SUM(amount) AS amount FROM expenses WHERE group_id = ?1 GROUP BY paid_by, currency
+                 UNION ALL
+                 SELECT es.user_id, e.currency, -SUM(es.amount) FROM expense_shares es JOIN expenses e ON e.id = es.expense_id
+                 WHERE e.group_id = ?1 GROUP BY es.user_id, e.currency
+                 UNION ALL
+                 SELECT from_user, currency, SUM(amount) FROM payments WHERE group_id = ?1 GROUP BY from_user, currency
+                 UNION ALL
+                 SELECT to_user, currency, -SUM(amount) FROM payments WHERE group_id = ?1 GROUP BY to_user, currency",
+            )
+            .bind(&[group_id.to_string().into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+
+        let mut by_currency: BTreeMap<String, HashMap<Uuid, i64>> = BTreeMap::new();
+        for row in rows {
+            let currency = row["currency"].as_str().unwrap_or(base_currency).to_string();
+            *by_currency.entry(currency).or_default().entry(Self::parse_uuid(&row["user_id"])?).or_insert(0) += Self::parse_minor(&row["amount"]);
         }
+        merge_balances(by_currency.entry(base_currency.to_string()).or_default(), ledger_transfer_totals(&self.db, group_id).await?);
+        Ok(by_currency)
+    }
+
+    async fn group_balance(
+        &self,
+        group_id: &Uuid,
+        currency: &str,
+        balances_map: HashMap<Uuid, i64>,
+        by_currency: Vec<(String, HashMap<Uuid, i64>)>,
+    ) -> Result<GroupBalance, ServiceError> {
+        // Convert to UserBalance vecs with display names
+        let mut user_ids: Vec<Uuid> = balances_map.keys().copied().collect();
+        for (_, balances) in &by_currency {
+            user_ids.extend(balances.keys().copied());
+        }
+        let names = load_display_names(&self.db, &user_ids).await?;
+        let user_balances = |balances: HashMap<Uuid, i64>, currency: &str| -> Vec<UserBalance> {
+            balances
+                .into_iter()
+                .map(|(user_id, net_balance)| UserBalance {
+                    user_id,
+                    username: display_name(&names, &user_id),
+                    net_balance: minor_to_major(net_balance, currency),
+                })
+                .collect()
+        };
 
         Ok(GroupBalance {
             group_id: *group_id,
-            group_name: format!("Group {}", group_id), // TODO: Get actual group name
-            balances,
+            group_name: self.get_group_name(group_id).await?,
+            currency: currency.to_string(),
+            balances: user_balances(balances_map, currency),
+            by_currency: by_currency
+                .into_iter()
+                .map(|(currency, balances)| CurrencyBalance { balances: user_balances(balances, &currency), currency })
+                .collect(),
         })
     }
 
-    pub async fn delete_expense(&self, expense_id: &Uuid) -> Result<(), WorkerError> {
-        // Delete shares first (foreign key constraint)
-        let delete_shares_stmt = self.db.prepare("DELETE FROM expense_shares WHERE expense_id = ?1");
-        delete_shares_stmt.bind(&[expense_id.to_string().into()])?.run().await?;
+    pub async fn delete_expense(&self, expense_id: &Uuid, user_id: &Uuid) -> Result<(), ServiceError> {
+        let (group_id, created_by) = self.expense_owner(expense_id).await?.ok_or_else(|| ServiceError::NotFound("Expense not found".to_string()))?;
+        self.authorizer.authorize(&group_id, user_id, GroupAction::DeleteExpense, Some(&created_by)).await?;
 
-        // Delete expense
-        let delete_expense_stmt = self.db.prepare("DELETE FROM expenses WHERE id = ?1");
-        delete_expense_stmt.bind(&[expense_id.to_string().into()])?.run().await?;
+        let paid_by = self
+            .db
+            .prepare("SELECT paid_by FROM expenses WHERE id = ?1")
+            .bind(&[expense_id.to_string().into()])?
+            .first::<Value>(None)
+            .await?
+            .map(|row| Self::parse_uuid(&row["paid_by"]))
+            .transpose()?;
+        let share_rows = self
+            .db
+            .prepare("SELECT user_id FROM expense_shares WHERE expense_id = ?1")
+            .bind(&[expense_id.to_string().into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+        let mut debtors = Vec::with_capacity(share_rows.len());
+        for row in &share_rows {
+            debtors.push(Self::parse_uuid(&row["user_id"])?);
+        }
+
+        // Children go before the expense row (foreign keys), all in one batch so a failure
+        // part way never leaves shares or allocations pointing at nothing
+        let id = expense_id.to_string();
+        let mut statements = vec![
+            self.db.prepare("DELETE FROM payment_allocations WHERE expense_id = ?1").bind(&[id.clone().into()])?,
+            self.db.prepare("DELETE FROM expense_revisions WHERE expense_id = ?1").bind(&[id.clone().into()])?,
+            self.db.prepare("DELETE FROM expense_shares WHERE expense_id = ?1").bind(&[id.clone().into()])?,
+            self.db.prepare("DELETE FROM expenses WHERE id = ?1").bind(&[id.into()])?,
+        ];
+        // What payments put towards the expense goes onto the debtors' other shares of what
+        // the payer paid for, and stays on their balances as credit past those
+        let unallocate = match paid_by {
+            Some(paid_by) => self.unallocate_debtor_statements(&group_id, &paid_by, &debtors)?,
+            None => Vec::new(),
+        };
+        let rebook = !unallocate.is_empty();
+        statements.extend(unallocate);
+        self.db.batch(statements).await?;
+
+        if rebook {
+            let settings = load_group_settings(&self.db, &group_id).await?;
+            self.update_books(&group_id, &settings).await?;
+        }
 
         Ok(())
     }
 
     // Additional methods needed by handlers
-    pub async fn create_expense_from_creation(&self, creation: ExpenseCreation, created_by: Uuid) -> Result<(), WorkerError> {
+    pub async fn create_expense_from_creation(&self, creation: ExpenseCreation, created_by: Uuid) -> Result<(), ServiceError> {
+        self.authorizer.authorize(&creation.group_id, &created_by, GroupAction::CreateExpense, None).await?;
+        let settings = require_module(&self.db, &creation.group_id, GroupModule::Expenses).await?;
+        let members = self.authorizer.members_among(&creation.group_id, &creation.participants).await?;
+        validate_expense(&creation.description, &creation.paid_by, &creation.participants, &members).map_err(ServiceError::Invalid)?;
+
+        // No currency means the group's default
+        let currency = match creation.currency.trim() {
+            "" => settings.default_currency.clone(),
+            currency => normalize_currency(currency).map_err(ServiceError::Invalid)?,
+        };
+        let amount = Money::from_major(creation.amount, currency).map_err(ServiceError::Invalid)?;
+        if !amount.is_positive() {
+            return Err(ServiceError::Invalid("Expense amount must be positive".to_string()));
+        }
+
+        // Converted at the rate of the day the money was spent, not the day it was entered
+        let date = creation.date.unwrap_or_else(|| Utc::now());
+        let rate_snapshot = self.snapshot(&amount, &settings.default_currency, settings.local_date(date)).await?;
+
         let expense = Expense {
             id: Uuid::new_v4(),
             group_id: creation.group_id,
-            description: creation.description.clone(),
-            amount: creation.amount,
-            currency: creation.currency.clone(),
+            description: creation.description.trim().to_string(),
+            amount,
+            rate_snapshot,
             paid_by: creation.paid_by,
             created_by,
             category: creation.category.clone(),
-            date: creation.date.unwrap_or_else(|| Utc::now()),
+            date,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
 
-        self.create_expense(&expense).await?;
-
-        // Calculate and create shares based on split_type
-        let expense_shares = self.calculate_shares_from_creation(&creation, &expense).await?;
-        if !expense_shares.is_empty() {
-            self.create_shares(&expense_shares).await?;
+        // Shares are worked out first so a bad split never leaves an expense without them
+        let expense_shares = self.calculate_shares(&expense, &creation.split_type, &creation.participants)?;
+        // The expense, its shares and the credit moved onto them are written in one batch, so a
+        // failure part way never leaves an expense without its shares
+        let mut statements = vec![self.insert_expense(&expense)?];
+        for share in &expense_shares {
+            statements.push(self.insert_share(share)?);
         }
+        // Credit the participants already have with the payer goes onto the new shares
+        let debtors: Vec<Uuid> = expense_shares.iter().map(|share| share.user_id).collect();
+        statements.extend(self.unallocate_debtor_statements(&expense.group_id, &expense.paid_by, &debtors)?);
+        self.db.batch(statements).await?;
+        self.update_books(&expense.group_id, &settings).await?;
+
+        let summary = format!("{} ({})", expense.description, expense.amount);
+        record_activity(&self.db, &NewActivity::new(expense.group_id, created_by, ActivityKind::ExpenseCreated, Some(expense.id), summary)).await;
 
         Ok(())
     }
 
-    pub async fn get_expense(&self, expense_id: &Uuid, _user_id: &Uuid) -> Result<Option<ExpenseInfo>, WorkerError> {
+    // A full edit, with the shares split again. When payments already paid off part of the
+    // old shares the edit needs `confirm_settled`, and the group's payments are then booked
+    // again against the new shares; otherwise only the participants' payments to the payer
+    // are, so any credit they had reaches the new shares. Every edit that changes something is
+    // kept as a revision.
+    pub async fn update_expense(&self, expense_id: &Uuid, update: ExpenseUpdate, user_id: &Uuid) -> Result<Option<ExpenseInfo>, ServiceError> {
+        let row = self
+            .db
+            .prepare("SELECT * FROM expenses WHERE id = ?1")
+            .bind(&[expense_id.to_string().into()])?
+            .first::<Value>(None)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Expense not found".to_string()))?;
+        let group_id = Self::parse_uuid(&row["group_id"])?;
+        let created_by = Self::parse_uuid(&row["created_by"])?;
+        self.authorizer.authorize(&group_id, user_id, GroupAction::EditExpense, Some(&created_by)).await?;
+        let settings = require_module(&self.db, &group_id, GroupModule::Expenses).await?;
+
+        let members = self.authorizer.members_among(&group_id, &update.participants).await?;
+        validate_expense(&update.description, &update.paid_by, &update.participants, &members).map_err(ServiceError::Invalid)?;
+
+        // Payments still waiting to be booked count towards what is settled
+        self.update_books(&group_id, &settings).await?;
+        let share_rows = self
+            .db
+            .prepare("SELECT user_id, amount, settled_amount FROM expense_shares WHERE expense_id = ?1")
+            .bind(&[expense_id.to_string().into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+        let settled = share_rows.iter().any(|share| Self::parse_minor(&share["settled_amount"]) > 0);
+        if settled && !update.confirm_settled {
+            return Err(ServiceError::Conflict(SETTLED_EXPENSE_EDIT.to_string()));
+        }
+
+        let currency = match update.currency.trim() {
+            "" => settings.default_currency.clone(),
+            currency => normalize_currency(currency).map_err(ServiceError::Invalid)?,
+        };
+        let amount = Money::from_major(update.amount, currency).map_err(ServiceError::Invalid)?;
+        if !amount.is_positive() {
+            return Err(ServiceError::Invalid("Expense amount must be positive".to_string()));
+        }
+
+        // The expense as it stands, in the same terms as the edited one
+        let old_currency = row["currency"].as_str().unwrap_or("USD");
+        let mut old_shares = BTreeMap::new();
+        for share in &share_rows {
+            old_shares.insert(Self::parse_uuid(&share["user_id"])?, Self::parse_minor(&share["amount"]));
+        }
+        let before = ExpenseState {
+            description: row["description"].as_str().unwrap_or("").to_string(),
+            amount: Self::parse_minor(&row["amount"]),
+            currency: old_currency.to_string(),
+            paid_by: Self::parse_uuid(&row["paid_by"])?,
+            category: row["category"].as_str().filter(|category| !category.is_empty()).map(|category| category.to_string()),
+            date: Self::parse_datetime(&row["date"])?,
+            shares: old_shares,
+        };
+
+        let now = Utc::now();
+        let date = update.date.unwrap_or(before.date);
+        let rate_snapshot = self.snapshot(&amount, &settings.default_currency, settings.local_date(date)).await?;
+        let expense = Expense {
+            id: *expense_id,
+            group_id,
+            description: update.description.trim().to_string(),
+            amount,
+            rate_snapshot,
+            paid_by: update.paid_by,
+            created_by,
+            category: update.category.clone(),
+            date,
+            created_at: Self::parse_datetime(&row["created_at"])?,
+            updated_at: now,
+        };
+        let shares = self.calculate_shares(&expense, &update.split_type, &update.participants)?;
+        let changes = ExpenseState::new(&expense, &shares).changes_since(&before);
+        if changes.is_empty() {
+            return self.get_expense(expense_id, user_id).await;
+        }
+
+        let next_revision = self
+            .db
+            .prepare("SELECT COALESCE(MAX(revision), 0) + 1 AS next FROM expense_revisions WHERE expense_id = ?1")
+            .bind(&[expense_id.to_string().into()])?
+            .first::<Value>(None)
+            .await?
+            .and_then(|row| row["next"].as_f64())
+            .unwrap_or(1.0);
+
+        // One batch, so the expense, its shares and its history never disagree. Two edits racing
+        // for the same revision number fail on its unique key rather than both landing.
+        let mut statements = vec![
+            self.db
+                .prepare(
+                    "UPDATE expenses SET description = ?2, amount = ?3, currency = ?4, exchange_rate = ?5, base_currency = ?6,
+                         base_amount = ?7, paid_by = ?8, category = ?9, date = ?10, updated_at = ?11
+                     WHERE id = ?1",
+                )
+                .bind(&[
+                    expense_id.to_string().into(),
+                    expense.description.clone().into(),
+                    (expense.amount.minor_units as f64).into(),
+                    expense.amount.currency.clone().into(),
+                    expense.rate_snapshot.rate.into(),
+                    expense.rate_snapshot.base_amount.currency.clone().into(),
+                    (expense.rate_snapshot.base_amount.minor_units as f64).into(),
+                    expense.paid_by.to_string().into(),
+                    expense.category.clone().unwrap_or_default().into(),
+                    expense.date.to_rfc3339().into(),
+                    now.to_rfc3339().into(),
+                ])?,
+        ];
+        if settled {
+            statements.extend(self.unallocate_statements(&group_id)?);
+        }
+        statements.push(self.db.prepare("DELETE FROM expense_shares WHERE expense_id = ?1").bind(&[expense_id.to_string().into()])?);
+        for share in &shares {
+            statements.push(self.insert_share(share)?);
+        }
+        if !settled {
+            let debtors: Vec<Uuid> = shares.iter().map(|share| share.user_id).collect();
+            statements.extend(self.unallocate_debtor_statements(&group_id, &expense.paid_by, &debtors)?);
+        }
+        let changes = serde_json::to_string(&changes).map_err(|e| ServiceError::Internal(e.to_string()))?;
+        statements.push(
+            self.db
+                .prepare(
+                    "INSERT INTO expense_revisions (id, expense_id, group_id, revision, edited_by, edited_at, changes)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )
+                .bind(&[
+                    Uuid::new_v4().to_string().into(),
+                    expense_id.to_string().into(),
+                    group_id.to_string().into(),
+                    next_revision.into(),
+                    user_id.to_string().into(),
+                    now.to_rfc3339().into(),
+                    changes.into(),
+                ])?,
+        );
+        self.db.batch(statements).await?;
+        self.apply_pending_settlements(&group_id, &settings).await?;
+
+        let summary = format!("{} ({})", expense.description, expense.amount);
+        record_activity(&self.db, &NewActivity::new(group_id, *user_id, ActivityKind::ExpenseUpdated, Some(*expense_id), summary)).await;
+
+        self.get_expense(expense_id, user_id).await
+    }
+
+    // Every edit of the expense, oldest first
+    pub async fn get_expense_revisions(&self, expense_id: &Uuid, user_id: &Uuid) -> Result<Vec<ExpenseRevision>, ServiceError> {
+        let (group_id, _) = self.expense_owner(expense_id).await?.ok_or_else(|| ServiceError::NotFound("Expense not found".to_string()))?;
+        self.authorizer.authorize(&group_id, user_id, GroupAction::ViewGroup, None).await?;
+
+        let rows = self
+            .db
+            .prepare("SELECT * FROM expense_revisions WHERE expense_id = ?1 ORDER BY revision")
+            .bind(&[expense_id.to_string().into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+        let mut user_ids = Vec::with_capacity(rows.len());
+        for row in &rows {
+            user_ids.push(Self::parse_uuid(&row["edited_by"])?);
+        }
+        let names = load_display_names(&self.db, &user_ids).await?;
+
+        rows.iter()
+            .zip(user_ids)
+            .map(|(row, edited_by)| {
+                Ok(ExpenseRevision {
+                    id: Self::parse_uuid(&row["id"])?,
+                    expense_id: *expense_id,
+                    revision: row["revision"].as_f64().unwrap_or(0.0) as u32,
+                    edited_by,
+                    edited_by_name: display_name(&names, &edited_by),
+                    edited_at: Self::parse_datetime(&row["edited_at"])?,
+                    changes: serde_json::from_str(row["changes"].as_str().unwrap_or("[]"))
+                        .map_err(|e| ServiceError::Internal(format!("Revision parse error: {}", e)))?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn get_expense(&self, expense_id: &Uuid, user_id: &Uuid) -> Result<Option<ExpenseInfo>, ServiceError> {
         let stmt = self.db.prepare("SELECT * FROM expenses WHERE id = ?1");
-        let result = stmt.bind(&[expense_id.to_string().into()])?.first::<Value>(None).await?;
-
-        if let Some(row) = result {
-            let paid_by = Uuid::parse_str(row["paid_by"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let created_by = Uuid::parse_str(row["created_by"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let group_id = Uuid::parse_str(row["group_id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-
-            let paid_by_name = self.get_username(&paid_by).await;
-            let created_by_name = self.get_username(&created_by).await;
-            let shares = self.get_expense_shares(expense_id).await?;
-
-            let expense_info = ExpenseInfo {
-                id: *expense_id,
-                group_id,
-                description: row["description"].as_str().unwrap_or("").to_string(),
-                amount: row["amount"].as_f64().unwrap_or(0.0),
-                currency: row["currency"].as_str().unwrap_or("USD").to_string(),
-                paid_by,
-                created_by,
-                category: Some(row["category"].as_str().unwrap_or("").to_string()),
-                date: DateTime::parse_from_rfc3339(row["date"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-                paid_by_name,
-                created_by_name,
-                shares,
-                created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
-                    .with_timezone(&Utc),
-            };
+        let row = match stmt.bind(&[expense_id.to_string().into()])?.first::<Value>(None).await? {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        self.authorizer.authorize(&Self::parse_uuid(&row["group_id"])?, user_id, GroupAction::ViewGroup, None).await?;
+
+        let share_stmt = self.db.prepare("SELECT * FROM expense_shares WHERE expense_id = ?1");
+        let share_rows = share_stmt.bind(&[expense_id.to_string().into()])?.all().await?.results::<Value>()?;
+
+        Ok(self.build_expense_infos(vec![row], share_rows).await?.pop())
+    }
+
+    // Balances are in the group's base currency. With `include_children`, balances in the
+    // group's open ledgers are added in as well; closed ledgers already count through their
+    // transfers. With `by_currency`, the unconverted balances per currency come along too.
+    pub async fn get_group_balances(&self, group_id: &Uuid, user_id: &Uuid, include_children: bool, by_currency: bool) -> Result<GroupBalance, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        let settings = load_group_settings(&self.db, group_id).await?;
+        let mut balances_map = self.net_balances(group_id).await?;
+        let mut currency_balances = if by_currency {
+            self.net_balances_by_currency(group_id, &settings.default_currency).await?
+        } else {
+            BTreeMap::new()
+        };
+        if include_children {
+            for child_id in open_child_ledgers(&self.db, group_id).await? {
+                // A ledger converts into its own base currency, so only a matching one adds up
+                let child_settings = load_group_settings(&self.db, &child_id).await?;
+                if child_settings.default_currency != settings.default_currency {
+                    return Err(ServiceError::Conflict(format!(
+                        "Ledger currency {} does not match the group's {}",
+                        child_settings.default_currency, settings.default_currency
+                    )));
+                }
+                merge_balances(&mut balances_map, self.net_balances(&child_id).await?);
+                if by_currency {
+                    for (currency, balances) in self.net_balances_by_currency(&child_id, &child_settings.default_currency).await? {
+                        merge_balances(currency_balances.entry(currency).or_default(), balances);
+                    }
+                }
+            }
+        }
+        self.group_balance(group_id, &settings.default_currency, balances_map, currency_balances.into_iter().collect()).await
+    }
+
+    // Carries every member's balance in a ledger into its parent group, then closes and
+    // archives the ledger. Afterwards the ledger nets to zero and the parent shows the totals.
+    pub async fn close_ledger(&self, group_id: &Uuid, user_id: &Uuid) -> Result<ClosedLedger, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::CloseLedger, None).await?;
+        let link = ledger_link(&self.db, group_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Group not found".to_string()))?;
+        let parent_group_id = link
+            .parent_group_id
+            .ok_or_else(|| ServiceError::Invalid("Only a ledger inside another group can be closed".to_string()))?;
+        if link.closed_at.is_some() {
+            return Err(ServiceError::Conflict("Ledger is already closed".to_string()));
+        }
+        if ledger_link(&self.db, &parent_group_id).await?.is_none() {
+            return Err(ServiceError::NotFound("Parent group not found".to_string()));
+        }
+
+        // Transfers are plain amounts, so both sides have to keep books in the same currency
+        let settings = load_group_settings(&self.db, group_id).await?;
+        let parent_settings = load_group_settings(&self.db, &parent_group_id).await?;
+        if settings.default_currency != parent_settings.default_currency {
+            return Err(ServiceError::Conflict(format!(
+                "Ledger currency {} does not match the parent group's {}",
+                settings.default_currency, parent_settings.default_currency
+            )));
+        }
+
+        let now = Utc::now();
+        self.update_books(group_id, &settings).await?;
+        let balances = self.net_balances(group_id).await?;
+        let ledger = ClosedLedger {
+            group_id: *group_id,
+            parent_group_id,
+            closed_at: now,
+            transfers: ledger_transfers(*group_id, parent_group_id, &balances, &settings.default_currency, now),
+        };
+        if !close_ledger(&self.db, &ledger, user_id).await? {
+            return Err(ServiceError::Conflict("Ledger is already closed".to_string()));
+        }
+        Ok(ledger)
+    }
+
+    pub async fn get_settle_up(&self, group_id: &Uuid, user_id: &Uuid) -> Result<SettleUpPlan, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        self.settle_up_plan(group_id).await
+    }
 
-            Ok(Some(expense_info))
+    // Who pays whom to settle the group, in its base currency: as few payments as possible,
+    // or the debts between each pair of people when the group turned simplifying off
+    pub async fn settle_up_plan(&self, group_id: &Uuid) -> Result<SettleUpPlan, ServiceError> {
+        let settings = load_group_settings(&self.db, group_id).await?;
+        let balances = self.net_balances(group_id).await?;
+        let transfers = if settings.simplify_debts {
+            simplify_debts(&balances)
         } else {
-            Ok(None)
+            pairwise_debts(&self.pairwise_owed(group_id).await?, &balances)
+        };
+
+        let mut user_ids = Vec::with_capacity(transfers.len() * 2);
+        for transfer in &transfers {
+            user_ids.push(transfer.from);
+            user_ids.push(transfer.to);
+        }
+        let names = load_display_names(&self.db, &user_ids).await?;
+
+        Ok(SettleUpPlan {
+            group_id: *group_id,
+            currency: settings.default_currency.clone(),
+            simplified: settings.simplify_debts,
+            debts: transfers
+                .into_iter()
+                .map(|transfer| DebtSummary {
+                    creditor_id: transfer.to,
+                    creditor_name: display_name(&names, &transfer.to),
+                    debtor_id: transfer.from,
+                    debtor_name: display_name(&names, &transfer.from),
+                    amount: minor_to_major(transfer.amount, &settings.default_currency),
+                    currency: settings.default_currency.clone(),
+                })
+                .collect(),
+        })
+    }
+
+    // What each person owes each other person directly, in minor units of the base currency:
+    // their shares of what the other paid, less what they already paid back, as of the last
+    // update_books.
+    async fn pairwise_owed(&self, group_id: &Uuid) -> Result<HashMap<(Uuid, Uuid), i64>, ServiceError> {
+        let rows = self
+            .db
+            .prepare(
+                "SELECT es.user_id AS debtor, e.paid_by AS creditor, SUM(es.base_amount) AS amount
+                 FROM expense_shares es JOIN expenses e ON e.id = es.expense_id
+                 WHERE e.group_id = ?1 AND es.user_id != e.paid_by GROUP BY es.user_id, e.paid_by
+                 UNION ALL
+                 SELECT from_user, to_user, -SUM(base_amount) FROM payments WHERE group_id = ?1 GROUP BY from_user, to_user",
+            )
+            .bind(&[group_id.to_string().into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+
+        let mut owed = HashMap::new();
+        for row in rows {
+            let pair = (Self::parse_uuid(&row["debtor"])?, Self::parse_uuid(&row["creditor"])?);
+            *owed.entry(pair).or_insert(0) += Self::parse_minor(&row["amount"]);
         }
+        Ok(owed)
     }
 
-    pub async fn get_group_balances(&self, group_id: &Uuid, _user_id: &Uuid) -> Result<GroupBalance, WorkerError> {
-        self.calculate_group_balances(group_id).await
+    // Debts involving the user across every group they are in, for the balance port
+    pub async fn user_debts(&self, user_id: &Uuid) -> Result<Vec<DebtSummary>, ServiceError> {
+        let rows = self
+            .db
+            .prepare(
+                "SELECT gm.group_id FROM group_members gm JOIN groups g ON g.id = gm.group_id
+                 WHERE gm.user_id = ?1 AND g.deleted_at IS NULL",
+            )
+            .bind(&[user_id.to_string().into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+
+        let mut debts = Vec::new();
+        for row in rows {
+            let plan = self.settle_up_plan(&Self::parse_uuid(&row["group_id"])?).await?;
+            debts.extend(plan.debts.into_iter().filter(|debt| debt.debtor_id == *user_id || debt.creditor_id == *user_id));
+        }
+        Ok(debts)
     }
 
-    pub async fn get_group_expenses_with_pagination(&self, group_id: &Uuid, _user_id: &Uuid, _limit: Option<usize>, _offset: Option<usize>) -> Result<Vec<ExpenseInfo>, WorkerError> {
+    pub async fn get_group_expenses_with_pagination(&self, group_id: &Uuid, user_id: &Uuid, _limit: Option<usize>, _offset: Option<usize>) -> Result<Vec<ExpenseInfo>, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+
         // For now, ignore pagination and return all expenses
         self.get_group_expenses(group_id).await
     }
 
-    pub async fn settle_debt(&self, group_id: &Uuid, settle: SettleDebt, settled_by: Uuid) -> Result<(), WorkerError> {
-        let payment = Payment {
+    // Records a payment and books it against the debtor's oldest shares of what the creditor
+    // paid for. Whatever is left once those are covered stays on the balances as credit.
+    pub async fn settle_debt(&self, group_id: &Uuid, settle: SettleDebt, settled_by: Uuid) -> Result<Payment, ServiceError> {
+        self.authorizer.authorize(group_id, &settled_by, GroupAction::SettleDebt, None).await?;
+        let settings = require_module(&self.db, group_id, GroupModule::Expenses).await?;
+        if settle.debtor_id == settle.creditor_id {
+            return Err(ServiceError::Invalid("Cannot settle a debt with yourself".to_string()));
+        }
+        let members = self.authorizer.members_among(group_id, &[settle.debtor_id, settle.creditor_id]).await?;
+        if !members.contains(&settle.debtor_id) || !members.contains(&settle.creditor_id) {
+            return Err(ServiceError::Invalid("Both sides of a settlement must be members of the group".to_string()));
+        }
+
+        // Debts can be paid back in any currency; no currency means the group's default
+        let currency = match settle.currency.trim() {
+            "" => settings.default_currency.clone(),
+            currency => normalize_currency(currency).map_err(ServiceError::Invalid)?,
+        };
+        let amount = Money::from_major(settle.amount, currency).map_err(ServiceError::Invalid)?;
+        if !amount.is_positive() {
+            return Err(ServiceError::Invalid("Settlement amount must be positive".to_string()));
+        }
+        let now = Utc::now();
+        let rate_snapshot = self.snapshot(&amount, &settings.default_currency, settings.local_date(now)).await?;
+
+        let mut payment = Payment {
             id: Uuid::new_v4(),
             group_id: *group_id,
             from_user: settle.debtor_id,
             to_user: settle.creditor_id,
-            amount: settle.amount,
-            currency: "USD".to_string(), // Default currency
+            amount,
+            rate_snapshot,
             description: "Debt settlement".to_string(), // Default description
-            created_at: Utc::now(),
+            recorded_by: settled_by,
+            allocations: Vec::new(),
+            created_at: now,
         };
 
-        self.create_payment(&payment).await
+        // The payment is stored unallocated first, so if booking it fails part-way the next
+        // look at the group's shares picks it up again
+        self.create_payment(&payment).await?;
+        self.update_books(group_id, &settings).await?;
+        payment.allocations = self.payment_allocations(&payment.id, &settings.default_currency).await?;
+
+        Ok(payment)
     }
 
-    // Helper method to calculate shares from ExpenseCreation
-    async fn calculate_shares_from_creation(&self, creation: &ExpenseCreation, expense: &Expense) -> Result<Vec<ExpenseShare>, WorkerError> {
-        let mut shares = Vec::new();
-        
-        match &creation.split_type {
-            SplitType::Equal => {
-                // Split equally among all participants
-                let amount_per_person = expense.amount / creation.participants.len() as f64;
-                for participant_id in &creation.participants {
-                    shares.push(ExpenseShare {
-                        expense_id: expense.id,
-                        user_id: *participant_id,
-                        amount: amount_per_person,
-                        is_settled: false,
-                    });
-                }
-            },
-            SplitType::Exact(amounts) => {
-                // Use exact amounts specified
-                for participant_id in &creation.participants {
-                    if let Some(&amount) = amounts.get(participant_id) {
-                        shares.push(ExpenseShare {
-                            expense_id: expense.id,
-                            user_id: *participant_id,
-                            amount,
-                            is_settled: false,
-                        });
-                    }
-                }
-            },
-            SplitType::Percentage(percentages) => {
-                // Calculate amounts based on percentages
-                for participant_id in &creation.participants {
-                    if let Some(&percentage) = percentages.get(participant_id) {
-                        let amount = expense.amount * (percentage / 100.0);
-                        shares.push(ExpenseShare {
-                            expense_id: expense.id,
-                            user_id: *participant_id,
-                            amount,
-                            is_settled: false,
-                        });
-                    }
-                }
-            },
-            SplitType::ByShares(share_counts) => {
-                // Calculate amounts based on share counts
-                let total_shares: u32 = share_counts.values().sum();
-                if total_shares > 0 {
-                    for participant_id in &creation.participants {
-                        if let Some(&user_shares) = share_counts.get(participant_id) {
-                            let amount = expense.amount * (user_shares as f64 / total_shares as f64);
-                            shares.push(ExpenseShare {
-                                expense_id: expense.id,
-                                user_id: *participant_id,
-                                amount,
-                                is_settled: false,
-                            });
-                        }
-                    }
-                }
-            },
+    // Books every payment not yet allocated against the debtor's oldest shares of what the
+    // creditor paid for, in the order the payments were made. That covers new payments,
+    // payments from before settlements were tracked and payments taken back off the shares
+    // when the group's currency changed. Expects up-to-date rate snapshots.
+    async fn apply_pending_settlements(&self, group_id: &Uuid, settings: &GroupSettings) -> Result<(), ServiceError> {
+        let base_currency = settings.default_currency.as_str();
+
+        let payment_rows = self
+            .db
+            .prepare("SELECT id, from_user, to_user, base_amount, created_at FROM payments WHERE group_id = ?1 AND allocated = 0 ORDER BY created_at, id")
+            .bind(&[group_id.to_string().into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+        for row in payment_rows {
+            let payment_id = row["id"].as_str().unwrap_or("").to_string();
+            let debtor = row["from_user"].as_str().unwrap_or("").to_string();
+            let paid_at = row["created_at"].as_str().unwrap_or("").to_string();
+            let amount = Money::new(Self::parse_minor(&row["base_amount"]), base_currency);
+            let shares = self.outstanding_shares(group_id, &debtor, row["to_user"].as_str().unwrap_or(""), base_currency).await?;
+
+            // Every statement only takes effect while the payment is still unallocated, so two
+            // requests catching up on the same payment, or an undo in between, book nothing twice
+            let mut statements = Vec::new();
+            for allocation in allocate_payment(&amount, &shares) {
+                let expense_id = allocation.expense_id.to_string();
+                let applied = allocation.amount.minor_units as f64;
+                statements.push(
+                    self.db
+                        .prepare(
+                            "INSERT INTO payment_allocations (payment_id, expense_id, user_id, amount)
+                             SELECT ?1, ?2, ?3, ?4 WHERE EXISTS (SELECT 1 FROM payments WHERE id = ?1 AND allocated = 0)",
+                        )
+                        .bind(&[payment_id.clone().into(), expense_id.clone().into(), debtor.clone().into(), applied.into()])?,
+                );
+                statements.push(
+                    self.db
+                        .prepare(
+                            "UPDATE expense_shares SET settled_amount = settled_amount + ?3,
+                                 is_settled = (settled_amount + ?3 >= base_amount),
+                                 settled_at = CASE WHEN settled_amount + ?3 >= base_amount THEN ?4 END
+                             WHERE expense_id = ?1 AND user_id = ?2
+                               AND EXISTS (SELECT 1 FROM payments WHERE id = ?5 AND allocated = 0)",
+                        )
+                        .bind(&[expense_id.into(), debtor.clone().into(), applied.into(), paid_at.clone().into(), payment_id.clone().into()])?,
+                );
+            }
+            statements.push(self.db.prepare("UPDATE payments SET allocated = 1 WHERE id = ?1").bind(&[payment_id.into()])?);
+            self.db.batch(statements).await?;
         }
-        
-        Ok(shares)
+
+        Ok(())
+    }
+
+    // The debtor's shares of what the creditor paid for that are not paid back yet, oldest
+    // expense first. Expects up-to-date rate snapshots.
+    async fn outstanding_shares(&self, group_id: &Uuid, debtor: &str, creditor: &str, base_currency: &str) -> Result<Vec<ExpenseShare>, ServiceError> {
+        let rows = self
+            .db
+            .prepare(
+                "SELECT es.expense_id, es.user_id, es.amount, e.currency, es.base_amount, es.settled_amount
+                 FROM expense_shares es JOIN expenses e ON e.id = es.expense_id
+                 WHERE e.group_id = ?1 AND es.user_id = ?2 AND e.paid_by = ?3 AND es.settled_amount < es.base_amount
+                 ORDER BY e.date, e.created_at, e.id",
+            )
+            .bind(&[group_id.to_string().into(), debtor.into(), creditor.into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ExpenseShare {
+                    expense_id: Self::parse_uuid(&row["expense_id"])?,
+                    user_id: Self::parse_uuid(&row["user_id"])?,
+                    amount: Money::new(Self::parse_minor(&row["amount"]), row["currency"].as_str().unwrap_or(base_currency)),
+                    base_amount: Money::new(Self::parse_minor(&row["base_amount"]), base_currency),
+                    settled_amount: Money::new(Self::parse_minor(&row["settled_amount"]), base_currency),
+                    is_settled: false,
+                    settled_at: None,
+                })
+            })
+            .collect()
+    }
+
+    async fn payment_allocations(&self, payment_id: &Uuid, base_currency: &str) -> Result<Vec<PaymentAllocation>, ServiceError> {
+        let rows = self
+            .db
+            .prepare("SELECT expense_id, amount FROM payment_allocations WHERE payment_id = ?1")
+            .bind(&[payment_id.to_string().into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+        rows.iter()
+            .map(|row| {
+                Ok(PaymentAllocation {
+                    expense_id: Self::parse_uuid(&row["expense_id"])?,
+                    amount: Money::new(Self::parse_minor(&row["amount"]), base_currency),
+                })
+            })
+            .collect()
+    }
+
+    // Takes a payment back and the balances move back. The debtor's other payments to the
+    // creditor are booked again, so later ones pay off what this one had covered.
+    // Whoever recorded the payment can undo it, as can the group's admins.
+    pub async fn undo_settlement(&self, payment_id: &Uuid, user_id: &Uuid) -> Result<(), ServiceError> {
+        let row = self
+            .db
+            .prepare("SELECT group_id, from_user, to_user, recorded_by FROM payments WHERE id = ?1")
+            .bind(&[payment_id.to_string().into()])?
+            .first::<Value>(None)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Payment not found".to_string()))?;
+        let group_id = Self::parse_uuid(&row["group_id"])?;
+        // Payments from before this was tracked have no author; only admins can undo those
+        let recorded_by = row["recorded_by"].as_str().and_then(|id| Uuid::parse_str(id).ok());
+        self.authorizer.authorize(&group_id, user_id, GroupAction::UndoSettlement, recorded_by.as_ref()).await?;
+
+        let debtor = Self::parse_uuid(&row["from_user"])?;
+        let creditor = Self::parse_uuid(&row["to_user"])?;
+        let mut statements = self.unallocate_pair_statements(&group_id, &debtor, &creditor)?;
+        statements.push(self.db.prepare("DELETE FROM payments WHERE id = ?1").bind(&[payment_id.to_string().into()])?);
+        self.db.batch(statements).await?;
+
+        let settings = load_group_settings(&self.db, &group_id).await?;
+        self.update_books(&group_id, &settings).await?;
+
+        Ok(())
+    }
+
+    // What is still owed on each share in the group, in its base currency and oldest first;
+    // with `involving`, only the shares that person owes or is owed
+    pub async fn get_unsettled_shares(&self, group_id: &Uuid, user_id: &Uuid, involving: Option<&Uuid>) -> Result<Vec<UnsettledShare>, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        let settings = load_group_settings(&self.db, group_id).await?;
+        let currency = settings.default_currency.as_str();
+
+        let rows = self
+            .db
+            .prepare(
+                "SELECT es.expense_id, es.user_id, es.base_amount, es.settled_amount, e.description, e.date, e.paid_by
+                 FROM expense_shares es JOIN expenses e ON e.id = es.expense_id
+                 WHERE e.group_id = ?1 AND es.user_id != e.paid_by AND es.settled_amount < es.base_amount
+                 ORDER BY e.date, e.created_at, e.id",
+            )
+            .bind(&[group_id.to_string().into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+
+        let mut owed = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let debtor_id = Self::parse_uuid(&row["user_id"])?;
+            let creditor_id = Self::parse_uuid(&row["paid_by"])?;
+            if involving.map_or(true, |id| *id == debtor_id || *id == creditor_id) {
+                owed.push((row, debtor_id, creditor_id));
+            }
+        }
+        let user_ids: Vec<Uuid> = owed.iter().flat_map(|(_, debtor_id, creditor_id)| [*debtor_id, *creditor_id]).collect();
+        let names = load_display_names(&self.db, &user_ids).await?;
+
+        owed.into_iter()
+            .map(|(row, debtor_id, creditor_id)| {
+                let amount = Self::parse_minor(&row["base_amount"]);
+                let settled_amount = Self::parse_minor(&row["settled_amount"]);
+                Ok(UnsettledShare {
+                    expense_id: Self::parse_uuid(&row["expense_id"])?,
+                    description: row["description"].as_str().unwrap_or("").to_string(),
+                    date: Self::parse_datetime(&row["date"])?,
+                    debtor_id,
+                    debtor_name: display_name(&names, &debtor_id),
+                    creditor_id,
+                    creditor_name: display_name(&names, &creditor_id),
+                    amount: minor_to_major(amount, currency),
+                    settled_amount: minor_to_major(settled_amount, currency),
+                    outstanding: minor_to_major(amount - settled_amount, currency),
+                    currency: currency.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    // Shares for a new or edited expense; the split is exact to the minor unit, in the
+    // expense's currency and in the group's base currency alike
+    fn calculate_shares(&self, expense: &Expense, split_type: &SplitType, participants: &[Uuid]) -> Result<Vec<ExpenseShare>, ServiceError> {
+        let shares = split_shares(&expense.amount, split_type, participants).map_err(ServiceError::Invalid)?;
+        let amounts: Vec<Money> = shares.iter().map(|(_, amount)| amount.clone()).collect();
+        let base_amounts = expense.rate_snapshot.share_amounts(&amounts).map_err(ServiceError::Internal)?;
+        Ok(shares
+            .into_iter()
+            .zip(base_amounts)
+            .map(|((user_id, amount), base_amount)| ExpenseShare {
+                expense_id: expense.id,
+                user_id,
+                amount,
+                settled_amount: Money::zero(base_amount.currency.clone()),
+                base_amount,
+                is_settled: false,
+                settled_at: None,
+            })
+            .collect())
     }
 }
@@ -0,0 +1,202 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use worker::{Fetch, Method, Request, RequestInit};
+use worker::send::SendFuture;
+
+use crate::error::ServiceError;
+use crate::expenses::domain::exchange::ExchangeRate;
+use crate::expenses::domain::ports::ExchangeRateProvider;
+
+// Rates from a fixed table, e.g. a JSON file bundled with tests or the EXCHANGE_RATES var.
+// A day without its own entry uses the latest earlier one; a day before the table starts
+// uses the first. A pair can be answered from its inverse.
+#[derive(Clone, Default)]
+pub struct StaticExchangeRateProvider {
+    rates: HashMap<(String, String), Vec<(NaiveDate, f64)>>,
+}
+
+impl StaticExchangeRateProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // `[{"from": "EUR", "to": "USD", "date": "2026-01-01", "rate": 1.08}, ...]`
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let rates: Vec<ExchangeRate> = serde_json::from_str(json).map_err(|e| format!("Invalid exchange rate table: {}", e))?;
+        let mut provider = Self::new();
+        for rate in rates {
+            provider = provider.with_rate(&rate.from, &rate.to, rate.date, rate.rate);
+        }
+        Ok(provider)
+    }
+
+    pub fn with_rate(mut self, from: &str, to: &str, date: NaiveDate, rate: f64) -> Self {
+        let entries = self.rates.entry((from.to_uppercase(), to.to_uppercase())).or_default();
+        entries.push((date, rate));
+        entries.sort_by_key(|(date, _)| *date);
+        self
+    }
+
+    fn lookup(&self, from: &str, to: &str, on: NaiveDate) -> Option<f64> {
+        let entries = self.rates.get(&(from.to_string(), to.to_string()))?;
+        entries
+            .iter()
+            .rev()
+            .find(|(date, _)| *date <= on)
+            .or_else(|| entries.first())
+            .map(|(_, rate)| *rate)
+    }
+}
+
+#[async_trait]
+impl ExchangeRateProvider for StaticExchangeRateProvider {
+    async fn rate(&self, from: &str, to: &str, on: NaiveDate) -> Result<f64, Box<dyn Error>> {
+        if from == to {
+            return Ok(1.0);
+        }
+        self.lookup(from, to, on)
+            .or_else(|| self.lookup(to, from, on).filter(|rate| *rate > 0.0).map(|rate| 1.0 / rate))
+            .ok_or_else(|| ServiceError::Invalid(format!("No exchange rate from {} to {} on {}", from, to, on)).into())
+    }
+}
+
+#[derive(Deserialize)]
+struct HttpRatesResponse {
+    rates: HashMap<String, f64>,
+}
+
+// Daily reference rates from a Frankfurter-compatible API:
+// GET {api_url}/{date}?from=EUR&to=USD -> {"rates": {"USD": 1.08}, ...}
+pub struct HttpExchangeRateProvider {
+    api_url: String,
+}
+
+impl HttpExchangeRateProvider {
+    pub fn new(api_url: String) -> Self {
+        Self { api_url: api_url.trim_end_matches('/').to_string() }
+    }
+}
+
+#[async_trait]
+impl ExchangeRateProvider for HttpExchangeRateProvider {
+    async fn rate(&self, from: &str, to: &str, on: NaiveDate) -> Result<f64, Box<dyn Error>> {
+        if from == to {
+            return Ok(1.0);
+        }
+        let url = format!("{}/{}?from={}&to={}", self.api_url, on.format("%Y-%m-%d"), from, to);
+
+        // Fetch handles are not Send; keep them inside the wrapped future
+        let (status, body) = SendFuture::new(async {
+            let request = Request::new_with_init(&url, RequestInit::new().with_method(Method::Get))?;
+            let mut response = Fetch::Request(request).send().await?;
+            let status = response.status_code();
+            Ok::<(u16, String), worker::Error>((status, response.text().await?))
+        })
+        .await
+        .map_err(|e| format!("Failed to fetch exchange rate: {}", e))?;
+
+        if !(200..300).contains(&status) {
+            return Err(format!("Exchange rate API responded with status {}", status).into());
+        }
+        let parsed: HttpRatesResponse = serde_json::from_str(&body).map_err(|e| format!("Invalid exchange rate response: {}", e))?;
+        parsed
+            .rates
+            .get(to)
+            .copied()
+            .ok_or_else(|| ServiceError::Invalid(format!("No exchange rate from {} to {} on {}", from, to, on)).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expenses::domain::exchange::snapshot_rate;
+    use crate::expenses::domain::money::Money;
+    use crate::test_support::block_on;
+
+    fn day(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    fn provider() -> StaticExchangeRateProvider {
+        StaticExchangeRateProvider::new()
+            .with_rate("EUR", "USD", day(1, 1), 1.08)
+            .with_rate("EUR", "USD", day(3, 1), 1.10)
+            .with_rate("USD", "JPY", day(1, 1), 150.0)
+    }
+
+    #[test]
+    fn uses_the_latest_rate_on_or_before_the_day() {
+        let rates = provider();
+        assert_eq!(block_on(rates.rate("EUR", "USD", day(2, 15))).unwrap(), 1.08);
+        assert_eq!(block_on(rates.rate("EUR", "USD", day(3, 1))).unwrap(), 1.10);
+        assert_eq!(block_on(rates.rate("EUR", "USD", day(12, 31))).unwrap(), 1.10);
+        // Before the table starts, the first entry
+        assert_eq!(block_on(rates.rate("EUR", "USD", NaiveDate::from_ymd_opt(2025, 6, 1).unwrap())).unwrap(), 1.08);
+    }
+
+    #[test]
+    fn answers_a_pair_from_its_inverse() {
+        let rate = block_on(provider().rate("JPY", "USD", day(2, 1))).unwrap();
+        assert!((rate - 1.0 / 150.0).abs() < 1e-12);
+        assert_eq!(block_on(provider().rate("GBP", "GBP", day(2, 1))).unwrap(), 1.0);
+        assert!(block_on(provider().rate("GBP", "USD", day(2, 1))).is_err());
+    }
+
+    #[test]
+    fn reads_a_json_table() {
+        let rates = StaticExchangeRateProvider::from_json(r#"[{"from": "eur", "to": "usd", "date": "2026-01-01", "rate": 1.08}]"#).unwrap();
+        assert_eq!(block_on(rates.rate("EUR", "USD", day(1, 2))).unwrap(), 1.08);
+        assert!(StaticExchangeRateProvider::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn snapshot_converts_at_the_rate_of_the_day() {
+        let rates = provider();
+        let amount = Money::new(10_000, "EUR");
+        let snapshot = block_on(snapshot_rate(&rates, &amount, "USD", day(2, 1))).unwrap();
+        assert_eq!(snapshot.rate, 1.08);
+        assert_eq!(snapshot.base_amount, Money::new(10_800, "USD"));
+
+        let snapshot = block_on(snapshot_rate(&rates, &amount, "USD", day(3, 2))).unwrap();
+        assert_eq!(snapshot.base_amount, Money::new(11_000, "USD"));
+    }
+
+    #[test]
+    fn snapshot_in_the_base_currency_keeps_a_rate_of_one() {
+        let snapshot = block_on(snapshot_rate(&StaticExchangeRateProvider::new(), &Money::new(1234, "USD"), "USD", day(1, 1))).unwrap();
+        assert_eq!(snapshot.rate, 1.0);
+        assert_eq!(snapshot.base_amount, Money::new(1234, "USD"));
+    }
+
+    #[test]
+    fn snapshot_converts_between_minor_units() {
+        // 12.34 USD at 150 yen per dollar is 1851 yen
+        let snapshot = block_on(snapshot_rate(&provider(), &Money::new(1234, "USD"), "JPY", day(1, 1))).unwrap();
+        assert_eq!(snapshot.base_amount, Money::new(1851, "JPY"));
+    }
+
+    #[test]
+    fn converted_shares_add_up_to_the_converted_total() {
+        let rates = provider();
+        // 100.00 EUR split three ways is 33.34 + 33.33 + 33.33; at 1.08 that is 108.00 USD
+        let total = Money::new(10_000, "EUR");
+        let shares = total.allocate(&[1, 1, 1]).unwrap();
+        let snapshot = block_on(snapshot_rate(&rates, &total, "USD", day(2, 1))).unwrap();
+        let base_shares = snapshot.share_amounts(&shares).unwrap();
+        assert_eq!(base_shares.iter().map(|share| share.minor_units).sum::<i64>(), snapshot.base_amount.minor_units);
+        assert_eq!(base_shares.iter().map(|share| share.minor_units).collect::<Vec<_>>(), vec![3601, 3600, 3599]);
+        assert!(base_shares.iter().all(|share| share.currency == "USD"));
+
+        // Yen shares converted into dollars, where rounding each on its own would drift
+        let total = Money::new(1000, "JPY");
+        let shares = total.allocate(&[1, 1, 1]).unwrap();
+        let snapshot = block_on(snapshot_rate(&rates, &total, "USD", day(2, 1))).unwrap();
+        let base_shares = snapshot.share_amounts(&shares).unwrap();
+        assert_eq!(snapshot.base_amount, Money::new(667, "USD"));
+        assert_eq!(base_shares.iter().map(|share| share.minor_units).sum::<i64>(), 667);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod persistence;
 pub mod direct_d1_service;
+pub mod d1_balance_repository;
+pub mod exchange_rates;
 
 pub use persistence::{
     InMemoryExpenseRepository,
@@ -9,3 +11,5 @@ pub use persistence::{
 };
 
 pub use direct_d1_service::DirectD1ExpenseService;
+pub use d1_balance_repository::D1BalanceRepository;
+pub use exchange_rates::{HttpExchangeRateProvider, StaticExchangeRateProvider};
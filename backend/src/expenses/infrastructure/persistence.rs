@@ -1,16 +1,18 @@
 use async_trait::async_trait;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 use chrono::Utc;
-use crate::auth::infrastructure::persistence::persistent_memory_repository::PersistentMemoryUserRepository;
 use crate::auth::domain::ports::UserRepository;
 
 use crate::expenses::domain::expense::{
-    Expense, ExpenseShare, ExpenseInfo, ExpenseShareInfo, UserBalance, 
-    GroupBalance, DebtSummary, Payment, ExpenseFilter
+    ConvertedAmount, Expense, ExpenseShare, ExpenseInfo, ExpenseShareInfo, UserBalance, 
+    GroupBalance, DebtSummary, Payment, PaymentAllocation, ExpenseFilter
 };
+use crate::expenses::domain::revision::ExpenseRevision;
+use crate::expenses::domain::money::{minor_to_major, Money};
+use crate::expenses::domain::settle_up::simplify_debts;
 use crate::expenses::domain::ports::{
     ExpenseRepository, ExpenseShareRepository, BalanceRepository, PaymentRepository
 };
@@ -20,16 +22,15 @@ use std::error::Error;
 static EXPENSES: Lazy<Mutex<HashMap<Uuid, Expense>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 static EXPENSE_SHARES: Lazy<Mutex<HashMap<Uuid, Vec<ExpenseShare>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 static PAYMENTS: Lazy<Mutex<HashMap<Uuid, Payment>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static EXPENSE_REVISIONS: Lazy<Mutex<HashMap<Uuid, Vec<ExpenseRevision>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 
 pub struct InMemoryExpenseRepository {
-    user_repo: PersistentMemoryUserRepository,
+    user_repo: Arc<dyn UserRepository>,
 }
 
 impl InMemoryExpenseRepository {
-    pub fn new() -> Self {
-        Self {
-            user_repo: PersistentMemoryUserRepository::new(),
-        }
+    pub fn new(user_repo: Arc<dyn UserRepository>) -> Self {
+        Self { user_repo }
     }
     
     async fn get_username(&self, user_id: &Uuid) -> String {
@@ -67,6 +68,9 @@ impl ExpenseRepository for InMemoryExpenseRepository {
         // Also remove associated shares
         let mut shares = EXPENSE_SHARES.lock().unwrap();
         shares.remove(expense_id);
+
+        let mut revisions = EXPENSE_REVISIONS.lock().unwrap();
+        revisions.remove(expense_id);
         
         Ok(())
     }
@@ -120,7 +124,8 @@ impl ExpenseRepository for InMemoryExpenseRepository {
                 share_infos.push(ExpenseShareInfo {
                     user_id: share.user_id,
                     username,
-                    amount: share.amount,
+                    amount: share.amount.to_major(),
+                    settled_amount: share.settled_amount.to_major(),
                     is_settled: share.is_settled,
                 });
             }
@@ -129,8 +134,13 @@ impl ExpenseRepository for InMemoryExpenseRepository {
                 id: expense.id,
                 group_id: expense.group_id,
                 description: expense.description.clone(),
-                amount: expense.amount,
-                currency: expense.currency.clone(),
+                amount: expense.amount.to_major(),
+                currency: expense.amount.currency.clone(),
+                converted: Some(ConvertedAmount {
+                    amount: expense.rate_snapshot.base_amount.to_major(),
+                    currency: expense.rate_snapshot.base_amount.currency.clone(),
+                    rate: expense.rate_snapshot.rate,
+                }),
                 paid_by: expense.paid_by,
                 created_by: expense.created_by,
                 category: expense.category.clone(),
@@ -173,6 +183,28 @@ impl ExpenseRepository for InMemoryExpenseRepository {
         };
         self.get_expenses(&filter).await
     }
+
+    async fn save_edit(&self, expense: &Expense, shares: &[ExpenseShare], revision: &ExpenseRevision) -> Result<u32, Box<dyn Error>> {
+        // All three stores stay locked until the edit is in, so no one sees half of it
+        let mut expenses = EXPENSES.lock().unwrap();
+        let mut expense_shares = EXPENSE_SHARES.lock().unwrap();
+        let mut revisions = EXPENSE_REVISIONS.lock().unwrap();
+        if !expenses.contains_key(&expense.id) {
+            return Err("Expense not found".into());
+        }
+
+        let history = revisions.entry(expense.id).or_default();
+        let number = history.iter().map(|revision| revision.revision).max().unwrap_or(0) + 1;
+        expenses.insert(expense.id, expense.clone());
+        expense_shares.insert(expense.id, shares.to_vec());
+        history.push(ExpenseRevision { revision: number, ..revision.clone() });
+        Ok(number)
+    }
+
+    async fn get_revisions(&self, expense_id: &Uuid) -> Result<Vec<ExpenseRevision>, Box<dyn Error>> {
+        let revisions = EXPENSE_REVISIONS.lock().unwrap();
+        Ok(revisions.get(expense_id).cloned().unwrap_or_default())
+    }
 }
 
 pub struct InMemoryExpenseShareRepository;
@@ -205,8 +237,11 @@ impl ExpenseShareRepository for InMemoryExpenseShareRepository {
         let mut expense_shares = EXPENSE_SHARES.lock().unwrap();
         if let Some(shares) = expense_shares.get_mut(&share.expense_id) {
             if let Some(existing_share) = shares.iter_mut().find(|s| s.user_id == share.user_id) {
-                existing_share.amount = share.amount;
+                existing_share.amount = share.amount.clone();
+                existing_share.base_amount = share.base_amount.clone();
+                existing_share.settled_amount = share.settled_amount.clone();
                 existing_share.is_settled = share.is_settled;
+                existing_share.settled_at = share.settled_at;
             }
         }
         Ok(())
@@ -244,17 +279,32 @@ impl ExpenseShareRepository for InMemoryExpenseShareRepository {
         
         Ok(user_shares)
     }
+
+    async fn get_unsettled_shares(&self, group_id: &Uuid) -> Result<Vec<(Expense, ExpenseShare)>, Box<dyn Error>> {
+        let shares = EXPENSE_SHARES.lock().unwrap();
+        let expenses = EXPENSES.lock().unwrap();
+
+        let mut unsettled = Vec::new();
+        for expense in expenses.values().filter(|expense| expense.group_id == *group_id) {
+            for share in shares.get(&expense.id).into_iter().flatten() {
+                if share.user_id != expense.paid_by && share.outstanding() > 0 {
+                    unsettled.push((expense.clone(), share.clone()));
+                }
+            }
+        }
+        unsettled.sort_by(|a, b| (a.0.date, a.0.created_at, a.0.id).cmp(&(b.0.date, b.0.created_at, b.0.id)));
+
+        Ok(unsettled)
+    }
 }
 
 pub struct InMemoryBalanceRepository {
-    user_repo: PersistentMemoryUserRepository,
+    user_repo: Arc<dyn UserRepository>,
 }
 
 impl InMemoryBalanceRepository {
-    pub fn new() -> Self {
-        Self {
-            user_repo: PersistentMemoryUserRepository::new(),
-        }
+    pub fn new(user_repo: Arc<dyn UserRepository>) -> Self {
+        Self { user_repo }
     }
     
     async fn get_username(&self, user_id: &Uuid) -> String {
@@ -270,26 +320,28 @@ impl InMemoryBalanceRepository {
 impl BalanceRepository for InMemoryBalanceRepository {
     async fn calculate_group_balances(&self, group_id: &Uuid) -> Result<GroupBalance, Box<dyn Error>> {
         // Calculate balances and release locks immediately
-        let user_balances_map = {
+        let (user_balances_map, currency) = {
             let expenses = EXPENSES.lock().unwrap();
             let shares = EXPENSE_SHARES.lock().unwrap();
             let payments = PAYMENTS.lock().unwrap();
             
-            let mut balances_map = HashMap::new();
+            let mut balances_map: HashMap<Uuid, i64> = HashMap::new();
+            let mut currency = None;
             
-            // Calculate balances for this group
+            // Calculate balances for this group, in minor units of the base currency
             for expense in expenses.values() {
                 if expense.group_id != *group_id {
                     continue;
                 }
+                currency.get_or_insert_with(|| expense.rate_snapshot.base_amount.currency.clone());
                 
                 // Add amount paid by user
-                *balances_map.entry(expense.paid_by).or_insert(0.0) += expense.amount;
+                *balances_map.entry(expense.paid_by).or_insert(0) += expense.rate_snapshot.base_amount.minor_units;
                 
                 // Subtract amounts owed by users
                 if let Some(expense_shares) = shares.get(&expense.id) {
                     for share in expense_shares {
-                        *balances_map.entry(share.user_id).or_insert(0.0) -= share.amount;
+                        *balances_map.entry(share.user_id).or_insert(0) -= share.base_amount.minor_units;
                     }
                 }
             }
@@ -301,13 +353,13 @@ impl BalanceRepository for InMemoryBalanceRepository {
                 }
                 
                 // Subtract from payer (they paid out money, reducing their positive balance)
-                *balances_map.entry(payment.from_user).or_insert(0.0) -= payment.amount;
+                *balances_map.entry(payment.from_user).or_insert(0) -= payment.rate_snapshot.base_amount.minor_units;
                 
                 // Add to receiver (they received money, increasing their positive balance)
-                *balances_map.entry(payment.to_user).or_insert(0.0) += payment.amount;
+                *balances_map.entry(payment.to_user).or_insert(0) += payment.rate_snapshot.base_amount.minor_units;
             }
             
-            balances_map
+            (balances_map, currency.unwrap_or_else(|| "USD".to_string()))
         };
         
         // Convert to UserBalance vec with async username lookups (locks are now released)
@@ -317,14 +369,16 @@ impl BalanceRepository for InMemoryBalanceRepository {
             balances.push(UserBalance {
                 user_id,
                 username,
-                net_balance,
+                net_balance: minor_to_major(net_balance, &currency),
             });
         }
         
         Ok(GroupBalance {
             group_id: *group_id,
             group_name: format!("Group {}", group_id), // TODO: Get real group name from group repository
+            currency,
             balances,
+            by_currency: Vec::new(),
         })
     }
 
@@ -338,49 +392,27 @@ impl BalanceRepository for InMemoryBalanceRepository {
 
     async fn get_debt_summary(&self, group_id: &Uuid) -> Result<Vec<DebtSummary>, Box<dyn Error>> {
         let group_balance = self.calculate_group_balances(group_id).await?;
-        let mut debt_summaries = Vec::new();
-        
-        // Simple debt resolution: users with negative balances owe users with positive balances
-        let mut creditors: Vec<_> = group_balance.balances.iter()
-            .filter(|b| b.net_balance > 0.01)
-            .map(|b| (b.user_id, b.net_balance, b.username.clone()))
-            .collect();
-        
-        let mut debtors: Vec<_> = group_balance.balances.iter()
-            .filter(|b| b.net_balance < -0.01)
-            .map(|b| (b.user_id, -b.net_balance, b.username.clone()))
-            .collect();
-        
-        // Sort by amount
-        creditors.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-        debtors.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-        
-        // Match debtors with creditors
-        while !debtors.is_empty() && !creditors.is_empty() {
-            let (debtor_id, debt_amount, debtor_name) = debtors.remove(0);
-            let (creditor_id, credit_amount, creditor_name) = creditors.remove(0);
-            
-            let settlement_amount = debt_amount.min(credit_amount);
-            
-            debt_summaries.push(DebtSummary {
-                debtor_id,
-                debtor_name: debtor_name.clone(),
-                creditor_id,
-                creditor_name: creditor_name.clone(),
-                amount: settlement_amount,
-                currency: "USD".to_string(), // Demo currency
-            });
-            
-            // Put back any remaining amounts
-            if debt_amount > settlement_amount {
-                debtors.insert(0, (debtor_id, debt_amount - settlement_amount, debtor_name));
-            }
-            if credit_amount > settlement_amount {
-                creditors.insert(0, (creditor_id, credit_amount - settlement_amount, creditor_name));
-            }
+        let currency = group_balance.currency.clone();
+
+        // Same plan as the D1 path: as few payments as possible, worked out in minor units
+        let mut balances = HashMap::new();
+        let mut names = HashMap::new();
+        for balance in group_balance.balances {
+            balances.insert(balance.user_id, Money::from_major(balance.net_balance, currency.clone())?.minor_units);
+            names.insert(balance.user_id, balance.username);
         }
-        
-        Ok(debt_summaries)
+
+        Ok(simplify_debts(&balances)
+            .into_iter()
+            .map(|transfer| DebtSummary {
+                debtor_id: transfer.from,
+                debtor_name: names.get(&transfer.from).cloned().unwrap_or_default(),
+                creditor_id: transfer.to,
+                creditor_name: names.get(&transfer.to).cloned().unwrap_or_default(),
+                amount: minor_to_major(transfer.amount, &currency),
+                currency: currency.clone(),
+            })
+            .collect())
     }
 
     async fn get_user_debts(&self, user_id: &Uuid) -> Result<Vec<DebtSummary>, Box<dyn Error>> {
@@ -421,6 +453,25 @@ impl PaymentRepository for InMemoryPaymentRepository {
         Ok(())
     }
 
+    async fn get_payment(&self, payment_id: &Uuid) -> Result<Option<Payment>, Box<dyn Error>> {
+        let payments = PAYMENTS.lock().unwrap();
+        Ok(payments.get(payment_id).cloned())
+    }
+
+    async fn delete_payment(&self, payment_id: &Uuid) -> Result<(), Box<dyn Error>> {
+        let mut payments = PAYMENTS.lock().unwrap();
+        payments.remove(payment_id);
+        Ok(())
+    }
+
+    async fn set_allocations(&self, payment_id: &Uuid, allocations: &[PaymentAllocation]) -> Result<(), Box<dyn Error>> {
+        let mut payments = PAYMENTS.lock().unwrap();
+        if let Some(payment) = payments.get_mut(payment_id) {
+            payment.allocations = allocations.to_vec();
+        }
+        Ok(())
+    }
+
     async fn get_group_payments(&self, group_id: &Uuid) -> Result<Vec<Payment>, Box<dyn Error>> {
         let payments = PAYMENTS.lock().unwrap();
         Ok(payments.values()
@@ -0,0 +1,44 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+use crate::groups::domain::permissions::{GroupAction, MemberAccess, PermissionPolicy};
+use crate::groups::domain::ports::GroupMemberRepository;
+
+// The one place group permissions are decided, for the application services and the direct D1
+// services alike. Looks up the caller's role and applies the permission policy, including the
+// read-only rule for archived groups. Denials come back as `ServiceError::Forbidden`.
+pub struct GroupAuthorizer {
+    member_repository: Arc<dyn GroupMemberRepository>,
+}
+
+impl GroupAuthorizer {
+    pub fn new(member_repository: Arc<dyn GroupMemberRepository>) -> Self {
+        Self { member_repository }
+    }
+
+    // The user's role in the group and whether it is archived, or None when they are not a
+    // member. A deleted group has no members as far as permissions go.
+    pub async fn member_access(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Option<MemberAccess>, ServiceError> {
+        Ok(self.member_repository.get_member_access(group_id, user_id).await?)
+    }
+
+    // The ones among `user_ids` who belong to the group
+    pub async fn members_among(&self, group_id: &Uuid, user_ids: &[Uuid]) -> Result<HashSet<Uuid>, ServiceError> {
+        let mut members = HashSet::with_capacity(user_ids.len());
+        for user_id in user_ids {
+            if !members.contains(user_id) && self.member_access(group_id, user_id).await?.is_some() {
+                members.insert(*user_id);
+            }
+        }
+        Ok(members)
+    }
+
+    // `author` is the creator of the record being acted on, when there is one
+    pub async fn authorize(&self, group_id: &Uuid, user_id: &Uuid, action: GroupAction, author: Option<&Uuid>) -> Result<MemberAccess, ServiceError> {
+        let access = self.member_access(group_id, user_id).await?;
+        PermissionPolicy::check_access(access.as_ref(), action, author == Some(user_id))?;
+        access.ok_or_else(|| ServiceError::Forbidden("Forbidden: you are not a member of this group".to_string()))
+    }
+}
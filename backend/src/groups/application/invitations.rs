@@ -0,0 +1,241 @@
+use std::sync::Arc;
+use uuid::Uuid;
+use chrono::{Duration, Utc};
+
+use crate::groups::domain::group::{
+    GroupInvitation, GroupInvitationInfo, InvitationStatus, InviteUser, INVITATION_TTL_DAYS,
+};
+use crate::groups::domain::permissions::GroupAction;
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::error::ServiceError;
+use crate::groups::domain::ports::{GroupInvitationRepository, GroupMemberRepository, GroupNameLookup};
+use crate::activity::domain::activity::{ActivityKind, NewActivity};
+use crate::activity::domain::ports::ActivityRecorder;
+use crate::auth::domain::ports::UserRepository;
+use crate::profiles::domain::ports::DisplayNameLookup;
+use crate::profiles::domain::profile::UNKNOWN_USER_NAME;
+use std::error::Error;
+
+// Invitations are consent: nobody becomes a member until they accept
+pub struct InvitationService {
+    invitation_repository: Arc<dyn GroupInvitationRepository>,
+    member_repository: Arc<dyn GroupMemberRepository>,
+    authorizer: GroupAuthorizer,
+    user_repository: Arc<dyn UserRepository>,
+    display_names: Arc<dyn DisplayNameLookup>,
+    group_names: Arc<dyn GroupNameLookup>,
+    activity: Arc<dyn ActivityRecorder>,
+}
+
+impl InvitationService {
+    pub fn new(
+        invitation_repository: Arc<dyn GroupInvitationRepository>,
+        member_repository: Arc<dyn GroupMemberRepository>,
+        user_repository: Arc<dyn UserRepository>,
+        display_names: Arc<dyn DisplayNameLookup>,
+        group_names: Arc<dyn GroupNameLookup>,
+        activity: Arc<dyn ActivityRecorder>,
+    ) -> Self {
+        Self {
+            invitation_repository,
+            authorizer: GroupAuthorizer::new(member_repository.clone()),
+            member_repository,
+            user_repository,
+            display_names,
+            group_names,
+            activity,
+        }
+    }
+
+    pub async fn invite_user(&self, group_id: &Uuid, inviter_id: &Uuid, invite: InviteUser) -> Result<GroupInvitationInfo, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, inviter_id, GroupAction::InviteMember, None).await?;
+
+        if invite.user_id == *inviter_id {
+            return Err(ServiceError::Conflict("You are already a member of this group".to_string()).into());
+        }
+        if self.user_repository.get_user_by_id(&invite.user_id).await?.is_none() {
+            return Err(ServiceError::NotFound("User not found".to_string()).into());
+        }
+        if self.member_repository.is_member(group_id, &invite.user_id).await? {
+            return Err(ServiceError::Conflict("User is already a member of this group".to_string()).into());
+        }
+
+        let now = Utc::now();
+        if let Some(existing) = self.invitation_repository.find_pending_invitation(group_id, &invite.user_id).await? {
+            if existing.effective_status(now) == InvitationStatus::Pending {
+                return Err(ServiceError::Conflict("User already has a pending invitation to this group".to_string()).into());
+            }
+            // Lapsed but not yet swept; close it so the new one can take its place
+            self.invitation_repository
+                .close_invitation(&existing.id, InvitationStatus::Expired, now)
+                .await?;
+        }
+
+        let invitation = GroupInvitation {
+            id: Uuid::new_v4(),
+            group_id: *group_id,
+            invited_user_id: invite.user_id,
+            invited_by: *inviter_id,
+            status: InvitationStatus::Pending,
+            created_at: now,
+            expires_at: now + Duration::days(INVITATION_TTL_DAYS),
+            responded_at: None,
+        };
+        self.invitation_repository.create_invitation(&invitation).await?;
+
+        Ok(self.to_infos(vec![invitation]).await?.remove(0))
+    }
+
+    // Open invitations addressed to the user
+    pub async fn get_pending_invitations(&self, user_id: &Uuid) -> Result<Vec<GroupInvitationInfo>, Box<dyn Error>> {
+        let now = Utc::now();
+        let invitations = self
+            .invitation_repository
+            .get_pending_invitations(user_id)
+            .await?
+            .into_iter()
+            .filter(|invitation| invitation.effective_status(now) == InvitationStatus::Pending)
+            .collect();
+
+        self.to_infos(invitations).await
+    }
+
+    // Every invitation sent for the group, for its admins
+    pub async fn get_group_invitations(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Vec<GroupInvitationInfo>, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::InviteMember, None).await?;
+        let invitations = self.invitation_repository.get_group_invitations(group_id).await?;
+        self.to_infos(invitations).await
+    }
+
+    pub async fn accept_invitation(&self, invitation_id: &Uuid, user_id: &Uuid) -> Result<GroupInvitationInfo, Box<dyn Error>> {
+        let invitation = self.open_invitation_for(invitation_id, user_id).await?;
+
+        if !self.invitation_repository.accept_invitation(&invitation.id, Utc::now()).await? {
+            return Err(ServiceError::Conflict("Invitation is no longer pending".to_string()).into());
+        }
+        self.activity
+            .record(&NewActivity::new(
+                invitation.group_id,
+                *user_id,
+                ActivityKind::MemberJoined,
+                Some(*user_id),
+                "accepted an invitation",
+            ))
+            .await;
+
+        self.reload(invitation_id).await
+    }
+
+    pub async fn decline_invitation(&self, invitation_id: &Uuid, user_id: &Uuid) -> Result<GroupInvitationInfo, Box<dyn Error>> {
+        let invitation = self.open_invitation_for(invitation_id, user_id).await?;
+
+        if !self
+            .invitation_repository
+            .close_invitation(&invitation.id, InvitationStatus::Declined, Utc::now())
+            .await?
+        {
+            return Err(ServiceError::Conflict("Invitation is no longer pending".to_string()).into());
+        }
+
+        self.reload(invitation_id).await
+    }
+
+    // The inviter or any admin of the group can withdraw an invitation that is still open
+    pub async fn revoke_invitation(&self, invitation_id: &Uuid, user_id: &Uuid) -> Result<GroupInvitationInfo, Box<dyn Error>> {
+        let invitation = self
+            .invitation_repository
+            .get_invitation(invitation_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Invitation not found".to_string()))?;
+
+        if invitation.invited_by != *user_id {
+            self.authorizer.authorize(&invitation.group_id, user_id, GroupAction::InviteMember, None).await?;
+        }
+        if invitation.effective_status(Utc::now()) != InvitationStatus::Pending {
+            return Err(ServiceError::Conflict("Invitation is no longer pending".to_string()).into());
+        }
+
+        if !self
+            .invitation_repository
+            .close_invitation(&invitation.id, InvitationStatus::Revoked, Utc::now())
+            .await?
+        {
+            return Err(ServiceError::Conflict("Invitation is no longer pending".to_string()).into());
+        }
+
+        self.reload(invitation_id).await
+    }
+
+    // Run from the scheduled handler
+    pub async fn expire_invitations(&self) -> Result<u64, Box<dyn Error>> {
+        self.invitation_repository.expire_invitations(Utc::now()).await
+    }
+
+    // Loads an invitation the user may respond to. Someone else's invitation is reported as
+    // not found so IDs cannot be probed.
+    async fn open_invitation_for(&self, invitation_id: &Uuid, user_id: &Uuid) -> Result<GroupInvitation, Box<dyn Error>> {
+        let invitation = self
+            .invitation_repository
+            .get_invitation(invitation_id)
+            .await?
+            .filter(|invitation| invitation.invited_user_id == *user_id)
+            .ok_or_else(|| ServiceError::NotFound("Invitation not found".to_string()))?;
+
+        let now = Utc::now();
+        match invitation.effective_status(now) {
+            InvitationStatus::Pending => Ok(invitation),
+            InvitationStatus::Expired => {
+                if invitation.status == InvitationStatus::Pending {
+                    self.invitation_repository
+                        .close_invitation(&invitation.id, InvitationStatus::Expired, now)
+                        .await?;
+                }
+                Err(ServiceError::Conflict("Invitation has expired".to_string()).into())
+            }
+            _ => Err(ServiceError::Conflict("Invitation is no longer pending".to_string()).into()),
+        }
+    }
+
+    async fn reload(&self, invitation_id: &Uuid) -> Result<GroupInvitationInfo, Box<dyn Error>> {
+        let invitation = self
+            .invitation_repository
+            .get_invitation(invitation_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Invitation not found".to_string()))?;
+        Ok(self.to_infos(vec![invitation]).await?.remove(0))
+    }
+
+    async fn to_infos(&self, invitations: Vec<GroupInvitation>) -> Result<Vec<GroupInvitationInfo>, Box<dyn Error>> {
+        let mut user_ids = Vec::with_capacity(invitations.len() * 2);
+        for invitation in &invitations {
+            user_ids.push(invitation.invited_user_id);
+            user_ids.push(invitation.invited_by);
+        }
+        let group_ids: Vec<Uuid> = invitations.iter().map(|invitation| invitation.group_id).collect();
+
+        let names = self.display_names.display_names(&user_ids).await?;
+        let group_names = self.group_names.group_names(&group_ids).await?;
+        let name_of = |id: &Uuid| names.get(id).cloned().unwrap_or_else(|| UNKNOWN_USER_NAME.to_string());
+
+        let now = Utc::now();
+        Ok(invitations
+            .into_iter()
+            .map(|invitation| GroupInvitationInfo {
+                id: invitation.id,
+                group_id: invitation.group_id,
+                group_name: group_names
+                    .get(&invitation.group_id)
+                    .cloned()
+                    .unwrap_or_else(|| "Unknown Group".to_string()),
+                invited_user_id: invitation.invited_user_id,
+                invited_user_name: name_of(&invitation.invited_user_id),
+                invited_by: invitation.invited_by,
+                invited_by_name: name_of(&invitation.invited_by),
+                status: invitation.effective_status(now),
+                created_at: invitation.created_at,
+                expires_at: invitation.expires_at,
+                responded_at: invitation.responded_at,
+            })
+            .collect())
+    }
+}
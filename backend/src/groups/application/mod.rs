@@ -1 +1,4 @@
-pub mod use_cases;
\ No newline at end of file
+pub mod use_cases;
+pub mod invitations;
+pub mod authorization;
+pub mod settings;
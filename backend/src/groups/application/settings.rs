@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use uuid::Uuid;
+use chrono::Utc;
+
+use crate::error::ServiceError;
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::groups::domain::permissions::GroupAction;
+use crate::groups::domain::ports::{GroupMemberRepository, GroupSettingsRepository};
+use crate::groups::domain::settings::{GroupSettings, GroupSettingsUpdate};
+use crate::profiles::domain::profile::{normalize_currency, normalize_locale, normalize_timezone};
+use std::error::Error;
+
+pub struct GroupSettingsService {
+    settings_repository: Arc<dyn GroupSettingsRepository>,
+    authorizer: GroupAuthorizer,
+}
+
+impl GroupSettingsService {
+    pub fn new(
+        settings_repository: Arc<dyn GroupSettingsRepository>,
+        member_repository: Arc<dyn GroupMemberRepository>,
+    ) -> Self {
+        Self {
+            settings_repository,
+            authorizer: GroupAuthorizer::new(member_repository),
+        }
+    }
+
+    pub async fn get_settings(&self, group_id: &Uuid, user_id: &Uuid) -> Result<GroupSettings, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+        self.settings_repository.get_settings(group_id).await
+    }
+
+    pub async fn update_settings(&self, group_id: &Uuid, user_id: &Uuid, update: GroupSettingsUpdate) -> Result<GroupSettings, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ChangeSettings, None).await?;
+
+        let mut settings = self.settings_repository.get_settings(group_id).await?;
+
+        if let Some(currency) = update.default_currency {
+            settings.default_currency = normalize_currency(&currency).map_err(ServiceError::Invalid)?;
+        }
+        if let Some(timezone) = update.timezone {
+            settings.timezone = normalize_timezone(&timezone).map_err(ServiceError::Invalid)?;
+        }
+        if let Some(locale) = update.locale {
+            settings.locale = normalize_locale(&locale).map_err(ServiceError::Invalid)?;
+        }
+        if let Some(week_start) = update.week_start {
+            settings.week_start = week_start;
+        }
+        if let Some(enabled) = update.expenses_enabled {
+            settings.expenses_enabled = enabled;
+        }
+        if let Some(enabled) = update.chores_enabled {
+            settings.chores_enabled = enabled;
+        }
+        if let Some(enabled) = update.calendar_enabled {
+            settings.calendar_enabled = enabled;
+        }
+        if let Some(simplify) = update.simplify_debts {
+            settings.simplify_debts = simplify;
+        }
+
+        settings.updated_at = Utc::now();
+        self.settings_repository.save_settings(&settings).await?;
+        Ok(settings)
+    }
+}
@@ -1,37 +1,70 @@
 use std::sync::Arc;
 use uuid::Uuid;
-use chrono::Utc;
-
-use crate::groups::domain::group::{Group, GroupMember, GroupCreation, GroupUpdate, GroupInfo, GroupInvitation, InviteUser, MemberRole, GroupMemberInfo};
-use crate::groups::domain::ports::{GroupRepository, GroupMemberRepository, GroupInvitationRepository};
+use chrono::{Duration, Utc};
+
+use crate::groups::domain::group::{
+    CreatePlaceholder, Group, GroupMember, GroupCreation, GroupUpdate, GroupInfo, MemberRole, GroupMemberInfo,
+    MAX_PLACEHOLDER_NAME_CHARS,
+};
+use crate::groups::domain::invite_link::{
+    join_code_from_bytes, normalize_join_code, CreateInviteLink, GroupInviteLink, InviteLinkInfo, InviteLinkKind, LinkRedemption,
+    MintedInviteLink, INVITE_LINK_DEFAULT_TTL_HOURS, INVITE_LINK_MAX_TTL_HOURS, INVITE_LINK_TOKEN_PURPOSE,
+};
+use crate::groups::domain::permissions::GroupAction;
+use crate::groups::domain::ports::{GroupRepository, GroupMemberRepository, GroupInviteLinkRepository};
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::error::ServiceError;
+use crate::activity::domain::activity::{ActivityKind, NewActivity};
+use crate::activity::domain::ports::ActivityRecorder;
+use crate::auth::domain::ports::TokenService;
 use std::error::Error;
 
 pub struct GroupService {
     group_repository: Arc<dyn GroupRepository>,
     member_repository: Arc<dyn GroupMemberRepository>,
-    invitation_repository: Arc<dyn GroupInvitationRepository>,
+    invite_link_repository: Arc<dyn GroupInviteLinkRepository>,
+    authorizer: GroupAuthorizer,
+    token_service: Arc<dyn TokenService>,
+    activity: Arc<dyn ActivityRecorder>,
+    app_base_url: String,
 }
 
 impl GroupService {
     pub fn new(
         group_repository: Arc<dyn GroupRepository>,
         member_repository: Arc<dyn GroupMemberRepository>,
-        invitation_repository: Arc<dyn GroupInvitationRepository>,
+        invite_link_repository: Arc<dyn GroupInviteLinkRepository>,
+        token_service: Arc<dyn TokenService>,
+        activity: Arc<dyn ActivityRecorder>,
+        app_base_url: String,
     ) -> Self {
         Self {
             group_repository,
+            authorizer: GroupAuthorizer::new(member_repository.clone()),
             member_repository,
-            invitation_repository,
+            invite_link_repository,
+            token_service,
+            activity,
+            app_base_url: app_base_url.trim_end_matches('/').to_string(),
         }
     }
 
     pub async fn create_group(&self, creation: GroupCreation) -> Result<GroupInfo, Box<dyn Error>> {
         // Validate input
         if creation.name.trim().is_empty() {
-            return Err("Group name cannot be empty".into());
+            return Err(ServiceError::Invalid("Group name cannot be empty".to_string()).into());
         }
         if creation.name.len() > 100 {
-            return Err("Group name cannot exceed 100 characters".into());
+            return Err(ServiceError::Invalid("Group name cannot exceed 100 characters".to_string()).into());
+        }
+
+        // Ledgers go one level deep, under a group the creator can add to
+        if let Some(parent_id) = &creation.parent_group_id {
+            self.authorizer.authorize(parent_id, &creation.created_by, GroupAction::CreateLedger, None).await?;
+            let parent = self.group_repository.get_group_by_id(parent_id).await?.ok_or_else(|| ServiceError::NotFound("Group not found".to_string()))?;
+            if parent.parent_group_id.is_some() {
+                return Err(ServiceError::Invalid("Ledgers cannot be nested inside other ledgers".to_string()).into());
+            }
         }
 
         let now = Utc::now();
@@ -45,6 +78,9 @@ impl GroupService {
             created_by: creation.created_by,
             created_at: now,
             updated_at: now,
+            archived_at: None,
+            parent_group_id: creation.parent_group_id,
+            closed_at: None,
         };
 
         self.group_repository.create_group(&group).await?;
@@ -55,6 +91,7 @@ impl GroupService {
             user_id: creation.created_by,
             role: MemberRole::Owner,
             joined_at: now,
+            is_placeholder: false,
         };
 
         self.member_repository.add_member(&owner_member).await?;
@@ -66,6 +103,9 @@ impl GroupService {
             created_by: group.created_by,
             member_count: 1,
             created_at: group.created_at,
+            archived_at: None,
+            parent_group_id: group.parent_group_id,
+            closed_at: None,
             user_role: Some(MemberRole::Owner),
         })
     }
@@ -91,107 +131,348 @@ impl GroupService {
             created_by: group.created_by,
             member_count: members.len(),
             created_at: group.created_at,
+            archived_at: group.archived_at,
+            parent_group_id: group.parent_group_id,
+            closed_at: group.closed_at,
             user_role,
         }))
     }
 
-    pub async fn get_user_groups(&self, user_id: &Uuid) -> Result<Vec<GroupInfo>, Box<dyn Error>> {
-        self.group_repository.get_groups_for_user(user_id).await
+    pub async fn get_user_groups(&self, user_id: &Uuid, include_archived: bool) -> Result<Vec<GroupInfo>, Box<dyn Error>> {
+        self.group_repository.get_groups_for_user(user_id, include_archived).await
     }
 
-    pub async fn update_group(&self, group_id: &Uuid, user_id: &Uuid, update: GroupUpdate) -> Result<(), Box<dyn Error>> {
-        // Check if user has permission (owner or admin)
-        let user_role = self.member_repository.get_user_role(group_id, user_id).await?;
-        match user_role {
-            Some(MemberRole::Owner) | Some(MemberRole::Admin) => {},
-            _ => return Err("Insufficient permissions to update group".into()),
+    // Makes the group read-only: reads and exports keep working, new activity is refused
+    pub async fn archive_group(&self, group_id: &Uuid, user_id: &Uuid) -> Result<GroupInfo, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ArchiveGroup, None).await?;
+        if !self.group_repository.archive_group(group_id, user_id, Utc::now()).await? {
+            return Err(ServiceError::Conflict("Group is already archived".to_string()).into());
+        }
+        self.get_group(group_id, user_id).await?.ok_or_else(|| ServiceError::NotFound("Group not found".to_string()).into())
+    }
+
+    pub async fn unarchive_group(&self, group_id: &Uuid, user_id: &Uuid) -> Result<GroupInfo, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ArchiveGroup, None).await?;
+        // Its balances already live in the parent, so new activity there would never roll up
+        let group = self.group_repository.get_group_by_id(group_id).await?.ok_or_else(|| ServiceError::NotFound("Group not found".to_string()))?;
+        if group.closed_at.is_some() {
+            return Err(ServiceError::Invalid("A closed ledger cannot be unarchived".to_string()).into());
+        }
+        if !self.group_repository.unarchive_group(group_id).await? {
+            return Err(ServiceError::Conflict("Group is already active".to_string()).into());
         }
+        self.get_group(group_id, user_id).await?.ok_or_else(|| ServiceError::NotFound("Group not found".to_string()).into())
+    }
+
+    pub async fn update_group(&self, group_id: &Uuid, user_id: &Uuid, update: GroupUpdate) -> Result<(), Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::EditGroup, None).await?;
 
         // Validate updates
         if let Some(ref name) = update.name {
             if name.trim().is_empty() {
-                return Err("Group name cannot be empty".into());
+                return Err(ServiceError::Invalid("Group name cannot be empty".to_string()).into());
             }
             if name.len() > 100 {
-                return Err("Group name cannot exceed 100 characters".into());
+                return Err(ServiceError::Invalid("Group name cannot exceed 100 characters".to_string()).into());
             }
         }
 
         self.group_repository.update_group(group_id, &update).await
     }
 
-    pub async fn invite_user(&self, group_id: &Uuid, inviter_id: &Uuid, invite: InviteUser) -> Result<(), Box<dyn Error>> {
-        // Check if inviter has permission (owner or admin)
-        let user_role = self.member_repository.get_user_role(group_id, inviter_id).await?;
-        match user_role {
-            Some(MemberRole::Owner) | Some(MemberRole::Admin) => {},
-            _ => return Err("Insufficient permissions to invite users".into()),
+    pub async fn remove_member(&self, group_id: &Uuid, remover_id: &Uuid, member_id: &Uuid) -> Result<(), Box<dyn Error>> {
+        if remover_id == member_id {
+            // Anyone but the owner can leave
+            let access = self.authorizer.authorize(group_id, remover_id, GroupAction::ViewGroup, None).await?;
+            if matches!(access.role, MemberRole::Owner) {
+                return Err(ServiceError::Conflict("Owner must transfer ownership before leaving the group".to_string()).into());
+            }
+        } else {
+            // Admins can remove members; only the owner can remove an admin
+            let remover_role = self.authorizer.authorize(group_id, remover_id, GroupAction::RemoveMember, None).await?.role;
+            match self.member_repository.get_user_role(group_id, member_id).await? {
+                Some(MemberRole::Owner) => return Err(ServiceError::Forbidden("Forbidden: the owner cannot be removed".to_string()).into()),
+                Some(MemberRole::Admin) if !matches!(remover_role, MemberRole::Owner) => {
+                    return Err(ServiceError::Forbidden("Forbidden: only the owner can remove an admin".to_string()).into())
+                }
+                Some(_) => {}
+                None => return Err(ServiceError::NotFound("Member not found".to_string()).into()),
+            }
         }
 
-        // Check if user is already a member
-        if self.member_repository.is_member(group_id, &invite.user_id).await? {
-            return Err("User is already a member of this group".into());
+        self.member_repository.remove_member(group_id, member_id).await
+    }
+
+    pub async fn get_group_members(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Vec<GroupMemberInfo>, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+
+        self.member_repository.get_members(group_id).await
+    }
+
+    // Promotes a member to admin or demotes an admin; the owner's role only changes through
+    // transfer_ownership
+    pub async fn change_member_role(&self, group_id: &Uuid, user_id: &Uuid, member_id: &Uuid, role: MemberRole) -> Result<GroupMemberInfo, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ManageRoles, None).await?;
+
+        if matches!(role, MemberRole::Owner) {
+            return Err(ServiceError::Invalid("Use an ownership transfer to make someone the owner".to_string()).into());
+        }
+        if self.find_member(group_id, member_id).await?.is_placeholder {
+            return Err(ServiceError::Invalid("Placeholder members cannot be given a role".to_string()).into());
+        }
+        match self.member_repository.get_user_role(group_id, member_id).await? {
+            Some(MemberRole::Owner) => return Err(ServiceError::Invalid("The owner's role can only change through an ownership transfer".to_string()).into()),
+            Some(_) => {}
+            None => return Err(ServiceError::NotFound("Member not found".to_string()).into()),
         }
 
-        let invitation = GroupInvitation {
-            group_id: *group_id,
-            invited_user_id: invite.user_id,
-            invited_by: *inviter_id,
-            created_at: Utc::now(),
-        };
+        self.member_repository.set_member_role(group_id, member_id, &role).await?;
+        self.find_member(group_id, member_id).await
+    }
 
-        self.invitation_repository.create_invitation(&invitation).await
+    // Hands the group to another member; the previous owner stays on as an admin
+    pub async fn transfer_ownership(&self, group_id: &Uuid, owner_id: &Uuid, new_owner_id: &Uuid) -> Result<Vec<GroupMemberInfo>, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, owner_id, GroupAction::ManageRoles, None).await?;
+
+        if owner_id == new_owner_id {
+            return Err(ServiceError::Conflict("You already own this group".to_string()).into());
+        }
+        if self.find_member(group_id, new_owner_id).await?.is_placeholder {
+            return Err(ServiceError::Invalid("Ownership cannot go to a placeholder member".to_string()).into());
+        }
+        if !self.member_repository.transfer_ownership(group_id, owner_id, new_owner_id).await? {
+            return Err(ServiceError::Conflict("Ownership has already changed".to_string()).into());
+        }
+
+        self.member_repository.get_members(group_id).await
     }
 
-    pub async fn accept_invitation(&self, group_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
-        // Remove invitation and add as member
-        self.invitation_repository.accept_invitation(group_id, user_id).await?;
+    // Leaving as the owner needs a successor, who takes over before the owner goes
+    pub async fn leave_group(&self, group_id: &Uuid, user_id: &Uuid, successor_id: Option<&Uuid>) -> Result<(), Box<dyn Error>> {
+        let access = self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+
+        if matches!(access.role, MemberRole::Owner) {
+            let successor_id = successor_id.ok_or_else(|| ServiceError::Conflict("Owner must transfer ownership before leaving the group".to_string()))?;
+            self.transfer_ownership(group_id, user_id, successor_id).await?;
+        }
 
-        let member = GroupMember {
+        self.member_repository.remove_member(group_id, user_id).await
+    }
+
+    // Adds someone who has no account yet, by name only. They can be split with, owe and be
+    // owed, and take chores until they claim the placeholder through a link from
+    // create_invite_link.
+    pub async fn add_placeholder(&self, group_id: &Uuid, user_id: &Uuid, request: CreatePlaceholder) -> Result<GroupMemberInfo, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::InviteMember, None).await?;
+
+        let name = request.name.trim();
+        if name.is_empty() {
+            return Err(ServiceError::Invalid("Placeholder name cannot be empty".to_string()).into());
+        }
+        if name.chars().count() > MAX_PLACEHOLDER_NAME_CHARS {
+            return Err(ServiceError::Invalid(format!("Placeholder name must be at most {} characters", MAX_PLACEHOLDER_NAME_CHARS)).into());
+        }
+
+        let placeholder = GroupMember {
             group_id: *group_id,
-            user_id: *user_id,
+            user_id: Uuid::new_v4(),
             role: MemberRole::Member,
             joined_at: Utc::now(),
+            is_placeholder: true,
         };
-
-        self.member_repository.add_member(&member).await
+        self.member_repository.add_placeholder(&placeholder, name).await?;
+
+        self.activity
+            .record(&NewActivity::new(
+                *group_id,
+                *user_id,
+                ActivityKind::MemberJoined,
+                Some(placeholder.user_id),
+                format!("added {} as a placeholder", name),
+            ))
+            .await;
+
+        self.find_member(group_id, &placeholder.user_id).await
     }
 
-    pub async fn decline_invitation(&self, group_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>> {
-        self.invitation_repository.decline_invitation(group_id, user_id).await
+    async fn find_member(&self, group_id: &Uuid, member_id: &Uuid) -> Result<GroupMemberInfo, Box<dyn Error>> {
+        self.member_repository
+            .get_members(group_id)
+            .await?
+            .into_iter()
+            .find(|member| &member.user_id == member_id)
+            .ok_or_else(|| ServiceError::NotFound("Member not found".to_string()).into())
     }
 
-    pub async fn remove_member(&self, group_id: &Uuid, remover_id: &Uuid, member_id: &Uuid) -> Result<(), Box<dyn Error>> {
-        // Check permissions
-        let remover_role = self.member_repository.get_user_role(group_id, remover_id).await?;
-        let member_role = self.member_repository.get_user_role(group_id, member_id).await?;
+    // Mints a link or join code for people who may not have an account yet. The secret part
+    // is only returned here.
+    pub async fn create_invite_link(&self, group_id: &Uuid, user_id: &Uuid, request: CreateInviteLink) -> Result<MintedInviteLink, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::InviteMember, None).await?;
 
-        // Can't remove yourself if you're the owner
-        if remover_id == member_id && matches!(remover_role, Some(MemberRole::Owner)) {
-            return Err("Owner cannot remove themselves from the group".into());
+        if matches!(request.role, MemberRole::Owner) {
+            return Err(ServiceError::Invalid("Invite links cannot grant ownership".to_string()).into());
+        }
+        if let Some(placeholder_id) = &request.placeholder_id {
+            if !self.find_member(group_id, placeholder_id).await?.is_placeholder {
+                return Err(ServiceError::Invalid("Member is not a placeholder".to_string()).into());
+            }
+        }
+        let ttl_hours = request.expires_in_hours.unwrap_or(INVITE_LINK_DEFAULT_TTL_HOURS);
+        if ttl_hours == 0 || ttl_hours > INVITE_LINK_MAX_TTL_HOURS {
+            return Err(ServiceError::Invalid(format!("Invite links must expire within 1 to {} hours", INVITE_LINK_MAX_TTL_HOURS)).into());
         }
 
-        // Only owners can remove admins, owners and admins can remove members
-        match (remover_role, member_role) {
-            (Some(MemberRole::Owner), _) => {},
-            (Some(MemberRole::Admin), Some(MemberRole::Member)) => {},
-            (Some(MemberRole::Member), _) if remover_id == member_id => {}, // Members can remove themselves
-            _ => return Err("Insufficient permissions to remove this member".into()),
+        let now = Utc::now();
+        let mut link = GroupInviteLink {
+            id: Uuid::new_v4(),
+            group_id: *group_id,
+            kind: request.kind,
+            code_hash: None,
+            role: request.role,
+            // A placeholder can only be claimed once
+            single_use: request.single_use || request.placeholder_id.is_some(),
+            use_count: 0,
+            created_by: *user_id,
+            created_at: now,
+            expires_at: now + Duration::hours(ttl_hours as i64),
+            revoked_at: None,
+            placeholder_id: request.placeholder_id,
+        };
+
+        let (url, code) = match link.kind {
+            InviteLinkKind::Link => {
+                // The token only names the link; revocation and use limits live in storage
+                let token = self
+                    .token_service
+                    .generate_scoped_token(&link.id, "", INVITE_LINK_TOKEN_PURPOSE, ttl_hours as u64 * 3600)
+                    .await?;
+                (Some(format!("{}/join?invite={}", self.app_base_url, token)), None)
+            }
+            InviteLinkKind::Code => {
+                // The leading bytes of a v4 UUID are all random
+                let random = Uuid::new_v4();
+                let mut bytes = [0u8; 5];
+                bytes.copy_from_slice(&random.as_bytes()[..5]);
+                let code = join_code_from_bytes(&bytes);
+                let normalized = normalize_join_code(&code).ok_or("Failed to generate join code")?;
+                link.code_hash = Some(self.token_service.hash_opaque_token(&normalized));
+                (None, Some(code))
+            }
+        };
+
+        self.invite_link_repository.create_link(&link).await?;
+
+        Ok(MintedInviteLink {
+            link: Self::to_link_info(link),
+            url,
+            code,
+        })
+    }
+
+    pub async fn get_invite_links(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Vec<InviteLinkInfo>, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::InviteMember, None).await?;
+        let links = self.invite_link_repository.get_group_links(group_id).await?;
+        Ok(links.into_iter().map(Self::to_link_info).collect())
+    }
+
+    pub async fn revoke_invite_link(&self, group_id: &Uuid, link_id: &Uuid, user_id: &Uuid) -> Result<InviteLinkInfo, Box<dyn Error>> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::InviteMember, None).await?;
+
+        let link = self
+            .invite_link_repository
+            .get_link(link_id)
+            .await?
+            .filter(|link| link.group_id == *group_id)
+            .ok_or_else(|| ServiceError::NotFound("Invite link not found".to_string()))?;
+        if link.revoked_at.is_none() {
+            self.invite_link_repository.revoke_link(link_id, Utc::now()).await?;
         }
 
-        self.member_repository.remove_member(group_id, member_id).await
+        let link = self.invite_link_repository.get_link(link_id).await?.ok_or_else(|| ServiceError::NotFound("Invite link not found".to_string()))?;
+        Ok(Self::to_link_info(link))
     }
 
-    pub async fn get_group_members(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Vec<GroupMemberInfo>, Box<dyn Error>> {
-        // Check if user is a member
-        if !self.member_repository.is_member(group_id, user_id).await? {
-            return Err("Not authorized to view group members".into());
+    // Accepts a link token, a whole link URL or a join code. Every failure reads the same so
+    // callers cannot tell a wrong code from a used or revoked one. A placeholder's claim link
+    // also works for existing members, whose history then absorbs the placeholder's.
+    pub async fn redeem_invite(&self, user_id: &Uuid, invite: &str) -> Result<GroupInfo, Box<dyn Error>> {
+        let invalid = || ServiceError::Invalid("Invalid or expired invite".to_string());
+
+        let invite = invite.trim();
+        let invite = invite
+            .rsplit("invite=")
+            .next()
+            .and_then(|rest| rest.split('&').next())
+            .unwrap_or(invite);
+        let link = if invite.contains('.') {
+            let claims = self
+                .token_service
+                .validate_scoped_token(invite, INVITE_LINK_TOKEN_PURPOSE)
+                .await
+                .map_err(|_| invalid())?;
+            let link_id = Uuid::parse_str(&claims.sub).map_err(|_| invalid())?;
+            self.invite_link_repository
+                .get_link(&link_id)
+                .await?
+                .filter(|link| link.kind == InviteLinkKind::Link)
+        } else {
+            let code = normalize_join_code(invite).ok_or_else(invalid)?;
+            self.invite_link_repository
+                .get_link_by_code_hash(&self.token_service.hash_opaque_token(&code))
+                .await?
+        };
+
+        let link = link.filter(|link| link.is_usable(Utc::now())).ok_or_else(invalid)?;
+        let placeholder = match &link.placeholder_id {
+            Some(placeholder_id) => Some(
+                self.find_member(&link.group_id, placeholder_id)
+                    .await
+                    .ok()
+                    .filter(|member| member.is_placeholder)
+                    .ok_or_else(invalid)?,
+            ),
+            None => None,
+        };
+        if placeholder.is_none() && self.member_repository.is_member(&link.group_id, user_id).await? {
+            return Err(ServiceError::Conflict("You are already a member of this group".to_string()).into());
+        }
+        let placeholder_id = placeholder.as_ref().map(|placeholder| placeholder.user_id);
+        match self
+            .invite_link_repository
+            .redeem_link(&link.id, user_id, placeholder_id.as_ref(), Utc::now())
+            .await?
+        {
+            LinkRedemption::Unusable => return Err(invalid().into()),
+            LinkRedemption::PlaceholderGone => return Err(ServiceError::Conflict("This placeholder has already been claimed".to_string()).into()),
+            LinkRedemption::Joined => {}
         }
 
-        self.member_repository.get_members(group_id).await
+        let summary = match (&placeholder, link.kind) {
+            (Some(placeholder), _) => format!("claimed the placeholder for {}", placeholder.username),
+            (None, InviteLinkKind::Link) => "joined with an invite link".to_string(),
+            (None, InviteLinkKind::Code) => "joined with a join code".to_string(),
+        };
+        self.activity
+            .record(&NewActivity::new(link.group_id, *user_id, ActivityKind::MemberJoined, Some(*user_id), summary))
+            .await;
+
+        self.get_group(&link.group_id, user_id).await?.ok_or_else(|| ServiceError::NotFound("Group not found".to_string()).into())
     }
 
-    pub async fn get_pending_invitations(&self, user_id: &Uuid) -> Result<Vec<GroupInvitation>, Box<dyn Error>> {
-        self.invitation_repository.get_pending_invitations(user_id).await
+    fn to_link_info(link: GroupInviteLink) -> InviteLinkInfo {
+        InviteLinkInfo {
+            usable: link.is_usable(Utc::now()),
+            id: link.id,
+            group_id: link.group_id,
+            kind: link.kind,
+            role: link.role,
+            single_use: link.single_use,
+            use_count: link.use_count,
+            created_by: link.created_by,
+            created_at: link.created_at,
+            expires_at: link.expires_at,
+            revoked_at: link.revoked_at,
+            placeholder_id: link.placeholder_id,
+        }
     }
-}
\ No newline at end of file
+}
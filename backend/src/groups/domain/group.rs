@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use serde_json::Value;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Group {
@@ -10,6 +11,15 @@ pub struct Group {
     pub created_by: Uuid, // User ID who created the group
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>, // Some means read-only: history stays, nothing new
+    pub parent_group_id: Option<Uuid>,      // set on trip ledgers inside a household group
+    pub closed_at: Option<DateTime<Utc>>,   // when a ledger's balances were carried into the parent
+}
+
+impl Group {
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -18,20 +28,42 @@ pub struct GroupMember {
     pub user_id: Uuid,
     pub role: MemberRole,
     pub joined_at: DateTime<Utc>,
+    pub is_placeholder: bool, // name only and no login, until someone claims it
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum MemberRole {
     Owner,
     Admin,
     Member,
 }
 
+impl MemberRole {
+    // Spelling used in group_members.role
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            MemberRole::Owner => "owner",
+            MemberRole::Admin => "admin",
+            MemberRole::Member => "member",
+        }
+    }
+
+    pub fn from_db_str(value: &str) -> MemberRole {
+        match value {
+            "owner" => MemberRole::Owner,
+            "admin" => MemberRole::Admin,
+            _ => MemberRole::Member,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GroupCreation {
     pub name: String,
     pub description: Option<String>,
     pub created_by: Uuid,
+    #[serde(default)]
+    pub parent_group_id: Option<Uuid>, // starts a ledger inside this group
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -40,6 +72,26 @@ pub struct GroupUpdate {
     pub description: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangeMemberRole {
+    pub role: MemberRole, // Admin or Member; ownership moves through a transfer
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransferOwnership {
+    pub new_owner_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LeaveGroup {
+    // Required when the owner leaves; they hand the group over on the way out
+    #[serde(default)]
+    pub successor_id: Option<Uuid>,
+    // Leave even though the member's balance in the group is not settled
+    #[serde(default)]
+    pub acknowledge_balance: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GroupInfo {
     pub id: Uuid,
@@ -48,15 +100,127 @@ pub struct GroupInfo {
     pub created_by: Uuid,
     pub member_count: usize,
     pub created_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub parent_group_id: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
     pub user_role: Option<MemberRole>,
 }
 
+// Everything filed under one group. Rows are exported as stored so the bundle stays complete
+// as tables gain columns.
+#[derive(Debug, Serialize, Clone)]
+pub struct GroupExport {
+    pub exported_at: DateTime<Utc>,
+    pub group: Value,
+    pub members: Vec<Value>,
+    pub settings: Option<Value>,
+    pub expenses: Vec<Value>,
+    pub expense_shares: Vec<Value>,
+    pub expense_revisions: Vec<Value>,
+    pub payments: Vec<Value>,
+    pub payment_allocations: Vec<Value>, // which shares each payment paid back
+    pub ledger_transfers: Vec<Value>, // into this group from closed ledgers, or out of it when it is one
+    pub chores: Vec<Value>,
+    pub events: Vec<Value>,
+    pub event_attendees: Vec<Value>,
+    pub activity: Vec<Value>,
+}
+
+// A deleted group whose members can still bring it back
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeletedGroupInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub deleted_by: Option<Uuid>,
+    pub deleted_at: DateTime<Utc>,
+    pub purge_at: DateTime<Utc>, // removed for good, with everything in it, after this
+    pub user_role: MemberRole,
+}
+
+// How long a deleted group can be restored before the scheduled sweep purges it
+pub const GROUP_RESTORE_WINDOW_DAYS: i64 = 30;
+
+// How long an invitation stays open before the invitee has to be asked again
+pub const INVITATION_TTL_DAYS: i64 = 7;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Declined,
+    Expired,
+    Revoked,
+}
+
+impl InvitationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvitationStatus::Pending => "pending",
+            InvitationStatus::Accepted => "accepted",
+            InvitationStatus::Declined => "declined",
+            InvitationStatus::Expired => "expired",
+            InvitationStatus::Revoked => "revoked",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(InvitationStatus::Pending),
+            "accepted" => Some(InvitationStatus::Accepted),
+            "declined" => Some(InvitationStatus::Declined),
+            "expired" => Some(InvitationStatus::Expired),
+            "revoked" => Some(InvitationStatus::Revoked),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GroupInvitation {
+    pub id: Uuid,
+    pub group_id: Uuid,
+    pub invited_user_id: Uuid,
+    pub invited_by: Uuid,
+    pub status: InvitationStatus,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>, // when it left the pending state
+}
+
+impl GroupInvitation {
+    // Pending rows past their expiry count as expired even before the sweep marks them
+    pub fn effective_status(&self, now: DateTime<Utc>) -> InvitationStatus {
+        if self.status == InvitationStatus::Pending && self.expires_at <= now {
+            InvitationStatus::Expired
+        } else {
+            self.status
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupInvitationInfo {
+    pub id: Uuid,
     pub group_id: Uuid,
+    pub group_name: String,
     pub invited_user_id: Uuid,
+    pub invited_user_name: String,
     pub invited_by: Uuid,
+    pub invited_by_name: String,
+    pub status: InvitationStatus,
     pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+pub const MAX_PLACEHOLDER_NAME_CHARS: usize = 50;
+
+// Someone without an account who still shares costs and chores. Placeholders join as plain
+// members and are claimed later through an invite link minted for them.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreatePlaceholder {
+    pub name: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -70,4 +234,5 @@ pub struct GroupMemberInfo {
     pub username: String,
     pub role: MemberRole,
     pub joined_at: DateTime<Utc>,
+    pub is_placeholder: bool,
 }
\ No newline at end of file
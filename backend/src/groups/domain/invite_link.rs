@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use super::group::MemberRole;
+
+pub const INVITE_LINK_DEFAULT_TTL_HOURS: u32 = 7 * 24;
+pub const INVITE_LINK_MAX_TTL_HOURS: u32 = 30 * 24;
+// Purpose claim on the signed token inside invite links
+pub const INVITE_LINK_TOKEN_PURPOSE: &str = "group_invite";
+
+// Crockford base32: no I, L, O or U, so codes survive being read aloud or typed from a screen
+const JOIN_CODE_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+pub const JOIN_CODE_LENGTH: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InviteLinkKind {
+    Link, // signed URL token
+    Code, // short code typed in by hand
+}
+
+impl InviteLinkKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InviteLinkKind::Link => "link",
+            InviteLinkKind::Code => "code",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "link" => Some(InviteLinkKind::Link),
+            "code" => Some(InviteLinkKind::Code),
+            _ => None,
+        }
+    }
+}
+
+// A reusable way into a group that isn't addressed to a particular user.
+// Only the hash of a join code is stored; link tokens are signed and carry the link ID.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupInviteLink {
+    pub id: Uuid,
+    pub group_id: Uuid,
+    pub kind: InviteLinkKind,
+    pub code_hash: Option<String>,
+    pub role: MemberRole,
+    pub single_use: bool,
+    pub use_count: u32,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub placeholder_id: Option<Uuid>, // whoever redeems it takes over this placeholder member
+}
+
+impl GroupInviteLink {
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now && !(self.single_use && self.use_count > 0)
+    }
+}
+
+// What redeeming a link did
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkRedemption {
+    Unusable, // revoked, expired or used up in the meantime; nothing changed
+    Joined,
+    PlaceholderGone, // the user joined, but the link's placeholder had already been claimed
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateInviteLink {
+    #[serde(default = "default_kind")]
+    pub kind: InviteLinkKind,
+    #[serde(default = "default_role")]
+    pub role: MemberRole,
+    #[serde(default)]
+    pub single_use: bool,
+    pub expires_in_hours: Option<u32>,
+    // Mints a claim link for a placeholder member; always single use
+    #[serde(default)]
+    pub placeholder_id: Option<Uuid>,
+}
+
+fn default_kind() -> InviteLinkKind {
+    InviteLinkKind::Link
+}
+
+fn default_role() -> MemberRole {
+    MemberRole::Member
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InviteLinkInfo {
+    pub id: Uuid,
+    pub group_id: Uuid,
+    pub kind: InviteLinkKind,
+    pub role: MemberRole,
+    pub single_use: bool,
+    pub use_count: u32,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub placeholder_id: Option<Uuid>,
+    pub usable: bool,
+}
+
+// Returned once, at creation; neither the URL nor the code can be recovered later
+#[derive(Debug, Serialize, Clone)]
+pub struct MintedInviteLink {
+    #[serde(flatten)]
+    pub link: InviteLinkInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RedeemInvite {
+    pub invite: String, // link token, full link URL or join code
+}
+
+// Formats five random bytes (40 bits) as a code like "7KQ4-M2XD"
+pub fn join_code_from_bytes(bytes: &[u8; 5]) -> String {
+    let mut bits: u64 = 0;
+    for byte in bytes {
+        bits = (bits << 8) | *byte as u64;
+    }
+
+    let mut code = String::with_capacity(JOIN_CODE_LENGTH + 1);
+    for i in 0..JOIN_CODE_LENGTH {
+        if i == JOIN_CODE_LENGTH / 2 {
+            code.push('-');
+        }
+        let shift = 5 * (JOIN_CODE_LENGTH - 1 - i);
+        code.push(JOIN_CODE_ALPHABET[((bits >> shift) & 0x1f) as usize] as char);
+    }
+    code
+}
+
+// Canonical form of a typed code: separators dropped, upper-cased, look-alikes mapped back.
+// Returns None when it cannot be a join code at all.
+pub fn normalize_join_code(input: &str) -> Option<String> {
+    let mut code = String::with_capacity(JOIN_CODE_LENGTH);
+    for c in input.chars() {
+        let c = match c.to_ascii_uppercase() {
+            '-' | ' ' => continue,
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        if !c.is_ascii() || !JOIN_CODE_ALPHABET.contains(&(c as u8)) {
+            return None;
+        }
+        code.push(c);
+    }
+
+    if code.len() == JOIN_CODE_LENGTH {
+        Some(code)
+    } else {
+        None
+    }
+}
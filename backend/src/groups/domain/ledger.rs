@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::expenses::domain::money::Money;
+
+// Where a group sits in the ledger tree. Only one level deep: a household holds trip ledgers,
+// and a ledger cannot hold ledgers of its own.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LedgerLink {
+    pub group_id: Uuid,
+    pub parent_group_id: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl LedgerLink {
+    pub fn is_ledger(&self) -> bool {
+        self.parent_group_id.is_some()
+    }
+}
+
+// One member's final balance on a closed ledger, carried into the parent group.
+// Positive means the parent group owes them, as with UserBalance.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LedgerTransfer {
+    pub id: Uuid,
+    pub group_id: Uuid,       // the parent that receives the balance
+    pub child_group_id: Uuid, // the ledger it came from
+    pub user_id: Uuid,
+    pub amount: Money,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClosedLedger {
+    pub group_id: Uuid,
+    pub parent_group_id: Uuid,
+    pub closed_at: DateTime<Utc>,
+    pub transfers: Vec<LedgerTransfer>,
+}
+
+// Turns a ledger's net balances, in minor units of `currency`, into the transfers that carry
+// them into `parent_group_id`. Members who are already square get none.
+pub fn ledger_transfers(
+    child_group_id: Uuid,
+    parent_group_id: Uuid,
+    balances: &HashMap<Uuid, i64>,
+    currency: &str,
+    at: DateTime<Utc>,
+) -> Vec<LedgerTransfer> {
+    let mut transfers: Vec<LedgerTransfer> = balances
+        .iter()
+        .filter(|(_, amount)| **amount != 0)
+        .map(|(user_id, amount)| LedgerTransfer {
+            id: Uuid::new_v4(),
+            group_id: parent_group_id,
+            child_group_id,
+            user_id: *user_id,
+            amount: Money::new(*amount, currency),
+            created_at: at,
+        })
+        .collect();
+    transfers.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+    transfers
+}
+
+// Adds one set of per-user balances onto another
+pub fn merge_balances(into: &mut HashMap<Uuid, i64>, from: impl IntoIterator<Item = (Uuid, i64)>) {
+    for (user_id, amount) in from {
+        *into.entry(user_id).or_insert(0) += amount;
+    }
+}
@@ -1,2 +1,6 @@
 pub mod group;
-pub mod ports;
\ No newline at end of file
+pub mod invite_link;
+pub mod ledger;
+pub mod permissions;
+pub mod ports;
+pub mod settings;
@@ -0,0 +1,171 @@
+use std::fmt;
+
+use super::group::MemberRole;
+
+// Everything a member can try to do inside a group
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupAction {
+    ViewGroup,
+    EditGroup,
+    ChangeSettings,
+    DeleteGroup,
+    RestoreGroup,
+    ArchiveGroup,
+    ExportGroup,
+    CreateLedger,
+    CloseLedger,
+    InviteMember,
+    RemoveMember,
+    ManageRoles,
+    CreateExpense,
+    EditExpense,
+    DeleteExpense,
+    SettleDebt,
+    UndoSettlement,
+    CreateChore,
+    EditChore,
+    CompleteChore,
+    AssignChore,
+    DeleteChore,
+    CreateEvent,
+    RespondToEvent,
+    EditEvent,
+    DeleteEvent,
+}
+
+impl GroupAction {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            GroupAction::ViewGroup => "view this group",
+            GroupAction::EditGroup => "edit this group",
+            GroupAction::ChangeSettings => "change this group's settings",
+            GroupAction::DeleteGroup => "delete this group",
+            GroupAction::RestoreGroup => "restore this group",
+            GroupAction::ArchiveGroup => "archive or unarchive this group",
+            GroupAction::ExportGroup => "export this group",
+            GroupAction::CreateLedger => "start a ledger inside this group",
+            GroupAction::CloseLedger => "close this ledger",
+            GroupAction::InviteMember => "invite members",
+            GroupAction::RemoveMember => "remove members",
+            GroupAction::ManageRoles => "change member roles",
+            GroupAction::CreateExpense => "add expenses",
+            GroupAction::EditExpense => "edit this expense",
+            GroupAction::DeleteExpense => "delete this expense",
+            GroupAction::SettleDebt => "record payments",
+            GroupAction::UndoSettlement => "undo this payment",
+            GroupAction::CreateChore => "add chores",
+            GroupAction::EditChore => "edit this chore",
+            GroupAction::CompleteChore => "update this chore's status",
+            GroupAction::AssignChore => "assign chores",
+            GroupAction::DeleteChore => "delete this chore",
+            GroupAction::CreateEvent => "add events",
+            GroupAction::RespondToEvent => "respond to events",
+            GroupAction::EditEvent => "edit this event",
+            GroupAction::DeleteEvent => "delete this event",
+        }
+    }
+
+    // Archived groups keep their history but take no new activity. Reading, exporting,
+    // unarchiving and deleting stay open, as does membership upkeep so people can still move on.
+    pub fn allowed_when_archived(&self) -> bool {
+        matches!(
+            self,
+            GroupAction::ViewGroup
+                | GroupAction::ExportGroup
+                | GroupAction::ArchiveGroup
+                | GroupAction::DeleteGroup
+                | GroupAction::RestoreGroup
+                | GroupAction::RemoveMember
+                | GroupAction::ManageRoles
+        )
+    }
+}
+
+// The caller's standing in a group as the policy sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberAccess {
+    pub role: MemberRole,
+    pub group_archived: bool,
+}
+
+impl MemberAccess {
+    pub fn allows(&self, action: GroupAction, is_author: bool) -> bool {
+        PermissionPolicy::allows(&self.role, action, is_author) && (!self.group_archived || action.allowed_when_archived())
+    }
+}
+
+// The single mapping from roles to actions. Members run the day-to-day household and
+// manage what they created themselves; admins manage everything, including restoring a deleted
+// group; only the owner can delete, archive or export the group or change who is an admin.
+pub struct PermissionPolicy;
+
+impl PermissionPolicy {
+    // `is_author` is whether the acting user created the expense, chore or event in question,
+    // or recorded the payment
+    pub fn allows(role: &MemberRole, action: GroupAction, is_author: bool) -> bool {
+        use GroupAction::*;
+
+        match role {
+            MemberRole::Owner => true,
+            MemberRole::Admin => !matches!(action, DeleteGroup | ManageRoles | ArchiveGroup | ExportGroup),
+            MemberRole::Member => match action {
+                ViewGroup | CreateExpense | SettleDebt | CreateChore | CompleteChore | AssignChore | CreateEvent | RespondToEvent
+                | CreateLedger => true,
+                EditExpense | DeleteExpense | UndoSettlement | EditChore | DeleteChore | EditEvent | DeleteEvent => is_author,
+                EditGroup | ChangeSettings | DeleteGroup | RestoreGroup | ArchiveGroup | ExportGroup | CloseLedger | InviteMember
+                | RemoveMember | ManageRoles => false,
+            },
+        }
+    }
+
+    // `role` is None for someone outside the group, who may do nothing in it
+    pub fn check(role: Option<&MemberRole>, action: GroupAction, is_author: bool) -> Result<(), PermissionDenied> {
+        match role {
+            Some(role) if Self::allows(role, action, is_author) => Ok(()),
+            Some(_) => Err(PermissionDenied { action, reason: DenialReason::Role }),
+            None => Err(PermissionDenied { action, reason: DenialReason::NotMember }),
+        }
+    }
+
+    // `check` plus the archived group's read-only rule
+    pub fn check_access(access: Option<&MemberAccess>, action: GroupAction, is_author: bool) -> Result<(), PermissionDenied> {
+        Self::check(access.map(|access| &access.role), action, is_author)?;
+        match access {
+            Some(access) if access.group_archived && !action.allowed_when_archived() => {
+                Err(PermissionDenied { action, reason: DenialReason::Archived })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DenialReason {
+    NotMember,
+    Role,
+    Archived,
+}
+
+// Becomes `ServiceError::Forbidden`, which handlers answer with 403; the message always starts
+// with "Forbidden"
+#[derive(Debug, Clone, PartialEq)]
+pub struct PermissionDenied {
+    pub action: GroupAction,
+    pub reason: DenialReason,
+}
+
+impl fmt::Display for PermissionDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            DenialReason::NotMember => write!(f, "Forbidden: you are not a member of this group"),
+            DenialReason::Role => write!(f, "Forbidden: your role does not allow you to {}", self.action.describe()),
+            DenialReason::Archived => write!(
+                f,
+                "Forbidden: this group is archived and read-only; unarchive it to {}",
+                self.action.describe()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PermissionDenied {}
@@ -1,6 +1,12 @@
 use async_trait::async_trait;
 use uuid::Uuid;
-use super::group::{Group, GroupMember, GroupCreation, GroupUpdate, GroupInfo, GroupInvitation, GroupMemberInfo};
+use chrono::{DateTime, Utc};
+use super::invite_link::{GroupInviteLink, LinkRedemption};
+use super::ledger::{ClosedLedger, LedgerLink};
+use super::permissions::MemberAccess;
+use super::settings::GroupSettings;
+use super::group::{Group, GroupMember, GroupCreation, GroupUpdate, GroupInfo, GroupInvitation, GroupMemberInfo, InvitationStatus};
+use std::collections::HashMap;
 use std::error::Error;
 
 #[async_trait]
@@ -9,7 +15,11 @@ pub trait GroupRepository: Send + Sync {
     async fn get_group_by_id(&self, group_id: &Uuid) -> Result<Option<Group>, Box<dyn Error>>;
     async fn update_group(&self, group_id: &Uuid, update: &GroupUpdate) -> Result<(), Box<dyn Error>>;
     async fn delete_group(&self, group_id: &Uuid) -> Result<(), Box<dyn Error>>;
-    async fn get_groups_for_user(&self, user_id: &Uuid) -> Result<Vec<GroupInfo>, Box<dyn Error>>;
+    // Archived groups are left out unless `include_archived`
+    async fn get_groups_for_user(&self, user_id: &Uuid, include_archived: bool) -> Result<Vec<GroupInfo>, Box<dyn Error>>;
+    // Both return false when the group was already in that state
+    async fn archive_group(&self, group_id: &Uuid, archived_by: &Uuid, at: DateTime<Utc>) -> Result<bool, Box<dyn Error>>;
+    async fn unarchive_group(&self, group_id: &Uuid) -> Result<bool, Box<dyn Error>>;
 }
 
 #[async_trait]
@@ -19,12 +29,71 @@ pub trait GroupMemberRepository: Send + Sync {
     async fn get_members(&self, group_id: &Uuid) -> Result<Vec<GroupMemberInfo>, Box<dyn Error>>;
     async fn is_member(&self, group_id: &Uuid, user_id: &Uuid) -> Result<bool, Box<dyn Error>>;
     async fn get_user_role(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Option<super::group::MemberRole>, Box<dyn Error>>;
+    // Role plus whether the group is archived, for the permission policy
+    async fn get_member_access(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Option<MemberAccess>, Box<dyn Error>>;
+    // Never touches the owner's row; false when nothing changed
+    async fn set_member_role(&self, group_id: &Uuid, user_id: &Uuid, role: &super::group::MemberRole) -> Result<bool, Box<dyn Error>>;
+    // Atomically makes `to` the owner and `from` an admin; false unless `from` was the owner
+    async fn transfer_ownership(&self, group_id: &Uuid, from: &Uuid, to: &Uuid) -> Result<bool, Box<dyn Error>>;
+    // Creates the login-less account behind a placeholder and adds it to the group
+    async fn add_placeholder(&self, member: &GroupMember, name: &str) -> Result<(), Box<dyn Error>>;
 }
 
 #[async_trait]
 pub trait GroupInvitationRepository: Send + Sync {
     async fn create_invitation(&self, invitation: &GroupInvitation) -> Result<(), Box<dyn Error>>;
+    async fn get_invitation(&self, invitation_id: &Uuid) -> Result<Option<GroupInvitation>, Box<dyn Error>>;
+    async fn find_pending_invitation(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Option<GroupInvitation>, Box<dyn Error>>;
     async fn get_pending_invitations(&self, user_id: &Uuid) -> Result<Vec<GroupInvitation>, Box<dyn Error>>;
-    async fn accept_invitation(&self, group_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>>;
-    async fn decline_invitation(&self, group_id: &Uuid, user_id: &Uuid) -> Result<(), Box<dyn Error>>;
-}
\ No newline at end of file
+    async fn get_group_invitations(&self, group_id: &Uuid) -> Result<Vec<GroupInvitation>, Box<dyn Error>>;
+    // Marks a pending invitation accepted and adds the invitee as a member in one step.
+    // Returns false when it was no longer pending.
+    async fn accept_invitation(&self, invitation_id: &Uuid, at: DateTime<Utc>) -> Result<bool, Box<dyn Error>>;
+    // Moves a pending invitation to a closed status; false when it was no longer pending
+    async fn close_invitation(&self, invitation_id: &Uuid, status: InvitationStatus, at: DateTime<Utc>) -> Result<bool, Box<dyn Error>>;
+    // Marks every pending invitation past its expiry as expired; returns how many
+    async fn expire_invitations(&self, now: DateTime<Utc>) -> Result<u64, Box<dyn Error>>;
+}
+
+#[async_trait]
+pub trait GroupInviteLinkRepository: Send + Sync {
+    async fn create_link(&self, link: &GroupInviteLink) -> Result<(), Box<dyn Error>>;
+    async fn get_link(&self, link_id: &Uuid) -> Result<Option<GroupInviteLink>, Box<dyn Error>>;
+    async fn get_link_by_code_hash(&self, code_hash: &str) -> Result<Option<GroupInviteLink>, Box<dyn Error>>;
+    async fn get_group_links(&self, group_id: &Uuid) -> Result<Vec<GroupInviteLink>, Box<dyn Error>>;
+    async fn revoke_link(&self, link_id: &Uuid, at: DateTime<Utc>) -> Result<bool, Box<dyn Error>>;
+    // Counts one use and adds the user with the link's role in one step, closing any pending
+    // invitation they had to the group. With a placeholder, the same step merges its history
+    // into the user and removes it.
+    async fn redeem_link(
+        &self,
+        link_id: &Uuid,
+        user_id: &Uuid,
+        placeholder_id: Option<&Uuid>,
+        at: DateTime<Utc>,
+    ) -> Result<LinkRedemption, Box<dyn Error>>;
+}
+
+// Resolves many group IDs to their names in one round trip; unknown IDs are absent
+#[async_trait]
+pub trait GroupNameLookup: Send + Sync {
+    async fn group_names(&self, group_ids: &[Uuid]) -> Result<HashMap<Uuid, String>, Box<dyn Error>>;
+}
+
+#[async_trait]
+pub trait GroupSettingsRepository: Send + Sync {
+    // Defaults when the group has never saved any settings
+    async fn get_settings(&self, group_id: &Uuid) -> Result<GroupSettings, Box<dyn Error>>;
+    async fn save_settings(&self, settings: &GroupSettings) -> Result<(), Box<dyn Error>>;
+}
+
+#[async_trait]
+pub trait GroupLedgerRepository: Send + Sync {
+    // None when the group does not exist or was deleted
+    async fn get_ledger_link(&self, group_id: &Uuid) -> Result<Option<LedgerLink>, Box<dyn Error>>;
+    async fn get_open_child_ledgers(&self, parent_group_id: &Uuid) -> Result<Vec<Uuid>, Box<dyn Error>>;
+    // What closed ledgers add to (parent) or take from (ledger) each user's balance
+    async fn get_transfer_totals(&self, group_id: &Uuid) -> Result<HashMap<Uuid, i64>, Box<dyn Error>>;
+    // False when the ledger was already closed
+    async fn close_ledger(&self, ledger: &ClosedLedger, closed_by: &Uuid) -> Result<bool, Box<dyn Error>>;
+}
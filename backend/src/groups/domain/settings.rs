@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, TimeZone, Utc, Weekday};
+
+use crate::error::ServiceError;
+use crate::profiles::domain::profile::{DEFAULT_CURRENCY, DEFAULT_LOCALE, DEFAULT_TIMEZONE};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WeekStart {
+    Monday,
+    Saturday,
+    Sunday,
+}
+
+impl WeekStart {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WeekStart::Monday => "monday",
+            WeekStart::Saturday => "saturday",
+            WeekStart::Sunday => "sunday",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "monday" => Some(WeekStart::Monday),
+            "saturday" => Some(WeekStart::Saturday),
+            "sunday" => Some(WeekStart::Sunday),
+            _ => None,
+        }
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Saturday => Weekday::Sat,
+            WeekStart::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+// The parts of the app a group can switch off
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupModule {
+    Expenses,
+    Chores,
+    Calendar,
+}
+
+impl GroupModule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupModule::Expenses => "expenses",
+            GroupModule::Chores => "chores",
+            GroupModule::Calendar => "calendar",
+        }
+    }
+}
+
+// How a group reads money and time. Every group has one, created with defaults on first read.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupSettings {
+    pub group_id: Uuid,
+    pub default_currency: String, // ISO 4217, e.g. "EUR"
+    pub timezone: String,         // IANA, e.g. "Europe/Berlin"
+    pub week_start: WeekStart,
+    pub locale: String,           // BCP 47, e.g. "en-GB"
+    pub expenses_enabled: bool,
+    pub chores_enabled: bool,
+    pub calendar_enabled: bool,
+    pub simplify_debts: bool, // settle up with the fewest payments; off means pairwise debts
+    pub updated_at: DateTime<Utc>,
+}
+
+impl GroupSettings {
+    pub fn new(group_id: Uuid, now: DateTime<Utc>) -> Self {
+        Self {
+            group_id,
+            default_currency: DEFAULT_CURRENCY.to_string(),
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            week_start: WeekStart::Monday,
+            locale: DEFAULT_LOCALE.to_string(),
+            expenses_enabled: true,
+            chores_enabled: true,
+            calendar_enabled: true,
+            simplify_debts: true,
+            updated_at: now,
+        }
+    }
+
+    pub fn is_enabled(&self, module: GroupModule) -> bool {
+        match module {
+            GroupModule::Expenses => self.expenses_enabled,
+            GroupModule::Chores => self.chores_enabled,
+            GroupModule::Calendar => self.calendar_enabled,
+        }
+    }
+
+    // A switched-off module is refused like a permission denial
+    pub fn require_enabled(&self, module: GroupModule) -> Result<(), ServiceError> {
+        if self.is_enabled(module) {
+            Ok(())
+        } else {
+            Err(ServiceError::Forbidden(format!("Forbidden: {} are turned off for this group", module.as_str())))
+        }
+    }
+
+    // Stored names are validated on write, so the UTC fallback only covers rows edited by hand
+    pub fn tz(&self) -> chrono_tz::Tz {
+        self.timezone.parse().unwrap_or(chrono_tz::UTC)
+    }
+
+    // The calendar day it is in the group at `at`
+    pub fn local_date(&self, at: DateTime<Utc>) -> NaiveDate {
+        at.with_timezone(&self.tz()).date_naive()
+    }
+
+    // Local midnight as an instant. When a DST jump skips midnight, the day starts at the
+    // first local time that exists.
+    pub fn start_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        let tz = self.tz();
+        let mut local = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        for _ in 0..4 {
+            match tz.from_local_datetime(&local) {
+                LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => return at.with_timezone(&Utc),
+                LocalResult::None => local += Duration::minutes(30),
+            }
+        }
+        local.and_utc()
+    }
+
+    // Last instant of the local day, used for "due on" dates
+    pub fn end_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        self.start_of_day(date + Duration::days(1)) - Duration::seconds(1)
+    }
+
+    // [start, end) of the local day, week (per week_start) or month containing `date`
+    pub fn day_range(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        (self.start_of_day(date), self.start_of_day(date + Duration::days(1)))
+    }
+
+    pub fn week_range(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let first = self.week_start.weekday().num_days_from_monday() as i64;
+        let offset = (date.weekday().num_days_from_monday() as i64 - first).rem_euclid(7);
+        let start = date - Duration::days(offset);
+        (self.start_of_day(start), self.start_of_day(start + Duration::days(7)))
+    }
+
+    pub fn month_range(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let start = date.with_day(1).expect("every month has a first day");
+        let next = if start.month() == 12 {
+            NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+        }
+        .expect("first of the next month is a valid date");
+        (self.start_of_day(start), self.start_of_day(next))
+    }
+}
+
+// PATCH body; absent fields are left alone
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GroupSettingsUpdate {
+    pub default_currency: Option<String>,
+    pub timezone: Option<String>,
+    pub week_start: Option<WeekStart>,
+    pub locale: Option<String>,
+    pub expenses_enabled: Option<bool>,
+    pub chores_enabled: Option<bool>,
+    pub calendar_enabled: Option<bool>,
+    pub simplify_debts: Option<bool>,
+}
@@ -0,0 +1,200 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{DateTime, TimeZone, Utc};
+use std::future::Future;
+
+use crate::groups::domain::group::{Group, GroupInfo, GroupUpdate, MemberRole};
+use crate::groups::domain::ports::GroupRepository;
+use crate::groups::infrastructure::deletion::group_purge_statements;
+
+// Port adapter over the groups table, for application services
+pub struct D1GroupRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1GroupRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+
+    fn parse_uuid(value: &Value) -> std::result::Result<Uuid, Box<dyn std::error::Error>> {
+        Ok(Uuid::parse_str(value.as_str().ok_or("Invalid ID")?).map_err(|e| format!("UUID parse error: {}", e))?)
+    }
+
+    fn parse_datetime(value: &Value) -> std::result::Result<DateTime<Utc>, Box<dyn std::error::Error>> {
+        Ok(DateTime::parse_from_rfc3339(value.as_str().unwrap_or(""))
+            .map_err(|e| format!("Date parse error: {}", e))?
+            .with_timezone(&Utc))
+    }
+
+    // archived_at and closed_at are INTEGER epoch seconds, unlike the older TEXT columns
+    fn parse_optional_epoch(value: &Value) -> Option<DateTime<Utc>> {
+        value
+            .as_i64()
+            .or_else(|| value.as_f64().map(|s| s as i64))
+            .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single())
+    }
+
+    fn parse_description(value: &Value) -> Option<String> {
+        value.as_str().filter(|d| !d.is_empty()).map(|d| d.to_string())
+    }
+
+    // JsValue params are not Send either, so they move straight into the wrapped future
+    fn execute<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<D1Result>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.run().await })
+    }
+
+    fn query_all<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<Vec<Value>>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.all().await?.results::<Value>() })
+    }
+}
+
+#[async_trait]
+impl GroupRepository for D1GroupRepository {
+    async fn create_group(&self, group: &Group) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "INSERT INTO groups (id, name, description, created_by, created_at, updated_at, parent_group_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            vec![
+                group.id.to_string().into(),
+                group.name.clone().into(),
+                group.description.clone().unwrap_or_default().into(),
+                group.created_by.to_string().into(),
+                group.created_at.to_rfc3339().into(),
+                group.updated_at.to_rfc3339().into(),
+                group.parent_group_id.map(|id| JsValue::from(id.to_string())).unwrap_or(JsValue::NULL),
+            ],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+
+        // A ledger starts out with its parent's settings so amounts roll up in one currency
+        if let Some(parent_id) = &group.parent_group_id {
+            self.execute(
+                "INSERT INTO group_settings (group_id, default_currency, timezone, week_start, locale, expenses_enabled, chores_enabled, calendar_enabled, simplify_debts, updated_at)
+                 SELECT ?1, default_currency, timezone, week_start, locale, expenses_enabled, chores_enabled, calendar_enabled, simplify_debts, ?2
+                 FROM group_settings WHERE group_id = ?3",
+                vec![
+                    group.id.to_string().into(),
+                    (group.created_at.timestamp() as f64).into(),
+                    parent_id.to_string().into(),
+                ],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+        }
+        Ok(())
+    }
+
+    async fn get_group_by_id(&self, group_id: &Uuid) -> std::result::Result<Option<Group>, Box<dyn std::error::Error>> {
+        let rows = self
+            .query_all(
+                "SELECT id, name, description, created_by, created_at, updated_at, archived_at, parent_group_id, closed_at FROM groups WHERE id = ?1 AND deleted_at IS NULL",
+                vec![group_id.to_string().into()],
+            )
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+
+        match rows.first() {
+            Some(row) => Ok(Some(Group {
+                id: Self::parse_uuid(&row["id"])?,
+                name: row["name"].as_str().unwrap_or("").to_string(),
+                description: Self::parse_description(&row["description"]),
+                created_by: Self::parse_uuid(&row["created_by"])?,
+                created_at: Self::parse_datetime(&row["created_at"])?,
+                updated_at: Self::parse_datetime(&row["updated_at"])?,
+                archived_at: Self::parse_optional_epoch(&row["archived_at"]),
+                parent_group_id: row["parent_group_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+                closed_at: Self::parse_optional_epoch(&row["closed_at"]),
+            })),
+            None => Ok(None),
+        }
+    }
+
+    async fn update_group(&self, group_id: &Uuid, update: &GroupUpdate) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        // COALESCE keeps the stored value for fields left out of the update
+        self.execute(
+            "UPDATE groups SET name = COALESCE(?1, name), description = COALESCE(?2, description), updated_at = ?3 WHERE id = ?4",
+            vec![
+                update.name.clone().map(JsValue::from).unwrap_or(JsValue::NULL),
+                update.description.clone().map(JsValue::from).unwrap_or(JsValue::NULL),
+                Utc::now().to_rfc3339().into(),
+                group_id.to_string().into(),
+            ],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn delete_group(&self, group_id: &Uuid) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        SendFuture::new(async {
+            let statements = group_purge_statements(&self.db, group_id)?;
+            self.db.batch(statements).await
+        })
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn get_groups_for_user(&self, user_id: &Uuid, include_archived: bool) -> std::result::Result<Vec<GroupInfo>, Box<dyn std::error::Error>> {
+        let rows = self
+            .query_all(
+                "SELECT g.*, gm.role,
+                        (SELECT COUNT(*) FROM group_members WHERE group_id = g.id) as member_count
+                 FROM groups g
+                 JOIN group_members gm ON g.id = gm.group_id
+                 WHERE gm.user_id = ?1 AND g.deleted_at IS NULL AND (?2 = 1 OR g.archived_at IS NULL)
+                 ORDER BY g.created_at DESC",
+                vec![user_id.to_string().into(), (if include_archived { 1.0 } else { 0.0 }).into()],
+            )
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+
+        let mut groups = Vec::with_capacity(rows.len());
+        for row in rows {
+            let role = MemberRole::from_db_str(row["role"].as_str().unwrap_or("member"));
+            groups.push(GroupInfo {
+                id: Self::parse_uuid(&row["id"])?,
+                name: row["name"].as_str().unwrap_or("").to_string(),
+                description: Self::parse_description(&row["description"]),
+                created_by: Self::parse_uuid(&row["created_by"])?,
+                member_count: row["member_count"].as_i64().unwrap_or(0) as usize,
+                created_at: Self::parse_datetime(&row["created_at"])?,
+                archived_at: Self::parse_optional_epoch(&row["archived_at"]),
+                parent_group_id: row["parent_group_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+                closed_at: Self::parse_optional_epoch(&row["closed_at"]),
+                user_role: Some(role),
+            });
+        }
+
+        Ok(groups)
+    }
+
+    async fn archive_group(&self, group_id: &Uuid, archived_by: &Uuid, at: DateTime<Utc>) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let result = self
+            .execute(
+                "UPDATE groups SET archived_at = ?1, archived_by = ?2 WHERE id = ?3 AND archived_at IS NULL",
+                vec![(at.timestamp() as f64).into(), archived_by.to_string().into(), group_id.to_string().into()],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+
+        Ok(result.meta()?.and_then(|meta| meta.changes).unwrap_or(0) > 0)
+    }
+
+    async fn unarchive_group(&self, group_id: &Uuid) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let result = self
+            .execute(
+                "UPDATE groups SET archived_at = NULL, archived_by = NULL WHERE id = ?1 AND archived_at IS NOT NULL",
+                vec![group_id.to_string().into()],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+
+        Ok(result.meta()?.and_then(|meta| meta.changes).unwrap_or(0) > 0)
+    }
+}
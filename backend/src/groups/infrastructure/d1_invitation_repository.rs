@@ -0,0 +1,197 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{DateTime, TimeZone, Utc};
+use std::future::Future;
+
+use crate::groups::domain::group::{GroupInvitation, InvitationStatus};
+use crate::groups::domain::ports::GroupInvitationRepository;
+
+const INVITATION_COLUMNS: &str = "id, group_id, invited_user_id, invited_by, status, created_at, expires_at, responded_at";
+
+// Invitations in the group_invitations table. Unlike the other group tables, timestamps are
+// INTEGER epoch seconds so the expiry sweep can compare them directly.
+pub struct D1GroupInvitationRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1GroupInvitationRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+
+    fn timestamp_value(at: &DateTime<Utc>) -> JsValue {
+        (at.timestamp() as f64).into()
+    }
+
+    fn parse_optional_timestamp(value: &Value) -> std::result::Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
+        match value.as_i64().or_else(|| value.as_f64().map(|s| s as i64)) {
+            Some(seconds) => Ok(Some(Utc.timestamp_opt(seconds, 0).single().ok_or("Invalid timestamp")?)),
+            None => Ok(None),
+        }
+    }
+
+    fn parse_uuid(value: &Value) -> std::result::Result<Uuid, Box<dyn std::error::Error>> {
+        Ok(Uuid::parse_str(value.as_str().ok_or("Invalid ID")?).map_err(|e| format!("UUID parse error: {}", e))?)
+    }
+
+    fn row_to_invitation(row: &Value) -> std::result::Result<GroupInvitation, Box<dyn std::error::Error>> {
+        Ok(GroupInvitation {
+            id: Self::parse_uuid(&row["id"])?,
+            group_id: Self::parse_uuid(&row["group_id"])?,
+            invited_user_id: Self::parse_uuid(&row["invited_user_id"])?,
+            invited_by: Self::parse_uuid(&row["invited_by"])?,
+            status: row["status"]
+                .as_str()
+                .and_then(InvitationStatus::parse)
+                .ok_or("Invalid invitation status")?,
+            created_at: Self::parse_optional_timestamp(&row["created_at"])?.ok_or("Invalid created_at")?,
+            expires_at: Self::parse_optional_timestamp(&row["expires_at"])?.ok_or("Invalid expires_at")?,
+            responded_at: Self::parse_optional_timestamp(&row["responded_at"])?,
+        })
+    }
+
+    // JsValue params are not Send either, so they move straight into the wrapped future
+    fn execute<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<D1Result>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.run().await })
+    }
+
+    fn query_all<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<Vec<Value>>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.all().await?.results::<Value>() })
+    }
+
+    // Not an async fn: that would keep the params in its own, non-Send state
+    fn invitations<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> impl Future<Output = std::result::Result<Vec<GroupInvitation>, Box<dyn std::error::Error>>> + 'a {
+        let rows = self.query_all(query, params);
+        async move {
+            let rows = rows.await.map_err(|e| format!("Query error: {}", e))?;
+            rows.iter().map(Self::row_to_invitation).collect()
+        }
+    }
+
+    fn changes(result: &D1Result) -> Result<u64> {
+        Ok(result.meta()?.and_then(|meta| meta.changes).unwrap_or(0) as u64)
+    }
+}
+
+#[async_trait]
+impl GroupInvitationRepository for D1GroupInvitationRepository {
+    async fn create_invitation(&self, invitation: &GroupInvitation) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "INSERT INTO group_invitations (id, group_id, invited_user_id, invited_by, status, created_at, expires_at, responded_at) VALUES (?, ?, ?, ?, ?, ?, ?, NULL)",
+            vec![
+                invitation.id.to_string().into(),
+                invitation.group_id.to_string().into(),
+                invitation.invited_user_id.to_string().into(),
+                invitation.invited_by.to_string().into(),
+                invitation.status.as_str().into(),
+                Self::timestamp_value(&invitation.created_at),
+                Self::timestamp_value(&invitation.expires_at),
+            ],
+        )
+        .await
+        .map_err(|e| {
+            // The partial unique index allows one pending invitation per group and user
+            if e.to_string().contains("UNIQUE") {
+                "User already has a pending invitation to this group".to_string()
+            } else {
+                format!("Run error: {}", e)
+            }
+        })?;
+        Ok(())
+    }
+
+    async fn get_invitation(&self, invitation_id: &Uuid) -> std::result::Result<Option<GroupInvitation>, Box<dyn std::error::Error>> {
+        let query = format!("SELECT {} FROM group_invitations WHERE id = ?", INVITATION_COLUMNS);
+        Ok(self
+            .invitations(&query, vec![invitation_id.to_string().into()])
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn find_pending_invitation(&self, group_id: &Uuid, user_id: &Uuid) -> std::result::Result<Option<GroupInvitation>, Box<dyn std::error::Error>> {
+        let query = format!(
+            "SELECT {} FROM group_invitations WHERE group_id = ? AND invited_user_id = ? AND status = 'pending'",
+            INVITATION_COLUMNS
+        );
+        Ok(self
+            .invitations(&query, vec![group_id.to_string().into(), user_id.to_string().into()])
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn get_pending_invitations(&self, user_id: &Uuid) -> std::result::Result<Vec<GroupInvitation>, Box<dyn std::error::Error>> {
+        let query = format!(
+            "SELECT {} FROM group_invitations WHERE invited_user_id = ? AND status = 'pending' ORDER BY created_at DESC",
+            INVITATION_COLUMNS
+        );
+        self.invitations(&query, vec![user_id.to_string().into()]).await
+    }
+
+    async fn get_group_invitations(&self, group_id: &Uuid) -> std::result::Result<Vec<GroupInvitation>, Box<dyn std::error::Error>> {
+        let query = format!(
+            "SELECT {} FROM group_invitations WHERE group_id = ? ORDER BY created_at DESC",
+            INVITATION_COLUMNS
+        );
+        self.invitations(&query, vec![group_id.to_string().into()]).await
+    }
+
+    async fn accept_invitation(&self, invitation_id: &Uuid, at: DateTime<Utc>) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let invitation_id = invitation_id.to_string();
+
+        // One batch: the member row is only written when this call moved the invitation out
+        // of pending, so two concurrent accepts cannot both succeed
+        let results = SendFuture::new(async {
+            let statements = vec![
+                self.db
+                    .prepare("UPDATE group_invitations SET status = 'accepted', responded_at = ?1 WHERE id = ?2 AND status = 'pending' AND expires_at > ?1")
+                    .bind(&[Self::timestamp_value(&at), invitation_id.clone().into()])?,
+                self.db
+                    .prepare(
+                        "INSERT OR IGNORE INTO group_members (group_id, user_id, role, joined_at)
+                         SELECT group_id, invited_user_id, 'member', ?1 FROM group_invitations
+                         WHERE id = ?2 AND status = 'accepted' AND responded_at = ?3",
+                    )
+                    .bind(&[at.to_rfc3339().into(), invitation_id.clone().into(), Self::timestamp_value(&at)])?,
+            ];
+            self.db.batch(statements).await
+        })
+        .await
+        .map_err(|e| format!("Failed to accept invitation: {}", e))?;
+
+        let accepted = match results.first() {
+            Some(result) => Self::changes(result)? > 0,
+            None => false,
+        };
+        Ok(accepted)
+    }
+
+    async fn close_invitation(&self, invitation_id: &Uuid, status: InvitationStatus, at: DateTime<Utc>) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let result = self
+            .execute(
+                "UPDATE group_invitations SET status = ?, responded_at = ? WHERE id = ? AND status = 'pending'",
+                vec![status.as_str().into(), Self::timestamp_value(&at), invitation_id.to_string().into()],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+
+        Ok(Self::changes(&result)? > 0)
+    }
+
+    async fn expire_invitations(&self, now: DateTime<Utc>) -> std::result::Result<u64, Box<dyn std::error::Error>> {
+        let result = self
+            .execute(
+                "UPDATE group_invitations SET status = 'expired', responded_at = expires_at WHERE status = 'pending' AND expires_at <= ?",
+                vec![Self::timestamp_value(&now)],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+
+        Ok(Self::changes(&result)?)
+    }
+}
@@ -0,0 +1,197 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{DateTime, TimeZone, Utc};
+use std::future::Future;
+
+use crate::groups::domain::group::MemberRole;
+use crate::groups::domain::invite_link::{GroupInviteLink, InviteLinkKind, LinkRedemption};
+use crate::groups::domain::ports::GroupInviteLinkRepository;
+use crate::groups::infrastructure::placeholders::placeholder_claim_statements;
+
+const LINK_COLUMNS: &str = "id, group_id, kind, code_hash, role, single_use, use_count, created_by, created_at, expires_at, revoked_at, placeholder_id";
+
+// Invite links and join codes in group_invite_links, timestamps stored as INTEGER epoch seconds
+pub struct D1GroupInviteLinkRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1GroupInviteLinkRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+
+    fn timestamp_value(at: &DateTime<Utc>) -> JsValue {
+        (at.timestamp() as f64).into()
+    }
+
+    fn parse_optional_timestamp(value: &Value) -> std::result::Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
+        match value.as_i64().or_else(|| value.as_f64().map(|s| s as i64)) {
+            Some(seconds) => Ok(Some(Utc.timestamp_opt(seconds, 0).single().ok_or("Invalid timestamp")?)),
+            None => Ok(None),
+        }
+    }
+
+    fn parse_uuid(value: &Value) -> std::result::Result<Uuid, Box<dyn std::error::Error>> {
+        Ok(Uuid::parse_str(value.as_str().ok_or("Invalid ID")?).map_err(|e| format!("UUID parse error: {}", e))?)
+    }
+
+    fn row_to_link(row: &Value) -> std::result::Result<GroupInviteLink, Box<dyn std::error::Error>> {
+        Ok(GroupInviteLink {
+            id: Self::parse_uuid(&row["id"])?,
+            group_id: Self::parse_uuid(&row["group_id"])?,
+            kind: row["kind"].as_str().and_then(InviteLinkKind::parse).ok_or("Invalid invite link kind")?,
+            code_hash: row["code_hash"].as_str().map(|hash| hash.to_string()),
+            role: MemberRole::from_db_str(row["role"].as_str().unwrap_or("member")),
+            single_use: row["single_use"].as_i64().or_else(|| row["single_use"].as_f64().map(|v| v as i64)).unwrap_or(0) != 0,
+            use_count: row["use_count"].as_i64().or_else(|| row["use_count"].as_f64().map(|v| v as i64)).unwrap_or(0) as u32,
+            created_by: Self::parse_uuid(&row["created_by"])?,
+            created_at: Self::parse_optional_timestamp(&row["created_at"])?.ok_or("Invalid created_at")?,
+            expires_at: Self::parse_optional_timestamp(&row["expires_at"])?.ok_or("Invalid expires_at")?,
+            revoked_at: Self::parse_optional_timestamp(&row["revoked_at"])?,
+            placeholder_id: row["placeholder_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+        })
+    }
+
+    // JsValue params are not Send either, so they move straight into the wrapped future
+    fn execute<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<D1Result>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.run().await })
+    }
+
+    fn query_all<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<Vec<Value>>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.all().await?.results::<Value>() })
+    }
+
+    // Not an async fn: that would keep the params in its own, non-Send state
+    fn links<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> impl Future<Output = std::result::Result<Vec<GroupInviteLink>, Box<dyn std::error::Error>>> + 'a {
+        let rows = self.query_all(query, params);
+        async move {
+            let rows = rows.await.map_err(|e| format!("Query error: {}", e))?;
+            rows.iter().map(Self::row_to_link).collect()
+        }
+    }
+}
+
+#[async_trait]
+impl GroupInviteLinkRepository for D1GroupInviteLinkRepository {
+    async fn create_link(&self, link: &GroupInviteLink) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "INSERT INTO group_invite_links (id, group_id, kind, code_hash, role, single_use, use_count, created_by, created_at, expires_at, revoked_at, placeholder_id) VALUES (?, ?, ?, ?, ?, ?, 0, ?, ?, ?, NULL, ?)",
+            vec![
+                link.id.to_string().into(),
+                link.group_id.to_string().into(),
+                link.kind.as_str().into(),
+                link.code_hash.clone().map(JsValue::from).unwrap_or(JsValue::NULL),
+                link.role.as_db_str().into(),
+                (if link.single_use { 1.0 } else { 0.0 }).into(),
+                link.created_by.to_string().into(),
+                Self::timestamp_value(&link.created_at),
+                Self::timestamp_value(&link.expires_at),
+                link.placeholder_id.map(|id| JsValue::from(id.to_string())).unwrap_or(JsValue::NULL),
+            ],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn get_link(&self, link_id: &Uuid) -> std::result::Result<Option<GroupInviteLink>, Box<dyn std::error::Error>> {
+        let query = format!("SELECT {} FROM group_invite_links WHERE id = ?", LINK_COLUMNS);
+        Ok(self.links(&query, vec![link_id.to_string().into()]).await?.into_iter().next())
+    }
+
+    async fn get_link_by_code_hash(&self, code_hash: &str) -> std::result::Result<Option<GroupInviteLink>, Box<dyn std::error::Error>> {
+        let query = format!("SELECT {} FROM group_invite_links WHERE code_hash = ?", LINK_COLUMNS);
+        Ok(self.links(&query, vec![code_hash.into()]).await?.into_iter().next())
+    }
+
+    async fn get_group_links(&self, group_id: &Uuid) -> std::result::Result<Vec<GroupInviteLink>, Box<dyn std::error::Error>> {
+        let query = format!(
+            "SELECT {} FROM group_invite_links WHERE group_id = ? ORDER BY created_at DESC",
+            LINK_COLUMNS
+        );
+        self.links(&query, vec![group_id.to_string().into()]).await
+    }
+
+    async fn revoke_link(&self, link_id: &Uuid, at: DateTime<Utc>) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let result = self
+            .execute(
+                "UPDATE group_invite_links SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL",
+                vec![Self::timestamp_value(&at), link_id.to_string().into()],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+
+        let changes = result.meta()?.and_then(|meta| meta.changes).unwrap_or(0);
+        Ok(changes > 0)
+    }
+
+    async fn redeem_link(
+        &self,
+        link_id: &Uuid,
+        user_id: &Uuid,
+        placeholder_id: Option<&Uuid>,
+        at: DateTime<Utc>,
+    ) -> std::result::Result<LinkRedemption, Box<dyn std::error::Error>> {
+        let link_uuid = link_id;
+        let user_uuid = user_id;
+        let link_id = link_id.to_string();
+        let user_id = user_id.to_string();
+
+        // One batch. The later statements only act on the link row this call claimed
+        // (last_used_at/last_used_by), so an exhausted or revoked link adds nobody and
+        // claims nothing.
+        let results = SendFuture::new(async {
+            let mut statements = vec![
+                self.db
+                    .prepare(
+                        "UPDATE group_invite_links SET use_count = use_count + 1, last_used_at = ?1, last_used_by = ?2
+                         WHERE id = ?3 AND revoked_at IS NULL AND expires_at > ?1 AND (single_use = 0 OR use_count = 0)",
+                    )
+                    .bind(&[Self::timestamp_value(&at), user_id.clone().into(), link_id.clone().into()])?,
+                self.db
+                    .prepare(
+                        "INSERT OR IGNORE INTO group_members (group_id, user_id, role, joined_at)
+                         SELECT group_id, ?1, role, ?2 FROM group_invite_links
+                         WHERE id = ?3 AND last_used_at = ?4 AND last_used_by = ?1",
+                    )
+                    .bind(&[
+                        user_id.clone().into(),
+                        at.to_rfc3339().into(),
+                        link_id.clone().into(),
+                        Self::timestamp_value(&at),
+                    ])?,
+                self.db
+                    .prepare(
+                        "UPDATE group_invitations SET status = 'accepted', responded_at = ?1
+                         WHERE invited_user_id = ?2 AND status = 'pending'
+                           AND group_id = (SELECT group_id FROM group_invite_links WHERE id = ?3 AND last_used_at = ?1 AND last_used_by = ?2)",
+                    )
+                    .bind(&[Self::timestamp_value(&at), user_id.clone().into(), link_id.clone().into()])?,
+            ];
+            if let Some(placeholder_id) = placeholder_id {
+                statements.extend(placeholder_claim_statements(&self.db, placeholder_id, user_uuid, link_uuid, at)?);
+            }
+            self.db.batch(statements).await
+        })
+        .await
+        .map_err(|e| format!("Failed to redeem invite: {}", e))?;
+
+        let changed = |result: Option<&D1Result>| -> std::result::Result<bool, Box<dyn std::error::Error>> {
+            Ok(match result {
+                Some(result) => result.meta()?.and_then(|meta| meta.changes).unwrap_or(0) > 0,
+                None => false,
+            })
+        };
+        if !changed(results.first())? {
+            Ok(LinkRedemption::Unusable)
+        } else if placeholder_id.is_some() && !changed(results.last())? {
+            Ok(LinkRedemption::PlaceholderGone)
+        } else {
+            Ok(LinkRedemption::Joined)
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::{D1Database, Error as WorkerError};
+use worker::send::{SendFuture, SendWrapper};
+use serde_json::Value;
+use chrono::{TimeZone, Utc};
+use std::collections::HashMap;
+
+use crate::groups::domain::ledger::{ClosedLedger, LedgerLink};
+use crate::groups::domain::ports::GroupLedgerRepository;
+
+fn parse_uuid(value: &Value) -> Option<Uuid> {
+    value.as_str().and_then(|id| Uuid::parse_str(id).ok())
+}
+
+fn parse_i64(value: &Value) -> Option<i64> {
+    value.as_i64().or_else(|| value.as_f64().map(|v| v.round() as i64))
+}
+
+// Where a live group sits in the ledger tree, or None when it does not exist or was deleted
+pub async fn ledger_link(db: &D1Database, group_id: &Uuid) -> Result<Option<LedgerLink>, WorkerError> {
+    let row = db
+        .prepare("SELECT parent_group_id, closed_at FROM groups WHERE id = ?1 AND deleted_at IS NULL")
+        .bind(&[group_id.to_string().into()])?
+        .first::<Value>(None)
+        .await?;
+
+    Ok(row.map(|row| LedgerLink {
+        group_id: *group_id,
+        parent_group_id: parse_uuid(&row["parent_group_id"]),
+        closed_at: parse_i64(&row["closed_at"]).and_then(|seconds| Utc.timestamp_opt(seconds, 0).single()),
+    }))
+}
+
+// Ledgers under `parent_group_id` that have not been closed into it yet
+pub async fn open_child_ledgers(db: &D1Database, parent_group_id: &Uuid) -> Result<Vec<Uuid>, WorkerError> {
+    let rows = db
+        .prepare("SELECT id FROM groups WHERE parent_group_id = ?1 AND closed_at IS NULL AND deleted_at IS NULL ORDER BY created_at")
+        .bind(&[parent_group_id.to_string().into()])?
+        .all()
+        .await?
+        .results::<Value>()?;
+    Ok(rows.iter().filter_map(|row| parse_uuid(&row["id"])).collect())
+}
+
+// Net effect of closed ledgers on a group's balances, per user, in minor units. A parent gains
+// each transfer posted into it; the ledger it came from loses the same amount, which brings it
+// to zero.
+pub async fn ledger_transfer_totals(db: &D1Database, group_id: &Uuid) -> Result<HashMap<Uuid, i64>, WorkerError> {
+    let rows = db
+        .prepare(
+            "SELECT user_id, SUM(CASE WHEN group_id = ?1 THEN amount ELSE -amount END) AS amount
+             FROM ledger_transfers WHERE group_id = ?1 OR child_group_id = ?1 GROUP BY user_id",
+        )
+        .bind(&[group_id.to_string().into()])?
+        .all()
+        .await?
+        .results::<Value>()?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| Some((parse_uuid(&row["user_id"])?, parse_i64(&row["amount"]).unwrap_or(0))))
+        .collect())
+}
+
+// Posts the transfers and marks the ledger closed (and archived) in one batch. Every statement
+// is guarded on the ledger still being open, so a second close changes nothing and returns false.
+pub async fn close_ledger(db: &D1Database, ledger: &ClosedLedger, closed_by: &Uuid) -> Result<bool, WorkerError> {
+    let child_id = ledger.group_id.to_string();
+    let closed_at = ledger.closed_at.timestamp() as f64;
+
+    let mut statements = Vec::with_capacity(ledger.transfers.len() + 1);
+    for transfer in &ledger.transfers {
+        statements.push(
+            db.prepare(
+                "INSERT INTO ledger_transfers (id, group_id, child_group_id, user_id, amount, currency, created_at)
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7
+                 WHERE EXISTS (SELECT 1 FROM groups WHERE id = ?3 AND closed_at IS NULL)",
+            )
+            .bind(&[
+                transfer.id.to_string().into(),
+                transfer.group_id.to_string().into(),
+                child_id.clone().into(),
+                transfer.user_id.to_string().into(),
+                (transfer.amount.minor_units as f64).into(),
+                transfer.amount.currency.clone().into(),
+                (transfer.created_at.timestamp() as f64).into(),
+            ])?,
+        );
+    }
+    statements.push(
+        db.prepare(
+            "UPDATE groups SET closed_at = ?2, archived_at = COALESCE(archived_at, ?2), archived_by = COALESCE(archived_by, ?3)
+             WHERE id = ?1 AND closed_at IS NULL",
+        )
+        .bind(&[child_id.into(), closed_at.into(), closed_by.to_string().into()])?,
+    );
+
+    let results = db.batch(statements).await?;
+    let changes = match results.last() {
+        Some(result) => result.meta()?.and_then(|meta| meta.changes).unwrap_or(0),
+        None => 0,
+    };
+    Ok(changes > 0)
+}
+
+// Port adapter for services that hold repositories rather than a raw D1 handle
+pub struct D1GroupLedgerRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1GroupLedgerRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+}
+
+#[async_trait]
+impl GroupLedgerRepository for D1GroupLedgerRepository {
+    async fn get_ledger_link(&self, group_id: &Uuid) -> Result<Option<LedgerLink>, Box<dyn std::error::Error>> {
+        let link = SendFuture::new(ledger_link(&self.db, group_id))
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+        Ok(link)
+    }
+
+    async fn get_open_child_ledgers(&self, parent_group_id: &Uuid) -> Result<Vec<Uuid>, Box<dyn std::error::Error>> {
+        let children = SendFuture::new(open_child_ledgers(&self.db, parent_group_id))
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+        Ok(children)
+    }
+
+    async fn get_transfer_totals(&self, group_id: &Uuid) -> Result<HashMap<Uuid, i64>, Box<dyn std::error::Error>> {
+        let totals = SendFuture::new(ledger_transfer_totals(&self.db, group_id))
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+        Ok(totals)
+    }
+
+    async fn close_ledger(&self, ledger: &ClosedLedger, closed_by: &Uuid) -> Result<bool, Box<dyn std::error::Error>> {
+        let closed = SendFuture::new(close_ledger(&self.db, ledger, closed_by))
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+        Ok(closed)
+    }
+}
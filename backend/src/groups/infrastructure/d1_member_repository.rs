@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{DateTime, Utc};
+use std::future::Future;
+
+use crate::groups::domain::group::{GroupMember, GroupMemberInfo, MemberRole};
+use crate::groups::domain::permissions::MemberAccess;
+use crate::groups::domain::ports::GroupMemberRepository;
+use crate::groups::infrastructure::placeholders::placeholder_create_statements;
+use crate::profiles::infrastructure::{display_name, load_display_names};
+
+// Port adapter over group_members, for application services that need membership checks
+pub struct D1GroupMemberRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1GroupMemberRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+
+    fn parse_role(value: &Value) -> MemberRole {
+        MemberRole::from_db_str(value.as_str().unwrap_or("member"))
+    }
+
+    // JsValue params are not Send either, so they move straight into the wrapped future
+    fn execute<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<D1Result>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.run().await })
+    }
+
+    fn query_first<'a>(&'a self, query: &'a str, params: Vec<JsValue>) -> SendFuture<impl Future<Output = Result<Option<Value>>> + 'a> {
+        SendFuture::new(async move { self.db.prepare(query).bind(&params)?.first::<Value>(None).await })
+    }
+}
+
+#[async_trait]
+impl GroupMemberRepository for D1GroupMemberRepository {
+    async fn add_member(&self, member: &GroupMember) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "INSERT INTO group_members (group_id, user_id, role, joined_at) VALUES (?1, ?2, ?3, ?4)",
+            vec![
+                member.group_id.to_string().into(),
+                member.user_id.to_string().into(),
+                member.role.as_db_str().into(),
+                member.joined_at.to_rfc3339().into(),
+            ],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn remove_member(&self, group_id: &Uuid, user_id: &Uuid) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.execute(
+            "DELETE FROM group_members WHERE group_id = ?1 AND user_id = ?2",
+            vec![group_id.to_string().into(), user_id.to_string().into()],
+        )
+        .await
+        .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+
+    async fn get_members(&self, group_id: &Uuid) -> std::result::Result<Vec<GroupMemberInfo>, Box<dyn std::error::Error>> {
+        let group_id = group_id.to_string();
+        let (rows, names) = SendFuture::new(async {
+            let rows = self
+                .db
+                .prepare(
+                    "SELECT gm.user_id, gm.role, gm.joined_at, COALESCE(u.is_placeholder, 0) AS is_placeholder
+                     FROM group_members gm LEFT JOIN users u ON u.id = gm.user_id
+                     WHERE gm.group_id = ?1 ORDER BY gm.joined_at",
+                )
+                .bind(&[group_id.clone().into()])?
+                .all()
+                .await?
+                .results::<Value>()?;
+            let user_ids: Vec<Uuid> = rows
+                .iter()
+                .filter_map(|row| row["user_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()))
+                .collect();
+            let names = load_display_names(&self.db, &user_ids).await?;
+            Ok::<_, Error>((rows, names))
+        })
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
+        let mut members = Vec::with_capacity(rows.len());
+        for row in rows {
+            let user_id = Uuid::parse_str(row["user_id"].as_str().ok_or("Invalid user ID")?)
+                .map_err(|e| format!("UUID parse error: {}", e))?;
+            members.push(GroupMemberInfo {
+                user_id,
+                username: display_name(&names, &user_id),
+                role: Self::parse_role(&row["role"]),
+                joined_at: DateTime::parse_from_rfc3339(row["joined_at"].as_str().unwrap_or(""))
+                    .map_err(|e| format!("Date parse error: {}", e))?
+                    .with_timezone(&Utc),
+                is_placeholder: row["is_placeholder"].as_i64().unwrap_or(0) != 0,
+            });
+        }
+
+        Ok(members)
+    }
+
+    async fn is_member(&self, group_id: &Uuid, user_id: &Uuid) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        Ok(self.get_user_role(group_id, user_id).await?.is_some())
+    }
+
+    async fn get_user_role(&self, group_id: &Uuid, user_id: &Uuid) -> std::result::Result<Option<MemberRole>, Box<dyn std::error::Error>> {
+        Ok(self.get_member_access(group_id, user_id).await?.map(|access| access.role))
+    }
+
+    async fn get_member_access(&self, group_id: &Uuid, user_id: &Uuid) -> std::result::Result<Option<MemberAccess>, Box<dyn std::error::Error>> {
+        let row = self
+            .query_first(
+                // Deleted groups have no members as far as permissions go
+                "SELECT gm.role, g.archived_at FROM group_members gm JOIN groups g ON g.id = gm.group_id
+                 WHERE gm.group_id = ?1 AND gm.user_id = ?2 AND g.deleted_at IS NULL",
+                vec![group_id.to_string().into(), user_id.to_string().into()],
+            )
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+
+        Ok(row.map(|row| MemberAccess {
+            role: Self::parse_role(&row["role"]),
+            group_archived: !row["archived_at"].is_null(),
+        }))
+    }
+
+    async fn set_member_role(&self, group_id: &Uuid, user_id: &Uuid, role: &MemberRole) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let result = self
+            .execute(
+                "UPDATE group_members SET role = ?1 WHERE group_id = ?2 AND user_id = ?3 AND role != 'owner'",
+                vec![role.as_db_str().into(), group_id.to_string().into(), user_id.to_string().into()],
+            )
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+
+        let changes = result.meta()?.and_then(|meta| meta.changes).unwrap_or(0);
+        Ok(changes > 0)
+    }
+
+    async fn transfer_ownership(&self, group_id: &Uuid, from: &Uuid, to: &Uuid) -> std::result::Result<bool, Box<dyn std::error::Error>> {
+        let group_id = group_id.to_string();
+        let from = from.to_string();
+        let to = to.to_string();
+
+        // One batch; both statements only act while `from` still holds the owner row, so a
+        // concurrent transfer cannot leave two owners
+        let results = SendFuture::new(async {
+            let statements = vec![
+                self.db
+                    .prepare(
+                        "UPDATE group_members SET role = 'owner'
+                         WHERE group_id = ?1 AND user_id = ?2
+                           AND EXISTS (SELECT 1 FROM group_members WHERE group_id = ?1 AND user_id = ?3 AND role = 'owner')",
+                    )
+                    .bind(&[group_id.clone().into(), to.clone().into(), from.clone().into()])?,
+                self.db
+                    .prepare(
+                        "UPDATE group_members SET role = 'admin'
+                         WHERE group_id = ?1 AND user_id = ?2 AND role = 'owner'
+                           AND EXISTS (SELECT 1 FROM group_members WHERE group_id = ?1 AND user_id = ?3 AND role = 'owner')",
+                    )
+                    .bind(&[group_id.clone().into(), from.clone().into(), to.clone().into()])?,
+            ];
+            self.db.batch(statements).await
+        })
+        .await
+        .map_err(|e| format!("Failed to transfer ownership: {}", e))?;
+
+        let transferred = match results.first() {
+            Some(result) => result.meta()?.and_then(|meta| meta.changes).unwrap_or(0) > 0,
+            None => false,
+        };
+        Ok(transferred)
+    }
+
+    async fn add_placeholder(&self, member: &GroupMember, name: &str) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        SendFuture::new(async { self.db.batch(placeholder_create_statements(&self.db, member, name)?).await })
+            .await
+            .map_err(|e| format!("Failed to add placeholder: {}", e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::{D1Database, Error as WorkerError};
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{TimeZone, Utc};
+
+use crate::error::ServiceError;
+use crate::groups::domain::ports::GroupSettingsRepository;
+use crate::groups::domain::settings::{GroupModule, GroupSettings, WeekStart};
+
+fn flag(value: &Value) -> Option<bool> {
+    value.as_i64().or_else(|| value.as_f64().map(|v| v as i64)).map(|v| v != 0)
+}
+
+fn row_to_settings(group_id: &Uuid, row: &Value) -> GroupSettings {
+    let now = Utc::now();
+    let defaults = GroupSettings::new(*group_id, now);
+    let updated_at = row["updated_at"]
+        .as_i64()
+        .or_else(|| row["updated_at"].as_f64().map(|s| s as i64))
+        .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single())
+        .unwrap_or(now);
+
+    GroupSettings {
+        group_id: *group_id,
+        default_currency: row["default_currency"].as_str().map(|s| s.to_string()).unwrap_or(defaults.default_currency),
+        timezone: row["timezone"].as_str().map(|s| s.to_string()).unwrap_or(defaults.timezone),
+        week_start: row["week_start"].as_str().and_then(WeekStart::parse).unwrap_or(defaults.week_start),
+        locale: row["locale"].as_str().map(|s| s.to_string()).unwrap_or(defaults.locale),
+        expenses_enabled: flag(&row["expenses_enabled"]).unwrap_or(true),
+        chores_enabled: flag(&row["chores_enabled"]).unwrap_or(true),
+        calendar_enabled: flag(&row["calendar_enabled"]).unwrap_or(true),
+        simplify_debts: flag(&row["simplify_debts"]).unwrap_or(true),
+        updated_at,
+    }
+}
+
+// Settings for one group, falling back to the defaults when none were saved yet
+pub async fn load_group_settings(db: &D1Database, group_id: &Uuid) -> Result<GroupSettings, WorkerError> {
+    let row = db
+        .prepare(
+            "SELECT default_currency, timezone, week_start, locale, expenses_enabled, chores_enabled, calendar_enabled, simplify_debts, updated_at
+             FROM group_settings WHERE group_id = ?",
+        )
+        .bind(&[group_id.to_string().into()])?
+        .first::<Value>(None)
+        .await?;
+
+    Ok(match row {
+        Some(row) => row_to_settings(group_id, &row),
+        None => GroupSettings::new(*group_id, Utc::now()),
+    })
+}
+
+// Settings for a group whose `module` must be switched on; otherwise `ServiceError::Forbidden`
+pub async fn require_module(db: &D1Database, group_id: &Uuid, module: GroupModule) -> Result<GroupSettings, ServiceError> {
+    let settings = load_group_settings(db, group_id).await?;
+    settings.require_enabled(module)?;
+    Ok(settings)
+}
+
+pub async fn save_group_settings(db: &D1Database, settings: &GroupSettings) -> Result<(), WorkerError> {
+    let flag_value = |on: bool| -> JsValue { (if on { 1.0 } else { 0.0 }).into() };
+    db.prepare(
+        "INSERT INTO group_settings (group_id, default_currency, timezone, week_start, locale, expenses_enabled, chores_enabled, calendar_enabled, simplify_debts, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(group_id) DO UPDATE SET
+             default_currency = excluded.default_currency,
+             timezone = excluded.timezone,
+             week_start = excluded.week_start,
+             locale = excluded.locale,
+             expenses_enabled = excluded.expenses_enabled,
+             chores_enabled = excluded.chores_enabled,
+             calendar_enabled = excluded.calendar_enabled,
+             simplify_debts = excluded.simplify_debts,
+             updated_at = excluded.updated_at",
+    )
+    .bind(&[
+        settings.group_id.to_string().into(),
+        settings.default_currency.clone().into(),
+        settings.timezone.clone().into(),
+        settings.week_start.as_str().into(),
+        settings.locale.clone().into(),
+        flag_value(settings.expenses_enabled),
+        flag_value(settings.chores_enabled),
+        flag_value(settings.calendar_enabled),
+        flag_value(settings.simplify_debts),
+        (settings.updated_at.timestamp() as f64).into(),
+    ])?
+    .run()
+    .await?;
+    Ok(())
+}
+
+// Port adapter for services that hold repositories rather than a raw D1 handle
+pub struct D1GroupSettingsRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1GroupSettingsRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+}
+
+#[async_trait]
+impl GroupSettingsRepository for D1GroupSettingsRepository {
+    async fn get_settings(&self, group_id: &Uuid) -> Result<GroupSettings, Box<dyn std::error::Error>> {
+        let settings = SendFuture::new(load_group_settings(&self.db, group_id))
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+        Ok(settings)
+    }
+
+    async fn save_settings(&self, settings: &GroupSettings) -> Result<(), Box<dyn std::error::Error>> {
+        SendFuture::new(save_group_settings(&self.db, settings))
+            .await
+            .map_err(|e| format!("Run error: {}", e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use uuid::Uuid;
+use worker::{console_log, D1Database, D1PreparedStatement, Error as WorkerError};
+use serde_json::Value;
+use chrono::{DateTime, Utc};
+
+// Removes a group and every row filed under it, children first. Run inside one batch so a
+// failure part-way leaves the group whole.
+pub fn group_purge_statements(db: &D1Database, group_id: &Uuid) -> Result<Vec<D1PreparedStatement>, WorkerError> {
+    let queries = [
+        "DELETE FROM expense_shares WHERE expense_id IN (SELECT id FROM expenses WHERE group_id = ?1)",
+        "DELETE FROM expense_revisions WHERE group_id = ?1",
+        "DELETE FROM expenses WHERE group_id = ?1",
+        "DELETE FROM payment_allocations WHERE payment_id IN (SELECT id FROM payments WHERE group_id = ?1)",
+        "DELETE FROM payments WHERE group_id = ?1",
+        "DELETE FROM event_attendees WHERE event_id IN (SELECT id FROM events WHERE group_id = ?1)",
+        "DELETE FROM events WHERE group_id = ?1",
+        "DELETE FROM chores WHERE group_id = ?1",
+        "DELETE FROM group_invitations WHERE group_id = ?1",
+        "DELETE FROM group_invite_links WHERE group_id = ?1",
+        "DELETE FROM group_settings WHERE group_id = ?1",
+        "DELETE FROM activity_reads WHERE group_id = ?1",
+        "DELETE FROM activity_log WHERE group_id = ?1",
+        // Closed ledgers' totals go with whichever side is purged
+        "DELETE FROM ledger_transfers WHERE group_id = ?1 OR child_group_id = ?1",
+        // Placeholders only ever belong to the group they were added to
+        "DELETE FROM user_profiles WHERE user_id IN (
+             SELECT gm.user_id FROM group_members gm JOIN users u ON u.id = gm.user_id
+             WHERE gm.group_id = ?1 AND u.is_placeholder = 1)",
+        "DELETE FROM users WHERE is_placeholder = 1 AND id IN (SELECT user_id FROM group_members WHERE group_id = ?1)",
+        "DELETE FROM group_members WHERE group_id = ?1",
+        // Open ledgers become top-level groups
+        "UPDATE groups SET parent_group_id = NULL WHERE parent_group_id = ?1",
+        "DELETE FROM groups WHERE id = ?1",
+    ];
+
+    let group_id = group_id.to_string();
+    queries
+        .iter()
+        .map(|query| db.prepare(*query).bind(&[group_id.clone().into()]))
+        .collect()
+}
+
+// Outcome of one purge sweep
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PurgeSummary {
+    pub purged: u64,
+    pub failed: u64, // left in place; the next sweep tries them again
+}
+
+// Purges every group deleted before `cutoff`, one batch per group. A group that fails is
+// logged and skipped so it cannot hold up the others.
+pub async fn purge_deleted_groups(db: &D1Database, cutoff: DateTime<Utc>) -> Result<PurgeSummary, WorkerError> {
+    let rows = db
+        .prepare("SELECT id FROM groups WHERE deleted_at IS NOT NULL AND deleted_at <= ?1")
+        .bind(&[(cutoff.timestamp() as f64).into()])?
+        .all()
+        .await?
+        .results::<Value>()?;
+
+    let mut summary = PurgeSummary::default();
+    for row in rows {
+        let Some(group_id) = row["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else {
+            continue;
+        };
+        let result = match group_purge_statements(db, &group_id) {
+            Ok(statements) => db.batch(statements).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => summary.purged += 1,
+            Err(e) => {
+                console_log!("Failed to purge group {}: {}", group_id, e);
+                summary.failed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
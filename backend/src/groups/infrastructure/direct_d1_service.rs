@@ -1,34 +1,33 @@
-use worker::{D1Database, Error as WorkerError};
+use worker::D1Database;
+use worker::wasm_bindgen::JsValue;
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use serde_json::Value;
 
+use crate::error::ServiceError;
+use crate::profiles::infrastructure::{display_name, load_display_names};
+use crate::groups::application::authorization::GroupAuthorizer;
+use crate::groups::domain::permissions::{GroupAction, PermissionPolicy};
+use crate::groups::infrastructure::deletion::{group_purge_statements, purge_deleted_groups, PurgeSummary};
+use crate::groups::infrastructure::d1_ledger_repository::ledger_link;
+use crate::groups::infrastructure::d1_settings_repository::{load_group_settings, save_group_settings};
+use crate::groups::domain::settings::GroupSettings;
 use crate::groups::domain::group::{
-    Group, GroupMember, GroupCreation, GroupUpdate, GroupInfo, GroupInvitation, 
-    InviteUser, GroupMemberInfo, MemberRole,
+    DeletedGroupInfo, Group, GroupExport, GroupMember, GroupCreation, GroupUpdate, GroupInfo, GroupMemberInfo, MemberRole,
+    GROUP_RESTORE_WINDOW_DAYS,
 };
 
 pub struct DirectD1GroupService {
     db: D1Database,
+    authorizer: GroupAuthorizer,
 }
 
 impl DirectD1GroupService {
-    pub fn new(db: D1Database) -> Self {
-        Self { db }
+    pub fn new(db: D1Database, authorizer: GroupAuthorizer) -> Self {
+        Self { db, authorizer }
     }
 
-    async fn get_username(&self, user_id: &Uuid) -> Result<String, WorkerError> {
-        let stmt = self.db.prepare("SELECT username FROM users WHERE id = ?1");
-        let result = stmt.bind(&[user_id.to_string().into()])?.first::<Value>(None).await?;
-        
-        if let Some(row) = result {
-            Ok(row["username"].as_str().unwrap_or("Unknown User").to_string())
-        } else {
-            Ok("Unknown User".to_string())
-        }
-    }
-
-    async fn get_group_name(&self, group_id: &Uuid) -> Result<String, WorkerError> {
+    async fn get_group_name(&self, group_id: &Uuid) -> Result<String, ServiceError> {
         let stmt = self.db.prepare("SELECT name FROM groups WHERE id = ?1");
         let result = stmt.bind(&[group_id.to_string().into()])?.first::<Value>(None).await?;
         
@@ -39,7 +38,13 @@ impl DirectD1GroupService {
         }
     }
 
-    pub async fn create_group_from_creation(&self, creation: GroupCreation, created_by: Uuid) -> Result<GroupInfo, WorkerError> {
+    pub async fn create_group_from_creation(&self, creation: GroupCreation, created_by: Uuid) -> Result<GroupInfo, ServiceError> {
+        // A ledger starts out with its parent's settings so amounts roll up in one currency
+        let parent_settings = match &creation.parent_group_id {
+            Some(parent_id) => Some(self.check_ledger_parent(parent_id, &created_by).await?),
+            None => None,
+        };
+
         let group = Group {
             id: Uuid::new_v4(),
             name: creation.name.clone(),
@@ -47,10 +52,16 @@ impl DirectD1GroupService {
             created_by,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            archived_at: None,
+            parent_group_id: creation.parent_group_id,
+            closed_at: None,
         };
 
         // Create the group
         self.create_group(&group).await?;
+        if let Some(parent_settings) = parent_settings {
+            save_group_settings(&self.db, &GroupSettings { group_id: group.id, updated_at: group.created_at, ..parent_settings }).await?;
+        }
 
         // Add the creator as owner
         let owner_member = GroupMember {
@@ -58,6 +69,7 @@ impl DirectD1GroupService {
             user_id: created_by,
             role: MemberRole::Owner,
             joined_at: Utc::now(),
+            is_placeholder: false,
         };
         self.add_member(&owner_member).await?;
 
@@ -69,12 +81,28 @@ impl DirectD1GroupService {
             created_by: group.created_by,
             member_count: 1,
             created_at: group.created_at,
+            archived_at: None,
+            parent_group_id: group.parent_group_id,
+            closed_at: None,
             user_role: Some(MemberRole::Owner),
         })
     }
 
-    pub async fn create_group(&self, group: &Group) -> Result<(), WorkerError> {
-        let stmt = self.db.prepare("INSERT INTO groups (id, name, description, created_by, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)");
+    // Anyone who can add to a group can start a ledger inside it, one level deep only.
+    // Returns the parent's settings for the new ledger to copy.
+    async fn check_ledger_parent(&self, parent_id: &Uuid, user_id: &Uuid) -> Result<GroupSettings, ServiceError> {
+        self.authorizer.authorize(parent_id, user_id, GroupAction::CreateLedger, None).await?;
+        let parent = ledger_link(&self.db, parent_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Group not found".to_string()))?;
+        if parent.is_ledger() {
+            return Err(ServiceError::Invalid("Ledgers cannot be nested inside other ledgers".to_string()));
+        }
+        Ok(load_group_settings(&self.db, parent_id).await?)
+    }
+
+    pub async fn create_group(&self, group: &Group) -> Result<(), ServiceError> {
+        let stmt = self.db.prepare("INSERT INTO groups (id, name, description, created_by, created_at, updated_at, parent_group_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)");
         
         stmt.bind(&[
             group.id.to_string().into(),
@@ -83,6 +111,7 @@ impl DirectD1GroupService {
             group.created_by.to_string().into(),
             group.created_at.to_rfc3339().into(),
             group.updated_at.to_rfc3339().into(),
+            group.parent_group_id.map(|id| JsValue::from(id.to_string())).unwrap_or(JsValue::NULL),
         ])?
         .run()
         .await?;
@@ -90,12 +119,8 @@ impl DirectD1GroupService {
         Ok(())
     }
 
-    pub async fn add_member(&self, member: &GroupMember) -> Result<(), WorkerError> {
-        let role_str = match member.role {
-            MemberRole::Owner => "admin",  // Map owner to admin for DB constraint
-            MemberRole::Admin => "admin", 
-            MemberRole::Member => "member",
-        };
+    pub async fn add_member(&self, member: &GroupMember) -> Result<(), ServiceError> {
+        let role_str = member.role.as_db_str();
 
         let stmt = self.db.prepare("INSERT INTO group_members (group_id, user_id, role, joined_at) VALUES (?1, ?2, ?3, ?4)");
         
@@ -111,7 +136,9 @@ impl DirectD1GroupService {
         Ok(())
     }
 
-    pub async fn get_group_by_id(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Option<GroupInfo>, WorkerError> {
+    pub async fn get_group_by_id(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Option<GroupInfo>, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+
         // Get group basic info
         let group_stmt = self.db.prepare("SELECT * FROM groups WHERE id = ?1");
         let group_result = group_stmt.bind(&[group_id.to_string().into()])?.first::<Value>(None).await?;
@@ -132,11 +159,14 @@ impl DirectD1GroupService {
                 name: group_row["name"].as_str().unwrap_or("").to_string(),
                 description: Some(group_row["description"].as_str().unwrap_or("").to_string()),
                 created_by: Uuid::parse_str(group_row["created_by"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?,
+                    .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))?,
                 member_count,
                 created_at: DateTime::parse_from_rfc3339(group_row["created_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
+                    .map_err(|e| ServiceError::Internal(format!("Date parse error: {}", e)))?
                     .with_timezone(&Utc),
+                archived_at: Self::parse_epoch(&group_row["archived_at"]).ok(),
+                parent_group_id: group_row["parent_group_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+                closed_at: Self::parse_epoch(&group_row["closed_at"]).ok(),
                 user_role: role,
             };
 
@@ -146,55 +176,41 @@ impl DirectD1GroupService {
         }
     }
 
-    pub async fn get_user_role(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Option<MemberRole>, WorkerError> {
-        let stmt = self.db.prepare("SELECT role FROM group_members WHERE group_id = ?1 AND user_id = ?2");
-        let result = stmt.bind(&[
-            group_id.to_string().into(),
-            user_id.to_string().into(),
-        ])?.first::<Value>(None).await?;
-
-        if let Some(row) = result {
-            let role_str = row["role"].as_str().unwrap_or("member");
-            let role = match role_str {
-                "admin" => MemberRole::Admin,  // Treat admin as admin (could be owner)
-                _ => MemberRole::Member,
-            };
-            Ok(Some(role))
-        } else {
-            Ok(None)
-        }
+    pub async fn get_user_role(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Option<MemberRole>, ServiceError> {
+        Ok(self.authorizer.member_access(group_id, user_id).await?.map(|access| access.role))
     }
 
-    pub async fn get_groups_for_user(&self, user_id: &Uuid) -> Result<Vec<GroupInfo>, WorkerError> {
+    // Archived groups are left out unless `include_archived`
+    pub async fn get_groups_for_user(&self, user_id: &Uuid, include_archived: bool) -> Result<Vec<GroupInfo>, ServiceError> {
         let stmt = self.db.prepare("
             SELECT g.*, gm.role, 
                    (SELECT COUNT(*) FROM group_members WHERE group_id = g.id) as member_count
             FROM groups g 
             JOIN group_members gm ON g.id = gm.group_id 
-            WHERE gm.user_id = ?1
+            WHERE gm.user_id = ?1 AND g.deleted_at IS NULL AND (?2 = 1 OR g.archived_at IS NULL)
             ORDER BY g.created_at DESC
         ");
-        let results = stmt.bind(&[user_id.to_string().into()])?.all().await?;
+        let include_archived: f64 = if include_archived { 1.0 } else { 0.0 };
+        let results = stmt.bind(&[user_id.to_string().into(), include_archived.into()])?.all().await?;
 
         let mut groups = Vec::new();
         for row in results.results::<Value>()? {
-            let role_str = row["role"].as_str().unwrap_or("member");
-            let role = match role_str {
-                "admin" => MemberRole::Admin,  // Treat admin as admin (could be owner)
-                _ => MemberRole::Member,
-            };
+            let role = MemberRole::from_db_str(row["role"].as_str().unwrap_or("member"));
 
             groups.push(GroupInfo {
                 id: Uuid::parse_str(row["id"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?,
+                    .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))?,
                 name: row["name"].as_str().unwrap_or("").to_string(),
                 description: Some(row["description"].as_str().unwrap_or("").to_string()),
                 created_by: Uuid::parse_str(row["created_by"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?,
+                    .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))?,
                 member_count: row["member_count"].as_i64().unwrap_or(0) as usize,
                 created_at: DateTime::parse_from_rfc3339(row["created_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
+                    .map_err(|e| ServiceError::Internal(format!("Date parse error: {}", e)))?
                     .with_timezone(&Utc),
+                archived_at: Self::parse_epoch(&row["archived_at"]).ok(),
+                parent_group_id: row["parent_group_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+                closed_at: Self::parse_epoch(&row["closed_at"]).ok(),
                 user_role: Some(role),
             });
         }
@@ -202,72 +218,194 @@ impl DirectD1GroupService {
         Ok(groups)
     }
 
-    pub async fn get_group_members(&self, group_id: &Uuid) -> Result<Vec<GroupMemberInfo>, WorkerError> {
-        let stmt = self.db.prepare("SELECT user_id, role, joined_at FROM group_members WHERE group_id = ?1");
-        let results = stmt.bind(&[group_id.to_string().into()])?.all().await?;
+    pub async fn get_group_members(&self, group_id: &Uuid, user_id: &Uuid) -> Result<Vec<GroupMemberInfo>, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ViewGroup, None).await?;
+
+        let stmt = self.db.prepare(
+            "SELECT gm.user_id, gm.role, gm.joined_at, COALESCE(u.is_placeholder, 0) AS is_placeholder
+             FROM group_members gm LEFT JOIN users u ON u.id = gm.user_id WHERE gm.group_id = ?1",
+        );
+        let rows = stmt.bind(&[group_id.to_string().into()])?.all().await?.results::<Value>()?;
+
+        let mut user_ids = Vec::with_capacity(rows.len());
+        for row in &rows {
+            user_ids.push(Uuid::parse_str(row["user_id"].as_str().unwrap_or(""))
+                .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))?);
+        }
+        let names = load_display_names(&self.db, &user_ids).await?;
 
         let mut members = Vec::new();
-        for row in results.results::<Value>()? {
-            let user_id = Uuid::parse_str(row["user_id"].as_str().unwrap_or(""))
-                .map_err(|e| WorkerError::RustError(format!("UUID parse error: {}", e)))?;
-            let username = self.get_username(&user_id).await.unwrap_or_else(|_| "Unknown User".to_string());
+        for (row, user_id) in rows.iter().zip(user_ids) {
+            let username = display_name(&names, &user_id);
             
-            let role_str = row["role"].as_str().unwrap_or("member");
-            let role = match role_str {
-                "admin" => MemberRole::Admin,  // Treat admin as admin (could be owner)
-                _ => MemberRole::Member,
-            };
+            let role = MemberRole::from_db_str(row["role"].as_str().unwrap_or("member"));
 
             members.push(GroupMemberInfo {
                 user_id,
                 username,
                 role,
                 joined_at: DateTime::parse_from_rfc3339(row["joined_at"].as_str().unwrap_or(""))
-                    .map_err(|e| WorkerError::RustError(format!("Date parse error: {}", e)))?
+                    .map_err(|e| ServiceError::Internal(format!("Date parse error: {}", e)))?
                     .with_timezone(&Utc),
+                is_placeholder: row["is_placeholder"].as_i64().unwrap_or(0) != 0,
             });
         }
 
         Ok(members)
     }
 
-    pub async fn invite_user(&self, group_id: &Uuid, invite: InviteUser, invited_by: Uuid) -> Result<(), WorkerError> {
-        // For now, directly add the user as a member (simplified invitation system)
-        let member = GroupMember {
-            group_id: *group_id,
-            user_id: invite.user_id,
-            role: MemberRole::Member,
-            joined_at: Utc::now(),
-        };
+    // Soft delete by default: the group disappears for everyone but can be restored for
+    // GROUP_RESTORE_WINDOW_DAYS, after which the scheduled sweep purges it. Returns when that
+    // happens, or None when `permanent` removed everything straight away.
+    pub async fn delete_group(&self, group_id: &Uuid, user_id: &Uuid, permanent: bool) -> Result<Option<DateTime<Utc>>, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::DeleteGroup, None).await?;
+
+        if permanent {
+            self.db.batch(group_purge_statements(&self.db, group_id)?).await?;
+            return Ok(None);
+        }
 
-        self.add_member(&member).await
+        // Open invitations and links die with the group and stay revoked if it comes back
+        let now = Utc::now();
+        let at: f64 = now.timestamp() as f64;
+        let id = group_id.to_string();
+        let statements = vec![
+            self.db
+                .prepare("UPDATE groups SET deleted_at = ?1, deleted_by = ?2 WHERE id = ?3 AND deleted_at IS NULL")
+                .bind(&[at.into(), user_id.to_string().into(), id.clone().into()])?,
+            self.db
+                .prepare("UPDATE group_invitations SET status = 'revoked', responded_at = ?1 WHERE group_id = ?2 AND status = 'pending'")
+                .bind(&[at.into(), id.clone().into()])?,
+            self.db
+                .prepare("UPDATE group_invite_links SET revoked_at = ?1 WHERE group_id = ?2 AND revoked_at IS NULL")
+                .bind(&[at.into(), id.into()])?,
+        ];
+        self.db.batch(statements).await?;
+
+        Ok(Some(now + Duration::days(GROUP_RESTORE_WINDOW_DAYS)))
     }
 
-    pub async fn leave_group(&self, group_id: &Uuid, user_id: &Uuid) -> Result<(), WorkerError> {
-        let stmt = self.db.prepare("DELETE FROM group_members WHERE group_id = ?1 AND user_id = ?2");
-        stmt.bind(&[
-            group_id.to_string().into(),
-            user_id.to_string().into(),
-        ])?.run().await?;
+    // Deleted groups the user belonged to that can still be restored, newest first
+    pub async fn get_deleted_groups(&self, user_id: &Uuid) -> Result<Vec<DeletedGroupInfo>, ServiceError> {
+        let cutoff = Utc::now() - Duration::days(GROUP_RESTORE_WINDOW_DAYS);
+        let rows = self
+            .db
+            .prepare(
+                "SELECT g.id, g.name, g.deleted_at, g.deleted_by, gm.role
+                 FROM groups g
+                 JOIN group_members gm ON g.id = gm.group_id
+                 WHERE gm.user_id = ?1 AND g.deleted_at IS NOT NULL AND g.deleted_at > ?2
+                 ORDER BY g.deleted_at DESC",
+            )
+            .bind(&[user_id.to_string().into(), (cutoff.timestamp() as f64).into()])?
+            .all()
+            .await?
+            .results::<Value>()?;
+
+        let mut groups = Vec::with_capacity(rows.len());
+        for row in rows {
+            let deleted_at = Self::parse_epoch(&row["deleted_at"])?;
+            groups.push(DeletedGroupInfo {
+                id: Uuid::parse_str(row["id"].as_str().unwrap_or(""))
+                    .map_err(|e| ServiceError::Internal(format!("UUID parse error: {}", e)))?,
+                name: row["name"].as_str().unwrap_or("").to_string(),
+                deleted_by: row["deleted_by"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+                deleted_at,
+                purge_at: deleted_at + Duration::days(GROUP_RESTORE_WINDOW_DAYS),
+                user_role: MemberRole::from_db_str(row["role"].as_str().unwrap_or("member")),
+            });
+        }
 
-        Ok(())
+        Ok(groups)
     }
 
-    pub async fn delete_group(&self, group_id: &Uuid, user_id: &Uuid) -> Result<(), WorkerError> {
-        // Verify user is owner
-        let role = self.get_user_role(group_id, user_id).await?;
-        if !matches!(role, Some(MemberRole::Owner)) {
-            return Err(WorkerError::RustError("Only owners can delete groups".to_string()));
+    // Brings a deleted group back with everything in it, if its restore window is still open
+    pub async fn restore_group(&self, group_id: &Uuid, user_id: &Uuid) -> Result<(), ServiceError> {
+        // The authorizer ignores deleted groups, so read the role straight from the membership
+        let row = self
+            .db
+            .prepare(
+                "SELECT gm.role, g.deleted_at FROM groups g
+                 JOIN group_members gm ON g.id = gm.group_id
+                 WHERE g.id = ?1 AND gm.user_id = ?2",
+            )
+            .bind(&[group_id.to_string().into(), user_id.to_string().into()])?
+            .first::<Value>(None)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Group not found".to_string()))?;
+
+        let role = MemberRole::from_db_str(row["role"].as_str().unwrap_or("member"));
+        PermissionPolicy::check(Some(&role), GroupAction::RestoreGroup, false)?;
+        if row["deleted_at"].is_null() {
+            return Err(ServiceError::Conflict("Group is already active".to_string()));
         }
 
-        // Delete all members first
-        let delete_members_stmt = self.db.prepare("DELETE FROM group_members WHERE group_id = ?1");
-        delete_members_stmt.bind(&[group_id.to_string().into()])?.run().await?;
-
-        // Delete group
-        let delete_group_stmt = self.db.prepare("DELETE FROM groups WHERE id = ?1");
-        delete_group_stmt.bind(&[group_id.to_string().into()])?.run().await?;
+        let cutoff = Utc::now() - Duration::days(GROUP_RESTORE_WINDOW_DAYS);
+        let result = self
+            .db
+            .prepare("UPDATE groups SET deleted_at = NULL, deleted_by = NULL WHERE id = ?1 AND deleted_at > ?2")
+            .bind(&[group_id.to_string().into(), (cutoff.timestamp() as f64).into()])?
+            .run()
+            .await?;
 
+        if result.meta()?.and_then(|meta| meta.changes).unwrap_or(0) == 0 {
+            return Err(ServiceError::NotFound("Group not found".to_string()));
+        }
         Ok(())
     }
+
+    // Every row filed under the group, for the owner to keep. Works on archived groups too.
+    pub async fn export_group(&self, group_id: &Uuid, user_id: &Uuid) -> Result<GroupExport, ServiceError> {
+        self.authorizer.authorize(group_id, user_id, GroupAction::ExportGroup, None).await?;
+
+        let group = self
+            .export_rows("SELECT * FROM groups WHERE id = ?1", group_id)
+            .await?
+            .pop()
+            .ok_or_else(|| ServiceError::NotFound("Group not found".to_string()))?;
+
+        Ok(GroupExport {
+            exported_at: Utc::now(),
+            group,
+            members: self.export_rows("SELECT * FROM group_members WHERE group_id = ?1 ORDER BY joined_at", group_id).await?,
+            settings: self.export_rows("SELECT * FROM group_settings WHERE group_id = ?1", group_id).await?.pop(),
+            expenses: self.export_rows("SELECT * FROM expenses WHERE group_id = ?1 ORDER BY date", group_id).await?,
+            expense_shares: self
+                .export_rows("SELECT * FROM expense_shares WHERE expense_id IN (SELECT id FROM expenses WHERE group_id = ?1)", group_id)
+                .await?,
+            expense_revisions: self
+                .export_rows("SELECT * FROM expense_revisions WHERE group_id = ?1 ORDER BY expense_id, revision", group_id)
+                .await?,
+            payments: self.export_rows("SELECT * FROM payments WHERE group_id = ?1 ORDER BY created_at", group_id).await?,
+            payment_allocations: self
+                .export_rows("SELECT * FROM payment_allocations WHERE payment_id IN (SELECT id FROM payments WHERE group_id = ?1)", group_id)
+                .await?,
+            ledger_transfers: self
+                .export_rows("SELECT * FROM ledger_transfers WHERE group_id = ?1 OR child_group_id = ?1 ORDER BY created_at", group_id)
+                .await?,
+            chores: self.export_rows("SELECT * FROM chores WHERE group_id = ?1 ORDER BY created_at", group_id).await?,
+            events: self.export_rows("SELECT * FROM events WHERE group_id = ?1 ORDER BY start_time", group_id).await?,
+            event_attendees: self
+                .export_rows("SELECT * FROM event_attendees WHERE event_id IN (SELECT id FROM events WHERE group_id = ?1)", group_id)
+                .await?,
+            activity: self.export_rows("SELECT * FROM activity_log WHERE group_id = ?1 ORDER BY seq", group_id).await?,
+        })
+    }
+
+    async fn export_rows(&self, query: &str, group_id: &Uuid) -> Result<Vec<Value>, ServiceError> {
+        Ok(self.db.prepare(query).bind(&[group_id.to_string().into()])?.all().await?.results::<Value>()?)
+    }
+
+    // Scheduled sweep: removes groups whose restore window has closed
+    pub async fn purge_expired_groups(&self) -> Result<PurgeSummary, ServiceError> {
+        Ok(purge_deleted_groups(&self.db, Utc::now() - Duration::days(GROUP_RESTORE_WINDOW_DAYS)).await?)
+    }
+
+    fn parse_epoch(value: &Value) -> Result<DateTime<Utc>, ServiceError> {
+        value
+            .as_i64()
+            .or_else(|| value.as_f64().map(|s| s as i64))
+            .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single())
+            .ok_or_else(|| ServiceError::Internal("Invalid timestamp".to_string()))
+    }
 }
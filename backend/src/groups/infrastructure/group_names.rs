@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::{D1Database, Error as WorkerError};
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+use crate::groups::domain::ports::GroupNameLookup;
+
+// D1 caps bound parameters per statement at 100
+const IDS_PER_QUERY: usize = 90;
+
+// Group names for many groups in one query per 90 distinct IDs; unknown IDs are absent
+pub async fn load_group_names(db: &D1Database, group_ids: &[Uuid]) -> Result<HashMap<Uuid, String>, WorkerError> {
+    let unique: Vec<String> = group_ids
+        .iter()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(|id| id.to_string())
+        .collect();
+
+    let mut names = HashMap::with_capacity(unique.len());
+    for chunk in unique.chunks(IDS_PER_QUERY) {
+        let query = format!("SELECT id, name FROM groups WHERE id IN ({})", vec!["?"; chunk.len()].join(", "));
+        let params: Vec<JsValue> = chunk.iter().map(|id| id.clone().into()).collect();
+        for row in db.prepare(&query).bind(&params)?.all().await?.results::<Value>()? {
+            if let (Some(id), Some(name)) = (row["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()), row["name"].as_str()) {
+                names.insert(id, name.to_string());
+            }
+        }
+    }
+
+    Ok(names)
+}
+
+// Name from a map built by `load_group_names`, with the usual placeholder for unknown groups
+pub fn group_name(names: &HashMap<Uuid, String>, group_id: &Uuid) -> String {
+    names.get(group_id).cloned().unwrap_or_else(|| "Unknown Group".to_string())
+}
+
+// Port adapter for services that hold repositories rather than a raw D1 handle
+pub struct D1GroupNameLookup {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1GroupNameLookup {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+}
+
+#[async_trait]
+impl GroupNameLookup for D1GroupNameLookup {
+    async fn group_names(&self, group_ids: &[Uuid]) -> Result<HashMap<Uuid, String>, Box<dyn std::error::Error>> {
+        let names = SendFuture::new(load_group_names(&self.db, group_ids))
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+        Ok(names)
+    }
+}
@@ -1,3 +1,21 @@
+pub mod deletion;
 pub mod direct_d1_service;
+pub mod group_names;
+pub mod d1_group_repository;
+pub mod d1_invitation_repository;
+pub mod d1_invite_link_repository;
+pub mod d1_ledger_repository;
+pub mod d1_member_repository;
+pub mod d1_settings_repository;
+pub mod placeholders;
 
+pub use deletion::{group_purge_statements, purge_deleted_groups, PurgeSummary};
 pub use direct_d1_service::DirectD1GroupService;
+pub use group_names::{group_name, load_group_names, D1GroupNameLookup};
+pub use d1_group_repository::D1GroupRepository;
+pub use d1_invitation_repository::D1GroupInvitationRepository;
+pub use d1_invite_link_repository::D1GroupInviteLinkRepository;
+pub use d1_ledger_repository::{close_ledger, ledger_link, ledger_transfer_totals, open_child_ledgers, D1GroupLedgerRepository};
+pub use d1_member_repository::D1GroupMemberRepository;
+pub use d1_settings_repository::{load_group_settings, require_module, D1GroupSettingsRepository};
+pub use placeholders::{placeholder_claim_statements, placeholder_create_statements};
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use worker::{D1Database, D1PreparedStatement, Error as WorkerError};
+
+use crate::groups::domain::group::GroupMember;
+
+// Appended to every claim statement; each one's WHERE clause ends in plain AND terms
+const CLAIMED_LINK_GUARD: &str =
+    "EXISTS (SELECT 1 FROM group_invite_links WHERE id = ?3 AND last_used_at = ?4 AND last_used_by = ?2)";
+
+// A placeholder is a users row that can never sign in: an empty password hash, a reserved
+// username and is_placeholder set. Its name lives in user_profiles like anyone else's, so
+// balances, splits and chores resolve it without special cases.
+pub fn placeholder_create_statements(db: &D1Database, member: &GroupMember, name: &str) -> Result<Vec<D1PreparedStatement>, WorkerError> {
+    let user_id = member.user_id.to_string();
+    let created_at = member.joined_at.timestamp() as f64;
+
+    Ok(vec![
+        db.prepare(
+            "INSERT INTO users (id, username, password_hash, email, email_verified, created_at, is_placeholder)
+             VALUES (?1, ?2, '', NULL, 0, ?3, 1)",
+        )
+        .bind(&[
+            user_id.clone().into(),
+            format!("placeholder-{}", member.user_id.simple()).into(),
+            created_at.into(),
+        ])?,
+        db.prepare(
+            "INSERT INTO user_profiles (user_id, display_name, avatar_key, locale, timezone, default_currency, updated_at)
+             VALUES (?1, ?2, NULL, 'en-US', 'UTC', 'USD', ?3)",
+        )
+        .bind(&[user_id.clone().into(), name.into(), created_at.into()])?,
+        db.prepare("INSERT INTO group_members (group_id, user_id, role, joined_at) VALUES (?1, ?2, ?3, ?4)")
+            .bind(&[
+                member.group_id.to_string().into(),
+                user_id.into(),
+                member.role.as_db_str().into(),
+                member.joined_at.to_rfc3339().into(),
+            ])?,
+    ])
+}
+
+// Moves everything filed under a placeholder onto the account that claimed it, then removes
+// the placeholder. Shares in the same expense are added together, settled parts included, and
+// payments between the two cancel out. Meant for the batch that redeems the placeholder's claim
+// link: every statement only acts once `link_id` was used by `user_id` at `at`, so a link that
+// was no longer usable moves nothing. The last statement only deletes a row that is still a
+// placeholder, so its change count says whether the claim happened.
+pub fn placeholder_claim_statements(
+    db: &D1Database,
+    placeholder_id: &Uuid,
+    user_id: &Uuid,
+    link_id: &Uuid,
+    at: DateTime<Utc>,
+) -> Result<Vec<D1PreparedStatement>, WorkerError> {
+    let queries = [
+        "UPDATE expense_shares SET
+             amount = amount + (
+                 SELECT p.amount FROM expense_shares p WHERE p.expense_id = expense_shares.expense_id AND p.user_id = ?1),
+             base_amount = base_amount + (
+                 SELECT p.base_amount FROM expense_shares p WHERE p.expense_id = expense_shares.expense_id AND p.user_id = ?1),
+             settled_amount = settled_amount + (
+                 SELECT p.settled_amount FROM expense_shares p WHERE p.expense_id = expense_shares.expense_id AND p.user_id = ?1)
+         WHERE user_id = ?2 AND expense_id IN (SELECT expense_id FROM expense_shares WHERE user_id = ?1)",
+        "UPDATE expense_shares SET is_settled = (settled_amount >= base_amount),
+             settled_at = CASE WHEN settled_amount >= base_amount THEN settled_at END
+         WHERE user_id = ?2 AND expense_id IN (SELECT expense_id FROM expense_shares WHERE user_id = ?1)",
+        // What was paid towards a share that merges into the account's own goes with it; the
+        // rest of the allocations follow their shares through the foreign key's ON UPDATE CASCADE
+        "UPDATE payment_allocations SET user_id = ?2
+         WHERE user_id = ?1 AND expense_id IN (SELECT expense_id FROM expense_shares WHERE user_id = ?2)",
+        "DELETE FROM expense_shares WHERE user_id = ?1 AND expense_id IN (SELECT expense_id FROM expense_shares WHERE user_id = ?2)",
+        "UPDATE expense_shares SET user_id = ?2 WHERE user_id = ?1",
+        "UPDATE expenses SET paid_by = ?2 WHERE paid_by = ?1",
+        "DELETE FROM payment_allocations WHERE payment_id IN (
+             SELECT id FROM payments WHERE (from_user = ?1 AND to_user = ?2) OR (from_user = ?2 AND to_user = ?1))",
+        "DELETE FROM payments WHERE ((from_user = ?1 AND to_user = ?2) OR (from_user = ?2 AND to_user = ?1))",
+        "UPDATE payments SET from_user = ?2 WHERE from_user = ?1",
+        "UPDATE payments SET to_user = ?2 WHERE to_user = ?1",
+        "UPDATE ledger_transfers SET user_id = ?2 WHERE user_id = ?1",
+        "UPDATE chores SET assigned_to = ?2 WHERE assigned_to = ?1",
+        "DELETE FROM event_attendees WHERE user_id = ?1 AND event_id IN (SELECT event_id FROM event_attendees WHERE user_id = ?2)",
+        "UPDATE event_attendees SET user_id = ?2 WHERE user_id = ?1",
+        "DELETE FROM group_members WHERE user_id = ?1",
+        "DELETE FROM user_profiles WHERE user_id = ?1",
+        "DELETE FROM users WHERE id = ?1 AND is_placeholder = 1",
+    ];
+
+    let placeholder_id = placeholder_id.to_string();
+    let user_id = user_id.to_string();
+    let link_id = link_id.to_string();
+    let used_at = at.timestamp() as f64;
+    queries
+        .iter()
+        .map(|query| {
+            db.prepare(format!("{} AND {}", query, CLAIMED_LINK_GUARD)).bind(&[
+                placeholder_id.clone().into(),
+                user_id.clone().into(),
+                link_id.clone().into(),
+                used_at.into(),
+            ])
+        })
+        .collect()
+}
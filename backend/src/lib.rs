@@ -2,7 +2,15 @@
 use uuid::Uuid;
 use worker::*;
 use serde::{Deserialize, Serialize};
-use chrono::Datelike;
+use crate::auth::domain::user::TokenError;
+use crate::error::ServiceError;
+use crate::auth::infrastructure::web::{authenticate, unauthorized_response, AuthenticatedUser};
+use crate::auth::infrastructure::web::{client_ip, rate_limit_unavailable_response, too_many_requests_response};
+use crate::auth::domain::rate_limit::RateLimitPolicy;
+use crate::auth::infrastructure::web::routes::device_info_from_request;
+
+pub mod config;
+pub mod error;
 
 // Domain modules with proper hexagonal architecture
 pub mod auth;
@@ -10,19 +18,35 @@ pub mod groups;
 pub mod expenses;
 pub mod chores;
 pub mod calendar;
+pub mod profiles;
+pub mod activity;
+
+#[cfg(test)]
+mod test_support;
 
 // Simple endpoint handlers that create services on-demand
-async fn handle_register_endpoint(mut req: Request, _ctx: RouteContext<()>) -> Result<Response> {
-    use std::sync::Arc;
-    use crate::auth::infrastructure::{WasmPasswordService, WasmTokenService};
-    use crate::auth::application::use_cases::AuthService;
+async fn handle_register_endpoint(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
     use crate::auth::domain::user::UserRegistration;
+    use crate::auth::domain::ports::UserRepositoryError;
     use serde::{Deserialize, Serialize};
 
     #[derive(Deserialize)]
     struct AuthPayload {
         username: String,
         password: String,
+        // Invite link, link token or join code to redeem once the account exists
+        #[serde(default)]
+        invite: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    struct RegisterResponse {
+        #[serde(flatten)]
+        user: crate::auth::domain::user::UserInfo,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        joined_group: Option<crate::groups::domain::group::GroupInfo>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        invite_error: Option<String>,
     }
 
     #[derive(Serialize)]
@@ -30,11 +54,13 @@ async fn handle_register_endpoint(mut req: Request, _ctx: RouteContext<()>) -> R
         error: String,
     }
 
-    // Create auth service with persistent memory storage
-    let user_repository = Arc::new(crate::auth::infrastructure::persistence::persistent_memory_repository::PersistentMemoryUserRepository::new());
-    let password_service = Arc::new(WasmPasswordService::new());
-    let token_service = Arc::new(WasmTokenService::new("demo-secret".to_string()));
-    let auth_service = AuthService::new(user_repository, password_service, token_service);
+    let ip = client_ip(&req);
+    if let Some(limited) = enforce_rate_limits(&ctx.env, &[(&RateLimitPolicy::REGISTER_IP, ip.as_str())]).await? {
+        return Ok(limited);
+    }
+
+    // Create auth service backed by D1
+    let auth_service = create_auth_service(&ctx.env)?;
 
     // Parse request
     let payload: AuthPayload = match req.json().await {
@@ -43,6 +69,7 @@ async fn handle_register_endpoint(mut req: Request, _ctx: RouteContext<()>) -> R
             error: "Invalid JSON".to_string(),
         }),
     };
+    let invite = payload.invite.filter(|invite| !invite.trim().is_empty());
 
     let registration = UserRegistration {
         username: payload.username,
@@ -51,27 +78,57 @@ async fn handle_register_endpoint(mut req: Request, _ctx: RouteContext<()>) -> R
 
     // Register user
     match auth_service.register(registration).await {
-        Ok(user_info) => Response::from_json(&user_info),
+        Ok(user_info) => {
+            // The account stands even if the invite turns out to be bad or the join limit is
+            // hit; the client can retry it through /api/groups/join
+            let (joined_group, invite_error) = match invite {
+                Some(invite) => match Uuid::parse_str(&user_info.id) {
+                    Ok(user_id) => {
+                        let join_limit = [(&RateLimitPolicy::JOIN_GROUP_IP, ip.as_str())];
+                        if enforce_rate_limits(&ctx.env, &join_limit).await?.is_some() {
+                            (None, Some("Too many attempts to join a group, please try again later".to_string()))
+                        } else {
+                            match create_group_service(&ctx.env)?.redeem_invite(&user_id, &invite).await {
+                                Ok(group) => (Some(group), None),
+                                Err(e) => (None, Some(e.to_string())),
+                            }
+                        }
+                    }
+                    Err(_) => (None, Some("Invalid user ID".to_string())),
+                },
+                None => (None, None),
+            };
+
+            Response::from_json(&RegisterResponse {
+                user: user_info,
+                joined_group,
+                invite_error,
+            })
+        }
         Err(e) => {
+            let status = match e.downcast_ref::<UserRepositoryError>() {
+                Some(UserRepositoryError::UsernameTaken) | Some(UserRepositoryError::EmailTaken) => 409,
+                None => 400,
+            };
             let response = Response::from_json(&ErrorResponse {
                 error: e.to_string(),
             })?;
-            Ok(response.with_status(400))
+            Ok(response.with_status(status))
         }
     }
 }
 
-async fn handle_login_endpoint(mut req: Request, _ctx: RouteContext<()>) -> Result<Response> {
-    use std::sync::Arc;
-    use crate::auth::infrastructure::{WasmPasswordService, WasmTokenService};
-    use crate::auth::application::use_cases::AuthService;
+async fn handle_login_endpoint(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
     use crate::auth::domain::user::UserLogin;
+    use crate::auth::domain::two_factor::LoginOutcome;
     use serde::{Deserialize, Serialize};
 
     #[derive(Deserialize)]
     struct AuthPayload {
         username: String,
         password: String,
+        #[serde(default)]
+        device_name: Option<String>,
     }
 
     #[derive(Serialize)]
@@ -79,11 +136,8 @@ async fn handle_login_endpoint(mut req: Request, _ctx: RouteContext<()>) -> Resu
         error: String,
     }
 
-    // Create auth service with persistent memory storage
-    let user_repository = Arc::new(crate::auth::infrastructure::persistence::persistent_memory_repository::PersistentMemoryUserRepository::new());
-    let password_service = Arc::new(WasmPasswordService::new());
-    let token_service = Arc::new(WasmTokenService::new("demo-secret".to_string()));
-    let auth_service = AuthService::new(user_repository, password_service, token_service);
+    // Create auth service backed by D1
+    let auth_service = create_auth_service(&ctx.env)?;
 
     // Parse request
     let payload: AuthPayload = match req.json().await {
@@ -93,14 +147,33 @@ async fn handle_login_endpoint(mut req: Request, _ctx: RouteContext<()>) -> Resu
         }),
     };
 
+    // Throttle per client address and per targeted account before touching the password
+    let ip = client_ip(&req);
+    let checks = [
+        (&RateLimitPolicy::LOGIN_IP, ip.as_str()),
+        (&RateLimitPolicy::LOGIN_ACCOUNT, payload.username.as_str()),
+    ];
+    if let Some(limited) = enforce_rate_limits(&ctx.env, &checks).await? {
+        return Ok(limited);
+    }
+
+    let device = device_info_from_request(&req, payload.device_name);
+    let username = payload.username.clone();
     let login = UserLogin {
         username: payload.username,
         password: payload.password,
     };
 
-    // Login user
-    match auth_service.login(login).await {
-        Ok(auth_result) => Response::from_json(&auth_result),
+    // Login user; accounts with 2FA get a challenge token instead of tokens
+    match auth_service.login(login, device).await {
+        Ok(outcome) => {
+            // Only a complete sign-in clears the account's failures; with 2FA that happens
+            // once the second factor has been checked
+            if matches!(outcome, LoginOutcome::Authenticated(_)) {
+                reset_rate_limit(&ctx.env, &RateLimitPolicy::LOGIN_ACCOUNT, &username).await?;
+            }
+            Response::from_json(&outcome)
+        }
         Err(e) => {
             let response = Response::from_json(&ErrorResponse {
                 error: e.to_string(),
@@ -110,467 +183,2409 @@ async fn handle_login_endpoint(mut req: Request, _ctx: RouteContext<()>) -> Resu
     }
 }
 
-// Future modules - properly structured following hexagonal architecture
-// pub mod expenses;
-// pub mod groups; 
-// pub mod chores;
-// pub mod calendar;
+async fn handle_two_factor_login(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::auth::domain::ports::TokenService;
+    use crate::auth::domain::two_factor::{TwoFactorLogin, TWO_FACTOR_CHALLENGE_PURPOSE};
+    use serde::Serialize;
 
-#[event(fetch)]
-pub async fn main(req: Request, env: Env, _ctx: worker::Context) -> Result<Response> {
-    console_error_panic_hook::set_once();
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
 
-    let router = Router::new();
-    
-    router
-        .get("/", |_, _| Response::ok("🎉 TwoDo API is running on Cloudflare Workers!"))
-        .get("/health", handle_health)
-        .get("/api/auth/status", handle_auth_status)
-        .post_async("/api/auth/register", handle_register_endpoint)
-        .post_async("/api/auth/login", handle_login_endpoint)
-        .get_async("/api/expenses/balances/:group_id", handle_get_balances)
-        .post_async("/api/expenses", handle_create_expense)
-        .get_async("/api/expenses/:id", handle_get_expense)
-        .put_async("/api/expenses/:id", handle_update_expense)
-        .delete_async("/api/expenses/:id", handle_delete_expense)
-        .get_async("/api/expenses/group/:group_id", handle_get_group_expenses)
-        .post_async("/api/expenses/settle", handle_settle_debt)
-        // Groups APIs
-        .post_async("/api/groups", handle_create_group)
-        .get_async("/api/groups", handle_get_user_groups)
-        .get_async("/api/groups/:id", handle_get_group)
-        .delete_async("/api/groups/:id", handle_delete_group)
-        .get_async("/api/groups/:id/members", handle_get_group_members)
-        .post_async("/api/groups/:id/invite", handle_invite_user)
-        .post_async("/api/groups/:id/leave", handle_leave_group)
-        // Calendar/Events APIs
-        .post_async("/api/events", handle_create_event)
-        .get_async("/api/events/:id", handle_get_event)
-        .delete_async("/api/events/:id", handle_delete_event)
-        .get_async("/api/events/group/:group_id", handle_get_group_events)
-        .get_async("/api/events/group/:group_id/calendar", handle_get_events_by_date)
-        // Chores APIs
-        .post_async("/api/chores", handle_create_chore)
-        .get_async("/api/chores/:id", handle_get_chore)
-        .delete_async("/api/chores/:id", handle_delete_chore)
-        .put_async("/api/chores/:id/status", handle_update_chore_status)
-        .post_async("/api/chores/assign", handle_assign_chore)
-        .get_async("/api/chores/group/:group_id", handle_get_group_chores)
-        .get_async("/api/chores/user/:user_id", handle_get_user_chores)
-        .run(req, env)
-        .await
-}
+    // Six-digit codes are cheap to guess, so the second step shares the login IP budget
+    let ip = client_ip(&req);
+    if let Some(limited) = enforce_rate_limits(&ctx.env, &[(&RateLimitPolicy::LOGIN_IP, ip.as_str())]).await? {
+        return Ok(limited);
+    }
 
-// Types are now defined in the auth domain module
+    let auth_service = create_auth_service(&ctx.env)?;
 
-fn handle_health(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
-    Response::from_json(&serde_json::json!({
-        "status": "healthy",
-        "timestamp": js_sys::Date::now(),
-        "version": "1.0.0",
-        "environment": "production"
-    }))
-}
+    let payload: TwoFactorLogin = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
 
-fn handle_auth_status(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
-    Response::from_json(&serde_json::json!({
-        "status": "✅ Authentication refactored with proper Hexagonal Architecture",
-        "architecture_implemented": {
-            "pattern": "Hexagonal Architecture (Ports & Adapters)",
-            "structure": "domain → application → infrastructure",
-            "separation_of_concerns": "✅ Complete"
-        },
-        "modules_refactored": {
-            "auth/domain/": {
-                "user.rs": "✅ User entities, value objects, and DTOs",
-                "ports.rs": "✅ Repository and service interfaces (traits)"
-            },
-            "auth/application/": {
-                "use_cases.rs": "✅ AuthService business logic"
-            },
-            "auth/infrastructure/": {
-                "crypto/": "✅ WASM-compatible crypto services",
-                "persistence/": "✅ D1 and in-memory repositories", 
-                "web/": "✅ HTTP route handlers"
-            }
-        },
-        "benefits_achieved": [
-            "🏗️ Modular and testable architecture",
-            "🔄 Swappable implementations (D1 ↔ In-memory)",
-            "🎯 Clear separation of concerns",
-            "📦 Independent deployable modules",
-            "🧪 Easy unit testing",
-            "🔧 WASM-compatible design"
-        ],
-        "api_demonstration": {
-            "description": "Real authentication APIs implemented following hexagonal principles",
-            "endpoints": {
-                "/api/auth/register": "POST - User registration with validation",
-                "/api/auth/login": "POST - User login with JWT generation"
-            },
-            "technologies": "Rust + WASM + Cloudflare Workers + D1"
-        },
-        "version": "2.0.0",
-        "live_endpoints": {
-            "/api/auth/register": "✅ Working with in-memory storage",
-            "/api/auth/login": "✅ Working with in-memory storage"
+    // ...and is throttled per targeted account too, so rotating addresses does not help.
+    // The account comes from the signed challenge, which the service checks again.
+    let claims = match create_token_service(&ctx.env)?
+        .validate_scoped_token(&payload.challenge_token, TWO_FACTOR_CHALLENGE_PURPOSE)
+        .await
+    {
+        Ok(claims) => claims,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid or expired challenge".to_string(),
+            })?;
+            return Ok(response.with_status(401));
         }
-    }))
-}
+    };
+    if let Some(limited) = enforce_rate_limits(&ctx.env, &[(&RateLimitPolicy::TWO_FACTOR_ACCOUNT, claims.sub.as_str())]).await? {
+        return Ok(limited);
+    }
 
-async fn handle_get_balances(req: Request, ctx: RouteContext<()>) -> Result<Response> {
-    if let Some(group_id) = ctx.param("group_id") {
-        match Uuid::parse_str(group_id) {
-            Ok(group_uuid) => {
-                let user_id = match get_authenticated_user_id(&req).await {
-                    Ok(id) => id,
-                    Err(_) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": "Authentication required"
-                        }))?;
-                        return Ok(response.with_status(401));
-                    }
-                };
-                
-                let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
-                    Ok(service) => service,
-                    Err(e) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": format!("Service error: {}", e)
-                        }))?;
-                        return Ok(response.with_status(500));
-                    }
-                };
-                
-                match expense_service.get_group_balances(&group_uuid, &user_id).await {
-                    Ok(balances) => Response::from_json(&balances),
-                    Err(e) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": e.to_string()
-                        }))?;
-                        Ok(response.with_status(400))
-                    }
-                }
-            }
-            Err(_) => {
-                let response = Response::from_json(&serde_json::json!({
-                    "error": "Invalid group_id format"
-                }))?;
-                Ok(response.with_status(400))
-            }
+    let device = device_info_from_request(&req, payload.device_name);
+    match auth_service.complete_two_factor_login(&payload.challenge_token, &payload.code, device).await {
+        Ok(auth_result) => {
+            reset_rate_limit(&ctx.env, &RateLimitPolicy::LOGIN_ACCOUNT, &auth_result.user.username).await?;
+            reset_rate_limit(&ctx.env, &RateLimitPolicy::TWO_FACTOR_ACCOUNT, &claims.sub).await?;
+            Response::from_json(&auth_result)
+        }
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(401))
         }
-    } else {
-        Response::error("Missing group_id", 400)
     }
 }
 
-async fn handle_create_expense(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
-    use crate::expenses::domain::expense::ExpenseCreation;
-    use serde::{Deserialize, Serialize};
+async fn handle_enroll_two_factor(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
 
-    #[derive(Deserialize)]
-    struct CreateExpenseRequest {
-        #[serde(flatten)]
-        expense: ExpenseCreation,
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let auth_service = create_auth_service(&ctx.env)?;
+    match auth_service.enroll_two_factor(&user_id).await {
+        Ok(enrollment) => Response::from_json(&enrollment),
+        Err(e) => {
+            let status = ServiceError::status_of(e.as_ref(), 400);
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(status))
+        }
     }
+}
+
+async fn handle_confirm_two_factor(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::auth::domain::two_factor::ConfirmTwoFactor;
+    use serde::Serialize;
 
     #[derive(Serialize)]
     struct ErrorResponse {
         error: String,
     }
 
-    let created_by = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let payload: ConfirmTwoFactor = match req.json().await {
+        Ok(p) => p,
         Err(_) => {
             let response = Response::from_json(&ErrorResponse {
-                error: "Authentication required".to_string(),
+                error: "Invalid JSON".to_string(),
             })?;
-            return Ok(response.with_status(401));
+            return Ok(response.with_status(400));
         }
     };
 
-    let payload: CreateExpenseRequest = match req.json().await {
-        Ok(p) => p,
-        Err(_) => return Response::from_json(&ErrorResponse {
-            error: "Invalid JSON".to_string(),
-        }),
-    };
-    
-    let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
-        Ok(service) => service,
+    let auth_service = create_auth_service(&ctx.env)?;
+    match auth_service.confirm_two_factor(&user_id, &payload.code).await {
+        Ok(recovery_codes) => Response::from_json(&recovery_codes),
         Err(e) => {
+            let status = ServiceError::status_of(e.as_ref(), 400);
             let response = Response::from_json(&ErrorResponse {
-                error: format!("Service error: {}", e),
+                error: e.to_string(),
             })?;
-            return Ok(response.with_status(500));
+            Ok(response.with_status(status))
         }
+    }
+}
+
+async fn handle_disable_two_factor(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::auth::domain::two_factor::DisableTwoFactor;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
     };
-    
-    match expense_service.create_expense_from_creation(payload.expense, created_by).await {
-        Ok(expense_info) => Response::from_json(&expense_info),
+
+    let payload: DisableTwoFactor = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let auth_service = create_auth_service(&ctx.env)?;
+    match auth_service.disable_two_factor(&user_id, &payload.password, &payload.code).await {
+        Ok(_) => Response::from_json(&serde_json::json!({
+            "message": "Two-factor authentication disabled"
+        })),
         Err(e) => {
+            let status = ServiceError::status_of(e.as_ref(), 400);
             let response = Response::from_json(&ErrorResponse {
                 error: e.to_string(),
             })?;
-            Ok(response.with_status(400))
+            Ok(response.with_status(status))
         }
     }
 }
 
-async fn handle_get_expense(req: Request, ctx: RouteContext<()>) -> Result<Response> {
-    if let Some(expense_id) = ctx.param("id") {
-        match Uuid::parse_str(expense_id) {
-            Ok(expense_uuid) => {
-                let user_id = match get_authenticated_user_id(&req).await {
-                    Ok(id) => id,
-                    Err(_) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": "Authentication required"
-                        }))?;
-                        return Ok(response.with_status(401));
-                    }
-                };
-                
-                let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
-                    Ok(service) => service,
-                    Err(e) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": format!("Service error: {}", e)
-                        }))?;
-                        return Ok(response.with_status(500));
-                    }
-                };
-                
-                match expense_service.get_expense(&expense_uuid, &user_id).await {
-                    Ok(Some(expense)) => Response::from_json(&expense),
-                    Ok(None) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": "Expense not found"
-                        }))?;
-                        Ok(response.with_status(404))
-                    }
-                    Err(e) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": e.to_string()
-                        }))?;
-                        Ok(response.with_status(400))
-                    }
-                }
-            }
-            Err(_) => {
-                let response = Response::from_json(&serde_json::json!({
-                    "error": "Invalid expense_id format"
-                }))?;
-                Ok(response.with_status(400))
-            }
+async fn handle_refresh_endpoint(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::auth::domain::session::RefreshRequest;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let auth_service = create_auth_service(&ctx.env)?;
+
+    let payload: RefreshRequest = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    match auth_service.refresh(&payload.refresh_token).await {
+        Ok(auth_result) => Response::from_json(&auth_result),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(401))
         }
-    } else {
-        Response::error("Missing expense_id", 400)
     }
 }
 
-async fn handle_update_expense(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
-    use serde::{Deserialize, Serialize};
+async fn handle_logout_endpoint(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
 
-    #[derive(Deserialize)]
-    struct UpdateExpenseRequest {
-        description: Option<String>,
-        amount: Option<f64>,
-        updated_by: Option<Uuid>,
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user = match get_authenticated_user(&req, &ctx.env).await? {
+        Ok(user) => user,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let auth_service = create_auth_service(&ctx.env)?;
+    match auth_service.logout(&user.session_id).await {
+        Ok(_) => Response::from_json(&serde_json::json!({
+            "message": "Logged out successfully"
+        })),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(500))
+        }
     }
+}
+
+async fn handle_list_sessions(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
 
     #[derive(Serialize)]
     struct ErrorResponse {
         error: String,
     }
 
-    if let Some(expense_id) = ctx.param("id") {
-        match Uuid::parse_str(expense_id) {
-            Ok(_expense_uuid) => {
-                let _payload: UpdateExpenseRequest = match req.json().await {
-                    Ok(p) => p,
-                    Err(_) => return Response::from_json(&ErrorResponse {
-                        error: "Invalid JSON".to_string(),
-                    }),
-                };
+    let user = match get_authenticated_user(&req, &ctx.env).await? {
+        Ok(user) => user,
+        Err(e) => return unauthorized_response(&e),
+    };
 
-                // For now, return not implemented since ExpenseService doesn't have update_expense method
-                let response = Response::from_json(&serde_json::json!({
-                    "error": "Update expense not yet implemented"
-                }))?;
-                Ok(response.with_status(501))
-            }
-            Err(_) => {
-                let response = Response::from_json(&serde_json::json!({
-                    "error": "Invalid expense_id format"
-                }))?;
-                Ok(response.with_status(400))
-            }
+    let auth_service = create_auth_service(&ctx.env)?;
+    match auth_service.list_sessions(&user.user_id, Some(&user.session_id)).await {
+        Ok(sessions) => Response::from_json(&sessions),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(500))
         }
-    } else {
-        Response::error("Missing expense_id", 400)
     }
 }
 
-async fn handle_delete_expense(req: Request, ctx: RouteContext<()>) -> Result<Response> {
-    if let Some(expense_id) = ctx.param("id") {
-        match Uuid::parse_str(expense_id) {
-            Ok(expense_uuid) => {
-                let user_id = match get_authenticated_user_id(&req).await {
-                    Ok(id) => id,
-                    Err(_) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": "Authentication required"
-                        }))?;
-                        return Ok(response.with_status(401));
-                    }
-                };
-                
-                let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
-                    Ok(service) => service,
-                    Err(e) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": format!("Service error: {}", e)
-                        }))?;
-                        return Ok(response.with_status(500));
-                    }
-                };
-                
-                                 match expense_service.delete_expense(&expense_uuid).await {
+async fn handle_revoke_session(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    if let Some(session_id) = ctx.param("id") {
+        match Uuid::parse_str(session_id) {
+            Ok(session_uuid) => {
+                let auth_service = create_auth_service(&ctx.env)?;
+                match auth_service.revoke_session(&user_id, &session_uuid).await {
                     Ok(_) => Response::from_json(&serde_json::json!({
-                        "message": "Expense deleted successfully"
+                        "message": "Session revoked successfully"
                     })),
                     Err(e) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": e.to_string()
-                        }))?;
-                        Ok(response.with_status(400))
+                        let status = ServiceError::status_of(e.as_ref(), 500);
+                        let response = Response::from_json(&ErrorResponse {
+                            error: e.to_string(),
+                        })?;
+                        Ok(response.with_status(status))
                     }
                 }
             }
             Err(_) => {
-                let response = Response::from_json(&serde_json::json!({
-                    "error": "Invalid expense_id format"
-                }))?;
+                let response = Response::from_json(&ErrorResponse {
+                    error: "Invalid session ID".to_string(),
+                })?;
                 Ok(response.with_status(400))
             }
         }
     } else {
-        Response::error("Missing expense_id", 400)
+        let response = Response::from_json(&ErrorResponse {
+            error: "Session ID is required".to_string(),
+        })?;
+        Ok(response.with_status(400))
     }
 }
 
-async fn handle_get_group_expenses(req: Request, ctx: RouteContext<()>) -> Result<Response> {
-    if let Some(group_id) = ctx.param("group_id") {
-        match Uuid::parse_str(group_id) {
-            Ok(group_uuid) => {
-                let user_id = match get_authenticated_user_id(&req).await {
-                    Ok(id) => id,
-                    Err(_) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": "Authentication required"
-                        }))?;
-                        return Ok(response.with_status(401));
-                    }
-                };
-                
-                let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
-                    Ok(service) => service,
-                    Err(e) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": format!("Service error: {}", e)
-                        }))?;
-                        return Ok(response.with_status(500));
-                    }
-                };
-                
-                                 match expense_service.get_group_expenses_with_pagination(&group_uuid, &user_id, None, None).await {
-                    Ok(expenses) => Response::from_json(&expenses),
-                    Err(e) => {
-                        let response = Response::from_json(&serde_json::json!({
-                            "error": e.to_string()
-                        }))?;
-                        Ok(response.with_status(400))
-                    }
-                }
-            }
-            Err(_) => {
-                let response = Response::from_json(&serde_json::json!({
-                    "error": "Invalid group_id format"
-                }))?;
-                Ok(response.with_status(400))
-            }
+async fn handle_revoke_all_sessions(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let auth_service = create_auth_service(&ctx.env)?;
+    match auth_service.revoke_all_sessions(&user_id).await {
+        Ok(_) => Response::from_json(&serde_json::json!({
+            "message": "All sessions revoked successfully"
+        })),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(500))
+        }
+    }
+}
+
+async fn handle_set_email(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::auth::domain::account_token::SetEmailRequest;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let payload: SetEmailRequest = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    if let Some(response) = mail_unavailable_response(&ctx.env)? {
+        return Ok(response);
+    }
+
+    let recovery_service = create_account_recovery_service(&ctx.env)?;
+    match recovery_service.set_email(&user_id, &payload.email).await {
+        Ok(user_info) => Response::from_json(&user_info),
+        Err(e) => {
+            let status = ServiceError::status_of(e.as_ref(), 400);
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_resend_verification(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    if let Some(response) = mail_unavailable_response(&ctx.env)? {
+        return Ok(response);
+    }
+
+    let recovery_service = create_account_recovery_service(&ctx.env)?;
+    match recovery_service.resend_verification(&user_id).await {
+        Ok(_) => Response::from_json(&serde_json::json!({
+            "message": "Verification email sent"
+        })),
+        Err(e) => {
+            let status = ServiceError::status_of(e.as_ref(), 400);
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_verify_email(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::auth::domain::account_token::VerifyEmailRequest;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let payload: VerifyEmailRequest = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let recovery_service = create_account_recovery_service(&ctx.env)?;
+    match recovery_service.verify_email(&payload.token).await {
+        Ok(user_info) => Response::from_json(&user_info),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(400))
+        }
+    }
+}
+
+async fn handle_forgot_password(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::auth::domain::account_token::ForgotPasswordRequest;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let payload: ForgotPasswordRequest = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    if let Some(response) = mail_unavailable_response(&ctx.env)? {
+        return Ok(response);
+    }
+
+    // Same answer whether or not the address belongs to an account
+    let recovery_service = create_account_recovery_service(&ctx.env)?;
+    if let Err(e) = recovery_service.request_password_reset(&payload.email).await {
+        console_log!("Password reset request failed: {}", e);
+    }
+
+    Response::from_json(&serde_json::json!({
+        "message": "If that email belongs to a verified account, a reset link is on its way"
+    }))
+}
+
+async fn handle_reset_password(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::auth::domain::account_token::ResetPasswordRequest;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let payload: ResetPasswordRequest = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let recovery_service = create_account_recovery_service(&ctx.env)?;
+    match recovery_service.reset_password(&payload.token, &payload.new_password).await {
+        Ok(_) => Response::from_json(&serde_json::json!({
+            "message": "Password updated. Please sign in again."
+        })),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(400))
+        }
+    }
+}
+
+async fn handle_delete_account(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::auth::domain::account::DeleteAccountRequest;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let payload: DeleteAccountRequest = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    // The profile row goes with the account; its avatar object has to be removed separately
+    let profile_service = create_profile_service(&ctx.env)?;
+    let avatar_key = profile_service.avatar_key(&user_id).await.ok().flatten();
+
+    let account_service = create_account_service(&ctx.env)?;
+    match account_service.delete_account(&user_id, payload).await {
+        Ok(summary) => {
+            if let Some(key) = avatar_key {
+                if let Err(e) = profile_service.discard_avatar(&key).await {
+                    console_log!("Failed to delete avatar {}: {}", key, e);
+                }
+            }
+            Response::from_json(&summary)
+        }
+        Err(e) => {
+            let message = e.to_string();
+            let status = ServiceError::status_of(e.as_ref(), 500);
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_export_account(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let account_service = create_account_service(&ctx.env)?;
+    match account_service.export_account(&user_id).await {
+        Ok(export) => {
+            let mut response = Response::from_json(&export)?;
+            response.headers_mut().set(
+                "Content-Disposition",
+                &format!("attachment; filename=\"twodo-export-{}.json\"", export.exported_at.format("%Y%m%d")),
+            )?;
+            Ok(response)
+        }
+        Err(e) => {
+            let status = ServiceError::status_of(e.as_ref(), 500);
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_get_profile(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let profile_service = create_profile_service(&ctx.env)?;
+    match profile_service.get_profile(&user_id).await {
+        Ok(profile) => Response::from_json(&profile),
+        Err(e) => {
+            let status = ServiceError::status_of(e.as_ref(), 500);
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_update_profile(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::profiles::domain::profile::ProfileUpdate;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let payload: ProfileUpdate = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let profile_service = create_profile_service(&ctx.env)?;
+    match profile_service.update_profile(&user_id, payload).await {
+        Ok(profile) => Response::from_json(&profile),
+        Err(e) => {
+            let message = e.to_string();
+            let status = ServiceError::status_of(e.as_ref(), 500);
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+// The body is the raw image; its Content-Type header says which format
+async fn handle_upload_avatar(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let content_type = req.headers().get("Content-Type")?.unwrap_or_default();
+    let bytes = req.bytes().await?;
+
+    let profile_service = create_profile_service(&ctx.env)?;
+    match profile_service.upload_avatar(&user_id, &content_type, bytes).await {
+        Ok(profile) => Response::from_json(&profile),
+        Err(e) => {
+            let message = e.to_string();
+            let status = ServiceError::status_of(e.as_ref(), 500);
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_remove_avatar(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let profile_service = create_profile_service(&ctx.env)?;
+    match profile_service.remove_avatar(&user_id).await {
+        Ok(profile) => Response::from_json(&profile),
+        Err(e) => {
+            let status = ServiceError::status_of(e.as_ref(), 500);
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_get_avatar(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    if let Err(e) = get_authenticated_user_id(&req, &ctx.env).await? {
+        return unauthorized_response(&e);
+    }
+
+    let user_id = match ctx.param("user_id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid user ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let profile_service = create_profile_service(&ctx.env)?;
+    match profile_service.get_avatar(&user_id).await {
+        Ok(Some(avatar)) => {
+            let mut response = Response::from_bytes(avatar.bytes)?;
+            response.headers_mut().set("Content-Type", &avatar.content_type)?;
+            // Keys change on every upload, so a stale copy only lives as long as this
+            response.headers_mut().set("Cache-Control", "private, max-age=300")?;
+            Ok(response)
+        }
+        Ok(None) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Avatar not found".to_string(),
+            })?;
+            Ok(response.with_status(404))
+        }
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(500))
+        }
+    }
+}
+
+// Future modules - properly structured following hexagonal architecture
+// pub mod expenses;
+// pub mod groups; 
+// pub mod chores;
+// pub mod calendar;
+
+#[event(fetch)]
+pub async fn main(req: Request, env: Env, _ctx: worker::Context) -> Result<Response> {
+    console_error_panic_hook::set_once();
+
+    let router = Router::new();
+    
+    router
+        .get("/", |_, _| Response::ok("🎉 TwoDo API is running on Cloudflare Workers!"))
+        .get("/health", handle_health)
+        .get("/api/auth/status", handle_auth_status)
+        .post_async("/api/auth/register", handle_register_endpoint)
+        .post_async("/api/auth/login", handle_login_endpoint)
+        .post_async("/api/auth/login/2fa", handle_two_factor_login)
+        .post_async("/api/auth/2fa/enroll", handle_enroll_two_factor)
+        .post_async("/api/auth/2fa/confirm", handle_confirm_two_factor)
+        .post_async("/api/auth/2fa/disable", handle_disable_two_factor)
+        .post_async("/api/auth/refresh", handle_refresh_endpoint)
+        .post_async("/api/auth/logout", handle_logout_endpoint)
+        .get_async("/api/auth/sessions", handle_list_sessions)
+        .delete_async("/api/auth/sessions", handle_revoke_all_sessions)
+        .delete_async("/api/auth/sessions/:id", handle_revoke_session)
+        .put_async("/api/auth/email", handle_set_email)
+        .post_async("/api/auth/email/resend", handle_resend_verification)
+        .post_async("/api/auth/email/verify", handle_verify_email)
+        .post_async("/api/auth/password/forgot", handle_forgot_password)
+        .post_async("/api/auth/password/reset", handle_reset_password)
+        .delete_async("/api/auth/me", handle_delete_account)
+        .get_async("/api/auth/me/export", handle_export_account)
+        .get_async("/api/profile", handle_get_profile)
+        .patch_async("/api/profile", handle_update_profile)
+        .put_async("/api/profile/avatar", handle_upload_avatar)
+        .delete_async("/api/profile/avatar", handle_remove_avatar)
+        .get_async("/api/profile/:user_id/avatar", handle_get_avatar)
+        .get_async("/api/expenses/balances/:group_id", handle_get_balances)
+        .post_async("/api/expenses", handle_create_expense)
+        .get_async("/api/expenses/:id", handle_get_expense)
+        .put_async("/api/expenses/:id", handle_update_expense)
+        .get_async("/api/expenses/:id/revisions", handle_get_expense_revisions)
+        .delete_async("/api/expenses/:id", handle_delete_expense)
+        .get_async("/api/expenses/group/:group_id", handle_get_group_expenses)
+        .get_async("/api/expenses/group/:group_id/settle-up", handle_get_settle_up)
+        .get_async("/api/expenses/group/:group_id/unsettled", handle_get_unsettled_shares)
+        .post_async("/api/expenses/settle", handle_settle_debt)
+        .delete_async("/api/expenses/settlements/:id", handle_undo_settlement)
+        // Groups APIs
+        .post_async("/api/groups", handle_create_group)
+        .get_async("/api/groups", handle_get_user_groups)
+        .get_async("/api/groups/:id", handle_get_group)
+        .delete_async("/api/groups/:id", handle_delete_group)
+        .get_async("/api/groups/deleted", handle_get_deleted_groups)
+        .post_async("/api/groups/:id/restore", handle_restore_group)
+        .post_async("/api/groups/:id/archive", handle_archive_group)
+        .post_async("/api/groups/:id/unarchive", handle_unarchive_group)
+        .post_async("/api/groups/:id/close-ledger", handle_close_ledger)
+        .get_async("/api/groups/:id/export", handle_export_group)
+        .get_async("/api/groups/:id/members", handle_get_group_members)
+        .post_async("/api/groups/:id/placeholders", handle_add_placeholder)
+        .post_async("/api/groups/:id/invite", handle_invite_user)
+        .get_async("/api/groups/:id/invitations", handle_get_group_invitations)
+        .post_async("/api/groups/:id/invite-links", handle_create_invite_link)
+        .get_async("/api/groups/:id/invite-links", handle_get_invite_links)
+        .delete_async("/api/groups/:id/invite-links/:link_id", handle_revoke_invite_link)
+        .post_async("/api/groups/join", handle_join_group)
+        .get_async("/api/invitations", handle_get_my_invitations)
+        .post_async("/api/invitations/:id/accept", handle_accept_invitation)
+        .post_async("/api/invitations/:id/decline", handle_decline_invitation)
+        .delete_async("/api/invitations/:id", handle_revoke_invitation)
+        .post_async("/api/groups/:id/leave", handle_leave_group)
+        .patch_async("/api/groups/:id", handle_update_group)
+        .patch_async("/api/groups/:id/members/:user_id", handle_change_member_role)
+        .post_async("/api/groups/:id/transfer-ownership", handle_transfer_ownership)
+        .get_async("/api/groups/:id/settings", handle_get_group_settings)
+        .patch_async("/api/groups/:id/settings", handle_update_group_settings)
+        .get_async("/api/groups/:id/activity", handle_get_group_activity)
+        .get_async("/api/activity", handle_get_activity_feed)
+        .post_async("/api/activity/read", handle_mark_activity_read)
+        // Calendar/Events APIs
+        .post_async("/api/events", handle_create_event)
+        .get_async("/api/events/:id", handle_get_event)
+        .delete_async("/api/events/:id", handle_delete_event)
+        .post_async("/api/events/:id/respond", handle_respond_to_event)
+        .get_async("/api/events/group/:group_id", handle_get_group_events)
+        .get_async("/api/events/group/:group_id/calendar", handle_get_events_by_date)
+        // Chores APIs
+        .post_async("/api/chores", handle_create_chore)
+        .get_async("/api/chores/:id", handle_get_chore)
+        .delete_async("/api/chores/:id", handle_delete_chore)
+        .put_async("/api/chores/:id/status", handle_update_chore_status)
+        .post_async("/api/chores/assign", handle_assign_chore)
+        .get_async("/api/chores/group/:group_id", handle_get_group_chores)
+        .get_async("/api/chores/user/:user_id", handle_get_user_chores)
+        .run(req, env)
+        .await
+}
+
+// Cron-triggered housekeeping (see [triggers] in wrangler.toml)
+#[event(scheduled)]
+pub async fn scheduled(_event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
+    console_error_panic_hook::set_once();
+
+    match create_invitation_service(&env) {
+        Ok(invitation_service) => match invitation_service.expire_invitations().await {
+            Ok(expired) => console_log!("Expired {} group invitations", expired),
+            Err(e) => console_log!("Invitation expiry sweep failed: {}", e),
+        },
+        Err(e) => console_log!("Invitation expiry sweep failed: {}", e),
+    }
+
+    match create_d1_group_service_with_env(&env) {
+        Ok(group_service) => match group_service.purge_expired_groups().await {
+            Ok(summary) => console_log!("Purged {} deleted groups, {} failed", summary.purged, summary.failed),
+            Err(e) => console_log!("Deleted group purge failed: {}", e),
+        },
+        Err(e) => console_log!("Deleted group purge failed: {}", e),
+    }
+
+    match create_d1_expense_service_with_env(&env) {
+        Ok(expense_service) => match expense_service.catch_up_books().await {
+            Ok(summary) => console_log!("Caught up the books of {} groups, {} failed", summary.caught_up, summary.failed),
+            Err(e) => console_log!("Books catch-up sweep failed: {}", e),
+        },
+        Err(e) => console_log!("Books catch-up sweep failed: {}", e),
+    }
+}
+
+// Types are now defined in the auth domain module
+
+fn handle_health(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+    Response::from_json(&serde_json::json!({
+        "status": "healthy",
+        "timestamp": js_sys::Date::now(),
+        "version": "1.0.0",
+        "environment": "production"
+    }))
+}
+
+fn handle_auth_status(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+    Response::from_json(&serde_json::json!({
+        "status": "✅ Authentication refactored with proper Hexagonal Architecture",
+        "architecture_implemented": {
+            "pattern": "Hexagonal Architecture (Ports & Adapters)",
+            "structure": "domain → application → infrastructure",
+            "separation_of_concerns": "✅ Complete"
+        },
+        "modules_refactored": {
+            "auth/domain/": {
+                "user.rs": "✅ User entities, value objects, and DTOs",
+                "ports.rs": "✅ Repository and service interfaces (traits)"
+            },
+            "auth/application/": {
+                "use_cases.rs": "✅ AuthService business logic"
+            },
+            "auth/infrastructure/": {
+                "crypto/": "✅ WASM-compatible crypto services",
+                "persistence/": "✅ D1 and in-memory repositories", 
+                "web/": "✅ HTTP route handlers"
+            }
+        },
+        "benefits_achieved": [
+            "🏗️ Modular and testable architecture",
+            "🔄 Swappable implementations (D1 ↔ In-memory)",
+            "🎯 Clear separation of concerns",
+            "📦 Independent deployable modules",
+            "🧪 Easy unit testing",
+            "🔧 WASM-compatible design"
+        ],
+        "api_demonstration": {
+            "description": "Real authentication APIs implemented following hexagonal principles",
+            "endpoints": {
+                "/api/auth/register": "POST - User registration with validation",
+                "/api/auth/login": "POST - User login with JWT generation"
+            },
+            "technologies": "Rust + WASM + Cloudflare Workers + D1"
+        },
+        "version": "2.0.0",
+        "live_endpoints": {
+            "/api/auth/register": "✅ Working with in-memory storage",
+            "/api/auth/login": "✅ Working with in-memory storage"
+        }
+    }))
+}
+
+async fn handle_get_balances(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    if let Some(group_id) = ctx.param("group_id") {
+        match Uuid::parse_str(group_id) {
+            Ok(group_uuid) => {
+                let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+                    Ok(id) => id,
+                    Err(e) => return unauthorized_response(&e),
+                };
+                
+                let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
+                    Ok(service) => service,
+                    Err(e) => {
+                        let response = Response::from_json(&serde_json::json!({
+                            "error": format!("Service error: {}", e)
+                        }))?;
+                        return Ok(response.with_status(500));
+                    }
+                };
+                
+                // ?include_children=true adds the balances of the group's open ledgers;
+                // ?by_currency=true adds the unconverted balances of each currency
+                let url = req.url()?;
+                let flag = |name: &str| url.query_pairs().any(|(key, value)| key == name && value == "true");
+                let include_children = flag("include_children");
+                let by_currency = flag("by_currency");
+
+                match expense_service.get_group_balances(&group_uuid, &user_id, include_children, by_currency).await {
+                    Ok(balances) => Response::from_json(&balances),
+                    Err(e) => {
+                        let response = Response::from_json(&serde_json::json!({
+                            "error": e.to_string()
+                        }))?;
+                        Ok(response.with_status(e.status()))
+                    }
+                }
+            }
+            Err(_) => {
+                let response = Response::from_json(&serde_json::json!({
+                    "error": "Invalid group_id format"
+                }))?;
+                Ok(response.with_status(400))
+            }
+        }
+    } else {
+        Response::error("Missing group_id", 400)
+    }
+}
+
+async fn handle_create_expense(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::expenses::domain::expense::ExpenseCreation;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize)]
+    struct CreateExpenseRequest {
+        #[serde(flatten)]
+        expense: ExpenseCreation,
+    }
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let created_by = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let payload: CreateExpenseRequest = match req.json().await {
+        Ok(p) => p,
+        Err(_) => return Response::from_json(&ErrorResponse {
+            error: "Invalid JSON".to_string(),
+        }),
+    };
+    
+    let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
+        Ok(service) => service,
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: format!("Service error: {}", e),
+            })?;
+            return Ok(response.with_status(500));
+        }
+    };
+    
+    match expense_service.create_expense_from_creation(payload.expense, created_by).await {
+        Ok(expense_info) => Response::from_json(&expense_info),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(e.status()))
+        }
+    }
+}
+
+async fn handle_get_expense(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    if let Some(expense_id) = ctx.param("id") {
+        match Uuid::parse_str(expense_id) {
+            Ok(expense_uuid) => {
+                let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+                    Ok(id) => id,
+                    Err(e) => return unauthorized_response(&e),
+                };
+                
+                let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
+                    Ok(service) => service,
+                    Err(e) => {
+                        let response = Response::from_json(&serde_json::json!({
+                            "error": format!("Service error: {}", e)
+                        }))?;
+                        return Ok(response.with_status(500));
+                    }
+                };
+                
+                match expense_service.get_expense(&expense_uuid, &user_id).await {
+                    Ok(Some(expense)) => Response::from_json(&expense),
+                    Ok(None) => {
+                        let response = Response::from_json(&serde_json::json!({
+                            "error": "Expense not found"
+                        }))?;
+                        Ok(response.with_status(404))
+                    }
+                    Err(e) => {
+                        let response = Response::from_json(&serde_json::json!({
+                            "error": e.to_string()
+                        }))?;
+                        Ok(response.with_status(e.status()))
+                    }
+                }
+            }
+            Err(_) => {
+                let response = Response::from_json(&serde_json::json!({
+                    "error": "Invalid expense_id format"
+                }))?;
+                Ok(response.with_status(400))
+            }
+        }
+    } else {
+        Response::error("Missing expense_id", 400)
+    }
+}
+
+async fn handle_update_expense(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::expenses::domain::expense::ExpenseUpdate;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let expense_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid expense_id format".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let payload: ExpenseUpdate = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let expense_service = create_d1_expense_service_with_env(&ctx.env)?;
+    // Editing an expense payments already paid off in part is a 409 until confirm_settled is set
+    match expense_service.update_expense(&expense_id, payload, &user_id).await {
+        Ok(Some(expense)) => Response::from_json(&expense),
+        Ok(None) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Expense not found".to_string(),
+            })?;
+            Ok(response.with_status(404))
+        }
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(e.status()))
+        }
+    }
+}
+
+async fn handle_get_expense_revisions(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let expense_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid expense_id format".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let expense_service = create_d1_expense_service_with_env(&ctx.env)?;
+    match expense_service.get_expense_revisions(&expense_id, &user_id).await {
+        Ok(revisions) => Response::from_json(&revisions),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(e.status()))
+        }
+    }
+}
+
+async fn handle_delete_expense(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    if let Some(expense_id) = ctx.param("id") {
+        match Uuid::parse_str(expense_id) {
+            Ok(expense_uuid) => {
+                let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+                    Ok(id) => id,
+                    Err(e) => return unauthorized_response(&e),
+                };
+                
+                let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
+                    Ok(service) => service,
+                    Err(e) => {
+                        let response = Response::from_json(&serde_json::json!({
+                            "error": format!("Service error: {}", e)
+                        }))?;
+                        return Ok(response.with_status(500));
+                    }
+                };
+                
+                                 match expense_service.delete_expense(&expense_uuid, &user_id).await {
+                    Ok(_) => Response::from_json(&serde_json::json!({
+                        "message": "Expense deleted successfully"
+                    })),
+                    Err(e) => {
+                        let response = Response::from_json(&serde_json::json!({
+                            "error": e.to_string()
+                        }))?;
+                        Ok(response.with_status(e.status()))
+                    }
+                }
+            }
+            Err(_) => {
+                let response = Response::from_json(&serde_json::json!({
+                    "error": "Invalid expense_id format"
+                }))?;
+                Ok(response.with_status(400))
+            }
+        }
+    } else {
+        Response::error("Missing expense_id", 400)
+    }
+}
+
+async fn handle_get_group_expenses(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    if let Some(group_id) = ctx.param("group_id") {
+        match Uuid::parse_str(group_id) {
+            Ok(group_uuid) => {
+                let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+                    Ok(id) => id,
+                    Err(e) => return unauthorized_response(&e),
+                };
+                
+                let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
+                    Ok(service) => service,
+                    Err(e) => {
+                        let response = Response::from_json(&serde_json::json!({
+                            "error": format!("Service error: {}", e)
+                        }))?;
+                        return Ok(response.with_status(500));
+                    }
+                };
+                
+                                 match expense_service.get_group_expenses_with_pagination(&group_uuid, &user_id, None, None).await {
+                    Ok(expenses) => Response::from_json(&expenses),
+                    Err(e) => {
+                        let response = Response::from_json(&serde_json::json!({
+                            "error": e.to_string()
+                        }))?;
+                        Ok(response.with_status(e.status()))
+                    }
+                }
+            }
+            Err(_) => {
+                let response = Response::from_json(&serde_json::json!({
+                    "error": "Invalid group_id format"
+                }))?;
+                Ok(response.with_status(400))
+            }
+        }
+    } else {
+        Response::error("Missing group_id", 400)
+    }
+}
+
+async fn handle_get_settle_up(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("group_id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group_id format".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let expense_service = create_d1_expense_service_with_env(&ctx.env)?;
+    match expense_service.get_settle_up(&group_id, &user_id).await {
+        Ok(plan) => Response::from_json(&plan),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(e.status()))
+        }
+    }
+}
+
+async fn handle_settle_debt(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::expenses::domain::expense::SettleDebt;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize)]
+    struct SettleDebtRequest {
+        group_id: Uuid,
+        #[serde(flatten)]
+        settle: SettleDebt,
+    }
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let settled_by = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let payload: SettleDebtRequest = match req.json().await {
+        Ok(p) => p,
+        Err(_) => return Response::from_json(&ErrorResponse {
+            error: "Invalid JSON".to_string(),
+        }),
+    };
+    
+    let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
+        Ok(service) => service,
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: format!("Service error: {}", e),
+            })?;
+            return Ok(response.with_status(500));
+        }
+    };
+    
+    match expense_service.settle_debt(&payload.group_id, payload.settle, settled_by).await {
+        Ok(payment) => Response::from_json(&serde_json::json!({
+            "message": "Debt settled successfully",
+            "payment": payment
+        })),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(e.status()))
+        }
+    }
+}
+
+async fn handle_get_unsettled_shares(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("group_id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group_id format".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    // ?user_id=... keeps only the shares that person owes or is owed
+    let involving = match req.url()?.query_pairs().find(|(key, _)| key == "user_id") {
+        Some((_, value)) => match Uuid::parse_str(&value) {
+            Ok(id) => Some(id),
+            Err(_) => {
+                let response = Response::from_json(&ErrorResponse {
+                    error: "Invalid user_id format".to_string(),
+                })?;
+                return Ok(response.with_status(400));
+            }
+        },
+        None => None,
+    };
+
+    let expense_service = create_d1_expense_service_with_env(&ctx.env)?;
+    match expense_service.get_unsettled_shares(&group_id, &user_id, involving.as_ref()).await {
+        Ok(shares) => Response::from_json(&shares),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(e.status()))
+        }
+    }
+}
+
+async fn handle_undo_settlement(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let payment_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid payment ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let expense_service = create_d1_expense_service_with_env(&ctx.env)?;
+    match expense_service.undo_settlement(&payment_id, &user_id).await {
+        Ok(()) => Response::from_json(&serde_json::json!({
+            "message": "Settlement undone"
+        })),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(e.status()))
+        }
+    }
+}
+
+// Groups API handlers
+async fn handle_create_group(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::groups::domain::group::GroupCreation;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize)]
+    struct CreateGroupRequest {
+        name: String,
+        description: Option<String>,
+        #[serde(default)]
+        parent_group_id: Option<Uuid>,
+    }
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let created_by = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let payload: CreateGroupRequest = match req.json().await {
+        Ok(p) => p,
+        Err(_) => return Response::from_json(&ErrorResponse {
+            error: "Invalid JSON".to_string(),
+        }),
+    };
+
+    let group_service = match create_d1_group_service_with_env(&ctx.env) {
+        Ok(service) => service,
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: format!("Service error: {}", e),
+            })?;
+            return Ok(response.with_status(500));
+        }
+    };
+
+    let creation = GroupCreation {
+        name: payload.name,
+        description: payload.description,
+        created_by,
+        parent_group_id: payload.parent_group_id,
+    };
+
+    match group_service.create_group_from_creation(creation, created_by).await {
+        Ok(group_info) => Response::from_json(&group_info),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(e.status()))
+        }
+    }
+}
+
+async fn handle_get_user_groups(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_service = match create_d1_group_service_with_env(&ctx.env) {
+        Ok(service) => service,
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: format!("Service error: {}", e),
+            })?;
+            return Ok(response.with_status(500));
+        }
+    };
+
+    // Archived groups only show up with ?include_archived=true
+    let include_archived = req
+        .url()?
+        .query_pairs()
+        .any(|(key, value)| key == "include_archived" && value == "true");
+
+    match group_service.get_groups_for_user(&user_id, include_archived).await {
+        Ok(groups) => Response::from_json(&groups),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(e.status()))
+        }
+    }
+}
+
+async fn handle_get_group(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    if let Some(group_id) = ctx.param("id") {
+        match Uuid::parse_str(group_id) {
+            Ok(group_uuid) => {
+                let group_service = match create_d1_group_service_with_env(&ctx.env) {
+                    Ok(service) => service,
+                    Err(e) => {
+                        let response = Response::from_json(&ErrorResponse {
+                            error: format!("Service error: {}", e),
+                        })?;
+                        return Ok(response.with_status(500));
+                    }
+                };
+
+                match group_service.get_group_by_id(&group_uuid, &user_id).await {
+                    Ok(Some(group)) => Response::from_json(&group),
+                    Ok(None) => {
+                        let response = Response::from_json(&ErrorResponse {
+                            error: "Group not found".to_string(),
+                        })?;
+                        Ok(response.with_status(404))
+                    }
+                    Err(e) => {
+                        let response = Response::from_json(&ErrorResponse {
+                            error: e.to_string(),
+                        })?;
+                        Ok(response.with_status(e.status()))
+                    }
+                }
+            }
+            Err(_) => {
+                let response = Response::from_json(&ErrorResponse {
+                    error: "Invalid group ID".to_string(),
+                })?;
+                Ok(response.with_status(400))
+            }
+        }
+    } else {
+        let response = Response::from_json(&ErrorResponse {
+            error: "Group ID is required".to_string(),
+        })?;
+        Ok(response.with_status(400))
+    }
+}
+
+async fn handle_get_group_members(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    if let Some(group_id) = ctx.param("id") {
+        match Uuid::parse_str(group_id) {
+            Ok(group_uuid) => {
+                let group_service = match create_d1_group_service_with_env(&ctx.env) {
+                    Ok(service) => service,
+                    Err(e) => {
+                        let response = Response::from_json(&ErrorResponse {
+                            error: format!("Service error: {}", e),
+                        })?;
+                        return Ok(response.with_status(500));
+                    }
+                };
+
+                match group_service.get_group_members(&group_uuid, &user_id).await {
+                    Ok(members) => Response::from_json(&members),
+                    Err(e) => {
+                        let response = Response::from_json(&ErrorResponse {
+                            error: e.to_string(),
+                        })?;
+                        Ok(response.with_status(e.status()))
+                    }
+                }
+            }
+            Err(_) => {
+                let response = Response::from_json(&ErrorResponse {
+                    error: "Invalid group ID".to_string(),
+                })?;
+                Ok(response.with_status(400))
+            }
+        }
+    } else {
+        let response = Response::from_json(&ErrorResponse {
+            error: "Group ID is required".to_string(),
+        })?;
+        Ok(response.with_status(400))
+    }
+}
+
+async fn handle_invite_user(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::groups::domain::group::InviteUser;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize)]
+    struct InviteRequest {
+        user_id: Uuid,
+    }
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let invited_by = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let inviter_key = invited_by.to_string();
+    if let Some(limited) = enforce_rate_limits(&ctx.env, &[(&RateLimitPolicy::INVITE_USER, inviter_key.as_str())]).await? {
+        return Ok(limited);
+    }
+
+    let payload: InviteRequest = match req.json().await {
+        Ok(p) => p,
+        Err(_) => return Response::from_json(&ErrorResponse {
+            error: "Invalid JSON".to_string(),
+        }),
+    };
+
+    if let Some(group_id) = ctx.param("id") {
+        match Uuid::parse_str(group_id) {
+            Ok(group_uuid) => {
+                let invitation_service = create_invitation_service(&ctx.env)?;
+                let invite = InviteUser {
+                    user_id: payload.user_id,
+                };
+
+                match invitation_service.invite_user(&group_uuid, &invited_by, invite).await {
+                    Ok(invitation) => Ok(Response::from_json(&invitation)?.with_status(201)),
+                    Err(e) => {
+                        let message = e.to_string();
+                        let response = Response::from_json(&ErrorResponse {
+                            error: message,
+                        })?;
+                        Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
+                    }
+                }
+            }
+            Err(_) => {
+                let response = Response::from_json(&ErrorResponse {
+                    error: "Invalid group ID".to_string(),
+                })?;
+                Ok(response.with_status(400))
+            }
+        }
+    } else {
+        let response = Response::from_json(&ErrorResponse {
+            error: "Group ID is required".to_string(),
+        })?;
+        Ok(response.with_status(400))
+    }
+}
+
+async fn handle_get_group_invitations(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let invitation_service = create_invitation_service(&ctx.env)?;
+    match invitation_service.get_group_invitations(&group_id, &user_id).await {
+        Ok(invitations) => Response::from_json(&invitations),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
+        }
+    }
+}
+
+async fn handle_get_my_invitations(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let invitation_service = create_invitation_service(&ctx.env)?;
+    match invitation_service.get_pending_invitations(&user_id).await {
+        Ok(invitations) => Response::from_json(&invitations),
+        Err(e) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
+        }
+    }
+}
+
+async fn handle_accept_invitation(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    respond_to_invitation(req, ctx, InvitationResponse::Accept).await
+}
+
+async fn handle_decline_invitation(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    respond_to_invitation(req, ctx, InvitationResponse::Decline).await
+}
+
+async fn handle_revoke_invitation(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    respond_to_invitation(req, ctx, InvitationResponse::Revoke).await
+}
+
+enum InvitationResponse {
+    Accept,
+    Decline,
+    Revoke,
+}
+
+// Accept and decline are for the invitee; revoke is for the inviter or a group admin
+async fn respond_to_invitation(req: Request, ctx: RouteContext<()>, action: InvitationResponse) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let invitation_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid invitation ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let invitation_service = create_invitation_service(&ctx.env)?;
+    let result = match action {
+        InvitationResponse::Accept => invitation_service.accept_invitation(&invitation_id, &user_id).await,
+        InvitationResponse::Decline => invitation_service.decline_invitation(&invitation_id, &user_id).await,
+        InvitationResponse::Revoke => invitation_service.revoke_invitation(&invitation_id, &user_id).await,
+    };
+
+    match result {
+        Ok(invitation) => Response::from_json(&invitation),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
+        }
+    }
+}
+
+async fn handle_create_invite_link(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::groups::domain::invite_link::CreateInviteLink;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let payload: CreateInviteLink = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let group_service = create_group_service(&ctx.env)?;
+    match group_service.create_invite_link(&group_id, &user_id, payload).await {
+        Ok(link) => Ok(Response::from_json(&link)?.with_status(201)),
+        Err(e) => {
+            let message = e.to_string();
+            let status = ServiceError::status_of(e.as_ref(), 500);
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_get_invite_links(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let group_service = create_group_service(&ctx.env)?;
+    match group_service.get_invite_links(&group_id, &user_id).await {
+        Ok(links) => Response::from_json(&links),
+        Err(e) => {
+            let status = ServiceError::status_of(e.as_ref(), 500);
+            let response = Response::from_json(&ErrorResponse {
+                error: e.to_string(),
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_revoke_invite_link(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let ids = (
+        ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()),
+        ctx.param("link_id").and_then(|id| Uuid::parse_str(id).ok()),
+    );
+    let (group_id, link_id) = match ids {
+        (Some(group_id), Some(link_id)) => (group_id, link_id),
+        _ => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group or invite link ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let group_service = create_group_service(&ctx.env)?;
+    match group_service.revoke_invite_link(&group_id, &link_id, &user_id).await {
+        Ok(link) => Response::from_json(&link),
+        Err(e) => {
+            let message = e.to_string();
+            let status = ServiceError::status_of(e.as_ref(), 500);
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_join_group(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::groups::domain::invite_link::RedeemInvite;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let ip = client_ip(&req);
+    if let Some(limited) = enforce_rate_limits(&ctx.env, &[(&RateLimitPolicy::JOIN_GROUP_IP, ip.as_str())]).await? {
+        return Ok(limited);
+    }
+
+    let payload: RedeemInvite = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let group_service = create_group_service(&ctx.env)?;
+    match group_service.redeem_invite(&user_id, &payload.invite).await {
+        Ok(group) => Response::from_json(&group),
+        Err(e) => {
+            let message = e.to_string();
+            let status = ServiceError::status_of(e.as_ref(), 500);
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(status))
+        }
+    }
+}
+
+async fn handle_update_group(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::groups::domain::group::GroupUpdate;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let payload: GroupUpdate = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let group_service = create_group_service(&ctx.env)?;
+    let result = match group_service.update_group(&group_id, &user_id, payload).await {
+        Ok(()) => group_service.get_group(&group_id, &user_id).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(Some(group)) => Response::from_json(&group),
+        Ok(None) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Group not found".to_string(),
+            })?;
+            Ok(response.with_status(404))
+        }
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
+        }
+    }
+}
+
+async fn handle_add_placeholder(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::groups::domain::group::CreatePlaceholder;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let payload: CreatePlaceholder = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let group_service = create_group_service(&ctx.env)?;
+    match group_service.add_placeholder(&group_id, &user_id, payload).await {
+        Ok(member) => Ok(Response::from_json(&member)?.with_status(201)),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
+        }
+    }
+}
+
+async fn handle_change_member_role(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::groups::domain::group::ChangeMemberRole;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let ids = (
+        ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()),
+        ctx.param("user_id").and_then(|id| Uuid::parse_str(id).ok()),
+    );
+    let (group_id, member_id) = match ids {
+        (Some(group_id), Some(member_id)) => (group_id, member_id),
+        _ => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group or user ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let payload: ChangeMemberRole = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let group_service = create_group_service(&ctx.env)?;
+    match group_service.change_member_role(&group_id, &user_id, &member_id, payload.role).await {
+        Ok(member) => Response::from_json(&member),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
+        }
+    }
+}
+
+async fn handle_transfer_ownership(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::groups::domain::group::TransferOwnership;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let payload: TransferOwnership = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let group_service = create_group_service(&ctx.env)?;
+    match group_service.transfer_ownership(&group_id, &user_id, &payload.new_owner_id).await {
+        Ok(members) => Response::from_json(&members),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
+        }
+    }
+}
+
+async fn handle_leave_group(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::groups::domain::group::LeaveGroup;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    #[derive(Serialize)]
+    struct UnsettledBalanceResponse {
+        error: String,
+        balance: f64,
+    }
+
+    #[derive(Serialize)]
+    struct LeaveResponse {
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        warning: Option<String>,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    // The body is optional; only an owner or someone with an open balance needs one
+    let payload: LeaveGroup = req.json().await.unwrap_or_default();
+
+    // Leaving with money still owed either way is blocked unless the member acknowledges it
+    let expense_service = create_d1_expense_service_with_env(&ctx.env)?;
+    let balance = match expense_service.get_group_balances(&group_id, &user_id, false, false).await {
+        Ok(group_balance) => group_balance
+            .balances
+            .iter()
+            .find(|balance| balance.user_id == user_id)
+            .map(|balance| balance.net_balance)
+            .unwrap_or(0.0),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            return Ok(response.with_status(e.status()));
+        }
+    };
+    let unsettled = balance != 0.0;
+    if unsettled && !payload.acknowledge_balance {
+        let response = Response::from_json(&UnsettledBalanceResponse {
+            error: "Your balance in this group is not settled; settle up or set acknowledge_balance to leave anyway".to_string(),
+            balance,
+        })?;
+        return Ok(response.with_status(409));
+    }
+
+    let group_service = create_group_service(&ctx.env)?;
+    match group_service.leave_group(&group_id, &user_id, payload.successor_id.as_ref()).await {
+        Ok(()) => Response::from_json(&LeaveResponse {
+            message: "Left group successfully".to_string(),
+            warning: unsettled.then(|| format!("You left with an unsettled balance of {:.2}", balance)),
+        }),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
+        }
+    }
+}
+
+async fn handle_get_group_settings(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let settings_service = create_group_settings_service(&ctx.env)?;
+    match settings_service.get_settings(&group_id, &user_id).await {
+        Ok(settings) => Response::from_json(&settings),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
         }
-    } else {
-        Response::error("Missing group_id", 400)
     }
 }
 
-async fn handle_settle_debt(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
-    use crate::expenses::domain::expense::SettleDebt;
-    use serde::{Deserialize, Serialize};
-
-    #[derive(Deserialize)]
-    struct SettleDebtRequest {
-        group_id: Uuid,
-        #[serde(flatten)]
-        settle: SettleDebt,
+// ?cursor= and ?limit= for the activity feeds
+fn activity_page_params(url: &Url) -> std::result::Result<(Option<i64>, Option<usize>), String> {
+    use crate::activity::domain::activity::parse_cursor;
+
+    let mut before = None;
+    let mut limit = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "cursor" => before = Some(parse_cursor(&value).ok_or("Invalid cursor")?),
+            "limit" => limit = Some(value.parse::<usize>().map_err(|_| "Invalid limit")?),
+            _ => {}
+        }
     }
+    Ok((before, limit))
+}
+
+async fn handle_get_group_activity(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
 
     #[derive(Serialize)]
     struct ErrorResponse {
         error: String,
     }
 
-    let settled_by = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(_) => {
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
             let response = Response::from_json(&ErrorResponse {
-                error: "Authentication required".to_string(),
+                error: "Invalid group ID".to_string(),
             })?;
-            return Ok(response.with_status(401));
+            return Ok(response.with_status(400));
         }
     };
 
-    let payload: SettleDebtRequest = match req.json().await {
-        Ok(p) => p,
-        Err(_) => return Response::from_json(&ErrorResponse {
-            error: "Invalid JSON".to_string(),
-        }),
+    let (before, limit) = match activity_page_params(&req.url()?) {
+        Ok(params) => params,
+        Err(error) => return Ok(Response::from_json(&ErrorResponse { error })?.with_status(400)),
     };
-    
-    let expense_service = match create_d1_expense_service_with_env(&ctx.env) {
-        Ok(service) => service,
+
+    let activity_service = create_d1_activity_service_with_env(&ctx.env)?;
+    match activity_service.get_group_activity(&group_id, &user_id, before, limit).await {
+        Ok(page) => Response::from_json(&page),
         Err(e) => {
+            let message = e.to_string();
             let response = Response::from_json(&ErrorResponse {
-                error: format!("Service error: {}", e),
+                error: message,
             })?;
-            return Ok(response.with_status(500));
+            Ok(response.with_status(e.status()))
         }
+    }
+}
+
+async fn handle_get_activity_feed(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
     };
-    
-    match expense_service.settle_debt(&payload.group_id, payload.settle, settled_by).await {
-        Ok(_) => Response::from_json(&serde_json::json!({
-            "message": "Debt settled successfully"
-        })),
+
+    let (before, limit) = match activity_page_params(&req.url()?) {
+        Ok(params) => params,
+        Err(error) => return Ok(Response::from_json(&ErrorResponse { error })?.with_status(400)),
+    };
+
+    let activity_service = create_d1_activity_service_with_env(&ctx.env)?;
+    match activity_service.get_feed(&user_id, before, limit).await {
+        Ok(page) => Response::from_json(&page),
         Err(e) => {
             let response = Response::from_json(&ErrorResponse {
                 error: e.to_string(),
             })?;
-            Ok(response.with_status(400))
+            Ok(response.with_status(500))
         }
     }
 }
 
-// Groups API handlers
-async fn handle_create_group(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
-    use crate::groups::domain::group::GroupCreation;
+async fn handle_mark_activity_read(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
     use serde::{Deserialize, Serialize};
 
-    #[derive(Deserialize)]
-    struct CreateGroupRequest {
-        name: String,
-        description: Option<String>,
+    // No group_id marks every group read
+    #[derive(Deserialize, Default)]
+    struct MarkReadRequest {
+        #[serde(default)]
+        group_id: Option<Uuid>,
+    }
+
+    #[derive(Serialize)]
+    struct MarkReadResponse {
+        unread_count: u64,
     }
 
     #[derive(Serialize)]
@@ -578,51 +2593,28 @@ async fn handle_create_group(mut req: Request, ctx: RouteContext<()>) -> Result<
         error: String,
     }
 
-    let created_by = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(_) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: "Authentication required".to_string(),
-            })?;
-            return Ok(response.with_status(401));
-        }
-    };
-
-    let payload: CreateGroupRequest = match req.json().await {
-        Ok(p) => p,
-        Err(_) => return Response::from_json(&ErrorResponse {
-            error: "Invalid JSON".to_string(),
-        }),
-    };
-
-    let group_service = match create_d1_group_service_with_env(&ctx.env) {
-        Ok(service) => service,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Service error: {}", e),
-            })?;
-            return Ok(response.with_status(500));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
-    let creation = GroupCreation {
-        name: payload.name,
-        description: payload.description,
-        created_by,
-    };
+    let payload: MarkReadRequest = req.json().await.unwrap_or_default();
 
-    match group_service.create_group_from_creation(creation, created_by).await {
-        Ok(group_info) => Response::from_json(&group_info),
+    let activity_service = create_d1_activity_service_with_env(&ctx.env)?;
+    match activity_service.mark_read(&user_id, payload.group_id.as_ref()).await {
+        Ok(unread_count) => Response::from_json(&MarkReadResponse { unread_count }),
         Err(e) => {
+            let message = e.to_string();
             let response = Response::from_json(&ErrorResponse {
-                error: e.to_string(),
+                error: message,
             })?;
-            Ok(response.with_status(400))
+            Ok(response.with_status(e.status()))
         }
     }
 }
 
-async fn handle_get_user_groups(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+async fn handle_update_group_settings(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::groups::domain::settings::GroupSettingsUpdate;
     use serde::Serialize;
 
     #[derive(Serialize)]
@@ -630,38 +2622,58 @@ async fn handle_get_user_groups(req: Request, ctx: RouteContext<()>) -> Result<R
         error: String,
     }
 
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(_) => {
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
             let response = Response::from_json(&ErrorResponse {
-                error: "Authentication required".to_string(),
+                error: "Invalid group ID".to_string(),
             })?;
-            return Ok(response.with_status(401));
+            return Ok(response.with_status(400));
         }
     };
 
-    let group_service = match create_d1_group_service_with_env(&ctx.env) {
-        Ok(service) => service,
-        Err(e) => {
+    let payload: GroupSettingsUpdate = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
             let response = Response::from_json(&ErrorResponse {
-                error: format!("Service error: {}", e),
+                error: "Invalid JSON".to_string(),
             })?;
-            return Ok(response.with_status(500));
+            return Ok(response.with_status(400));
         }
     };
 
-    match group_service.get_groups_for_user(&user_id).await {
-        Ok(groups) => Response::from_json(&groups),
+    // A new currency needs every expense and payment converted into it before anyone reads
+    // the balances again, which never convert anything themselves
+    let currency_changed = payload.default_currency.is_some();
+    let settings_service = create_group_settings_service(&ctx.env)?;
+    match settings_service.update_settings(&group_id, &user_id, payload).await {
+        Ok(settings) => {
+            if currency_changed {
+                if let Err(e) = create_d1_expense_service_with_env(&ctx.env)?.update_books(&group_id, &settings).await {
+                    let response = Response::from_json(&ErrorResponse {
+                        error: format!("Failed to convert the group's expenses into {}: {}", settings.default_currency, e),
+                    })?;
+                    return Ok(response.with_status(500));
+                }
+            }
+            Response::from_json(&settings)
+        }
         Err(e) => {
+            let message = e.to_string();
             let response = Response::from_json(&ErrorResponse {
-                error: e.to_string(),
+                error: message,
             })?;
-            Ok(response.with_status(500))
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
         }
     }
 }
 
-async fn handle_get_group(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+async fn handle_delete_group(req: Request, ctx: RouteContext<()>) -> Result<Response> {
     use serde::Serialize;
 
     #[derive(Serialize)]
@@ -669,14 +2681,9 @@ async fn handle_get_group(req: Request, ctx: RouteContext<()>) -> Result<Respons
         error: String,
     }
 
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(_) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: "Authentication required".to_string(),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     if let Some(group_id) = ctx.param("id") {
@@ -692,19 +2699,25 @@ async fn handle_get_group(req: Request, ctx: RouteContext<()>) -> Result<Respons
                     }
                 };
 
-                match group_service.get_group_by_id(&group_uuid, &user_id).await {
-                    Ok(Some(group)) => Response::from_json(&group),
-                    Ok(None) => {
-                        let response = Response::from_json(&ErrorResponse {
-                            error: "Group not found".to_string(),
-                        })?;
-                        Ok(response.with_status(404))
-                    }
+                // ?permanent=true skips the restore window and removes everything now
+                let permanent = req
+                    .url()?
+                    .query_pairs()
+                    .any(|(key, value)| key == "permanent" && value == "true");
+
+                match group_service.delete_group(&group_uuid, &user_id, permanent).await {
+                    Ok(Some(purge_at)) => Response::from_json(&serde_json::json!({
+                        "message": "Group deleted. It can be restored until it is purged.",
+                        "purge_at": purge_at
+                    })),
+                    Ok(None) => Response::from_json(&serde_json::json!({
+                        "message": "Group permanently deleted"
+                    })),
                     Err(e) => {
                         let response = Response::from_json(&ErrorResponse {
                             error: e.to_string(),
                         })?;
-                        Ok(response.with_status(500))
+                        Ok(response.with_status(e.status()))
                     }
                 }
             }
@@ -723,7 +2736,7 @@ async fn handle_get_group(req: Request, ctx: RouteContext<()>) -> Result<Respons
     }
 }
 
-async fn handle_get_group_members(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+async fn handle_get_deleted_groups(req: Request, ctx: RouteContext<()>) -> Result<Response> {
     use serde::Serialize;
 
     #[derive(Serialize)]
@@ -731,130 +2744,121 @@ async fn handle_get_group_members(req: Request, ctx: RouteContext<()>) -> Result
         error: String,
     }
 
-    let _user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(_) => {
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_service = create_d1_group_service_with_env(&ctx.env)?;
+    match group_service.get_deleted_groups(&user_id).await {
+        Ok(groups) => Response::from_json(&groups),
+        Err(e) => {
             let response = Response::from_json(&ErrorResponse {
-                error: "Authentication required".to_string(),
+                error: e.to_string(),
             })?;
-            return Ok(response.with_status(401));
+            Ok(response.with_status(500))
         }
+    }
+}
+
+async fn handle_restore_group(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
     };
 
-    if let Some(group_id) = ctx.param("id") {
-        match Uuid::parse_str(group_id) {
-            Ok(group_uuid) => {
-                let group_service = match create_d1_group_service_with_env(&ctx.env) {
-                    Ok(service) => service,
-                    Err(e) => {
-                        let response = Response::from_json(&ErrorResponse {
-                            error: format!("Service error: {}", e),
-                        })?;
-                        return Ok(response.with_status(500));
-                    }
-                };
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid group ID".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
 
-                match group_service.get_group_members(&group_uuid).await {
-                    Ok(members) => Response::from_json(&members),
-                    Err(e) => {
-                        let response = Response::from_json(&ErrorResponse {
-                            error: e.to_string(),
-                        })?;
-                        Ok(response.with_status(500))
-                    }
-                }
-            }
-            Err(_) => {
-                let response = Response::from_json(&ErrorResponse {
-                    error: "Invalid group ID".to_string(),
-                })?;
-                Ok(response.with_status(400))
-            }
+    let group_service = create_d1_group_service_with_env(&ctx.env)?;
+    let result = match group_service.restore_group(&group_id, &user_id).await {
+        Ok(()) => group_service.get_group_by_id(&group_id, &user_id).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(Some(group)) => Response::from_json(&group),
+        Ok(None) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Group not found".to_string(),
+            })?;
+            Ok(response.with_status(404))
+        }
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(e.status()))
         }
-    } else {
-        let response = Response::from_json(&ErrorResponse {
-            error: "Group ID is required".to_string(),
-        })?;
-        Ok(response.with_status(400))
     }
 }
 
-async fn handle_invite_user(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
-    use crate::groups::domain::group::InviteUser;
-    use serde::{Deserialize, Serialize};
+async fn handle_archive_group(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    set_group_archived(req, ctx, true).await
+}
 
-    #[derive(Deserialize)]
-    struct InviteRequest {
-        user_id: Uuid,
-    }
+async fn handle_unarchive_group(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    set_group_archived(req, ctx, false).await
+}
+
+async fn set_group_archived(req: Request, ctx: RouteContext<()>, archived: bool) -> Result<Response> {
+    use serde::Serialize;
 
     #[derive(Serialize)]
     struct ErrorResponse {
         error: String,
     }
 
-    let invited_by = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(_) => {
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
             let response = Response::from_json(&ErrorResponse {
-                error: "Authentication required".to_string(),
+                error: "Invalid group ID".to_string(),
             })?;
-            return Ok(response.with_status(401));
+            return Ok(response.with_status(400));
         }
     };
 
-    let payload: InviteRequest = match req.json().await {
-        Ok(p) => p,
-        Err(_) => return Response::from_json(&ErrorResponse {
-            error: "Invalid JSON".to_string(),
-        }),
+    let group_service = create_group_service(&ctx.env)?;
+    let result = if archived {
+        group_service.archive_group(&group_id, &user_id).await
+    } else {
+        group_service.unarchive_group(&group_id, &user_id).await
     };
 
-    if let Some(group_id) = ctx.param("id") {
-        match Uuid::parse_str(group_id) {
-            Ok(group_uuid) => {
-                let group_service = match create_d1_group_service_with_env(&ctx.env) {
-                    Ok(service) => service,
-                    Err(e) => {
-                        let response = Response::from_json(&ErrorResponse {
-                            error: format!("Service error: {}", e),
-                        })?;
-                        return Ok(response.with_status(500));
-                    }
-                };
-
-                let invite = InviteUser {
-                    user_id: payload.user_id,
-                };
-
-                match group_service.invite_user(&group_uuid, invite, invited_by).await {
-                    Ok(_) => Response::from_json(&serde_json::json!({
-                        "message": "User invited successfully"
-                    })),
-                    Err(e) => {
-                        let response = Response::from_json(&ErrorResponse {
-                            error: e.to_string(),
-                        })?;
-                        Ok(response.with_status(400))
-                    }
-                }
-            }
-            Err(_) => {
-                let response = Response::from_json(&ErrorResponse {
-                    error: "Invalid group ID".to_string(),
-                })?;
-                Ok(response.with_status(400))
-            }
-        }
-    } else {
-        let response = Response::from_json(&ErrorResponse {
-            error: "Group ID is required".to_string(),
-        })?;
-        Ok(response.with_status(400))
+    match result {
+        Ok(group) => Response::from_json(&group),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(ServiceError::status_of(e.as_ref(), 500)))
+        }
     }
 }
 
-async fn handle_leave_group(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+async fn handle_close_ledger(req: Request, ctx: RouteContext<()>) -> Result<Response> {
     use serde::Serialize;
 
     #[derive(Serialize)]
@@ -862,57 +2866,35 @@ async fn handle_leave_group(req: Request, ctx: RouteContext<()>) -> Result<Respo
         error: String,
     }
 
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(_) => {
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
             let response = Response::from_json(&ErrorResponse {
-                error: "Authentication required".to_string(),
+                error: "Invalid group ID".to_string(),
             })?;
-            return Ok(response.with_status(401));
+            return Ok(response.with_status(400));
         }
     };
 
-    if let Some(group_id) = ctx.param("id") {
-        match Uuid::parse_str(group_id) {
-            Ok(group_uuid) => {
-                let group_service = match create_d1_group_service_with_env(&ctx.env) {
-                    Ok(service) => service,
-                    Err(e) => {
-                        let response = Response::from_json(&ErrorResponse {
-                            error: format!("Service error: {}", e),
-                        })?;
-                        return Ok(response.with_status(500));
-                    }
-                };
-
-                match group_service.leave_group(&group_uuid, &user_id).await {
-                    Ok(_) => Response::from_json(&serde_json::json!({
-                        "message": "Left group successfully"
-                    })),
-                    Err(e) => {
-                        let response = Response::from_json(&ErrorResponse {
-                            error: e.to_string(),
-                        })?;
-                        Ok(response.with_status(400))
-                    }
-                }
-            }
-            Err(_) => {
-                let response = Response::from_json(&ErrorResponse {
-                    error: "Invalid group ID".to_string(),
-                })?;
-                Ok(response.with_status(400))
-            }
+    let expense_service = create_d1_expense_service_with_env(&ctx.env)?;
+    match expense_service.close_ledger(&group_id, &user_id).await {
+        Ok(ledger) => Response::from_json(&ledger),
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(e.status()))
         }
-    } else {
-        let response = Response::from_json(&ErrorResponse {
-            error: "Group ID is required".to_string(),
-        })?;
-        Ok(response.with_status(400))
     }
 }
 
-async fn handle_delete_group(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+async fn handle_export_group(req: Request, ctx: RouteContext<()>) -> Result<Response> {
     use serde::Serialize;
 
     #[derive(Serialize)]
@@ -920,53 +2902,38 @@ async fn handle_delete_group(req: Request, ctx: RouteContext<()>) -> Result<Resp
         error: String,
     }
 
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(_) => {
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let group_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
             let response = Response::from_json(&ErrorResponse {
-                error: "Authentication required".to_string(),
+                error: "Invalid group ID".to_string(),
             })?;
-            return Ok(response.with_status(401));
+            return Ok(response.with_status(400));
         }
     };
 
-    if let Some(group_id) = ctx.param("id") {
-        match Uuid::parse_str(group_id) {
-            Ok(group_uuid) => {
-                let group_service = match create_d1_group_service_with_env(&ctx.env) {
-                    Ok(service) => service,
-                    Err(e) => {
-                        let response = Response::from_json(&ErrorResponse {
-                            error: format!("Service error: {}", e),
-                        })?;
-                        return Ok(response.with_status(500));
-                    }
-                };
-
-                match group_service.delete_group(&group_uuid, &user_id).await {
-                    Ok(_) => Response::from_json(&serde_json::json!({
-                        "message": "Group deleted successfully"
-                    })),
-                    Err(e) => {
-                        let response = Response::from_json(&ErrorResponse {
-                            error: e.to_string(),
-                        })?;
-                        Ok(response.with_status(400))
-                    }
-                }
-            }
-            Err(_) => {
-                let response = Response::from_json(&ErrorResponse {
-                    error: "Invalid group ID".to_string(),
-                })?;
-                Ok(response.with_status(400))
-            }
+    let group_service = create_d1_group_service_with_env(&ctx.env)?;
+    match group_service.export_group(&group_id, &user_id).await {
+        Ok(export) => {
+            let mut response = Response::from_json(&export)?;
+            response.headers_mut().set(
+                "Content-Disposition",
+                &format!("attachment; filename=\"twodo-group-{}-{}.json\"", group_id, export.exported_at.format("%Y%m%d")),
+            )?;
+            Ok(response)
+        }
+        Err(e) => {
+            let message = e.to_string();
+            let response = Response::from_json(&ErrorResponse {
+                error: message,
+            })?;
+            Ok(response.with_status(e.status()))
         }
-    } else {
-        let response = Response::from_json(&ErrorResponse {
-            error: "Group ID is required".to_string(),
-        })?;
-        Ok(response.with_status(400))
     }
 }
 
@@ -978,14 +2945,9 @@ async fn handle_create_event(mut req: Request, ctx: RouteContext<()>) -> Result<
     }
 
     // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Parse event creation request
@@ -1017,7 +2979,7 @@ async fn handle_create_event(mut req: Request, ctx: RouteContext<()>) -> Result<
             let response = Response::from_json(&ErrorResponse {
                 error: format!("Failed to create event: {}", e),
             })?;
-            Ok(response.with_status(500))
+            Ok(response.with_status(e.status()))
         }
     }
 }
@@ -1029,14 +2991,9 @@ async fn handle_get_event(req: Request, ctx: RouteContext<()>) -> Result<Respons
     }
 
     // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Get event ID from URL
@@ -1075,7 +3032,7 @@ async fn handle_get_event(req: Request, ctx: RouteContext<()>) -> Result<Respons
                 let response = Response::from_json(&ErrorResponse {
                     error: format!("Database error: {}", e),
                 })?;
-                Ok(response.with_status(500))
+                Ok(response.with_status(e.status()))
             }
         }
     } else {
@@ -1093,14 +3050,9 @@ async fn handle_delete_event(req: Request, ctx: RouteContext<()>) -> Result<Resp
     }
 
     // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Get event ID from URL
@@ -1136,7 +3088,7 @@ async fn handle_delete_event(req: Request, ctx: RouteContext<()>) -> Result<Resp
                 let response = Response::from_json(&ErrorResponse {
                     error: format!("Failed to delete event: {}", e),
                 })?;
-                Ok(response.with_status(500))
+                Ok(response.with_status(e.status()))
             }
         }
     } else {
@@ -1147,21 +3099,66 @@ async fn handle_delete_event(req: Request, ctx: RouteContext<()>) -> Result<Resp
     }
 }
 
-async fn handle_get_group_events(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+async fn handle_respond_to_event(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    use crate::calendar::domain::event::RespondToEvent;
+
     #[derive(Serialize)]
     struct ErrorResponse {
         error: String,
     }
 
-    // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
+    };
+
+    let event_id = match ctx.param("id").and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(id) => id,
+        None => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid event ID format".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let payload: RespondToEvent = match req.json().await {
+        Ok(p) => p,
+        Err(_) => {
+            let response = Response::from_json(&ErrorResponse {
+                error: "Invalid JSON".to_string(),
+            })?;
+            return Ok(response.with_status(400));
+        }
+    };
+
+    let calendar_service = create_d1_calendar_service_with_env(&ctx.env)?;
+    match calendar_service.respond_to_event(&event_id, &user_id, payload.status).await {
+        Ok(()) => match calendar_service.get_event_by_id(&event_id, &user_id).await {
+            Ok(Some(event)) => Response::from_json(&event),
+            Ok(None) => Response::error("Event not found", 404),
+            Err(e) => Response::error(e.to_string(), 500),
+        },
         Err(e) => {
+            let message = e.to_string();
             let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
+                error: message,
             })?;
-            return Ok(response.with_status(401));
+            Ok(response.with_status(e.status()))
         }
+    }
+}
+
+async fn handle_get_group_events(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    #[derive(Serialize)]
+    struct ErrorResponse {
+        error: String,
+    }
+
+    // Get authenticated user
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
+        Ok(id) => id,
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Get group ID from URL
@@ -1194,7 +3191,7 @@ async fn handle_get_group_events(req: Request, ctx: RouteContext<()>) -> Result<
                 let response = Response::from_json(&ErrorResponse {
                     error: format!("Database error: {}", e),
                 })?;
-                Ok(response.with_status(500))
+                Ok(response.with_status(e.status()))
             }
         }
     } else {
@@ -1212,14 +3209,9 @@ async fn handle_get_events_by_date(req: Request, ctx: RouteContext<()>) -> Resul
     }
 
     // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Get group ID from URL
@@ -1234,10 +3226,37 @@ async fn handle_get_events_by_date(req: Request, ctx: RouteContext<()>) -> Resul
             }
         };
 
-        // Parse query parameters for date range (simplified - default to current month)
-        let now = chrono::Utc::now();
-        let start_date = now.date_naive().with_day(1).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
-        let end_date = start_date + chrono::Duration::days(31);
+        // ?view=day|week|month (default month) and ?date=YYYY-MM-DD (default today in the group)
+        let url = req.url()?;
+        let mut view = crate::calendar::domain::event::ViewType::Month;
+        let mut date = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "view" => {
+                    view = match value.as_ref() {
+                        "day" => crate::calendar::domain::event::ViewType::Day,
+                        "week" => crate::calendar::domain::event::ViewType::Week,
+                        "month" => crate::calendar::domain::event::ViewType::Month,
+                        _ => {
+                            let response = Response::from_json(&ErrorResponse {
+                                error: "view must be day, week or month".to_string(),
+                            })?;
+                            return Ok(response.with_status(400));
+                        }
+                    };
+                }
+                "date" => match chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+                    Ok(parsed) => date = Some(parsed),
+                    Err(_) => {
+                        let response = Response::from_json(&ErrorResponse {
+                            error: "date must be formatted as YYYY-MM-DD".to_string(),
+                        })?;
+                        return Ok(response.with_status(400));
+                    }
+                },
+                _ => {}
+            }
+        }
 
         // Create calendar service
         let calendar_service = match create_d1_calendar_service_with_env(&ctx.env) {
@@ -1250,9 +3269,9 @@ async fn handle_get_events_by_date(req: Request, ctx: RouteContext<()>) -> Resul
             }
         };
 
-        // Get events in date range
-        match calendar_service.get_events_in_date_range(&group_id, &start_date, &end_date, &user_id).await {
-            Ok(events) => Response::from_json(&serde_json::json!({
+        // Get events in the group's local range
+        match calendar_service.get_events_for_view(&group_id, &view, date, &user_id).await {
+            Ok((start_date, end_date, events)) => Response::from_json(&serde_json::json!({
                 "events": events,
                 "start_date": start_date,
                 "end_date": end_date,
@@ -1262,7 +3281,7 @@ async fn handle_get_events_by_date(req: Request, ctx: RouteContext<()>) -> Resul
                 let response = Response::from_json(&ErrorResponse {
                     error: format!("Database error: {}", e),
                 })?;
-                Ok(response.with_status(500))
+                Ok(response.with_status(e.status()))
             }
         }
     } else {
@@ -1281,14 +3300,9 @@ async fn handle_create_chore(mut req: Request, ctx: RouteContext<()>) -> Result<
     }
 
     // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Parse chore creation request
@@ -1320,7 +3334,7 @@ async fn handle_create_chore(mut req: Request, ctx: RouteContext<()>) -> Result<
             let response = Response::from_json(&ErrorResponse {
                 error: format!("Failed to create chore: {}", e),
             })?;
-            Ok(response.with_status(500))
+            Ok(response.with_status(e.status()))
         }
     }
 }
@@ -1332,14 +3346,9 @@ async fn handle_get_chore(req: Request, ctx: RouteContext<()>) -> Result<Respons
     }
 
     // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Get chore ID from URL
@@ -1378,7 +3387,7 @@ async fn handle_get_chore(req: Request, ctx: RouteContext<()>) -> Result<Respons
                 let response = Response::from_json(&ErrorResponse {
                     error: format!("Database error: {}", e),
                 })?;
-                Ok(response.with_status(500))
+                Ok(response.with_status(e.status()))
             }
         }
     } else {
@@ -1396,14 +3405,9 @@ async fn handle_delete_chore(req: Request, ctx: RouteContext<()>) -> Result<Resp
     }
 
     // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Get chore ID from URL
@@ -1439,7 +3443,7 @@ async fn handle_delete_chore(req: Request, ctx: RouteContext<()>) -> Result<Resp
                 let response = Response::from_json(&ErrorResponse {
                     error: format!("Failed to delete chore: {}", e),
                 })?;
-                Ok(response.with_status(500))
+                Ok(response.with_status(e.status()))
             }
         }
     } else {
@@ -1462,14 +3466,9 @@ async fn handle_update_chore_status(mut req: Request, ctx: RouteContext<()>) ->
     }
 
     // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Get chore ID from URL
@@ -1516,7 +3515,7 @@ async fn handle_update_chore_status(mut req: Request, ctx: RouteContext<()>) ->
                 let response = Response::from_json(&ErrorResponse {
                     error: format!("Failed to update chore status: {}", e),
                 })?;
-                Ok(response.with_status(500))
+                Ok(response.with_status(e.status()))
             }
         }
     } else {
@@ -1534,14 +3533,9 @@ async fn handle_assign_chore(mut req: Request, ctx: RouteContext<()>) -> Result<
     }
 
     // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Parse assignment request
@@ -1576,7 +3570,7 @@ async fn handle_assign_chore(mut req: Request, ctx: RouteContext<()>) -> Result<
             let response = Response::from_json(&ErrorResponse {
                 error: format!("Failed to assign chore: {}", e),
             })?;
-            Ok(response.with_status(500))
+            Ok(response.with_status(e.status()))
         }
     }
 }
@@ -1588,14 +3582,9 @@ async fn handle_get_group_chores(req: Request, ctx: RouteContext<()>) -> Result<
     }
 
     // Get authenticated user
-    let user_id = match get_authenticated_user_id(&req).await {
+    let user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Get group ID from URL
@@ -1628,7 +3617,7 @@ async fn handle_get_group_chores(req: Request, ctx: RouteContext<()>) -> Result<
                 let response = Response::from_json(&ErrorResponse {
                     error: format!("Database error: {}", e),
                 })?;
-                Ok(response.with_status(500))
+                Ok(response.with_status(e.status()))
             }
         }
     } else {
@@ -1646,14 +3635,9 @@ async fn handle_get_user_chores(req: Request, ctx: RouteContext<()>) -> Result<R
     }
 
     // Get authenticated user
-    let _user_id = match get_authenticated_user_id(&req).await {
+    let _user_id = match get_authenticated_user_id(&req, &ctx.env).await? {
         Ok(id) => id,
-        Err(e) => {
-            let response = Response::from_json(&ErrorResponse {
-                error: format!("Authentication error: {}", e),
-            })?;
-            return Ok(response.with_status(401));
-        }
+        Err(e) => return unauthorized_response(&e),
     };
 
     // Get user ID from URL
@@ -1687,7 +3671,7 @@ async fn handle_get_user_chores(req: Request, ctx: RouteContext<()>) -> Result<R
                 let response = Response::from_json(&ErrorResponse {
                     error: format!("Database error: {}", e),
                 })?;
-                Ok(response.with_status(500))
+                Ok(response.with_status(e.status()))
             }
         }
     } else {
@@ -1705,9 +3689,26 @@ fn create_d1_expense_service_with_env(env: &Env) -> Result<crate::expenses::infr
 
     // Get D1 database using the correct binding name "DB"
     let d1 = env.d1("DB")?;
+    let config = crate::config::Config::from_worker_env(env)?;
 
     // Use direct D1 service - no async traits, no Send issues!
-    Ok(DirectD1ExpenseService::new(d1))
+    Ok(DirectD1ExpenseService::new(d1, create_group_authorizer(env)?, create_exchange_rate_provider(&config)?))
+}
+
+// Helper function to pick the exchange rate source: the HTTP API when configured, otherwise
+// the EXCHANGE_RATES table, otherwise an empty table that only converts a currency to itself
+fn create_exchange_rate_provider(
+    config: &crate::config::Config,
+) -> Result<std::sync::Arc<dyn crate::expenses::domain::ports::ExchangeRateProvider>> {
+    use std::sync::Arc;
+    use crate::expenses::infrastructure::{HttpExchangeRateProvider, StaticExchangeRateProvider};
+
+    let provider: Arc<dyn crate::expenses::domain::ports::ExchangeRateProvider> = match (&config.exchange_rate_api_url, &config.exchange_rates) {
+        (Some(api_url), _) => Arc::new(HttpExchangeRateProvider::new(api_url.clone())),
+        (None, Some(table)) => Arc::new(StaticExchangeRateProvider::from_json(table).map_err(Error::RustError)?),
+        (None, None) => Arc::new(StaticExchangeRateProvider::new()),
+    };
+    Ok(provider)
 }
 
 // Helper function to create D1 groups service
@@ -1718,7 +3719,14 @@ fn create_d1_group_service_with_env(env: &Env) -> Result<crate::groups::infrastr
     let d1 = env.d1("DB")?;
 
     // Use direct D1 service - no async traits, no Send issues!
-    Ok(DirectD1GroupService::new(d1))
+    Ok(DirectD1GroupService::new(d1, create_group_authorizer(env)?))
+}
+
+// Helper function to create D1 activity feed service
+fn create_d1_activity_service_with_env(env: &Env) -> Result<crate::activity::infrastructure::DirectD1ActivityService> {
+    use crate::activity::infrastructure::DirectD1ActivityService;
+
+    Ok(DirectD1ActivityService::new(env.d1("DB")?, create_group_authorizer(env)?))
 }
 
 // Helper function to create D1 calendar service
@@ -1729,7 +3737,7 @@ fn create_d1_calendar_service_with_env(env: &Env) -> Result<crate::calendar::inf
     let d1 = env.d1("DB")?;
 
     // Use direct D1 service - no async traits, no Send issues!
-    Ok(DirectD1CalendarService::new(d1))
+    Ok(DirectD1CalendarService::new(d1, create_group_authorizer(env)?))
 }
 
 // Helper function to create D1 chore service
@@ -1740,22 +3748,238 @@ fn create_d1_chore_service_with_env(env: &Env) -> Result<crate::chores::infrastr
     let d1 = env.d1("DB")?;
 
     // Use direct D1 service - no async traits, no Send issues!
-    Ok(DirectD1ChoreService::new(d1))
+    Ok(DirectD1ChoreService::new(d1, create_group_authorizer(env)?))
+}
+
+// Helper function to create the group permission check shared by the direct D1 services
+fn create_group_authorizer(env: &Env) -> Result<crate::groups::application::authorization::GroupAuthorizer> {
+    use std::sync::Arc;
+    use crate::groups::application::authorization::GroupAuthorizer;
+    use crate::groups::infrastructure::D1GroupMemberRepository;
+
+    Ok(GroupAuthorizer::new(Arc::new(D1GroupMemberRepository::new(env.d1("DB")?))))
+}
+
+// Helper function to create the application-layer group service
+fn create_group_service(env: &Env) -> Result<crate::groups::application::use_cases::GroupService> {
+    use std::sync::Arc;
+    use crate::groups::application::use_cases::GroupService;
+    use crate::groups::infrastructure::{D1GroupInviteLinkRepository, D1GroupMemberRepository, D1GroupRepository};
+    use crate::activity::infrastructure::D1ActivityRecorder;
+
+    let config = crate::config::Config::from_worker_env(env)?;
+    Ok(GroupService::new(
+        Arc::new(D1GroupRepository::new(env.d1("DB")?)),
+        Arc::new(D1GroupMemberRepository::new(env.d1("DB")?)),
+        Arc::new(D1GroupInviteLinkRepository::new(env.d1("DB")?)),
+        Arc::new(create_token_service(env)?),
+        Arc::new(D1ActivityRecorder::new(env.d1("DB")?)),
+        config.app_base_url.clone(),
+    ))
+}
+
+// Helper function to create the group settings service
+fn create_group_settings_service(env: &Env) -> Result<crate::groups::application::settings::GroupSettingsService> {
+    use std::sync::Arc;
+    use crate::groups::application::settings::GroupSettingsService;
+    use crate::groups::infrastructure::{D1GroupMemberRepository, D1GroupSettingsRepository};
+
+    Ok(GroupSettingsService::new(
+        Arc::new(D1GroupSettingsRepository::new(env.d1("DB")?)),
+        Arc::new(D1GroupMemberRepository::new(env.d1("DB")?)),
+    ))
+}
+
+// Helper function to create the group invitation service
+fn create_invitation_service(env: &Env) -> Result<crate::groups::application::invitations::InvitationService> {
+    use std::sync::Arc;
+    use crate::groups::application::invitations::InvitationService;
+    use crate::groups::infrastructure::{D1GroupInvitationRepository, D1GroupMemberRepository, D1GroupNameLookup};
+    use crate::profiles::infrastructure::D1DisplayNameLookup;
+    use crate::auth::infrastructure::D1UserRepository;
+    use crate::activity::infrastructure::D1ActivityRecorder;
+
+    Ok(InvitationService::new(
+        Arc::new(D1GroupInvitationRepository::new(env.d1("DB")?)),
+        Arc::new(D1GroupMemberRepository::new(env.d1("DB")?)),
+        Arc::new(D1UserRepository::new(env.d1("DB")?)),
+        Arc::new(D1DisplayNameLookup::new(env.d1("DB")?)),
+        Arc::new(D1GroupNameLookup::new(env.d1("DB")?)),
+        Arc::new(D1ActivityRecorder::new(env.d1("DB")?)),
+    ))
 }
 
-// Helper function to extract user ID from auth token
-async fn get_authenticated_user_id(req: &Request) -> Result<Uuid> {
-    // Extract Authorization header
-    let _auth_header = match req.headers().get("Authorization") {
-        Ok(Some(header)) => header,
-        Ok(None) => return Err("Missing Authorization header".into()),
-        Err(_) => return Err("Invalid Authorization header".into()),
+// Helper function to create the auth service backed by the D1 users table
+fn create_auth_service(env: &Env) -> Result<crate::auth::application::use_cases::AuthService> {
+    use std::sync::Arc;
+    use crate::auth::application::use_cases::AuthService;
+    use crate::auth::infrastructure::{
+        D1SessionRepository, D1TwoFactorRepository, D1UserRepository, WasmPasswordService, WasmTotpService,
     };
-    
-    // For demo purposes, just return a fixed user ID
-    // In production, this would validate the JWT and extract the user ID
-    match Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000") {
-        Ok(uuid) => Ok(uuid),
-        Err(_) => Err("Invalid UUID".into()),
+
+    let config = crate::config::Config::from_worker_env(env)?;
+    let user_repository = Arc::new(D1UserRepository::new(env.d1("DB")?));
+    let password_service = Arc::new(WasmPasswordService::new());
+    let token_service = Arc::new(create_token_service(env)?);
+    let session_repository = Arc::new(D1SessionRepository::new(env.d1("DB")?));
+    let two_factor_repository = Arc::new(D1TwoFactorRepository::new(env.d1("DB")?));
+    let totp_service = Arc::new(WasmTotpService::new());
+
+    Ok(AuthService::new(
+        user_repository,
+        password_service,
+        token_service,
+        session_repository,
+        two_factor_repository,
+        totp_service,
+    )
+    .with_refresh_token_ttl(config.refresh_token_ttl_secs()))
+}
+
+// Helper function to create the email verification / password reset service
+fn create_account_recovery_service(env: &Env) -> Result<crate::auth::application::account_recovery::AccountRecoveryService> {
+    use std::sync::Arc;
+    use crate::auth::application::account_recovery::AccountRecoveryService;
+    use crate::auth::infrastructure::{
+        D1AccountTokenRepository, D1SessionRepository, D1UserRepository, UnavailableMailSender, WasmPasswordService,
+    };
+
+    let config = crate::config::Config::from_worker_env(env)?;
+    Ok(AccountRecoveryService::new(
+        Arc::new(D1UserRepository::new(env.d1("DB")?)),
+        Arc::new(WasmPasswordService::new()),
+        Arc::new(create_token_service(env)?),
+        Arc::new(D1SessionRepository::new(env.d1("DB")?)),
+        Arc::new(D1AccountTokenRepository::new(env.d1("DB")?)),
+        create_mail_sender(&config).unwrap_or_else(|| Arc::new(UnavailableMailSender)),
+        config.app_base_url.clone(),
+    ))
+}
+
+// Helper function to pick the mail transport: the HTTP API when configured, the console only
+// when MAIL_CONSOLE explicitly asks for it, otherwise none
+fn create_mail_sender(config: &crate::config::Config) -> Option<std::sync::Arc<dyn crate::auth::domain::ports::MailSender>> {
+    use std::sync::Arc;
+    use crate::auth::infrastructure::{ConsoleMailSender, HttpMailSender};
+
+    match (&config.mail_api_url, &config.mail_api_key) {
+        (Some(api_url), Some(api_key)) => Some(Arc::new(HttpMailSender::new(
+            api_url.clone(),
+            api_key.clone(),
+            config.mail_from.clone(),
+        ))),
+        _ if config.mail_console => Some(Arc::new(ConsoleMailSender::new())),
+        _ => None,
+    }
+}
+
+// Helper function for flows that send mail: a 503 response when no mail transport is configured
+fn mail_unavailable_response(env: &Env) -> Result<Option<Response>> {
+    use crate::auth::infrastructure::MAIL_UNAVAILABLE;
+
+    let config = crate::config::Config::from_worker_env(env)?;
+    if create_mail_sender(&config).is_some() {
+        return Ok(None);
+    }
+    let response = Response::from_json(&serde_json::json!({ "error": MAIL_UNAVAILABLE }))?;
+    Ok(Some(response.with_status(503)))
+}
+
+// Helper function to create the account deletion / export service
+fn create_account_service(env: &Env) -> Result<crate::auth::application::account::AccountService> {
+    use std::sync::Arc;
+    use crate::auth::application::account::AccountService;
+    use crate::auth::infrastructure::{D1AccountDataRepository, D1UserRepository, WasmPasswordService};
+
+    Ok(AccountService::new(
+        Arc::new(D1UserRepository::new(env.d1("DB")?)),
+        Arc::new(WasmPasswordService::new()),
+        Arc::new(D1AccountDataRepository::new(env.d1("DB")?)),
+    ))
+}
+
+// Helper function to create the profile service with avatars in the FILES bucket
+fn create_profile_service(env: &Env) -> Result<crate::profiles::application::use_cases::ProfileService> {
+    use std::sync::Arc;
+    use crate::profiles::application::use_cases::ProfileService;
+    use crate::profiles::infrastructure::{D1ProfileRepository, R2AvatarStorage};
+    use crate::auth::infrastructure::D1UserRepository;
+
+    Ok(ProfileService::new(
+        Arc::new(D1UserRepository::new(env.d1("DB")?)),
+        Arc::new(D1ProfileRepository::new(env.d1("DB")?)),
+        Arc::new(R2AvatarStorage::new(env.bucket("FILES")?)),
+    ))
+}
+
+// Helper function to create the rate limiter backed by the D1 rate_limits table
+fn create_rate_limiter(env: &Env) -> Result<crate::auth::application::rate_limiter::RateLimiter> {
+    use std::sync::Arc;
+    use crate::auth::application::rate_limiter::RateLimiter;
+    use crate::auth::infrastructure::D1RateLimitStore;
+
+    Ok(RateLimiter::new(Arc::new(D1RateLimitStore::new(env.d1("DB")?))))
+}
+
+// Helper function to count one attempt against each (policy, subject) pair.
+// Returns the 429 to send when any of them is exhausted, or a 503 when attempts cannot be
+// counted (the limiter fails closed); otherwise sleeps for the longest progressive delay.
+async fn enforce_rate_limits(env: &Env, checks: &[(&RateLimitPolicy, &str)]) -> Result<Option<Response>> {
+    use crate::auth::domain::rate_limit::RateLimitDecision;
+
+    let rate_limiter = create_rate_limiter(env)?;
+    let mut delay_ms = 0;
+
+    for (policy, subject) in checks {
+        match rate_limiter.hit(policy, subject).await {
+            Ok(RateLimitDecision::Limited { retry_after_secs }) => {
+                return Ok(Some(too_many_requests_response(retry_after_secs)?));
+            }
+            Ok(RateLimitDecision::Allowed { delay_ms: delay, .. }) => delay_ms = delay_ms.max(delay),
+            Err(e) => {
+                console_log!("Rate limiter unavailable for {}: {}", policy.scope, e);
+                return Ok(Some(rate_limit_unavailable_response()?));
+            }
+        }
+    }
+
+    if delay_ms > 0 {
+        Delay::from(std::time::Duration::from_millis(delay_ms)).await;
+    }
+    Ok(None)
+}
+
+// Helper function to forget an account's failed attempts once someone has fully signed in to it
+async fn reset_rate_limit(env: &Env, policy: &RateLimitPolicy, subject: &str) -> Result<()> {
+    if let Err(e) = create_rate_limiter(env)?.reset(policy, subject).await {
+        console_log!("Failed to reset the {} rate limit: {}", policy.scope, e);
     }
+    Ok(())
+}
+
+// Helper function to create the token service from the JWT_SECRET key ring and configured lifetime
+fn create_token_service(env: &Env) -> Result<crate::auth::infrastructure::WasmTokenService> {
+    use crate::auth::infrastructure::{KeyRing, WasmTokenService};
+
+    let config = crate::config::Config::from_worker_env(env)?;
+    let key_ring = KeyRing::parse(&config.jwt_secret)
+        .map_err(|e| Error::RustError(format!("Invalid JWT_SECRET: {}", e)))?;
+
+    Ok(WasmTokenService::new(key_ring, config.access_token_ttl_secs()))
+}
+
+// Helper function to authenticate the caller from the bearer token and its session.
+// The outer error means the server is misconfigured or D1 failed; the inner one is a rejected token.
+async fn get_authenticated_user(req: &Request, env: &Env) -> Result<std::result::Result<AuthenticatedUser, TokenError>> {
+    use crate::auth::infrastructure::D1SessionRepository;
+
+    let token_service = create_token_service(env)?;
+    let session_repository = D1SessionRepository::new(env.d1("DB")?);
+    authenticate(req, &token_service, &session_repository).await
+}
+
+// Helper function to extract just the caller's user ID from the bearer token
+async fn get_authenticated_user_id(req: &Request, env: &Env) -> Result<std::result::Result<Uuid, TokenError>> {
+    Ok(get_authenticated_user(req, env).await?.map(|user| user.user_id))
 }
+
@@ -0,0 +1 @@
+pub mod use_cases;
\ No newline at end of file
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::profiles::domain::profile::{
+    normalize_currency, normalize_locale, normalize_timezone, Avatar, Profile, ProfileInfo, ProfileUpdate,
+};
+use crate::profiles::domain::ports::{AvatarStorage, ProfileRepository};
+use crate::auth::domain::ports::{Clock, UserRepository};
+use crate::auth::domain::clock::SystemClock;
+use crate::error::ServiceError;
+use std::error::Error;
+
+const MAX_DISPLAY_NAME_CHARS: usize = 50;
+const MAX_AVATAR_BYTES: usize = 2 * 1024 * 1024;
+const AVATAR_CONTENT_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/webp", "image/gif"];
+
+pub struct ProfileService {
+    user_repository: Arc<dyn UserRepository>,
+    profile_repository: Arc<dyn ProfileRepository>,
+    avatar_storage: Arc<dyn AvatarStorage>,
+    clock: Arc<dyn Clock>,
+}
+
+impl ProfileService {
+    pub fn new(
+        user_repository: Arc<dyn UserRepository>,
+        profile_repository: Arc<dyn ProfileRepository>,
+        avatar_storage: Arc<dyn AvatarStorage>,
+    ) -> Self {
+        Self {
+            user_repository,
+            profile_repository,
+            avatar_storage,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub async fn get_profile(&self, user_id: &Uuid) -> Result<ProfileInfo, Box<dyn Error>> {
+        let username = self.username(user_id).await?;
+        let profile = self.load_or_default(user_id).await?;
+        Ok(Self::to_info(profile, username))
+    }
+
+    pub async fn update_profile(&self, user_id: &Uuid, update: ProfileUpdate) -> Result<ProfileInfo, Box<dyn Error>> {
+        let username = self.username(user_id).await?;
+        let mut profile = self.load_or_default(user_id).await?;
+
+        if let Some(display_name) = update.display_name {
+            profile.display_name = match display_name.map(|name| name.trim().to_string()) {
+                Some(name) if name.chars().count() > MAX_DISPLAY_NAME_CHARS => {
+                    return Err(ServiceError::Invalid(format!("Display name must be at most {} characters", MAX_DISPLAY_NAME_CHARS)).into());
+                }
+                Some(name) if !name.is_empty() => Some(name),
+                _ => None,
+            };
+        }
+        if let Some(locale) = update.locale {
+            profile.locale = normalize_locale(&locale).map_err(ServiceError::Invalid)?;
+        }
+        if let Some(timezone) = update.timezone {
+            profile.timezone = normalize_timezone(&timezone).map_err(ServiceError::Invalid)?;
+        }
+        if let Some(currency) = update.default_currency {
+            profile.default_currency = normalize_currency(&currency).map_err(ServiceError::Invalid)?;
+        }
+
+        profile.updated_at = self.clock.now();
+        self.profile_repository.save_profile(&profile).await?;
+        Ok(Self::to_info(profile, username))
+    }
+
+    pub async fn upload_avatar(&self, user_id: &Uuid, content_type: &str, bytes: Vec<u8>) -> Result<ProfileInfo, Box<dyn Error>> {
+        let content_type = content_type.split(';').next().unwrap_or("").trim().to_lowercase();
+        if !AVATAR_CONTENT_TYPES.contains(&content_type.as_str()) {
+            return Err(ServiceError::Invalid("Avatar must be a PNG, JPEG, WebP or GIF image".to_string()).into());
+        }
+        if bytes.is_empty() || bytes.len() > MAX_AVATAR_BYTES {
+            return Err(ServiceError::Invalid(format!("Avatar must be between 1 byte and {} KiB", MAX_AVATAR_BYTES / 1024)).into());
+        }
+
+        let username = self.username(user_id).await?;
+        let mut profile = self.load_or_default(user_id).await?;
+
+        // New key per upload so cached copies of the old image are never served as the new one
+        let key = format!("avatars/{}/{}", user_id, Uuid::new_v4());
+        self.avatar_storage.put_avatar(&key, &Avatar { content_type, bytes }).await?;
+
+        let previous = profile.avatar_key.replace(key);
+        profile.updated_at = self.clock.now();
+        self.profile_repository.save_profile(&profile).await?;
+
+        // Best effort: an orphaned object is harmless
+        if let Some(previous) = previous {
+            let _ = self.avatar_storage.delete_avatar(&previous).await;
+        }
+
+        Ok(Self::to_info(profile, username))
+    }
+
+    pub async fn remove_avatar(&self, user_id: &Uuid) -> Result<ProfileInfo, Box<dyn Error>> {
+        let username = self.username(user_id).await?;
+        let mut profile = self.load_or_default(user_id).await?;
+
+        if let Some(key) = profile.avatar_key.take() {
+            profile.updated_at = self.clock.now();
+            self.profile_repository.save_profile(&profile).await?;
+            let _ = self.avatar_storage.delete_avatar(&key).await;
+        }
+
+        Ok(Self::to_info(profile, username))
+    }
+
+    pub async fn get_avatar(&self, user_id: &Uuid) -> Result<Option<Avatar>, Box<dyn Error>> {
+        match self.profile_repository.get_profile(user_id).await? {
+            Some(Profile { avatar_key: Some(key), .. }) => self.avatar_storage.get_avatar(&key).await,
+            _ => Ok(None),
+        }
+    }
+
+    // Object key of the current avatar, so callers deleting the profile row can clean up after
+    pub async fn avatar_key(&self, user_id: &Uuid) -> Result<Option<String>, Box<dyn Error>> {
+        Ok(self.profile_repository.get_profile(user_id).await?.and_then(|profile| profile.avatar_key))
+    }
+
+    pub async fn discard_avatar(&self, key: &str) -> Result<(), Box<dyn Error>> {
+        self.avatar_storage.delete_avatar(key).await
+    }
+
+    async fn username(&self, user_id: &Uuid) -> Result<String, Box<dyn Error>> {
+        let user = self.user_repository.get_user_by_id(user_id).await?.ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+        Ok(user.username)
+    }
+
+    async fn load_or_default(&self, user_id: &Uuid) -> Result<Profile, Box<dyn Error>> {
+        Ok(self
+            .profile_repository
+            .get_profile(user_id)
+            .await?
+            .unwrap_or_else(|| Profile::new(*user_id, self.clock.now())))
+    }
+
+    fn to_info(profile: Profile, username: String) -> ProfileInfo {
+        ProfileInfo {
+            user_id: profile.user_id,
+            display_name: profile.display_name.unwrap_or_else(|| username.clone()),
+            username,
+            avatar_url: profile
+                .avatar_key
+                .map(|_| format!("/api/profile/{}/avatar", profile.user_id)),
+            locale: profile.locale,
+            timezone: profile.timezone,
+            default_currency: profile.default_currency,
+            updated_at: profile.updated_at,
+        }
+    }
+}
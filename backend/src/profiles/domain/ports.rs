@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use std::collections::HashMap;
+use super::profile::{Avatar, Profile};
+use std::error::Error;
+
+#[async_trait]
+pub trait ProfileRepository: Send + Sync {
+    async fn get_profile(&self, user_id: &Uuid) -> Result<Option<Profile>, Box<dyn Error>>;
+    async fn save_profile(&self, profile: &Profile) -> Result<(), Box<dyn Error>>;
+}
+
+// Object storage for avatar images
+#[async_trait]
+pub trait AvatarStorage: Send + Sync {
+    async fn put_avatar(&self, key: &str, avatar: &Avatar) -> Result<(), Box<dyn Error>>;
+    async fn get_avatar(&self, key: &str) -> Result<Option<Avatar>, Box<dyn Error>>;
+    async fn delete_avatar(&self, key: &str) -> Result<(), Box<dyn Error>>;
+}
+
+// Resolves many user IDs to the names shown in the UI in one round trip.
+// Unknown IDs are simply absent from the map.
+#[async_trait]
+pub trait DisplayNameLookup: Send + Sync {
+    async fn display_names(&self, user_ids: &[Uuid]) -> Result<HashMap<Uuid, String>, Box<dyn Error>>;
+}
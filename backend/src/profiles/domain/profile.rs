@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+pub const DEFAULT_LOCALE: &str = "en-US";
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+pub const DEFAULT_CURRENCY: &str = "USD";
+pub const DELETED_USER_NAME: &str = "Deleted user";
+pub const UNKNOWN_USER_NAME: &str = "Unknown User";
+
+// Personal presentation settings; every user has one, created with defaults on first read
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Profile {
+    pub user_id: Uuid,
+    pub display_name: Option<String>,
+    pub avatar_key: Option<String>, // object key in the FILES bucket
+    pub locale: String,             // BCP 47, e.g. "en-GB"
+    pub timezone: String,           // IANA, e.g. "Europe/Berlin"
+    pub default_currency: String,   // ISO 4217, e.g. "EUR"
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Profile {
+    pub fn new(user_id: Uuid, now: DateTime<Utc>) -> Self {
+        Self {
+            user_id,
+            display_name: None,
+            avatar_key: None,
+            locale: DEFAULT_LOCALE.to_string(),
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            default_currency: DEFAULT_CURRENCY.to_string(),
+            updated_at: now,
+        }
+    }
+}
+
+// PATCH body; absent fields are left alone, `display_name: null` clears it
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProfileUpdate {
+    #[serde(default, with = "double_option")]
+    pub display_name: Option<Option<String>>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub default_currency: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfileInfo {
+    pub user_id: Uuid,
+    pub username: String,
+    pub display_name: String, // falls back to the username
+    pub avatar_url: Option<String>,
+    pub locale: String,
+    pub timezone: String,
+    pub default_currency: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Avatar {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+// Shared by profiles and group settings. Each returns the canonical spelling or a message.
+
+// BCP 47 shape: a 2-3 letter language, then alphanumeric subtags of up to 8 characters
+pub fn normalize_locale(locale: &str) -> Result<String, String> {
+    let locale = locale.trim().replace('_', "-");
+    let mut subtags = locale.split('-');
+    let language_ok = subtags
+        .next()
+        .map(|language| (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_alphabetic()))
+        .unwrap_or(false);
+    let rest_ok = subtags.all(|tag| (1..=8).contains(&tag.len()) && tag.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    if !language_ok || !rest_ok || locale.len() > 35 {
+        return Err(format!("Invalid locale: {}", locale));
+    }
+    Ok(locale)
+}
+
+// IANA name, e.g. "Europe/Berlin"
+pub fn normalize_timezone(timezone: &str) -> Result<String, String> {
+    let tz: chrono_tz::Tz = timezone
+        .trim()
+        .parse()
+        .map_err(|_| format!("Unknown timezone: {}", timezone.trim()))?;
+    Ok(tz.name().to_string())
+}
+
+// ISO 4217 shape: three letters
+pub fn normalize_currency(currency: &str) -> Result<String, String> {
+    let currency = currency.trim().to_uppercase();
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(format!("Invalid currency code: {}", currency));
+    }
+    Ok(currency)
+}
+
+// Distinguishes a missing field from an explicit null
+mod double_option {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, T>(value: &Option<Option<T>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        match value {
+            Some(inner) => inner.serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        Option::<T>::deserialize(deserializer).map(Some)
+    }
+}
@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use chrono::{TimeZone, Utc};
+
+use crate::profiles::domain::profile::{Profile, DEFAULT_CURRENCY, DEFAULT_LOCALE, DEFAULT_TIMEZONE};
+use crate::profiles::domain::ports::ProfileRepository;
+
+// Profiles in the user_profiles table, updated_at stored as INTEGER epoch seconds
+pub struct D1ProfileRepository {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1ProfileRepository {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+
+    fn optional_text(value: &Option<String>) -> JsValue {
+        match value {
+            Some(text) => text.clone().into(),
+            None => JsValue::NULL,
+        }
+    }
+
+    fn row_to_profile(row: &Value) -> std::result::Result<Profile, Box<dyn std::error::Error>> {
+        let updated_at = row["updated_at"].as_i64().or_else(|| row["updated_at"].as_f64().map(|s| s as i64)).ok_or("Invalid updated_at")?;
+        Ok(Profile {
+            user_id: Uuid::parse_str(row["user_id"].as_str().ok_or("Invalid user ID")?)
+                .map_err(|e| format!("UUID parse error: {}", e))?,
+            display_name: row["display_name"].as_str().map(|s| s.to_string()),
+            avatar_key: row["avatar_key"].as_str().map(|s| s.to_string()),
+            locale: row["locale"].as_str().unwrap_or(DEFAULT_LOCALE).to_string(),
+            timezone: row["timezone"].as_str().unwrap_or(DEFAULT_TIMEZONE).to_string(),
+            default_currency: row["default_currency"].as_str().unwrap_or(DEFAULT_CURRENCY).to_string(),
+            updated_at: Utc.timestamp_opt(updated_at, 0).single().ok_or("Invalid updated_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl ProfileRepository for D1ProfileRepository {
+    async fn get_profile(&self, user_id: &Uuid) -> std::result::Result<Option<Profile>, Box<dyn std::error::Error>> {
+        let result = SendFuture::new(async {
+            self.db
+                .prepare("SELECT user_id, display_name, avatar_key, locale, timezone, default_currency, updated_at FROM user_profiles WHERE user_id = ?")
+                .bind(&[user_id.to_string().into()])?
+                .first::<Value>(None)
+                .await
+        })
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
+        match result {
+            Some(row) => Ok(Some(Self::row_to_profile(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn save_profile(&self, profile: &Profile) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        SendFuture::new(async {
+            self.db
+                .prepare(
+                    "INSERT INTO user_profiles (user_id, display_name, avatar_key, locale, timezone, default_currency, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)
+                     ON CONFLICT(user_id) DO UPDATE SET display_name = excluded.display_name, avatar_key = excluded.avatar_key,
+                         locale = excluded.locale, timezone = excluded.timezone, default_currency = excluded.default_currency,
+                         updated_at = excluded.updated_at",
+                )
+                .bind(&[
+                    profile.user_id.to_string().into(),
+                    Self::optional_text(&profile.display_name),
+                    Self::optional_text(&profile.avatar_key),
+                    profile.locale.clone().into(),
+                    profile.timezone.clone().into(),
+                    profile.default_currency.clone().into(),
+                    (profile.updated_at.timestamp() as f64).into(),
+                ])?
+                .run()
+                .await
+        })
+        .await
+        .map_err(|e| format!("Failed to save profile: {}", e))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+use worker::wasm_bindgen::JsValue;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+use crate::profiles::domain::profile::{DELETED_USER_NAME, UNKNOWN_USER_NAME};
+use crate::profiles::domain::ports::DisplayNameLookup;
+
+// D1 caps bound parameters per statement at 100
+const IDS_PER_QUERY: usize = 90;
+
+// Names to show for many users at once: the profile display name, else the username.
+// Deleted accounts come back as "Deleted user". One query per 90 distinct IDs.
+pub async fn load_display_names(db: &D1Database, user_ids: &[Uuid]) -> Result<HashMap<Uuid, String>> {
+    let mut unique: Vec<String> = Vec::new();
+    let mut seen = HashSet::new();
+    for user_id in user_ids {
+        if seen.insert(*user_id) {
+            unique.push(user_id.to_string());
+        }
+    }
+
+    let mut names = HashMap::with_capacity(unique.len());
+    for chunk in unique.chunks(IDS_PER_QUERY) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let query = format!(
+            "SELECT u.id, u.username, u.deleted_at, p.display_name
+             FROM users u LEFT JOIN user_profiles p ON p.user_id = u.id
+             WHERE u.id IN ({})",
+            placeholders
+        );
+        let params: Vec<JsValue> = chunk.iter().map(|id| id.clone().into()).collect();
+        let rows = db.prepare(&query).bind(&params)?.all().await?.results::<Value>()?;
+
+        for row in rows {
+            let user_id = match row["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) {
+                Some(user_id) => user_id,
+                None => continue,
+            };
+            let name = if !row["deleted_at"].is_null() {
+                DELETED_USER_NAME.to_string()
+            } else {
+                row["display_name"]
+                    .as_str()
+                    .or_else(|| row["username"].as_str())
+                    .unwrap_or(UNKNOWN_USER_NAME)
+                    .to_string()
+            };
+            names.insert(user_id, name);
+        }
+    }
+
+    Ok(names)
+}
+
+// Name from a map built by `load_display_names`, with the usual placeholder for strangers
+pub fn display_name(names: &HashMap<Uuid, String>, user_id: &Uuid) -> String {
+    names.get(user_id).cloned().unwrap_or_else(|| UNKNOWN_USER_NAME.to_string())
+}
+
+// Port adapter for services that hold repositories rather than a raw D1 handle
+pub struct D1DisplayNameLookup {
+    db: SendWrapper<D1Database>,
+}
+
+impl D1DisplayNameLookup {
+    pub fn new(db: D1Database) -> Self {
+        Self { db: SendWrapper::new(db) }
+    }
+}
+
+#[async_trait]
+impl DisplayNameLookup for D1DisplayNameLookup {
+    async fn display_names(&self, user_ids: &[Uuid]) -> std::result::Result<HashMap<Uuid, String>, Box<dyn std::error::Error>> {
+        let names = SendFuture::new(load_display_names(&self.db, user_ids))
+            .await
+            .map_err(|e| format!("Query error: {}", e))?;
+        Ok(names)
+    }
+}
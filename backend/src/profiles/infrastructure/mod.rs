@@ -0,0 +1,7 @@
+pub mod d1_profile_repository;
+pub mod r2_avatar_storage;
+pub mod display_names;
+
+pub use d1_profile_repository::D1ProfileRepository;
+pub use r2_avatar_storage::R2AvatarStorage;
+pub use display_names::{display_name, load_display_names, D1DisplayNameLookup};
@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use worker::*;
+use worker::send::{SendFuture, SendWrapper};
+
+use crate::profiles::domain::profile::Avatar;
+use crate::profiles::domain::ports::AvatarStorage;
+
+// Avatars in the FILES R2 bucket, with the content type kept as HTTP metadata
+pub struct R2AvatarStorage {
+    bucket: SendWrapper<Bucket>,
+}
+
+impl R2AvatarStorage {
+    pub fn new(bucket: Bucket) -> Self {
+        Self { bucket: SendWrapper::new(bucket) }
+    }
+}
+
+#[async_trait]
+impl AvatarStorage for R2AvatarStorage {
+    async fn put_avatar(&self, key: &str, avatar: &Avatar) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        SendFuture::new(async {
+            self.bucket
+                .put(key, Data::Bytes(avatar.bytes.clone()))
+                .http_metadata(HttpMetadata {
+                    content_type: Some(avatar.content_type.clone()),
+                    ..Default::default()
+                })
+                .execute()
+                .await
+        })
+        .await
+        .map_err(|e| format!("Failed to store avatar: {}", e))?;
+
+        Ok(())
+    }
+
+    async fn get_avatar(&self, key: &str) -> std::result::Result<Option<Avatar>, Box<dyn std::error::Error>> {
+        let avatar = SendFuture::new(async {
+            let object = match self.bucket.get(key).execute().await? {
+                Some(object) => object,
+                None => return Ok(None),
+            };
+            let content_type = object
+                .http_metadata()
+                .content_type
+                .unwrap_or_else(|| "application/octet-stream".to_string());
+            let bytes = match object.body() {
+                Some(body) => body.bytes().await?,
+                None => return Ok(None),
+            };
+            Ok::<Option<Avatar>, Error>(Some(Avatar { content_type, bytes }))
+        })
+        .await
+        .map_err(|e| format!("Failed to read avatar: {}", e))?;
+
+        Ok(avatar)
+    }
+
+    async fn delete_avatar(&self, key: &str) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        SendFuture::new(async { self.bucket.delete(key).await })
+            .await
+            .map_err(|e| format!("Failed to delete avatar: {}", e))?;
+        Ok(())
+    }
+}
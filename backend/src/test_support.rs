@@ -0,0 +1,16 @@
+// Helpers shared by unit tests
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+// Drive a future to completion on the current thread. The in-memory adapters used in tests
+// never wait on anything, so polling until ready is all an executor has to do.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}